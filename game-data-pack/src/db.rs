@@ -10,6 +10,8 @@ use flate2::{Compression, FlushCompress};
 use xiv_gen::Language;
 use xiv_gen::csv_to_rkyv::read_data_from;
 
+use crate::diff::PackSnapshot;
+
 /// Every language packed.
 pub const LANGUAGES: [Language; 7] = [
     Language::En,
@@ -34,6 +36,8 @@ pub struct DbOutput {
     /// `(item id, icon id)` of the *named* items in the `en` data, ascending by
     /// item id — the icon extraction reads exactly these out of the game files.
     pub en_named_items: Vec<(i32, i32)>,
+    /// The `en` data as the patch diff sees it.
+    pub en_snapshot: PackSnapshot,
 }
 
 /// Items worth extracting an icon for: the rows that actually have a name, as
@@ -59,6 +63,7 @@ pub fn build_packs(datamining_root: &Path, out_dir: &Path) -> anyhow::Result<DbO
 
     let mut packs = Vec::with_capacity(LANGUAGES.len());
     let mut en_named_items = Vec::new();
+    let mut en_snapshot = PackSnapshot::default();
     for lang in LANGUAGES {
         let data = read_data_from(datamining_root, lang);
         if lang == Language::En {
@@ -67,6 +72,7 @@ pub fn build_packs(datamining_root: &Path, out_dir: &Path) -> anyhow::Result<DbO
                     .iter()
                     .map(|(id, item)| (id.0, item.name.as_str(), item.icon)),
             );
+            en_snapshot = PackSnapshot::from_data(&data);
         }
         let items = data.items.len();
 
@@ -108,6 +114,7 @@ pub fn build_packs(datamining_root: &Path, out_dir: &Path) -> anyhow::Result<DbO
     Ok(DbOutput {
         packs,
         en_named_items,
+        en_snapshot,
     })
 }
 
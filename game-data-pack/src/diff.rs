@@ -0,0 +1,525 @@
+//! Compares two game-data packs into an [`ultros_api_types::patch_diff::PatchDiff`].
+//!
+//! Both sides are first cut down to a [`PackSnapshot`] holding only the sheets
+//! the report covers, keyed by id. The comparison never sees the full `Data`,
+//! so it can be tested with a handful of rows.
+//!
+//! Each build writes its snapshot as JSON next to the packs, and the next
+//! build diffs against that file. A pack can also be decoded and cut down
+//! directly, but only with the `Data` layout it was archived with: once a
+//! sheet is added, older packs no longer decode and only their snapshots can
+//! be compared.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{Context, anyhow, ensure};
+use serde::{Deserialize, Serialize};
+use ultros_api_types::patch_diff::{
+    DiffItem, DiffRecipe, ItemAmount, PatchDiff, PriceChange, RecipeChange, SpecialShopEntry,
+    VendorItem,
+};
+use xiv_gen::{Data, RecipeLevelTableId};
+
+/// `CraftType` order.
+const CRAFT_TYPES: [&str; 8] = ["CRP", "BSM", "ARM", "GSM", "LTW", "WVR", "ALC", "CUL"];
+
+/// How many rows of each section the text report prints.
+const REPORT_ROWS: usize = 50;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PackSnapshot {
+    /// Named items only.
+    pub items: BTreeMap<i32, String>,
+    pub recipes: BTreeMap<i32, DiffRecipe>,
+    /// Items some gil shop sells, at their listed price.
+    pub vendor_prices: BTreeMap<i32, i32>,
+    pub special_shop_entries: BTreeSet<SpecialShopEntry>,
+}
+
+impl PackSnapshot {
+    pub fn from_data(data: &Data) -> Self {
+        let items = data
+            .items
+            .iter()
+            .filter(|(_, item)| !item.name.trim().is_empty())
+            .map(|(id, item)| (id.0, item.name.clone()))
+            .collect();
+        let recipes = data
+            .recipes
+            .values()
+            .filter(|recipe| recipe.item_result > 0)
+            .map(|recipe| {
+                let diff_recipe = DiffRecipe {
+                    recipe_id: recipe.key_id.0,
+                    item_id: recipe.item_result,
+                    craft_type: recipe.craft_type,
+                    level: data
+                        .recipe_level_tables
+                        .get(&RecipeLevelTableId(recipe.recipe_level_table))
+                        .map(|table| table.class_job_level as i32)
+                        .unwrap_or_default(),
+                    amount_result: recipe.amount_result,
+                    ingredients: recipe
+                        .ingredient
+                        .iter()
+                        .zip(recipe.amount_ingredient)
+                        .filter(|(item_id, amount)| **item_id > 0 && *amount > 0)
+                        .map(|(&item_id, amount)| ItemAmount { item_id, amount })
+                        .collect(),
+                };
+                (recipe.key_id.0, diff_recipe)
+            })
+            .collect();
+        let vendor_prices = data
+            .gil_shop_items
+            .values()
+            .flatten()
+            .filter_map(|shop_item| {
+                let item = data.items.get(&xiv_gen::ItemId(shop_item.item))?;
+                (item.price_mid > 0).then_some((shop_item.item, item.price_mid as i32))
+            })
+            .collect();
+        let mut special_shop_entries = BTreeSet::new();
+        for shop in data.special_shops.values() {
+            for slot in 0..shop.item_receive_0.len() {
+                let costs: Vec<ItemAmount> = [
+                    (&shop.item_cost_0, &shop.count_cost_0),
+                    (&shop.item_cost_1, &shop.count_cost_1),
+                    (&shop.item_cost_2, &shop.count_cost_2),
+                ]
+                .into_iter()
+                .filter_map(|(items, counts)| {
+                    let item_id = *items.get(slot)? as i32;
+                    (item_id > 0).then(|| ItemAmount {
+                        item_id,
+                        amount: counts.get(slot).copied().unwrap_or_default() as i32,
+                    })
+                })
+                .collect();
+                for (items, counts) in [
+                    (&shop.item_receive_0, &shop.count_receive_0),
+                    (&shop.item_receive_1, &shop.count_receive_1),
+                ] {
+                    let item_id = items.get(slot).copied().unwrap_or_default() as i32;
+                    if item_id > 0 {
+                        special_shop_entries.insert(SpecialShopEntry {
+                            shop_id: shop.key_id.0,
+                            item_id,
+                            quantity: counts.get(slot).copied().unwrap_or_default() as i32,
+                            costs: costs.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        PackSnapshot {
+            items,
+            recipes,
+            vendor_prices,
+            special_shop_entries,
+        }
+    }
+
+    fn item(&self, item_id: i32) -> DiffItem {
+        DiffItem {
+            item_id,
+            name: self.items.get(&item_id).cloned().unwrap_or_default(),
+        }
+    }
+}
+
+fn read_file(path: &Path) -> anyhow::Result<Vec<u8>> {
+    let bytes = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    ensure!(
+        !bytes.starts_with(b"version https://git-lfs.github.com/spec/v1"),
+        "{} is a git-lfs pointer; run `git lfs pull`",
+        path.display()
+    );
+    Ok(bytes)
+}
+
+/// Decodes a pack the way `xiv-gen-db` does at runtime.
+pub fn read_pack(path: &Path) -> anyhow::Result<Data> {
+    let bytes = read_file(path)?;
+    let mut decoded = Vec::new();
+    flate2::read::ZlibDecoder::new(bytes.as_slice())
+        .read_to_end(&mut decoded)
+        .with_context(|| format!("inflating {}", path.display()))?;
+    // rkyv needs the buffer aligned, which a plain Vec<u8> isn't.
+    let mut aligned = rkyv::AlignedVec::with_capacity(decoded.len());
+    aligned.extend_from_slice(&decoded);
+    rkyv::from_bytes::<Data>(&aligned)
+        .map_err(|e| anyhow!("decoding {} with rkyv: {e:?}", path.display()))
+}
+
+/// Reads a snapshot written by [`write_snapshot`].
+pub fn read_snapshot(path: &Path) -> anyhow::Result<PackSnapshot> {
+    let bytes = read_file(path)?;
+    serde_json::from_slice(&bytes).with_context(|| format!("parsing {}", path.display()))
+}
+
+/// Reads either side of a diff: a `.json` snapshot, or anything else as a
+/// pack.
+pub fn read_pack_or_snapshot(path: &Path) -> anyhow::Result<PackSnapshot> {
+    if path.extension().is_some_and(|ext| ext == "json") {
+        read_snapshot(path)
+    } else {
+        Ok(PackSnapshot::from_data(&read_pack(path)?))
+    }
+}
+
+pub fn write_snapshot(path: &Path, snapshot: &PackSnapshot) -> anyhow::Result<()> {
+    let json = serde_json::to_string(snapshot)?;
+    std::fs::write(path, json + "\n").with_context(|| format!("writing {}", path.display()))
+}
+
+/// Everything in `new` that isn't in `old`, and the other way round.
+fn added<'a, T: Ord + Clone + 'a>(
+    old: impl IntoIterator<Item = &'a T>,
+    new: impl IntoIterator<Item = &'a T>,
+) -> (Vec<T>, Vec<T>) {
+    let old: BTreeSet<&T> = old.into_iter().collect();
+    let new: BTreeSet<&T> = new.into_iter().collect();
+    (
+        new.difference(&old).map(|t| (*t).clone()).collect(),
+        old.difference(&new).map(|t| (*t).clone()).collect(),
+    )
+}
+
+pub fn diff(from: &str, old: &PackSnapshot, to: &str, new: &PackSnapshot) -> PatchDiff {
+    let (new_item_ids, removed_item_ids) = added(old.items.keys(), new.items.keys());
+    let (new_recipe_ids, removed_recipe_ids) = added(old.recipes.keys(), new.recipes.keys());
+    let (new_vendor_ids, removed_vendor_ids) =
+        added(old.vendor_prices.keys(), new.vendor_prices.keys());
+    let (new_special_shop_entries, removed_special_shop_entries) =
+        added(&old.special_shop_entries, &new.special_shop_entries);
+    PatchDiff {
+        from: from.to_string(),
+        to: to.to_string(),
+        new_items: new_item_ids.into_iter().map(|id| new.item(id)).collect(),
+        removed_items: removed_item_ids
+            .into_iter()
+            .map(|id| old.item(id))
+            .collect(),
+        new_recipes: new_recipe_ids
+            .iter()
+            .map(|id| new.recipes[id].clone())
+            .collect(),
+        removed_recipes: removed_recipe_ids
+            .iter()
+            .map(|id| old.recipes[id].clone())
+            .collect(),
+        changed_recipes: new
+            .recipes
+            .values()
+            .filter_map(|after| {
+                let before = old.recipes.get(&after.recipe_id)?;
+                (before.ingredients != after.ingredients).then(|| RecipeChange {
+                    recipe_id: after.recipe_id,
+                    item_id: after.item_id,
+                    before: before.ingredients.clone(),
+                    after: after.ingredients.clone(),
+                })
+            })
+            .collect(),
+        new_vendor_items: new_vendor_ids
+            .iter()
+            .map(|id| VendorItem {
+                item_id: *id,
+                price: new.vendor_prices[id],
+            })
+            .collect(),
+        removed_vendor_items: removed_vendor_ids
+            .iter()
+            .map(|id| VendorItem {
+                item_id: *id,
+                price: old.vendor_prices[id],
+            })
+            .collect(),
+        vendor_price_changes: new
+            .vendor_prices
+            .iter()
+            .filter_map(|(&item_id, &after)| {
+                let before = *old.vendor_prices.get(&item_id)?;
+                (before != after).then_some(PriceChange {
+                    item_id,
+                    before,
+                    after,
+                })
+            })
+            .collect(),
+        new_special_shop_entries,
+        removed_special_shop_entries,
+    }
+}
+
+/// The diff as text for the terminal. `name` resolves item ids.
+pub fn render(diff: &PatchDiff, name: impl Fn(i32) -> String) -> String {
+    let amounts = |amounts: &[ItemAmount]| {
+        amounts
+            .iter()
+            .map(|a| format!("{}× {}", a.amount, name(a.item_id)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut out = format!("patch diff {} -> {}\n", diff.from, diff.to);
+    let mut section = |title: &str, rows: Vec<String>| {
+        if rows.is_empty() {
+            return;
+        }
+        let _ = writeln!(out, "\n{title} ({})", rows.len());
+        for row in rows.iter().take(REPORT_ROWS) {
+            let _ = writeln!(out, "  {row}");
+        }
+        if rows.len() > REPORT_ROWS {
+            let _ = writeln!(out, "  ... and {} more", rows.len() - REPORT_ROWS);
+        }
+    };
+    let recipe_row = |recipe: &DiffRecipe| {
+        let job = usize::try_from(recipe.craft_type)
+            .ok()
+            .and_then(|i| CRAFT_TYPES.get(i))
+            .unwrap_or(&"?");
+        format!(
+            "{} ×{} ({job} {}): {}",
+            name(recipe.item_id),
+            recipe.amount_result,
+            recipe.level,
+            amounts(&recipe.ingredients)
+        )
+    };
+
+    section(
+        "new items",
+        diff.new_items
+            .iter()
+            .map(|i| format!("{} {}", i.item_id, i.name))
+            .collect(),
+    );
+    section(
+        "removed items",
+        diff.removed_items
+            .iter()
+            .map(|i| format!("{} {}", i.item_id, i.name))
+            .collect(),
+    );
+    section(
+        "new recipes",
+        diff.new_recipes.iter().map(recipe_row).collect(),
+    );
+    section(
+        "removed recipes",
+        diff.removed_recipes.iter().map(recipe_row).collect(),
+    );
+    section(
+        "changed recipes",
+        diff.changed_recipes
+            .iter()
+            .map(|c| {
+                format!(
+                    "{}: {} -> {}",
+                    name(c.item_id),
+                    amounts(&c.before),
+                    amounts(&c.after)
+                )
+            })
+            .collect(),
+    );
+    section(
+        "new vendor items",
+        diff.new_vendor_items
+            .iter()
+            .map(|v| format!("{} at {} gil", name(v.item_id), v.price))
+            .collect(),
+    );
+    section(
+        "removed vendor items",
+        diff.removed_vendor_items
+            .iter()
+            .map(|v| format!("{} at {} gil", name(v.item_id), v.price))
+            .collect(),
+    );
+    section(
+        "vendor price changes",
+        diff.vendor_price_changes
+            .iter()
+            .map(|c| format!("{}: {} -> {} gil", name(c.item_id), c.before, c.after))
+            .collect(),
+    );
+    let shop_row = |e: &SpecialShopEntry| {
+        format!(
+            "shop {}: {}× {} for {}",
+            e.shop_id,
+            e.quantity,
+            name(e.item_id),
+            amounts(&e.costs)
+        )
+    };
+    section(
+        "new special shop entries",
+        diff.new_special_shop_entries.iter().map(shop_row).collect(),
+    );
+    section(
+        "removed special shop entries",
+        diff.removed_special_shop_entries
+            .iter()
+            .map(shop_row)
+            .collect(),
+    );
+    section(
+        "existing items the new recipes use",
+        diff.ingredient_demand()
+            .iter()
+            .map(|d| {
+                format!(
+                    "{}: {} recipes, {} per craft",
+                    name(d.item_id),
+                    d.recipes,
+                    d.amount
+                )
+            })
+            .collect(),
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe(recipe_id: i32, item_id: i32, ingredients: &[(i32, i32)]) -> DiffRecipe {
+        DiffRecipe {
+            recipe_id,
+            item_id,
+            craft_type: 1,
+            level: 100,
+            amount_result: 1,
+            ingredients: ingredients
+                .iter()
+                .map(|&(item_id, amount)| ItemAmount { item_id, amount })
+                .collect(),
+        }
+    }
+
+    fn snapshot(
+        items: &[(i32, &str)],
+        recipes: Vec<DiffRecipe>,
+        vendor: &[(i32, i32)],
+    ) -> PackSnapshot {
+        PackSnapshot {
+            items: items
+                .iter()
+                .map(|&(id, name)| (id, name.to_string()))
+                .collect(),
+            recipes: recipes.into_iter().map(|r| (r.recipe_id, r)).collect(),
+            vendor_prices: vendor.iter().copied().collect(),
+            special_shop_entries: BTreeSet::new(),
+        }
+    }
+
+    #[test]
+    fn reports_added_removed_and_changed_rows() {
+        let old = snapshot(
+            &[(1, "Ore"), (2, "Ingot"), (3, "Old Sword")],
+            vec![recipe(10, 2, &[(1, 3)]), recipe(11, 3, &[(2, 2)])],
+            &[(1, 10), (3, 500)],
+        );
+        let mut new = snapshot(
+            &[(1, "Ore"), (2, "Ingot"), (4, "New Sword")],
+            vec![recipe(10, 2, &[(1, 4)]), recipe(12, 4, &[(2, 3)])],
+            &[(1, 12), (2, 40)],
+        );
+        new.special_shop_entries.insert(SpecialShopEntry {
+            shop_id: 7,
+            item_id: 4,
+            quantity: 1,
+            costs: vec![ItemAmount {
+                item_id: 28,
+                amount: 500,
+            }],
+        });
+
+        let diff = diff("a", &old, "b", &new);
+        assert_eq!((diff.from.as_str(), diff.to.as_str()), ("a", "b"));
+        assert_eq!(
+            diff.new_items,
+            vec![DiffItem {
+                item_id: 4,
+                name: "New Sword".into()
+            }]
+        );
+        assert_eq!(diff.removed_items[0].name, "Old Sword");
+        assert_eq!(diff.new_recipes.len(), 1);
+        assert_eq!(diff.removed_recipes[0].recipe_id, 11);
+        assert_eq!(diff.changed_recipes.len(), 1);
+        assert_eq!(diff.changed_recipes[0].after[0].amount, 4);
+        assert_eq!(
+            diff.new_vendor_items,
+            vec![VendorItem {
+                item_id: 2,
+                price: 40
+            }]
+        );
+        assert_eq!(diff.removed_vendor_items[0].item_id, 3);
+        assert_eq!(
+            diff.vendor_price_changes,
+            vec![PriceChange {
+                item_id: 1,
+                before: 10,
+                after: 12
+            }]
+        );
+        assert_eq!(diff.new_special_shop_entries.len(), 1);
+        assert!(diff.removed_special_shop_entries.is_empty());
+
+        let report = render(&diff, |id| new.item(id).name);
+        assert!(report.contains("new recipes (1)\n  New Sword ×1 (BSM 100): 3× Ingot"));
+        assert!(report.contains("Ore: 10 -> 12 gil"));
+    }
+
+    #[test]
+    fn snapshots_round_trip_through_json() {
+        let mut pack = snapshot(&[(1, "Ore")], vec![recipe(10, 2, &[(1, 3)])], &[(1, 10)]);
+        pack.special_shop_entries.insert(SpecialShopEntry {
+            shop_id: 7,
+            item_id: 2,
+            quantity: 1,
+            costs: vec![ItemAmount {
+                item_id: 28,
+                amount: 500,
+            }],
+        });
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("en.snapshot.json");
+
+        write_snapshot(&path, &pack).expect("write snapshot");
+        assert_eq!(read_snapshot(&path).expect("read snapshot"), pack);
+        assert_eq!(
+            read_pack_or_snapshot(&path).expect("read as diff side"),
+            pack
+        );
+    }
+
+    #[test]
+    fn lfs_pointer_packs_are_refused() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("en.rkyv");
+        std::fs::write(
+            &path,
+            "version https://git-lfs.github.com/spec/v1\noid sha256:00\nsize 1\n",
+        )
+        .expect("write pointer");
+
+        let error = read_pack_or_snapshot(&path).expect_err("a pointer is not a pack");
+        assert!(format!("{error:#}").contains("git lfs pull"));
+    }
+
+    #[test]
+    fn identical_packs_have_an_empty_diff() {
+        let pack = snapshot(&[(1, "Ore")], vec![recipe(10, 2, &[(1, 3)])], &[(1, 10)]);
+        assert!(diff("a", &pack, "a", &pack).is_empty());
+    }
+}
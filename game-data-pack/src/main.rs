@@ -2,8 +2,13 @@
 //!
 //! Fetches the pinned upstream sources (sparse + blobless), runs the CSV→rkyv
 //! pipeline for every language and the PNG→WebP pipeline for the item icons.
+//! Each build also records what changed against the packs it replaced in
+//! `data/patch_diff.json`, diffing against the snapshot the previous build
+//! left in `data/xiv-db/en.snapshot.json`, or against the old `en.rkyv` when
+//! there is no snapshot yet.
 
 mod db;
+mod diff;
 mod fetch;
 mod icons;
 mod manifest;
@@ -34,6 +39,10 @@ Options:
   --skip-icons             Skip the icon pack (no FFXIV install needed)
   --game-path <path>       FFXIV install root to extract icons from (default:
                            search the standard install locations)
+  --diff <old> <new>       Compare two xiv-db packs or pack snapshots
+                           (en.snapshot.json) and print what changed, without
+                           building anything
+  --json                   With --diff, print the report as JSON
 ";
 
 #[derive(Debug, PartialEq, Eq)]
@@ -48,6 +57,9 @@ struct Args {
     offline_source: Option<PathBuf>,
     skip_icons: bool,
     game_path: Option<PathBuf>,
+    /// `(old, new)` pack or snapshot files to compare instead of building.
+    diff: Option<(PathBuf, PathBuf)>,
+    json: bool,
 }
 
 fn main() {
@@ -65,6 +77,9 @@ fn run() -> anyhow::Result<()> {
             std::process::exit(2);
         }
     };
+    if let Some((old, new)) = &args.diff {
+        return run_diff(old, new, args.json);
+    }
 
     let repo_root = Path::new(env!("CARGO_MANIFEST_DIR"))
         .parent()
@@ -72,10 +87,19 @@ fn run() -> anyhow::Result<()> {
     let data_dir = repo_root.join("data");
 
     let manifest_path = data_dir.join("manifest.toml");
-    let layout = match &args.offline_source {
+    // The packs on disk were built from the current pins, so those name them.
+    let previous_revision = Manifest::load(&manifest_path)
+        .ok()
+        .as_ref()
+        .and_then(datamining_revision)
+        .unwrap_or_else(|| "unknown".to_string());
+    let (layout, revision) = match &args.offline_source {
         Some(checkout) => {
             println!("building from {} (no fetching)", checkout.display());
-            Layout::offline(checkout)
+            (
+                Layout::offline(checkout),
+                format!("local checkout {}", checkout.display()),
+            )
         }
         None => {
             let mut manifest = Manifest::load(&manifest_path)?;
@@ -87,11 +111,27 @@ fn run() -> anyhow::Result<()> {
                 manifest.save(&manifest_path)?;
                 println!("updated {}", manifest_path.display());
             }
-            fetch::fetch_all(&cache_root, &manifest)?
+            let revision = datamining_revision(&manifest).unwrap_or_else(|| "unknown".to_string());
+            (fetch::fetch_all(&cache_root, &manifest)?, revision)
         }
     };
 
     let xiv_db_dir = data_dir.join("xiv-db");
+    let snapshot_path = xiv_db_dir.join("en.snapshot.json");
+    // Read before the build overwrites it. Packs from before snapshots were
+    // written still decode as long as the `Data` layout hasn't changed since.
+    let previous = match diff::read_snapshot(&snapshot_path) {
+        Ok(snapshot) => Some(snapshot),
+        Err(snapshot_error) => match diff::read_pack(&xiv_db_dir.join("en.rkyv")) {
+            Ok(data) => Some(diff::PackSnapshot::from_data(&data)),
+            Err(pack_error) => {
+                println!(
+                    "no previous en snapshot ({snapshot_error:#}) or pack ({pack_error:#}) to diff against"
+                );
+                None
+            }
+        },
+    };
     let output = db::build_packs(&layout.datamining, &xiv_db_dir)?;
     println!("\nxiv-db packs -> {}", xiv_db_dir.display());
     for pack in &output.packs {
@@ -103,6 +143,17 @@ fn run() -> anyhow::Result<()> {
             mib(pack.packed_bytes),
         );
     }
+    diff::write_snapshot(&snapshot_path, &output.en_snapshot)?;
+    println!("  en snapshot -> {}", snapshot_path.display());
+    if let Some(previous) = previous {
+        let patch_diff = diff::diff(
+            &previous_revision,
+            &previous,
+            &revision,
+            &output.en_snapshot,
+        );
+        write_patch_diff(&data_dir.join("patch_diff.json"), &patch_diff)?;
+    }
 
     if args.skip_icons {
         println!("\nicons skipped");
@@ -175,6 +226,70 @@ fn run() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// `--diff`: prints what changed between two pack or snapshot files.
+fn run_diff(old: &Path, new: &Path, json: bool) -> anyhow::Result<()> {
+    let old_pack = diff::read_pack_or_snapshot(old)?;
+    let new_pack = diff::read_pack_or_snapshot(new)?;
+    let report = diff::diff(
+        &old.display().to_string(),
+        &old_pack,
+        &new.display().to_string(),
+        &new_pack,
+    );
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!(
+            "{}",
+            diff::render(&report, |item_id| {
+                new_pack
+                    .items
+                    .get(&item_id)
+                    .or_else(|| old_pack.items.get(&item_id))
+                    .cloned()
+                    .unwrap_or_else(|| format!("item {item_id}"))
+            })
+        );
+    }
+    Ok(())
+}
+
+/// The `ffxiv-datamining` commit a manifest pins, which names a pack build.
+fn datamining_revision(manifest: &Manifest) -> Option<String> {
+    manifest
+        .sources
+        .get("ffxiv-datamining")
+        .map(|source| source.sha.clone())
+}
+
+/// Replaces the committed patch report, unless the build changed nothing it
+/// covers. Rebuilding the same pins would otherwise wipe the last patch's
+/// report with an empty one.
+fn write_patch_diff(
+    path: &Path,
+    patch_diff: &ultros_api_types::patch_diff::PatchDiff,
+) -> anyhow::Result<()> {
+    if patch_diff.is_empty() {
+        println!(
+            "
+patch diff: nothing changed, keeping {}",
+            path.display()
+        );
+        return Ok(());
+    }
+    let json = serde_json::to_string_pretty(patch_diff)?;
+    std::fs::write(path, json + "\n").with_context(|| format!("writing {}", path.display()))?;
+    println!(
+        "\npatch diff -> {}\n  {} new items, {} new recipes, {} changed recipes, {} vendor price changes",
+        path.display(),
+        patch_diff.new_items.len(),
+        patch_diff.new_recipes.len(),
+        patch_diff.changed_recipes.len(),
+        patch_diff.vendor_price_changes.len(),
+    );
+    Ok(())
+}
+
 struct Extraction {
     /// `(icon id, image)` for every distinct icon that decoded.
     icons: Vec<(i32, image::RgbaImage)>,
@@ -310,6 +425,8 @@ fn parse_args(argv: impl IntoIterator<Item = String>) -> anyhow::Result<Args> {
         offline_source: None,
         skip_icons: false,
         game_path: None,
+        diff: None,
+        json: false,
     };
     let mut argv = argv.into_iter();
     while let Some(arg) = argv.next() {
@@ -331,6 +448,16 @@ fn parse_args(argv: impl IntoIterator<Item = String>) -> anyhow::Result<Args> {
                 }
                 args.game_path = Some(PathBuf::from(path));
             }
+            "--diff" => {
+                let (Some(old), Some(new)) = (argv.next(), argv.next()) else {
+                    bail!("--diff needs an old and a new pack path");
+                };
+                if old.starts_with("--") || new.starts_with("--") {
+                    bail!("--diff needs an old and a new pack path, got {old:?} {new:?}");
+                }
+                args.diff = Some((PathBuf::from(old), PathBuf::from(new)));
+            }
+            "--json" => args.json = true,
             "--help" | "-h" => {
                 println!("{USAGE}");
                 std::process::exit(0);
@@ -341,6 +468,12 @@ fn parse_args(argv: impl IntoIterator<Item = String>) -> anyhow::Result<Args> {
     if args.pins == Pins::Latest && args.offline_source.is_some() {
         bail!("--latest cannot be combined with --offline-source: an offline build never fetches");
     }
+    if args.diff.is_some() && (args.pins == Pins::Latest || args.offline_source.is_some()) {
+        bail!("--diff compares two existing packs and cannot be combined with a build option");
+    }
+    if args.json && args.diff.is_none() {
+        bail!("--json only applies to --diff");
+    }
     Ok(args)
 }
 
@@ -418,6 +551,20 @@ mod arg_tests {
         assert!(newest_missing(&[], 20).is_empty());
     }
 
+    #[test]
+    fn diff_takes_two_paths_and_runs_alone() {
+        let args = parse(&["--diff", "old.json", "new.json", "--json"]).expect("should parse");
+        assert_eq!(
+            args.diff,
+            Some((PathBuf::from("old.json"), PathBuf::from("new.json")))
+        );
+        assert!(args.json);
+        assert!(parse(&["--diff", "old.json"]).is_err());
+        assert!(parse(&["--diff", "old.json", "--json"]).is_err());
+        assert!(parse(&["--diff", "a", "b", "--latest"]).is_err());
+        assert!(parse(&["--json"]).is_err());
+    }
+
     #[test]
     fn rejects_latest_with_offline_source() {
         assert!(parse(&["--latest", "--offline-source", "/repo"]).is_err());
@@ -37,6 +37,12 @@ mod m20260805_000001_group_invite;
 mod m20260808_000001_active_listing_identity_columns;
 mod m20260809_000001_notification_endpoint_health;
 mod m20260811_000001_drop_unused_sale_history_full_index;
+mod m20261017_000001_alert_expression;
+mod m20261017_000002_alert_market_move;
+mod m20261017_000003_alert_digest;
+mod m20261017_000004_user_session;
+mod m20261017_000005_api_token;
+mod m20261017_000006_retainer_sale;
 
 pub struct Migrator;
 
@@ -83,6 +89,12 @@ impl MigratorTrait for Migrator {
             Box::new(m20260808_000001_active_listing_identity_columns::Migration),
             Box::new(m20260809_000001_notification_endpoint_health::Migration),
             Box::new(m20260811_000001_drop_unused_sale_history_full_index::Migration),
+            Box::new(m20261017_000001_alert_expression::Migration),
+            Box::new(m20261017_000002_alert_market_move::Migration),
+            Box::new(m20261017_000003_alert_digest::Migration),
+            Box::new(m20261017_000004_user_session::Migration),
+            Box::new(m20261017_000005_api_token::Migration),
+            Box::new(m20261017_000006_retainer_sale::Migration),
         ]
     }
 }
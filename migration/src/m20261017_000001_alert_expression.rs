@@ -0,0 +1,65 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240424_000001_create_notification_endpoints::Alert;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AlertExpression::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AlertExpression::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AlertExpression::AlertId)
+                            .integer()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(AlertExpression::ItemId).integer().not_null())
+                    .col(
+                        ColumnDef::new(AlertExpression::WorldSelector)
+                            .json()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AlertExpression::Rule).json().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_alert_expression_alert_id")
+                            .from(AlertExpression::Table, AlertExpression::AlertId)
+                            .to(Alert::Table, Alert::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AlertExpression::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum AlertExpression {
+    Table,
+    Id,
+    AlertId,
+    ItemId,
+    WorldSelector,
+    Rule,
+}
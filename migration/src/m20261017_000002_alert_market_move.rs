@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240424_000001_create_notification_endpoints::Alert;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AlertMarketMove::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AlertMarketMove::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AlertMarketMove::AlertId)
+                            .integer()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(AlertMarketMove::ItemId).integer().not_null())
+                    .col(
+                        ColumnDef::new(AlertMarketMove::WorldSelector)
+                            .json()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AlertMarketMove::HqOnly)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(AlertMarketMove::Rule).json().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_alert_market_move_alert_id")
+                            .from(AlertMarketMove::Table, AlertMarketMove::AlertId)
+                            .to(Alert::Table, Alert::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AlertMarketMove::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum AlertMarketMove {
+    Table,
+    Id,
+    AlertId,
+    ItemId,
+    WorldSelector,
+    HqOnly,
+    Rule,
+}
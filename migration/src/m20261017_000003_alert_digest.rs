@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Per-endpoint delivery schedule.
+        //
+        // `schedule` holds a serialized `DeliverySchedule`; NULL means fires
+        // are sent immediately, which is how every existing endpoint keeps
+        // behaving. `digest_sent_at` is when the last summary went out, so an
+        // hourly digest measures its interval from there.
+        db.execute_unprepared(
+            r#"ALTER TABLE notification_endpoint
+                ADD COLUMN IF NOT EXISTS schedule json,
+                ADD COLUMN IF NOT EXISTS digest_sent_at timestamp with time zone"#,
+        )
+        .await?;
+
+        // Fires held for a scheduled endpoint until its next summary. One row
+        // per (fire, endpoint): an alert bound to both an immediate and a
+        // digest endpoint still reaches the immediate one straight away.
+        db.execute_unprepared(
+            r#"CREATE TABLE IF NOT EXISTS alert_digest_entry (
+                id bigserial PRIMARY KEY,
+                endpoint_id integer NOT NULL
+                    REFERENCES notification_endpoint (id) ON DELETE CASCADE,
+                alert_id integer NOT NULL REFERENCES alert (id) ON DELETE CASCADE,
+                title text NOT NULL,
+                click_url text NOT NULL,
+                queued_at timestamp with time zone NOT NULL DEFAULT now()
+            )"#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"CREATE INDEX IF NOT EXISTS alert_digest_entry_endpoint_id
+                ON alert_digest_entry (endpoint_id, queued_at)"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(r#"DROP TABLE IF EXISTS alert_digest_entry"#)
+            .await?;
+        db.execute_unprepared(
+            r#"ALTER TABLE notification_endpoint
+                DROP COLUMN IF EXISTS schedule,
+                DROP COLUMN IF EXISTS digest_sent_at"#,
+        )
+        .await?;
+        Ok(())
+    }
+}
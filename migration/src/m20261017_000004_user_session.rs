@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Login sessions. The browser holds a random token in the private
+        // `discord_auth` cookie; only its SHA-256 lands here, so reading this
+        // table doesn't yield working sessions. `expires_at` slides forward as
+        // the session is used.
+        db.execute_unprepared(
+            r#"CREATE TABLE IF NOT EXISTS user_session (
+                id bigserial PRIMARY KEY,
+                token_hash text NOT NULL UNIQUE,
+                discord_user_id bigint NOT NULL
+                    REFERENCES discord_user (id) ON DELETE CASCADE,
+                avatar_url text NOT NULL,
+                user_agent text,
+                created_at timestamp with time zone NOT NULL DEFAULT now(),
+                last_seen_at timestamp with time zone NOT NULL DEFAULT now(),
+                expires_at timestamp with time zone NOT NULL
+            )"#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"CREATE INDEX IF NOT EXISTS user_session_discord_user_id
+                ON user_session (discord_user_id)"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(r#"DROP TABLE IF EXISTS user_session"#)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Personal API tokens for scripts, sent as `Authorization: Bearer`.
+        // Like `user_session`, only the SHA-256 of the token is kept. `scopes`
+        // is a space-separated list such as `read:lists manage:alerts`; a
+        // null `expires_at` never expires.
+        db.execute_unprepared(
+            r#"CREATE TABLE IF NOT EXISTS api_token (
+                id bigserial PRIMARY KEY,
+                token_hash text NOT NULL UNIQUE,
+                discord_user_id bigint NOT NULL
+                    REFERENCES discord_user (id) ON DELETE CASCADE,
+                name text NOT NULL,
+                scopes text NOT NULL,
+                avatar_url text NOT NULL,
+                created_at timestamp with time zone NOT NULL DEFAULT now(),
+                expires_at timestamp with time zone,
+                last_used_at timestamp with time zone
+            )"#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"CREATE INDEX IF NOT EXISTS api_token_discord_user_id
+                ON api_token (discord_user_id)"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(r#"DROP TABLE IF EXISTS api_token"#)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // The retainer sales ledger: sales attributed to a tracked retainer by
+        // matching one of its listings disappearing against a sale of the
+        // same item, quality, quantity and price on the same world.
+        // `sale_history_id` is unique so a sale is only ever credited once;
+        // it deliberately has no foreign key, since the ledger outlives
+        // `sale_history` pruning. `tax` is the 5% marketboard cut of `gross`
+        // and `listed_at` is the listing's last review time, which bounds how
+        // long it took to sell.
+        db.execute_unprepared(
+            r#"CREATE TABLE IF NOT EXISTS retainer_sale (
+                id bigserial PRIMARY KEY,
+                retainer_id integer NOT NULL
+                    REFERENCES retainer (id) ON DELETE CASCADE,
+                sale_history_id integer NOT NULL UNIQUE,
+                world_id integer NOT NULL,
+                item_id integer NOT NULL,
+                hq boolean NOT NULL,
+                quantity integer NOT NULL,
+                price_per_unit integer NOT NULL,
+                gross bigint NOT NULL,
+                tax bigint NOT NULL,
+                listed_at timestamp NOT NULL,
+                sold_at timestamp NOT NULL
+            )"#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"CREATE INDEX IF NOT EXISTS retainer_sale_retainer_sold_at
+                ON retainer_sale (retainer_id, sold_at)"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(r#"DROP TABLE IF EXISTS retainer_sale"#)
+            .await?;
+        Ok(())
+    }
+}
@@ -1,6 +1,11 @@
+use chrono::{DateTime, Duration, Timelike, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 
-use crate::world_helper::AnySelector;
+use crate::{
+    alert_rule::{AlertRule, MarketMoveRule},
+    world_helper::AnySelector,
+};
 
 /// What kind of condition the alert checks.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -22,6 +27,23 @@ pub enum AlertTrigger {
     RetainerUndercut { margin_percent: i32 },
     /// Fire when a list or one of its rows changes.
     ListUpdate { list_id: i32 },
+    /// Fire when a listing for this item satisfies `rule`. `world_selector`
+    /// scopes both the listings considered and the worlds the rule's market
+    /// statistics are aggregated over.
+    Expression {
+        item_id: i32,
+        world_selector: AnySelector,
+        rule: AlertRule,
+    },
+    /// Fire when the cheapest listing or latest sale for this item moves
+    /// further than the rule allows from its rolling median or band, measured
+    /// across `world_selector`.
+    MarketMove {
+        item_id: i32,
+        world_selector: AnySelector,
+        hq_only: bool,
+        rule: MarketMoveRule,
+    },
 }
 
 /// Where to send a fired alert.
@@ -114,6 +136,69 @@ pub enum EndpointMethod {
     WebPush {
         subscription_id: i32,
     },
+    /// POST arbitrary JSON to any https URL (Slack, Home Assistant, a personal
+    /// bot). `template` is JSON text whose strings may reference `{{variable}}`
+    /// placeholders; see [`WEBHOOK_TEMPLATE_VARIABLES`]. Without a template the
+    /// server sends an object carrying every variable.
+    GenericWebhook {
+        url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        template: Option<String>,
+        /// Extra request headers, sent verbatim on every delivery.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        headers: Vec<WebhookHeader>,
+        /// HMAC-SHA256 signing key. Write-only: the server never returns it and
+        /// reports `signed` instead. On update, `None` keeps the stored secret
+        /// and an empty string removes it.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        secret: Option<String>,
+        /// Whether deliveries carry an `X-Ultros-Signature` header. Set by the
+        /// server; ignored on create and update.
+        #[serde(default)]
+        signed: bool,
+    },
+}
+
+/// A custom header sent with every [`EndpointMethod::GenericWebhook`] delivery.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookHeader {
+    pub name: String,
+    pub value: String,
+}
+
+/// Placeholders a generic webhook template may use. A string that is exactly
+/// one placeholder (`"{{price}}"`) becomes the typed value — a number, or
+/// `null` when the fire has no such detail — while placeholders inside longer
+/// strings are spliced in as text.
+pub const WEBHOOK_TEMPLATE_VARIABLES: &[&str] = &[
+    "title",
+    "body",
+    "url",
+    "alert_id",
+    "item_id",
+    "item_name",
+    "world_id",
+    "world_name",
+    "price",
+    "listing_id",
+    "retainer_name",
+    "fired_at",
+];
+
+/// Names of the `{{placeholder}}`s in `text`, in order. An opening `{{`
+/// without a closing `}}` is literal text.
+pub fn webhook_template_placeholders(text: &str) -> Vec<&str> {
+    let mut names = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        names.push(&after[..end]);
+        rest = &after[end + 2..];
+    }
+    names
 }
 
 /// Body for `POST /api/v1/push/subscribe`. The browser obtains `endpoint`, `p256dh`,
@@ -149,6 +234,8 @@ pub struct Endpoint {
     /// the added field.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub disabled_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "DeliverySchedule::is_immediate")]
+    pub schedule: DeliverySchedule,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -156,12 +243,121 @@ pub struct CreateEndpointRequest {
     pub name: String,
     #[serde(flatten)]
     pub method: EndpointMethod,
+    #[serde(default, skip_serializing_if = "DeliverySchedule::is_immediate")]
+    pub schedule: DeliverySchedule,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct UpdateEndpointRequest {
     pub name: Option<String>,
     pub method: Option<EndpointMethod>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<DeliverySchedule>,
+}
+
+/// Shortest digest interval an endpoint may use, in minutes.
+pub const MIN_DIGEST_INTERVAL_MINUTES: i32 = 15;
+/// Longest digest interval an endpoint may use, in minutes (one week).
+pub const MAX_DIGEST_INTERVAL_MINUTES: i32 = 7 * 24 * 60;
+
+/// When alert fires reach an endpoint.
+///
+/// Anything other than `Immediate` holds fires in a per-endpoint queue and
+/// sends them later as one summary message, so a busy alert on patch day
+/// produces one message instead of hundreds.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum DeliverySchedule {
+    /// Send every fire as it happens.
+    #[default]
+    Immediate,
+    /// Send one summary every `interval_minutes` (60 = hourly, 1440 = daily).
+    Digest { interval_minutes: i32 },
+    /// Send fires as they happen, except between `start_hour` and `end_hour`
+    /// local time; fires in that window are summarised when it ends. The
+    /// window may wrap midnight. `timezone` is an IANA zone name such as
+    /// `America/New_York`, so the window follows daylight saving.
+    QuietHours {
+        start_hour: u8,
+        end_hour: u8,
+        timezone: String,
+    },
+}
+
+impl DeliverySchedule {
+    pub fn is_immediate(&self) -> bool {
+        matches!(self, DeliverySchedule::Immediate)
+    }
+
+    /// A human-readable reason the schedule can't be used, if any.
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            DeliverySchedule::Immediate => Ok(()),
+            DeliverySchedule::Digest { interval_minutes } => {
+                if (MIN_DIGEST_INTERVAL_MINUTES..=MAX_DIGEST_INTERVAL_MINUTES)
+                    .contains(&interval_minutes)
+                {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "digest interval must be between {MIN_DIGEST_INTERVAL_MINUTES} and {MAX_DIGEST_INTERVAL_MINUTES} minutes"
+                    ))
+                }
+            }
+            DeliverySchedule::QuietHours {
+                start_hour,
+                end_hour,
+                ref timezone,
+            } => {
+                if start_hour > 23 || end_hour > 23 {
+                    Err("quiet hours must be between 0 and 23".to_string())
+                } else if start_hour == end_hour {
+                    Err("quiet hours must not start and end at the same hour".to_string())
+                } else if timezone.parse::<Tz>().is_err() {
+                    Err(format!("unknown timezone {timezone:?}"))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Whether a fire at `now` should be queued for a later summary rather
+    /// than sent straight away.
+    pub fn holds_at(&self, now: DateTime<Utc>) -> bool {
+        match *self {
+            DeliverySchedule::Immediate => false,
+            DeliverySchedule::Digest { .. } => true,
+            DeliverySchedule::QuietHours {
+                start_hour,
+                end_hour,
+                ref timezone,
+            } => {
+                // Validation rejects unknown zones; a stored one that has
+                // since vanished from the database falls back to UTC.
+                let zone = timezone.parse::<Tz>().unwrap_or(Tz::UTC);
+                let hour = now.with_timezone(&zone).hour() as u8;
+                if start_hour < end_hour {
+                    (start_hour..end_hour).contains(&hour)
+                } else {
+                    hour >= start_hour || hour < end_hour
+                }
+            }
+        }
+    }
+
+    /// Whether queued fires should be summarised at `now`. `since` is when
+    /// the last summary went out, or when the oldest queued fire arrived if
+    /// none has yet. Switching back to `Immediate` flushes anything left.
+    pub fn summary_due(&self, since: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        match *self {
+            DeliverySchedule::Immediate => true,
+            DeliverySchedule::Digest { interval_minutes } => {
+                now - since >= Duration::minutes(interval_minutes as i64)
+            }
+            DeliverySchedule::QuietHours { .. } => !self.holds_at(now),
+        }
+    }
 }
 
 /// Response for `DELETE /api/v1/endpoints/{id}`.
@@ -214,6 +410,7 @@ mod endpoint_tests {
                 guild_name: None,
             },
             disabled_reason: Some("Unknown Channel".to_string()),
+            schedule: DeliverySchedule::Immediate,
         };
         let v = serde_json::to_value(&e).unwrap();
         assert_eq!(v["disabled_reason"], json!("Unknown Channel"));
@@ -244,6 +441,7 @@ mod endpoint_tests {
                 url: "https://example.invalid".into(),
             },
             disabled_reason: None,
+            schedule: DeliverySchedule::Immediate,
         };
         let v = serde_json::to_value(&e).unwrap();
         assert_eq!(
@@ -262,6 +460,9 @@ mod endpoint_tests {
                 guild_id: None,
                 guild_name: None,
             },
+            schedule: DeliverySchedule::Digest {
+                interval_minutes: 60,
+            },
         };
         let s = serde_json::to_string(&req).unwrap();
         let back: CreateEndpointRequest = serde_json::from_str(&s).unwrap();
@@ -273,6 +474,7 @@ mod endpoint_tests {
         let req = UpdateEndpointRequest {
             name: None,
             method: None,
+            schedule: None,
         };
         let s = serde_json::to_string(&req).unwrap();
         let back: UpdateEndpointRequest = serde_json::from_str(&s).unwrap();
@@ -284,6 +486,7 @@ mod endpoint_tests {
         let req = UpdateEndpointRequest {
             name: Some("renamed".to_string()),
             method: None,
+            schedule: None,
         };
         let s = serde_json::to_string(&req).unwrap();
         let back: UpdateEndpointRequest = serde_json::from_str(&s).unwrap();
@@ -297,17 +500,141 @@ mod endpoint_tests {
             method: Some(EndpointMethod::Webhook {
                 url: "https://discord.com/api/webhooks/1/abc".into(),
             }),
+            schedule: Some(DeliverySchedule::QuietHours {
+                start_hour: 23,
+                end_hour: 7,
+                timezone: "Europe/London".to_string(),
+            }),
         };
         let s = serde_json::to_string(&req).unwrap();
         let back: UpdateEndpointRequest = serde_json::from_str(&s).unwrap();
         assert_eq!(req, back);
     }
 
+    #[test]
+    fn endpoint_without_schedule_is_immediate() {
+        let v =
+            json!({"id": 7, "name": "Test", "method": "Webhook", "url": "https://example.invalid"});
+        let e: Endpoint = serde_json::from_value(v).unwrap();
+        assert_eq!(e.schedule, DeliverySchedule::Immediate);
+    }
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        use chrono::TimeZone;
+        Utc.with_ymd_and_hms(2026, 10, 17, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn quiet_hours_wrap_midnight_in_local_time() {
+        // 22:00-07:00 in Berlin, on summer time until the 25th: 20:00-05:00
+        // UTC.
+        let quiet = DeliverySchedule::QuietHours {
+            start_hour: 22,
+            end_hour: 7,
+            timezone: "Europe/Berlin".to_string(),
+        };
+        assert!(!quiet.holds_at(at(19, 59)));
+        assert!(quiet.holds_at(at(20, 0)));
+        assert!(quiet.holds_at(at(3, 0)));
+        assert!(!quiet.holds_at(at(5, 0)));
+        assert!(!quiet.summary_due(at(20, 0), at(4, 0)));
+        assert!(quiet.summary_due(at(20, 0), at(5, 0)));
+    }
+
+    #[test]
+    fn quiet_hours_follow_daylight_saving() {
+        use chrono::TimeZone;
+        let quiet = DeliverySchedule::QuietHours {
+            start_hour: 22,
+            end_hour: 7,
+            timezone: "Europe/Berlin".to_string(),
+        };
+        // 20:30 UTC is 22:30 in October but 21:30 once winter time starts.
+        assert!(quiet.holds_at(at(20, 30)));
+        let winter = Utc.with_ymd_and_hms(2026, 11, 17, 20, 30, 0).unwrap();
+        assert!(!quiet.holds_at(winter));
+    }
+
+    #[test]
+    fn digest_is_due_once_the_interval_has_passed() {
+        let hourly = DeliverySchedule::Digest {
+            interval_minutes: 60,
+        };
+        assert!(hourly.holds_at(at(12, 0)));
+        assert!(!hourly.summary_due(at(12, 0), at(12, 59)));
+        assert!(hourly.summary_due(at(12, 0), at(13, 0)));
+        // Leftovers from a previous schedule go out straight away.
+        assert!(DeliverySchedule::Immediate.summary_due(at(12, 0), at(12, 0)));
+        assert!(!DeliverySchedule::Immediate.holds_at(at(12, 0)));
+    }
+
+    #[test]
+    fn schedule_validation_rejects_nonsense() {
+        assert!(
+            DeliverySchedule::Digest {
+                interval_minutes: 1
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(
+            DeliverySchedule::QuietHours {
+                start_hour: 8,
+                end_hour: 8,
+                timezone: "UTC".to_string(),
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(
+            DeliverySchedule::QuietHours {
+                start_hour: 24,
+                end_hour: 8,
+                timezone: "UTC".to_string(),
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(
+            DeliverySchedule::QuietHours {
+                start_hour: 22,
+                end_hour: 8,
+                timezone: "Mars/Olympus_Mons".to_string(),
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(
+            DeliverySchedule::Digest {
+                interval_minutes: 1440
+            }
+            .validate()
+            .is_ok()
+        );
+    }
+
     #[test]
     fn alert_trigger_new_variants_round_trip() {
         for trigger in [
             AlertTrigger::RetainerUndercut { margin_percent: 5 },
             AlertTrigger::ListUpdate { list_id: 42 },
+            AlertTrigger::Expression {
+                item_id: 5057,
+                world_selector: AnySelector::Datacenter(3),
+                rule: crate::alert_rule::AlertRule::Hq,
+            },
+            AlertTrigger::MarketMove {
+                item_id: 5057,
+                world_selector: AnySelector::Region(1),
+                hq_only: true,
+                rule: MarketMoveRule {
+                    source: crate::alert_rule::MoveSource::LatestSale,
+                    direction: crate::alert_rule::MoveDirection::Either,
+                    reference: crate::alert_rule::MoveReference::Band,
+                    percent: 25,
+                    window_days: 30,
+                },
+            },
         ] {
             let s = serde_json::to_string(&trigger).unwrap();
             let back: AlertTrigger = serde_json::from_str(&s).unwrap();
@@ -327,4 +654,43 @@ mod endpoint_tests {
         let back: UpdateAlertRequest = serde_json::from_str(&s).unwrap();
         assert_eq!(req, back);
     }
+
+    #[test]
+    fn generic_webhook_endpoint_round_trips() {
+        let endpoint = Endpoint {
+            id: 4,
+            name: "Home Assistant".into(),
+            method: EndpointMethod::GenericWebhook {
+                url: "https://ha.example.com/api/webhook/ultros".into(),
+                template: Some(r#"{"text": "{{title}}", "price": "{{price}}"}"#.into()),
+                headers: vec![WebhookHeader {
+                    name: "Authorization".into(),
+                    value: "Bearer abc".into(),
+                }],
+                secret: None,
+                signed: true,
+            },
+            disabled_reason: None,
+            schedule: DeliverySchedule::Immediate,
+        };
+        let s = serde_json::to_string(&endpoint).unwrap();
+        assert!(s.contains(r#""method":"GenericWebhook""#));
+        assert!(!s.contains("secret"));
+        let back: Endpoint = serde_json::from_str(&s).unwrap();
+        assert_eq!(endpoint, back);
+    }
+
+    #[test]
+    fn template_placeholders_are_found_in_order() {
+        assert_eq!(
+            webhook_template_placeholders("{{item_name}} is {{price}} gil on {{world_name}}"),
+            vec!["item_name", "price", "world_name"]
+        );
+        assert_eq!(
+            webhook_template_placeholders("{{ price }}"),
+            vec![" price "]
+        );
+        assert!(webhook_template_placeholders("no {{ closing").is_empty());
+        assert!(webhook_template_placeholders("plain").is_empty());
+    }
 }
@@ -0,0 +1,644 @@
+//! Composable alert rules.
+//!
+//! The fixed [`AlertTrigger`](crate::alert::AlertTrigger) variants each
+//! answer one question ("is anything below N gil?"). An [`AlertRule`] is a
+//! small serde expression tree, in the spirit of
+//! [`FilterPredicate`](crate::websocket::FilterPredicate), that can also
+//! compare a listing against the rolling market statistics the analyzer
+//! keeps per item. "HQ under 60% of the 7-day median, and at least 3 sales a
+//! day" is:
+//!
+//! ```json
+//! {"op": "all", "rules": [
+//!   {"op": "hq"},
+//!   {"op": "compare",
+//!    "left": {"term": "listing_price"}, "cmp": "lt",
+//!    "right": {"term": "scaled", "percent": 60,
+//!              "of": {"term": "stat", "stat": "median", "window_days": 7}}},
+//!   {"op": "compare",
+//!    "left": {"term": "stat", "stat": "sales_per_day", "window_days": 7},
+//!    "cmp": "ge", "right": {"term": "constant", "value": 3}}
+//! ]}
+//! ```
+//!
+//! Numbers are integers on the wire (gil, units, percent) so the rule stays
+//! `Eq` alongside the rest of the alert types; evaluation happens in `f64`.
+//!
+//! Statistics can be missing — a new item has no rollup yet. Evaluation is
+//! three-valued for that reason: a comparison against a missing statistic is
+//! *unknown*, not false, so `not` can't turn "no data" into a fire. Only a
+//! rule that evaluates to a definite `true` fires.
+//!
+//! [`MarketMoveRule`] is the common case packaged up: "tell me when the price
+//! moves X% away from normal". It compiles down to an [`AlertRule`], so both
+//! share one evaluator.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Rollup windows the server keeps statistics for, in days.
+pub const STAT_WINDOWS: [u16; 4] = [1, 7, 30, 90];
+/// Deepest nesting a rule may use. Rules are user-authored and evaluated on
+/// the listing hot path, so both depth and size are bounded.
+pub const MAX_RULE_DEPTH: usize = 8;
+/// Most nodes (rules and terms together) a rule may contain.
+pub const MAX_RULE_NODES: usize = 64;
+/// Largest multiplier a `scaled` term accepts, in percent.
+pub const MAX_SCALE_PERCENT: i64 = 10_000;
+/// Largest spike a [`MarketMoveRule`] can watch for, in percent.
+pub const MAX_MOVE_PERCENT: i32 = 1_000;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum AlertRule {
+    /// True when every child is true.
+    All {
+        rules: Vec<AlertRule>,
+    },
+    /// True when any child is true.
+    Any {
+        rules: Vec<AlertRule>,
+    },
+    Not {
+        rule: Box<AlertRule>,
+    },
+    /// True when the listing is high quality.
+    Hq,
+    Compare {
+        left: RuleTerm,
+        cmp: RuleComparison,
+        right: RuleTerm,
+    },
+}
+
+/// A number a rule can compare.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "term", rename_all = "snake_case")]
+pub enum RuleTerm {
+    Constant {
+        value: i64,
+    },
+    /// Unit price of the listing being evaluated.
+    ListingPrice,
+    ListingQuantity,
+    /// A rolling statistic over the alert's world scope, for the same quality
+    /// (HQ or NQ) as the listing being evaluated.
+    Stat {
+        stat: MarketStat,
+        window_days: u16,
+    },
+    /// `of × percent / 100`.
+    Scaled {
+        percent: i64,
+        of: Box<RuleTerm>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketStat {
+    /// Median unit price of the noise-filtered sales.
+    Median,
+    /// Volume-weighted average unit price of the noise-filtered sales.
+    Vwap,
+    P10,
+    P25,
+    P75,
+    P90,
+    /// Sales (transactions, not units) per day.
+    SalesPerDay,
+    UnitsPerDay,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleComparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl RuleComparison {
+    fn compare(self, left: f64, right: f64) -> bool {
+        match self {
+            RuleComparison::Lt => left < right,
+            RuleComparison::Le => left <= right,
+            RuleComparison::Gt => left > right,
+            RuleComparison::Ge => left >= right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AlertRuleError {
+    #[error("rule nests deeper than {MAX_RULE_DEPTH} levels")]
+    TooDeep,
+    #[error("rule has more than {MAX_RULE_NODES} nodes")]
+    TooLarge,
+    #[error("`all`/`any` needs at least one rule")]
+    EmptyGroup,
+    #[error("window_days must be one of 1, 7, 30 or 90 (got {0})")]
+    UnsupportedWindow(u16),
+    #[error("percent must be between 1 and {MAX_SCALE_PERCENT} (got {0})")]
+    ScaleOutOfRange(i64),
+    #[error("comparison between two constants never changes")]
+    ConstantComparison,
+    #[error("percent must be between 1 and {0} for this direction (got {1})")]
+    MoveOutOfRange(i32, i32),
+}
+
+/// Which price a [`MarketMoveRule`] watches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveSource {
+    /// The cheapest listing across the alert's worlds, re-checked whenever
+    /// listings are added or removed there.
+    CheapestListing,
+    /// Each new sale across the alert's worlds.
+    LatestSale,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveDirection {
+    Drop,
+    Spike,
+    Either,
+}
+
+/// What "normal" is for a [`MarketMoveRule`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveReference {
+    /// The rolling median.
+    Median,
+    /// The interquartile band: drops are measured from p25, spikes from p75.
+    /// Quieter than the median on items whose price normally wanders.
+    Band,
+}
+
+/// Fire when a price moves more than `percent` away from its rolling market.
+///
+/// Relative thresholds survive the price shifts every patch brings, where a
+/// fixed gil figure would need retuning.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketMoveRule {
+    pub source: MoveSource,
+    pub direction: MoveDirection,
+    pub reference: MoveReference,
+    pub percent: i32,
+    pub window_days: u16,
+}
+
+impl MarketMoveRule {
+    pub fn validate(&self) -> Result<(), AlertRuleError> {
+        // A 100% drop is a price of zero, which never happens.
+        let max = match self.direction {
+            MoveDirection::Drop | MoveDirection::Either => 99,
+            MoveDirection::Spike => MAX_MOVE_PERCENT,
+        };
+        if !(1..=max).contains(&self.percent) {
+            return Err(AlertRuleError::MoveOutOfRange(max, self.percent));
+        }
+        self.to_rule(false).validate()
+    }
+
+    /// The equivalent [`AlertRule`], with an HQ guard when `hq_only`.
+    pub fn to_rule(&self, hq_only: bool) -> AlertRule {
+        let stat = |stat| {
+            Box::new(RuleTerm::Stat {
+                stat,
+                window_days: self.window_days,
+            })
+        };
+        let (low, high) = match self.reference {
+            MoveReference::Median => (MarketStat::Median, MarketStat::Median),
+            MoveReference::Band => (MarketStat::P25, MarketStat::P75),
+        };
+        let drop = AlertRule::Compare {
+            left: RuleTerm::ListingPrice,
+            cmp: RuleComparison::Lt,
+            right: RuleTerm::Scaled {
+                percent: 100 - self.percent as i64,
+                of: stat(low),
+            },
+        };
+        let spike = AlertRule::Compare {
+            left: RuleTerm::ListingPrice,
+            cmp: RuleComparison::Gt,
+            right: RuleTerm::Scaled {
+                percent: 100 + self.percent as i64,
+                of: stat(high),
+            },
+        };
+        let moved = match self.direction {
+            MoveDirection::Drop => drop,
+            MoveDirection::Spike => spike,
+            MoveDirection::Either => AlertRule::Any {
+                rules: vec![drop, spike],
+            },
+        };
+        if hq_only {
+            AlertRule::All {
+                rules: vec![AlertRule::Hq, moved],
+            }
+        } else {
+            moved
+        }
+    }
+}
+
+/// What a rule is evaluated against: one listing plus the market statistics
+/// for its item, quality and the alert's world scope. Rules that watch sales
+/// see the sale through the same `listing_*` accessors.
+pub trait RuleInputs {
+    fn listing_price(&self) -> i32;
+    fn listing_quantity(&self) -> i32;
+    fn listing_hq(&self) -> bool;
+    /// `None` when there is no rollup for this item/quality/window yet.
+    fn stat(&self, stat: MarketStat, window_days: u16) -> Option<f64>;
+}
+
+impl AlertRule {
+    /// Check a rule before storing it. Structural limits keep evaluation cheap;
+    /// the remaining checks reject rules that can't mean what the user meant.
+    pub fn validate(&self) -> Result<(), AlertRuleError> {
+        let mut nodes = 0;
+        self.validate_at(1, &mut nodes)
+    }
+
+    fn validate_at(&self, depth: usize, nodes: &mut usize) -> Result<(), AlertRuleError> {
+        if depth > MAX_RULE_DEPTH {
+            return Err(AlertRuleError::TooDeep);
+        }
+        *nodes += 1;
+        if *nodes > MAX_RULE_NODES {
+            return Err(AlertRuleError::TooLarge);
+        }
+        match self {
+            AlertRule::All { rules } | AlertRule::Any { rules } => {
+                if rules.is_empty() {
+                    return Err(AlertRuleError::EmptyGroup);
+                }
+                for rule in rules {
+                    rule.validate_at(depth + 1, nodes)?;
+                }
+                Ok(())
+            }
+            AlertRule::Not { rule } => rule.validate_at(depth + 1, nodes),
+            AlertRule::Hq => Ok(()),
+            AlertRule::Compare { left, right, .. } => {
+                if left.is_constant() && right.is_constant() {
+                    return Err(AlertRuleError::ConstantComparison);
+                }
+                left.validate_at(depth + 1, nodes)?;
+                right.validate_at(depth + 1, nodes)
+            }
+        }
+    }
+
+    /// Statistic windows the rule reads, so the caller can fetch exactly those.
+    pub fn stat_windows(&self) -> BTreeSet<u16> {
+        let mut windows = BTreeSet::new();
+        self.collect_windows(&mut windows);
+        windows
+    }
+
+    fn collect_windows(&self, windows: &mut BTreeSet<u16>) {
+        match self {
+            AlertRule::All { rules } | AlertRule::Any { rules } => {
+                rules.iter().for_each(|r| r.collect_windows(windows))
+            }
+            AlertRule::Not { rule } => rule.collect_windows(windows),
+            AlertRule::Hq => {}
+            AlertRule::Compare { left, right, .. } => {
+                left.collect_windows(windows);
+                right.collect_windows(windows);
+            }
+        }
+    }
+
+    /// Three-valued evaluation: `None` when the answer depends on a statistic
+    /// that isn't available.
+    pub fn evaluate<I: RuleInputs>(&self, inputs: &I) -> Option<bool> {
+        match self {
+            AlertRule::All { rules } => {
+                let mut unknown = false;
+                for rule in rules {
+                    match rule.evaluate(inputs) {
+                        Some(false) => return Some(false),
+                        None => unknown = true,
+                        Some(true) => {}
+                    }
+                }
+                (!unknown).then_some(true)
+            }
+            AlertRule::Any { rules } => {
+                let mut unknown = false;
+                for rule in rules {
+                    match rule.evaluate(inputs) {
+                        Some(true) => return Some(true),
+                        None => unknown = true,
+                        Some(false) => {}
+                    }
+                }
+                (!unknown).then_some(false)
+            }
+            AlertRule::Not { rule } => rule.evaluate(inputs).map(|b| !b),
+            AlertRule::Hq => Some(inputs.listing_hq()),
+            AlertRule::Compare { left, cmp, right } => {
+                Some(cmp.compare(left.value(inputs)?, right.value(inputs)?))
+            }
+        }
+    }
+
+    /// True only when the rule definitely holds for `inputs`.
+    pub fn matches<I: RuleInputs>(&self, inputs: &I) -> bool {
+        self.evaluate(inputs) == Some(true)
+    }
+}
+
+impl RuleTerm {
+    fn is_constant(&self) -> bool {
+        match self {
+            RuleTerm::Constant { .. } => true,
+            RuleTerm::Scaled { of, .. } => of.is_constant(),
+            RuleTerm::ListingPrice | RuleTerm::ListingQuantity | RuleTerm::Stat { .. } => false,
+        }
+    }
+
+    fn validate_at(&self, depth: usize, nodes: &mut usize) -> Result<(), AlertRuleError> {
+        if depth > MAX_RULE_DEPTH {
+            return Err(AlertRuleError::TooDeep);
+        }
+        *nodes += 1;
+        if *nodes > MAX_RULE_NODES {
+            return Err(AlertRuleError::TooLarge);
+        }
+        match self {
+            RuleTerm::Stat { window_days, .. } if !STAT_WINDOWS.contains(window_days) => {
+                Err(AlertRuleError::UnsupportedWindow(*window_days))
+            }
+            RuleTerm::Scaled { percent, of } => {
+                if !(1..=MAX_SCALE_PERCENT).contains(percent) {
+                    return Err(AlertRuleError::ScaleOutOfRange(*percent));
+                }
+                of.validate_at(depth + 1, nodes)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn collect_windows(&self, windows: &mut BTreeSet<u16>) {
+        match self {
+            RuleTerm::Stat { window_days, .. } => {
+                windows.insert(*window_days);
+            }
+            RuleTerm::Scaled { of, .. } => of.collect_windows(windows),
+            RuleTerm::Constant { .. } | RuleTerm::ListingPrice | RuleTerm::ListingQuantity => {}
+        }
+    }
+
+    fn value<I: RuleInputs>(&self, inputs: &I) -> Option<f64> {
+        match self {
+            RuleTerm::Constant { value } => Some(*value as f64),
+            RuleTerm::ListingPrice => Some(inputs.listing_price() as f64),
+            RuleTerm::ListingQuantity => Some(inputs.listing_quantity() as f64),
+            RuleTerm::Stat { stat, window_days } => inputs.stat(*stat, *window_days),
+            RuleTerm::Scaled { percent, of } => Some(of.value(inputs)? * *percent as f64 / 100.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use serde_json::json;
+
+    struct Inputs {
+        price: i32,
+        quantity: i32,
+        hq: bool,
+        stats: HashMap<(MarketStat, u16), f64>,
+    }
+
+    impl RuleInputs for Inputs {
+        fn listing_price(&self) -> i32 {
+            self.price
+        }
+
+        fn listing_quantity(&self) -> i32 {
+            self.quantity
+        }
+
+        fn listing_hq(&self) -> bool {
+            self.hq
+        }
+
+        fn stat(&self, stat: MarketStat, window_days: u16) -> Option<f64> {
+            self.stats.get(&(stat, window_days)).copied()
+        }
+    }
+
+    fn inputs(price: i32, hq: bool, stats: &[(MarketStat, u16, f64)]) -> Inputs {
+        Inputs {
+            price,
+            quantity: 1,
+            hq,
+            stats: stats.iter().map(|(s, w, v)| ((*s, *w), *v)).collect(),
+        }
+    }
+
+    /// The example from the module docs.
+    fn cheap_hq_rule() -> AlertRule {
+        serde_json::from_value(json!({"op": "all", "rules": [
+            {"op": "hq"},
+            {"op": "compare",
+             "left": {"term": "listing_price"}, "cmp": "lt",
+             "right": {"term": "scaled", "percent": 60,
+                       "of": {"term": "stat", "stat": "median", "window_days": 7}}},
+            {"op": "compare",
+             "left": {"term": "stat", "stat": "sales_per_day", "window_days": 7},
+             "cmp": "ge", "right": {"term": "constant", "value": 3}}
+        ]}))
+        .unwrap()
+    }
+
+    #[test]
+    fn documented_example_parses_and_validates() {
+        let rule = cheap_hq_rule();
+        assert_eq!(rule.validate(), Ok(()));
+        assert_eq!(rule.stat_windows(), BTreeSet::from([7]));
+    }
+
+    #[test]
+    fn fires_only_when_every_condition_holds() {
+        let rule = cheap_hq_rule();
+        let stats = [
+            (MarketStat::Median, 7, 1000.0),
+            (MarketStat::SalesPerDay, 7, 4.0),
+        ];
+        assert!(rule.matches(&inputs(599, true, &stats)));
+        // Not below 60% of the median.
+        assert!(!rule.matches(&inputs(600, true, &stats)));
+        // NQ listing.
+        assert!(!rule.matches(&inputs(500, false, &stats)));
+        // Too thinly traded.
+        let thin = [
+            (MarketStat::Median, 7, 1000.0),
+            (MarketStat::SalesPerDay, 7, 2.5),
+        ];
+        assert!(!rule.matches(&inputs(500, true, &thin)));
+    }
+
+    #[test]
+    fn missing_statistic_is_unknown_and_never_fires() {
+        let rule = cheap_hq_rule();
+        let no_stats = inputs(1, true, &[]);
+        assert_eq!(rule.evaluate(&no_stats), None);
+        let negated = AlertRule::Not {
+            rule: Box::new(rule),
+        };
+        assert_eq!(negated.evaluate(&no_stats), None);
+        assert!(!negated.matches(&no_stats));
+    }
+
+    #[test]
+    fn a_definite_answer_wins_over_unknown() {
+        let rule = cheap_hq_rule();
+        // NQ settles `all` to false regardless of the missing stats.
+        assert_eq!(rule.evaluate(&inputs(1, false, &[])), Some(false));
+        let any = AlertRule::Any {
+            rules: vec![rule, AlertRule::Hq],
+        };
+        assert_eq!(any.evaluate(&inputs(1, true, &[])), Some(true));
+    }
+
+    #[test]
+    fn validation_rejects_unsupported_windows_and_scales() {
+        let stat = |window_days| RuleTerm::Stat {
+            stat: MarketStat::Median,
+            window_days,
+        };
+        let compare = |right| AlertRule::Compare {
+            left: RuleTerm::ListingPrice,
+            cmp: RuleComparison::Le,
+            right,
+        };
+        assert_eq!(
+            compare(stat(14)).validate(),
+            Err(AlertRuleError::UnsupportedWindow(14))
+        );
+        assert_eq!(
+            compare(RuleTerm::Scaled {
+                percent: 0,
+                of: Box::new(stat(7)),
+            })
+            .validate(),
+            Err(AlertRuleError::ScaleOutOfRange(0))
+        );
+        assert_eq!(compare(stat(30)).validate(), Ok(()));
+    }
+
+    fn market_move(direction: MoveDirection, reference: MoveReference) -> MarketMoveRule {
+        MarketMoveRule {
+            source: MoveSource::CheapestListing,
+            direction,
+            reference,
+            percent: 20,
+            window_days: 30,
+        }
+    }
+
+    #[test]
+    fn market_move_median_drop_and_spike() {
+        let stats = [(MarketStat::Median, 30, 1000.0)];
+        let drop = market_move(MoveDirection::Drop, MoveReference::Median).to_rule(false);
+        assert!(drop.matches(&inputs(799, false, &stats)));
+        assert!(!drop.matches(&inputs(800, false, &stats)));
+        let spike = market_move(MoveDirection::Spike, MoveReference::Median).to_rule(false);
+        assert!(spike.matches(&inputs(1201, false, &stats)));
+        assert!(!spike.matches(&inputs(1200, false, &stats)));
+        let either = market_move(MoveDirection::Either, MoveReference::Median).to_rule(false);
+        assert!(either.matches(&inputs(799, false, &stats)));
+        assert!(either.matches(&inputs(1201, false, &stats)));
+        assert!(!either.matches(&inputs(1000, false, &stats)));
+    }
+
+    #[test]
+    fn market_move_band_measures_from_the_quartiles() {
+        let stats = [
+            (MarketStat::P25, 30, 500.0),
+            (MarketStat::Median, 30, 1000.0),
+            (MarketStat::P75, 30, 2000.0),
+        ];
+        let either = market_move(MoveDirection::Either, MoveReference::Band).to_rule(false);
+        // 20% under the median, but not under p25.
+        assert!(!either.matches(&inputs(700, false, &stats)));
+        assert!(either.matches(&inputs(399, false, &stats)));
+        assert!(either.matches(&inputs(2401, false, &stats)));
+    }
+
+    #[test]
+    fn market_move_hq_only_guards_quality() {
+        let stats = [(MarketStat::Median, 30, 1000.0)];
+        let rule = market_move(MoveDirection::Drop, MoveReference::Median).to_rule(true);
+        assert!(rule.matches(&inputs(100, true, &stats)));
+        assert!(!rule.matches(&inputs(100, false, &stats)));
+    }
+
+    #[test]
+    fn market_move_validation_bounds_percent_by_direction() {
+        let mut rule = market_move(MoveDirection::Drop, MoveReference::Median);
+        rule.percent = 100;
+        assert_eq!(
+            rule.validate(),
+            Err(AlertRuleError::MoveOutOfRange(99, 100))
+        );
+        rule.direction = MoveDirection::Spike;
+        assert_eq!(rule.validate(), Ok(()));
+        rule.percent = 0;
+        assert!(rule.validate().is_err());
+        rule.percent = 50;
+        rule.window_days = 14;
+        assert_eq!(rule.validate(), Err(AlertRuleError::UnsupportedWindow(14)));
+    }
+
+    #[test]
+    fn validation_rejects_degenerate_and_oversized_rules() {
+        assert_eq!(
+            AlertRule::All { rules: vec![] }.validate(),
+            Err(AlertRuleError::EmptyGroup)
+        );
+        assert_eq!(
+            AlertRule::Compare {
+                left: RuleTerm::Constant { value: 1 },
+                cmp: RuleComparison::Lt,
+                right: RuleTerm::Scaled {
+                    percent: 50,
+                    of: Box::new(RuleTerm::Constant { value: 4 }),
+                },
+            }
+            .validate(),
+            Err(AlertRuleError::ConstantComparison)
+        );
+
+        let mut deep = AlertRule::Hq;
+        for _ in 0..MAX_RULE_DEPTH {
+            deep = AlertRule::Not {
+                rule: Box::new(deep),
+            };
+        }
+        assert_eq!(deep.validate(), Err(AlertRuleError::TooDeep));
+
+        let wide = AlertRule::Any {
+            rules: vec![AlertRule::Hq; MAX_RULE_NODES],
+        };
+        assert_eq!(wide.validate(), Err(AlertRuleError::TooLarge));
+    }
+}
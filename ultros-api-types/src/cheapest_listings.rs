@@ -4,6 +4,7 @@ use std::fmt;
 
 /// "item_id":6605,"hq":false,"cheapest_price":6999999,"world_id":99
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CheapestListingItem {
     pub item_id: i32,
     pub hq: bool,
@@ -12,6 +13,7 @@ pub struct CheapestListingItem {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CheapestListings {
     pub cheapest_listings: Vec<CheapestListingItem>,
 }
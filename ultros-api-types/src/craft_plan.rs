@@ -0,0 +1,169 @@
+//! Buy-or-craft plan wire types.
+//!
+//! `POST /api/v1/craft_plan/{world}` answers "what is the cheapest way to end
+//! up holding N of this item?". The server walks the whole recipe tree and,
+//! for every intermediate, compares buying it against crafting it. Buying is
+//! priced by filling the quantity from the order book (every stack, not just
+//! the floor) or from a gil shop, and crafting by solving the ingredients the
+//! same way. On-hand stock is spent before anything is bought, and recipes
+//! above the given crafter levels aren't considered.
+//!
+//! Each node's cost is what that branch costs on its own. Branches that need
+//! the same ingredient are priced against the same stacks, so the shopping
+//! list re-solves each item's combined quantity once, and `total_cost` comes
+//! from that list.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::fill_cost::FillStop;
+
+/// Deepest recipe tree the server walks.
+pub const MAX_PLAN_DEPTH: u8 = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CraftPlanRequest {
+    pub item_id: i32,
+    pub quantity: i32,
+    /// Crafter levels in `CraftType` order: CRP, BSM, ARM, GSM, LTW, WVR, ALC,
+    /// CUL. Missing jobs count as level 0. Without them every recipe counts as
+    /// craftable.
+    #[serde(default)]
+    pub crafter_levels: Option<Vec<i32>>,
+    /// Units already owned, by item id. Spent before anything is bought.
+    #[serde(default)]
+    pub on_hand: HashMap<i32, i32>,
+    /// Whether gil shops may supply ingredients, at their listed price.
+    #[serde(default)]
+    pub use_vendors: bool,
+    /// Whether crystals, shards and clusters are planned for. Most crafters
+    /// hold plenty, so by default they're left out of the cost.
+    #[serde(default)]
+    pub include_crystals: bool,
+    /// How many recipe levels below the requested item may be crafted.
+    /// Defaults to [`MAX_PLAN_DEPTH`], which covers every recipe in the game.
+    #[serde(default)]
+    pub max_depth: Option<u8>,
+}
+
+impl CraftPlanRequest {
+    /// `max_depth`, defaulted and capped at [`MAX_PLAN_DEPTH`].
+    pub fn depth(&self) -> u8 {
+        self.max_depth.unwrap_or(MAX_PLAN_DEPTH).min(MAX_PLAN_DEPTH)
+    }
+}
+
+/// How a node's quantity past its on-hand share is obtained.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum PlanSource {
+    /// On-hand stock covers it all.
+    OnHand,
+    Market,
+    Vendor,
+    Craft,
+    /// A crystal while `include_crystals` is off.
+    Excluded,
+    /// Not enough on the market, not sold by a vendor, and not craftable.
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CraftStep {
+    pub recipe_id: i32,
+    /// `CraftType`, the index into `crafter_levels`.
+    pub craft_type: i32,
+    pub crafts: i32,
+    /// Units one craft yields.
+    pub amount_result: i32,
+    #[cfg_attr(feature = "openapi", schema(no_recursion))]
+    pub ingredients: Vec<PlanNode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PlanNode {
+    pub item_id: i32,
+    pub quantity: i32,
+    pub from_on_hand: i32,
+    pub source: PlanSource,
+    /// Gil this branch costs on its own, ingredients included.
+    pub cost: i64,
+    /// What buying the quantity past on-hand would cost, to compare against a
+    /// craft. `None` when it can't be bought in full.
+    pub buy_cost: Option<i64>,
+    /// False when something below this node is unavailable.
+    pub complete: bool,
+    pub craft: Option<CraftStep>,
+}
+
+/// One item to buy on one world, with the retainers selling it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WorldPurchase {
+    pub item_id: i32,
+    pub quantity: i32,
+    pub cost: i64,
+    pub stops: Vec<FillStop>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WorldShoppingList {
+    pub world_id: i32,
+    pub purchases: Vec<WorldPurchase>,
+    pub cost: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VendorPurchase {
+    pub item_id: i32,
+    pub quantity: i32,
+    pub unit_price: i32,
+    pub cost: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Shortfall {
+    pub item_id: i32,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CraftPlan {
+    pub root: PlanNode,
+    /// Market purchases, one list per world in ascending world id.
+    pub shopping: Vec<WorldShoppingList>,
+    pub vendor: Vec<VendorPurchase>,
+    /// Shopping list plus vendor purchases.
+    pub total_cost: i64,
+    /// What the plan couldn't source, by item.
+    pub unavailable: Vec<Shortfall>,
+}
+
+impl CraftPlan {
+    pub fn is_complete(&self) -> bool {
+        self.unavailable.is_empty()
+    }
+
+    /// Every craft in the plan, ingredients before the items they go into,
+    /// as `(item_id, recipe_id, crafts)`.
+    pub fn crafts(&self) -> Vec<(i32, i32, i32)> {
+        fn walk(node: &PlanNode, out: &mut Vec<(i32, i32, i32)>) {
+            if let Some(step) = &node.craft {
+                step.ingredients.iter().for_each(|child| walk(child, out));
+                out.push((node.item_id, step.recipe_id, step.crafts));
+            }
+        }
+        let mut out = vec![];
+        walk(&self.root, &mut out);
+        out
+    }
+}
@@ -7,6 +7,7 @@ pub struct UnknownCharacter {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, PartialEq, PartialOrd, Eq, Ord, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct FfxivCharacter {
     pub id: i32,
     pub first_name: String,
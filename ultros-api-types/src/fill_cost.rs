@@ -0,0 +1,341 @@
+//! "What does it actually cost to buy N of this?" wire types and solver.
+//!
+//! Pricing by the cheapest listing undercounts badly once the order needs
+//! more than the first stack holds. The fill solver walks the whole order
+//! book instead: listings are bought whole (the marketboard doesn't split
+//! stacks), and the chosen set is the cheapest one that covers the quantity.
+//!
+//! Cheapest-unit-first is *not* that set. Needing 10, a 1-unit listing at 1
+//! gil followed by a 10-unit listing at 2 gil costs 21 greedily but 20 by
+//! skipping the single. So [`optimal_fill`] solves the covering knapsack
+//! exactly when the book is small enough to do so cheaply, and falls back to
+//! cheapest-unit-first past [`MAX_EXACT_LISTINGS`] candidate listings.
+//!
+//! The solver lives here rather than in the server so the list views can run
+//! it on listings they have already fetched.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ActiveListing, Retainer};
+
+/// Largest quantity the server will solve for. The solver's table is
+/// `listings × quantity` cells of one byte each, so together with
+/// [`MAX_EXACT_LISTINGS`] this bounds one solve's memory and time.
+pub const MAX_FILL_QUANTITY: i32 = 2_000;
+
+/// Most listings the exact solver will consider. Deeper books (after the
+/// greedy bound has pruned them) get the cheapest-unit-first cover instead.
+pub const MAX_EXACT_LISTINGS: usize = 64;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FillCostQuery {
+    pub item_id: i32,
+    /// `None` accepts either quality.
+    #[serde(default)]
+    pub hq: Option<bool>,
+    pub quantity: i32,
+}
+
+/// One retainer to visit, with what to buy from them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FillStop {
+    pub world_id: i32,
+    pub retainer_id: i32,
+    pub retainer_name: Option<String>,
+    pub quantity: i32,
+    pub cost: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FillCost {
+    pub item_id: i32,
+    pub hq: Option<bool>,
+    pub requested_quantity: i32,
+    /// Units the chosen listings hold. At least `requested_quantity` when
+    /// `complete`; whole stacks routinely overshoot.
+    pub filled_quantity: i32,
+    /// False when the whole order book holds fewer units than requested, in
+    /// which case every listing is returned.
+    pub complete: bool,
+    pub total_cost: i64,
+    /// Unit price of the dearest listing bought: what the last units cost.
+    /// `None` when nothing is listed.
+    pub marginal_unit_price: Option<i32>,
+    /// `total_cost / filled_quantity`, rounded up.
+    pub average_unit_price: Option<i64>,
+    /// Chosen listings, cheapest unit price first.
+    pub listings: Vec<(ActiveListing, Option<Retainer>)>,
+    /// Retainers to visit, grouped by world in ascending world id.
+    pub stops: Vec<FillStop>,
+}
+
+/// Indices into `listings` of the cheapest set holding at least `quantity`
+/// units, cheapest unit price first, and whether that quantity was reachable
+/// at all. When it wasn't, every usable listing is returned.
+///
+/// Listings with a nonpositive price or quantity are never chosen. Past
+/// [`MAX_EXACT_LISTINGS`] candidates the set is cheapest-unit-first rather
+/// than optimal.
+pub fn optimal_fill(listings: &[ActiveListing], quantity: i32) -> (Vec<usize>, bool) {
+    let mut usable: Vec<usize> = (0..listings.len())
+        .filter(|&i| listings[i].price_per_unit > 0 && listings[i].quantity > 0)
+        .collect();
+    usable.sort_by_key(|&i| {
+        (
+            listings[i].price_per_unit,
+            listings[i].quantity,
+            listings[i].id,
+        )
+    });
+    if quantity <= 0 {
+        return (vec![], true);
+    }
+    let cost = |i: usize| listings[i].price_per_unit as i64 * listings[i].quantity as i64;
+
+    // Cheapest-unit-first is a valid (if not always optimal) cover, so its
+    // cost bounds the answer: any single listing dearer than that can't be in
+    // the optimum. Dropping those keeps the table small on deep books.
+    let mut held = 0i64;
+    let mut greedy_cost = 0i64;
+    let mut greedy_len = 0;
+    for &i in &usable {
+        if held >= quantity as i64 {
+            break;
+        }
+        held += listings[i].quantity as i64;
+        greedy_cost += cost(i);
+        greedy_len += 1;
+    }
+    if held < quantity as i64 {
+        return (usable, false);
+    }
+    usable.retain(|&i| cost(i) <= greedy_cost);
+    if usable.len() > MAX_EXACT_LISTINGS {
+        // Every listing the greedy walk took costs at most the whole walk, so
+        // the pruning kept them all, in the same order.
+        usable.truncate(greedy_len);
+        return (usable, true);
+    }
+
+    // dp[q]: cheapest cost of holding at least q units (q capped at target).
+    let target = quantity as usize;
+    let mut dp = vec![i64::MAX; target + 1];
+    dp[0] = 0;
+    // took[k][q]: listing `usable[k]` produced dp[q] at stage k.
+    let mut took = vec![vec![false; target + 1]; usable.len()];
+    // Predecessor of the last update to dp[target] at each stage; every other
+    // cell's predecessor is just `q - quantity`.
+    let mut full_from = vec![0usize; usable.len()];
+    for (k, &i) in usable.iter().enumerate() {
+        let units = listings[i].quantity as usize;
+        let price = cost(i);
+        for q in (0..target).rev() {
+            if dp[q] == i64::MAX {
+                continue;
+            }
+            let next = (q + units).min(target);
+            let candidate = dp[q] + price;
+            if candidate < dp[next] {
+                dp[next] = candidate;
+                took[k][next] = true;
+                if next == target {
+                    full_from[k] = q;
+                }
+            }
+        }
+    }
+
+    let mut chosen = vec![];
+    let mut q = target;
+    for k in (0..usable.len()).rev() {
+        if q == 0 {
+            break;
+        }
+        if took[k][q] {
+            chosen.push(usable[k]);
+            q = if q == target {
+                full_from[k]
+            } else {
+                q - listings[usable[k]].quantity as usize
+            };
+        }
+    }
+    chosen.reverse();
+    (chosen, true)
+}
+
+impl FillCost {
+    /// Solves for `query` over `listings`, which should already be narrowed
+    /// to the item (and worlds) in question; quality is filtered here.
+    pub fn solve(query: FillCostQuery, listings: Vec<(ActiveListing, Option<Retainer>)>) -> Self {
+        let listings: Vec<_> = listings
+            .into_iter()
+            .filter(|(l, _)| l.item_id == query.item_id && query.hq.is_none_or(|hq| l.hq == hq))
+            .collect();
+        let plain: Vec<ActiveListing> = listings.iter().map(|(l, _)| l.clone()).collect();
+        let (chosen, complete) = optimal_fill(&plain, query.quantity);
+        let chosen: Vec<_> = chosen.into_iter().map(|i| listings[i].clone()).collect();
+
+        let filled_quantity = chosen.iter().map(|(l, _)| l.quantity).sum::<i32>();
+        let total_cost = chosen
+            .iter()
+            .map(|(l, _)| l.price_per_unit as i64 * l.quantity as i64)
+            .sum::<i64>();
+        let mut stops: Vec<FillStop> = vec![];
+        for (listing, retainer) in &chosen {
+            let cost = listing.price_per_unit as i64 * listing.quantity as i64;
+            match stops
+                .iter_mut()
+                .find(|s| s.retainer_id == listing.retainer_id && s.world_id == listing.world_id)
+            {
+                Some(stop) => {
+                    stop.quantity += listing.quantity;
+                    stop.cost += cost;
+                }
+                None => stops.push(FillStop {
+                    world_id: listing.world_id,
+                    retainer_id: listing.retainer_id,
+                    retainer_name: retainer.as_ref().map(|r| r.name.clone()),
+                    quantity: listing.quantity,
+                    cost,
+                }),
+            }
+        }
+        stops.sort_by_key(|s| (s.world_id, std::cmp::Reverse(s.cost), s.retainer_id));
+
+        FillCost {
+            item_id: query.item_id,
+            hq: query.hq,
+            requested_quantity: query.quantity,
+            filled_quantity,
+            complete,
+            total_cost,
+            marginal_unit_price: chosen.iter().map(|(l, _)| l.price_per_unit).max(),
+            average_unit_price: (filled_quantity > 0)
+                .then(|| (total_cost + filled_quantity as i64 - 1) / filled_quantity as i64),
+            listings: chosen,
+            stops,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn listing(id: i32, price_per_unit: i32, quantity: i32) -> ActiveListing {
+        ActiveListing {
+            id,
+            world_id: 1,
+            item_id: 5,
+            retainer_id: id,
+            price_per_unit,
+            quantity,
+            hq: false,
+            timestamp: NaiveDateTime::default(),
+            materia: vec![],
+        }
+    }
+
+    fn ids(listings: &[ActiveListing], quantity: i32) -> (Vec<i32>, bool) {
+        let (chosen, complete) = optimal_fill(listings, quantity);
+        let mut ids: Vec<_> = chosen.into_iter().map(|i| listings[i].id).collect();
+        ids.sort();
+        (ids, complete)
+    }
+
+    #[test]
+    fn skips_a_cheap_single_that_forces_an_extra_stack() {
+        let listings = [listing(1, 1, 1), listing(2, 2, 10)];
+        assert_eq!(ids(&listings, 10), (vec![2], true));
+    }
+
+    #[test]
+    fn walks_depth_cheapest_first_when_that_is_optimal() {
+        let listings = [listing(1, 100, 5), listing(2, 200, 10), listing(3, 300, 5)];
+        assert_eq!(ids(&listings, 12), (vec![1, 2], true));
+        assert_eq!(ids(&listings, 5), (vec![1], true));
+    }
+
+    #[test]
+    fn combines_small_stacks_over_one_big_one() {
+        // 3 + 3 + 4 at 10 each (100) beats 99 at 2 each (198).
+        let listings = [
+            listing(1, 10, 3),
+            listing(2, 10, 3),
+            listing(3, 10, 4),
+            listing(4, 2, 99),
+        ];
+        assert_eq!(ids(&listings, 10), (vec![1, 2, 3], true));
+    }
+
+    #[test]
+    fn deep_books_fall_back_to_cheapest_first() {
+        let listings: Vec<_> = (1..=100).map(|id| listing(id, 10 + id, 1)).collect();
+        assert_eq!(ids(&listings, 70), ((1..=70).collect::<Vec<_>>(), true));
+    }
+
+    #[test]
+    fn short_supply_returns_everything_as_incomplete() {
+        let listings = [listing(1, 10, 3), listing(2, 20, 3)];
+        assert_eq!(ids(&listings, 10), (vec![1, 2], false));
+    }
+
+    #[test]
+    fn unusable_listings_are_never_chosen() {
+        let listings = [listing(1, 0, 50), listing(2, 10, 0), listing(3, 10, 5)];
+        assert_eq!(ids(&listings, 5), (vec![3], true));
+    }
+
+    #[test]
+    fn solve_reports_totals_and_groups_retainers() {
+        let mut a = listing(1, 100, 5);
+        let mut b = listing(2, 150, 5);
+        let c = listing(3, 400, 5);
+        // Same retainer for the first two.
+        a.retainer_id = 9;
+        b.retainer_id = 9;
+        let retainer = Retainer {
+            id: 9,
+            world_id: 1,
+            name: "Bob".to_string(),
+            retainer_city_id: 1,
+        };
+        let cost = FillCost::solve(
+            FillCostQuery {
+                item_id: 5,
+                hq: Some(false),
+                quantity: 8,
+            },
+            vec![(c, None), (a, Some(retainer.clone())), (b, Some(retainer))],
+        );
+        assert!(cost.complete);
+        assert_eq!(cost.filled_quantity, 10);
+        assert_eq!(cost.total_cost, 500 + 750);
+        assert_eq!(cost.marginal_unit_price, Some(150));
+        assert_eq!(cost.average_unit_price, Some(125));
+        assert_eq!(cost.stops.len(), 1);
+        assert_eq!(cost.stops[0].retainer_name.as_deref(), Some("Bob"));
+        assert_eq!(cost.stops[0].quantity, 10);
+    }
+
+    #[test]
+    fn solve_filters_quality() {
+        let mut hq = listing(1, 10, 5);
+        hq.hq = true;
+        let nq = listing(2, 1, 5);
+        let cost = FillCost::solve(
+            FillCostQuery {
+                item_id: 5,
+                hq: Some(true),
+                quantity: 5,
+            },
+            vec![(hq, None), (nq, None)],
+        );
+        assert_eq!(cost.total_cost, 50);
+    }
+}
@@ -1,7 +1,10 @@
 pub mod alert;
+pub mod alert_rule;
 pub mod bootstrap;
 pub mod cheapest_listings;
+pub mod craft_plan;
 mod ffxiv_character;
+pub mod fill_cost;
 pub mod freshness;
 pub mod game_history;
 pub mod icon_size;
@@ -10,15 +13,19 @@ pub mod list;
 mod listings;
 pub mod market_heat;
 pub mod market_pulse;
+pub mod patch_diff;
 pub mod price_density;
 pub mod price_series;
 pub mod recent_sales;
+pub mod repricing;
 pub mod resale_quality;
 pub mod result;
 pub mod retainer;
+pub mod route_planner;
 mod sale_history;
 pub mod search;
 pub mod sparklines;
+pub mod teamcraft;
 pub mod trends;
 pub mod user;
 pub mod websocket;
@@ -26,7 +33,7 @@ pub mod world;
 pub mod world_helper;
 
 pub use ffxiv_character::*;
-pub use listings::ActiveListing;
+pub use listings::{ActiveListing, ListingMateria};
 pub use price_series::{HqFilter, PriceBucket, PriceSeries, PriceSeriesEntry, SeriesGroup};
 pub use retainer::Retainer;
 pub use sale_history::{CompactSale, ExtendedSaleHistory, SaleHistory};
@@ -42,12 +49,14 @@ use serde::{Deserialize, Serialize};
 /// which [`ActiveListing::timestamp`] carries (when the seller last touched the
 /// listing in-game).
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct WorldItemLastUpdated {
     pub world_id: i32,
     pub updated_at: NaiveDateTime,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CurrentlyShownItem {
     pub listings: Vec<(ActiveListing, Retainer)>,
     pub sales: Vec<SaleHistory>,
@@ -147,6 +156,7 @@ mod tests {
                 quantity: 1,
                 hq: false,
                 timestamp: NaiveDateTime::default(),
+                materia: vec![],
             },
             Retainer {
                 id: 1,
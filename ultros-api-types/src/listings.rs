@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 /// This mostly matches with the ultros-db/entity type, but doesn't include the sea-orm specifics
 /// See [ultros-db::active_listing::Entity]
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ActiveListing {
     pub id: i32,
     pub world_id: i32,
@@ -12,6 +13,21 @@ pub struct ActiveListing {
     pub quantity: i32,
     pub hq: bool,
     pub timestamp: NaiveDateTime,
+    /// Materia melded into the item. Only as fresh as the last board fetch:
+    /// Universalis sends no events when melds change.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub materia: Vec<ListingMateria>,
+}
+
+/// One materia melded into a listed item, as Universalis reports it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ListingMateria {
+    /// Row in the game's Materia sheet, i.e. which stat the materia adds.
+    pub materia_id: i32,
+    /// Universalis' `slotID`, which is the materia's grade: the index into
+    /// the Materia row's items, not the gear slot it sits in.
+    pub slot_id: Option<i32>,
 }
 
 impl ActiveListing {
@@ -117,6 +133,7 @@ mod tests {
             quantity: 1,
             hq: false,
             timestamp: chrono::Utc::now().naive_utc(),
+            materia: vec![],
         };
         let excluded_worlds = vec![100, 101];
         assert!(listing.is_excluded(&excluded_worlds));
@@ -133,6 +150,7 @@ mod tests {
             quantity: 1,
             hq: false,
             timestamp: chrono::Utc::now().naive_utc(),
+            materia: vec![],
         };
         let excluded_worlds = vec![100, 101];
         assert!(!listing.is_excluded(&excluded_worlds));
@@ -150,6 +168,7 @@ mod tests {
             quantity: 1,
             hq: false,
             timestamp: chrono::Utc::now().naive_utc(),
+            materia: vec![],
         };
         let mut excluded = HashSet::new();
         excluded.insert("Aether".to_string());
@@ -168,6 +187,7 @@ mod tests {
             quantity: 1,
             hq: false,
             timestamp: chrono::Utc::now().naive_utc(),
+            materia: vec![],
         };
         let mut excluded = HashSet::new();
         excluded.insert("Aether".to_string());
@@ -186,6 +206,7 @@ mod tests {
             quantity: 1,
             hq: false,
             timestamp: chrono::Utc::now().naive_utc(),
+            materia: vec![],
         };
         let mut excluded = HashSet::new();
         excluded.insert("Aether".to_string());
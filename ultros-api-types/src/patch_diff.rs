@@ -0,0 +1,198 @@
+//! What changed between two game-data packs.
+//!
+//! `game-data-pack` writes one of these to `data/patch_diff.json` whenever a
+//! build replaces the packs with different data, and `--diff` prints one for
+//! any two pack files. The server hands the committed report out at
+//! `/api/v1/patch_diff` for the "new this patch" page.
+//!
+//! Item names are the English ones at the time of the diff. Pages that know
+//! the item data should show their own localized names instead.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ItemAmount {
+    pub item_id: i32,
+    pub amount: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DiffItem {
+    pub item_id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DiffRecipe {
+    pub recipe_id: i32,
+    /// The crafted item.
+    pub item_id: i32,
+    /// `CraftType`: CRP, BSM, ARM, GSM, LTW, WVR, ALC, CUL.
+    pub craft_type: i32,
+    pub level: i32,
+    pub amount_result: i32,
+    pub ingredients: Vec<ItemAmount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RecipeChange {
+    pub recipe_id: i32,
+    pub item_id: i32,
+    pub before: Vec<ItemAmount>,
+    pub after: Vec<ItemAmount>,
+}
+
+/// An item sold in a gil shop, at its listed price.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VendorItem {
+    pub item_id: i32,
+    pub price: i32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PriceChange {
+    pub item_id: i32,
+    pub before: i32,
+    pub after: i32,
+}
+
+/// One trade in a special (currency) shop.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SpecialShopEntry {
+    pub shop_id: i32,
+    /// The item received.
+    pub item_id: i32,
+    pub quantity: i32,
+    /// Item ids and counts paid. Low ids are currencies, the way the game
+    /// data stores them.
+    pub costs: Vec<ItemAmount>,
+}
+
+/// How often the new recipes of a patch call for an existing item.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct IngredientDemand {
+    pub item_id: i32,
+    /// New recipes using it.
+    pub recipes: i32,
+    /// Units one craft of each of those recipes takes, summed.
+    pub amount: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PatchDiff {
+    /// Source revision of the older pack.
+    pub from: String,
+    /// Source revision of the newer pack.
+    pub to: String,
+    pub new_items: Vec<DiffItem>,
+    pub removed_items: Vec<DiffItem>,
+    pub new_recipes: Vec<DiffRecipe>,
+    pub removed_recipes: Vec<DiffRecipe>,
+    /// Recipes whose ingredients changed.
+    pub changed_recipes: Vec<RecipeChange>,
+    pub new_vendor_items: Vec<VendorItem>,
+    pub removed_vendor_items: Vec<VendorItem>,
+    pub vendor_price_changes: Vec<PriceChange>,
+    pub new_special_shop_entries: Vec<SpecialShopEntry>,
+    pub removed_special_shop_entries: Vec<SpecialShopEntry>,
+}
+
+impl PatchDiff {
+    pub fn is_empty(&self) -> bool {
+        self.new_items.is_empty()
+            && self.removed_items.is_empty()
+            && self.new_recipes.is_empty()
+            && self.removed_recipes.is_empty()
+            && self.changed_recipes.is_empty()
+            && self.new_vendor_items.is_empty()
+            && self.removed_vendor_items.is_empty()
+            && self.vendor_price_changes.is_empty()
+            && self.new_special_shop_entries.is_empty()
+            && self.removed_special_shop_entries.is_empty()
+    }
+
+    /// Items that existed before the patch and that the new recipes use,
+    /// most used first. These are the mats worth buying ahead of demand.
+    pub fn ingredient_demand(&self) -> Vec<IngredientDemand> {
+        let new_items: HashSet<i32> = self.new_items.iter().map(|i| i.item_id).collect();
+        let mut demand: BTreeMap<i32, IngredientDemand> = BTreeMap::new();
+        for ingredient in self.new_recipes.iter().flat_map(|r| &r.ingredients) {
+            if new_items.contains(&ingredient.item_id) {
+                continue;
+            }
+            let entry = demand
+                .entry(ingredient.item_id)
+                .or_insert(IngredientDemand {
+                    item_id: ingredient.item_id,
+                    recipes: 0,
+                    amount: 0,
+                });
+            entry.recipes += 1;
+            entry.amount += ingredient.amount;
+        }
+        let mut demand: Vec<_> = demand.into_values().collect();
+        demand.sort_by_key(|d| (std::cmp::Reverse(d.recipes), std::cmp::Reverse(d.amount)));
+        demand
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe(recipe_id: i32, item_id: i32, ingredients: &[(i32, i32)]) -> DiffRecipe {
+        DiffRecipe {
+            recipe_id,
+            item_id,
+            craft_type: 1,
+            level: 100,
+            amount_result: 1,
+            ingredients: ingredients
+                .iter()
+                .map(|&(item_id, amount)| ItemAmount { item_id, amount })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn ingredient_demand_skips_new_items_and_ranks_by_use() {
+        let diff = PatchDiff {
+            new_items: vec![
+                DiffItem {
+                    item_id: 100,
+                    name: "New Sword".into(),
+                },
+                DiffItem {
+                    item_id: 101,
+                    name: "New Ingot".into(),
+                },
+            ],
+            new_recipes: vec![
+                recipe(1, 100, &[(101, 2), (5, 3), (7, 1)]),
+                recipe(2, 101, &[(5, 4), (8, 9)]),
+            ],
+            ..Default::default()
+        };
+        assert!(!diff.is_empty());
+        let demand = diff.ingredient_demand();
+        let ids: Vec<_> = demand.iter().map(|d| d.item_id).collect();
+        assert_eq!(ids, vec![5, 8, 7]);
+        assert_eq!((demand[0].recipes, demand[0].amount), (2, 7));
+    }
+
+    #[test]
+    fn default_diff_is_empty() {
+        assert!(PatchDiff::default().is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+//! Relist price suggestions for a user's own listings.
+//!
+//! The server gathers the market around each listing into [`RepriceInputs`]
+//! and the suggestion itself is a pure function of those inputs and an
+//! urgency, so the retainers page can move its "fast sale ↔ max price" slider
+//! without a round trip and undercut DMs quote the same number the page does.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ActiveListing, Retainer};
+
+/// Slider position used when the user hasn't picked one, e.g. in DMs.
+pub const DEFAULT_URGENCY: u8 = 50;
+
+/// Recent sell-through on the listing's world, from the analyzer's sale buffer.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SellThrough {
+    pub median: i32,
+    pub low: i32,
+    pub high: i32,
+    /// Sales per day at the recent pace.
+    pub velocity_per_day: Option<f32>,
+    pub sale_count: u8,
+}
+
+/// The cleaned 30-day price distribution on the listing's world.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PriceBand {
+    pub p10: u32,
+    pub p25: u32,
+    pub p50: u32,
+    pub p75: u32,
+    pub p90: u32,
+}
+
+impl PriceBand {
+    /// Where `price` falls in the band (0-100), interpolating linearly between
+    /// the percentile breakpoints.
+    pub fn percentile(&self, price: u32) -> u8 {
+        let breakpoints: [(u32, u8); 5] = [
+            (self.p10, 10),
+            (self.p25, 25),
+            (self.p50, 50),
+            (self.p75, 75),
+            (self.p90, 90),
+        ];
+        if price <= self.p10 {
+            return 0;
+        }
+        if price >= self.p90 {
+            return 100;
+        }
+        for w in breakpoints.windows(2) {
+            let (lo_p, lo_pct) = w[0];
+            let (hi_p, hi_pct) = w[1];
+            if price >= lo_p && price <= hi_p {
+                if hi_p == lo_p {
+                    return lo_pct;
+                }
+                let span = (hi_p - lo_p) as f32;
+                let delta = (price - lo_p) as f32;
+                let pct = lo_pct as f32 + (delta / span) * (hi_pct - lo_pct) as f32;
+                return pct.round() as u8;
+            }
+        }
+        50
+    }
+}
+
+/// Everything a suggestion is computed from, for one item and quality on one
+/// world.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RepriceInputs {
+    /// Unit prices of the other listings a buyer would weigh against this one,
+    /// cheapest first: every quality for an NQ listing, HQ only for an HQ one.
+    /// The user's own listings are left out so retainers don't undercut each
+    /// other.
+    pub competitors: Vec<i32>,
+    pub sell_through: Option<SellThrough>,
+    pub band: Option<PriceBand>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RepriceSuggestion {
+    /// Unit price to relist at.
+    pub price: i32,
+    /// How many competing listings would still be cheaper.
+    pub position: usize,
+    /// Days until the listings ahead and this one sell, at the recent pace.
+    pub est_days_to_sell: Option<f32>,
+    /// Where the price sits in the 30-day band, 0-100.
+    pub percentile: Option<u8>,
+}
+
+impl RepriceInputs {
+    /// The cheapest price that still makes sense: just under the cheapest
+    /// competitor, but never below the bottom of what the item actually sells
+    /// for, so one dumped listing isn't chased down.
+    fn fast_price(&self) -> Option<i32> {
+        let floor = self
+            .band
+            .map(|band| band.p10 as i32)
+            .or(self.sell_through.map(|recent| recent.low));
+        let undercut = self.competitors.first().map(|cheapest| cheapest - 1);
+        let fair = self
+            .band
+            .map(|band| band.p50 as i32)
+            .or(self.sell_through.map(|recent| recent.median));
+        match (undercut, floor) {
+            (Some(undercut), Some(floor)) => Some(undercut.max(floor)),
+            (Some(undercut), None) => Some(undercut),
+            // Nobody to undercut: the going rate sells.
+            (None, _) => fair,
+        }
+    }
+
+    /// The most the item plausibly sells for.
+    fn max_price(&self) -> Option<i32> {
+        self.band
+            .map(|band| band.p90 as i32)
+            .or(self.sell_through.map(|recent| recent.high))
+    }
+
+    /// A relist price between the quickest sale (`urgency` 100) and the best
+    /// price the market has recently paid (`urgency` 0). `None` when there's
+    /// neither a competitor nor any sale history to go on.
+    pub fn suggest(&self, urgency: u8) -> Option<RepriceSuggestion> {
+        let fast = self.fast_price();
+        let max = self.max_price();
+        let (fast, max) = match (fast, max) {
+            (Some(fast), Some(max)) => (fast, max.max(fast)),
+            (Some(price), None) | (None, Some(price)) => (price, price),
+            (None, None) => return None,
+        };
+        let urgency = urgency.min(100) as f64 / 100.0;
+        let price = (max as f64 - (max - fast) as f64 * urgency).round() as i32;
+        let price = price.max(1);
+        let position = self
+            .competitors
+            .iter()
+            .take_while(|competitor| **competitor < price)
+            .count();
+        Some(RepriceSuggestion {
+            price,
+            position,
+            est_days_to_sell: self
+                .sell_through
+                .and_then(|recent| recent.velocity_per_day)
+                .filter(|velocity| *velocity > 0.0)
+                .map(|velocity| (position + 1) as f32 / velocity),
+            percentile: self.band.map(|band| band.percentile(price as u32)),
+        })
+    }
+}
+
+/// One of the user's listings and the market around it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ListingReprice {
+    pub listing: ActiveListing,
+    pub inputs: RepriceInputs,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RetainerReprice {
+    pub retainer: Retainer,
+    pub listings: Vec<ListingReprice>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn band() -> PriceBand {
+        PriceBand {
+            p10: 800,
+            p25: 900,
+            p50: 1_000,
+            p75: 1_100,
+            p90: 1_200,
+        }
+    }
+
+    fn recent(velocity_per_day: f32) -> SellThrough {
+        SellThrough {
+            median: 1_000,
+            low: 700,
+            high: 1_300,
+            velocity_per_day: Some(velocity_per_day),
+            sale_count: 20,
+        }
+    }
+
+    #[test]
+    fn urgency_slides_from_the_band_top_to_an_undercut() {
+        let inputs = RepriceInputs {
+            competitors: vec![950, 1_000, 1_150],
+            sell_through: Some(recent(2.0)),
+            band: Some(band()),
+        };
+        let fast = inputs.suggest(100).unwrap();
+        assert_eq!(fast.price, 949);
+        assert_eq!(fast.position, 0);
+        assert_eq!(fast.est_days_to_sell, Some(0.5));
+
+        let max = inputs.suggest(0).unwrap();
+        assert_eq!(max.price, 1_200);
+        assert_eq!(max.position, 3);
+        assert_eq!(max.percentile, Some(100));
+        assert_eq!(max.est_days_to_sell, Some(2.0));
+
+        let middle = inputs.suggest(50).unwrap();
+        assert!(fast.price < middle.price && middle.price < max.price);
+    }
+
+    #[test]
+    fn a_dumped_listing_is_not_chased_below_the_band() {
+        let inputs = RepriceInputs {
+            competitors: vec![100, 1_000],
+            sell_through: None,
+            band: Some(band()),
+        };
+        let fast = inputs.suggest(100).unwrap();
+        assert_eq!(fast.price, 800);
+        assert_eq!(fast.position, 1);
+        assert_eq!(fast.percentile, Some(0));
+    }
+
+    #[test]
+    fn falls_back_to_recent_sales_then_competitors() {
+        let recent_only = RepriceInputs {
+            competitors: vec![],
+            sell_through: Some(recent(1.0)),
+            band: None,
+        };
+        assert_eq!(recent_only.suggest(100).unwrap().price, 1_000);
+        assert_eq!(recent_only.suggest(0).unwrap().price, 1_300);
+
+        let competitors_only = RepriceInputs {
+            competitors: vec![500],
+            sell_through: None,
+            band: None,
+        };
+        let suggestion = competitors_only.suggest(0).unwrap();
+        assert_eq!(suggestion.price, 499);
+        assert_eq!(suggestion.est_days_to_sell, None);
+
+        assert_eq!(RepriceInputs::default().suggest(50), None);
+    }
+
+    #[test]
+    fn percentile_interpolates_between_breakpoints() {
+        assert_eq!(band().percentile(700), 0);
+        assert_eq!(band().percentile(900), 25);
+        assert_eq!(band().percentile(950), 38);
+        assert_eq!(band().percentile(5_000), 100);
+    }
+}
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[derive(Deserialize, Serialize, Error, Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum ApiError {
     #[error("Generic error: {0}")]
     Message(String),
@@ -17,7 +18,9 @@ pub enum ApiError {
     BadRequest(String),
 }
 
+/// The body of every `/api/v1` error response.
 #[derive(Deserialize, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum JsonErrorWrapper {
     ApiError(ApiError),
 }
@@ -0,0 +1,55 @@
+//! Multi-stop arbitrage route wire types.
+//!
+//! `/api/v1/route_planner/{world}` answers "with this much gil, which
+//! listings across my data center should I buy to resell at home?". The
+//! server walks actual listing depth (every stack, not just the cheapest
+//! unit) and caps each item at what the home world can plausibly absorb in
+//! the requested horizon, then groups the purchases into one stop per world.
+//!
+//! Stops come back in visiting order: the home world first (no travel), then
+//! the remaining worlds by descending expected profit, so a shopper who runs
+//! out of patience part-way still collects the best of the route.
+
+use serde::{Deserialize, Serialize};
+
+/// One stack to buy. Whole listings only — the marketboard doesn't split.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RoutePurchase {
+    /// Ultros' `active_listing.id`, so the frontend can cross-reference the
+    /// item page's listing table.
+    pub listing_id: i32,
+    pub item_id: i32,
+    pub hq: bool,
+    pub quantity: i32,
+    pub price_per_unit: i32,
+    pub retainer_name: Option<String>,
+    /// Units of this stack the home world is expected to absorb within the
+    /// horizon. Less than `quantity` when the stack overshoots the
+    /// sell-through cap; the surplus is priced in at zero.
+    pub sellable_quantity: i32,
+    /// Post-tax gil per unit the plan expects to sell at.
+    pub net_sale_price: i32,
+    /// `net_sale_price * sellable_quantity - price_per_unit * quantity`.
+    pub expected_profit: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RouteStop {
+    pub world_id: i32,
+    pub purchases: Vec<RoutePurchase>,
+    pub cost: i64,
+    pub expected_profit: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RoutePlan {
+    /// The world everything is resold on.
+    pub home_world_id: i32,
+    pub datacenter_id: i32,
+    pub budget: i64,
+    /// Sell-through horizon the per-item caps were computed for.
+    pub horizon_days: f32,
+    pub stops: Vec<RouteStop>,
+    pub total_cost: i64,
+    pub expected_profit: i64,
+}
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 /// Relates to the sale history stored in ultros_db, but is a clean type
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct SaleHistory {
     pub id: i32,
     pub quantity: i32,
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct SearchResult {
     pub score: f32,
     pub title: String,
@@ -9,3 +10,318 @@ pub struct SearchResult {
     pub icon_id: Option<i32>,
     pub category: Option<String>,
 }
+
+/// One page of `/api/v1/search/page`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    /// Matches across every page, not just this one.
+    pub total: usize,
+    pub offset: usize,
+    pub facets: SearchFacets,
+}
+
+/// Match counts per value over the whole result set, most common first, for
+/// narrowing a query with a `cat:` or `type:` filter.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SearchFacets {
+    /// Item UI categories ("Body", "Seafood", …), in the requested language.
+    pub categories: Vec<SearchFacet>,
+    /// Result types: `item`, `category`, `job equipment`, `currency`.
+    pub types: Vec<SearchFacet>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SearchFacet {
+    pub value: String,
+    pub count: u64,
+}
+
+/// Inclusive bounds on a numeric item attribute.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct NumberFilter {
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+}
+
+impl NumberFilter {
+    /// Parse `690`, `=690`, `>=690`, `>690`, `<=690`, `<690` or `690..700`.
+    fn parse(value: &str) -> Option<Self> {
+        let number = |s: &str| s.trim().parse::<u32>().ok();
+        if let Some((min, max)) = value.split_once("..") {
+            return Some(Self {
+                min: Some(number(min)?),
+                max: Some(number(max)?),
+            });
+        }
+        let filter = if let Some(n) = value.strip_prefix(">=") {
+            Self {
+                min: Some(number(n)?),
+                max: None,
+            }
+        } else if let Some(n) = value.strip_prefix("<=") {
+            Self {
+                min: None,
+                max: Some(number(n)?),
+            }
+        } else if let Some(n) = value.strip_prefix('>') {
+            Self {
+                min: Some(number(n)?.checked_add(1)?),
+                max: None,
+            }
+        } else if let Some(n) = value.strip_prefix('<') {
+            Self {
+                min: None,
+                max: Some(number(n)?.checked_sub(1)?),
+            }
+        } else {
+            let n = number(value.strip_prefix('=').unwrap_or(value))?;
+            Self {
+                min: Some(n),
+                max: Some(n),
+            }
+        };
+        Some(filter)
+    }
+
+    /// Both filters must hold.
+    fn intersect(self, other: Self) -> Self {
+        Self {
+            min: self.min.max(other.min),
+            max: match (self.max, other.max) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            },
+        }
+    }
+}
+
+/// Structured filters pulled out of a search query by [`parse_search_query`].
+///
+/// Repeating `job:`, `cat:` or `type:` (or separating values with commas)
+/// accepts any of the values; repeating a numeric key narrows the range.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct SearchFilters {
+    /// `ilvl:` — item level.
+    pub item_level: Option<NumberFilter>,
+    /// `level:` / `lvl:` — level required to equip.
+    pub equip_level: Option<NumberFilter>,
+    /// `job:` — canonical English job acronyms, upper case ("WHM").
+    pub jobs: Vec<String>,
+    /// `cat:` / `category:` — item UI category names or ids, lower case.
+    pub categories: Vec<String>,
+    /// `type:` — result types, lower case.
+    pub types: Vec<String>,
+    /// `hq:` — whether the item can be high quality.
+    pub hq: Option<bool>,
+    /// `craftable:` — whether any recipe produces the item.
+    pub craftable: Option<bool>,
+    /// `marketable:` — whether the item can be sold on the market board.
+    /// Unset means marketable items only, like an unfiltered search.
+    pub marketable: Option<bool>,
+}
+
+impl SearchFilters {
+    /// Applies one `key:value` filter. False when the key is unknown or the
+    /// value doesn't parse, so the caller can search for the token as text.
+    fn apply(&mut self, key: &str, value: &str) -> bool {
+        let list = |value: &str| -> Vec<String> {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        let narrow = |current: Option<NumberFilter>, value: &str| {
+            NumberFilter::parse(value).map(|new| match current {
+                Some(current) => current.intersect(new),
+                None => new,
+            })
+        };
+        match key.to_ascii_lowercase().as_str() {
+            "ilvl" => match narrow(self.item_level, value) {
+                Some(filter) => self.item_level = Some(filter),
+                None => return false,
+            },
+            "level" | "lvl" => match narrow(self.equip_level, value) {
+                Some(filter) => self.equip_level = Some(filter),
+                None => return false,
+            },
+            "job" => self
+                .jobs
+                .extend(list(value).into_iter().map(|v| v.to_ascii_uppercase())),
+            "cat" | "category" => self
+                .categories
+                .extend(list(value).into_iter().map(|v| v.to_lowercase())),
+            "type" => self
+                .types
+                .extend(list(value).into_iter().map(|v| v.to_lowercase())),
+            "hq" => match parse_bool(value) {
+                Some(b) => self.hq = Some(b),
+                None => return false,
+            },
+            "craftable" => match parse_bool(value) {
+                Some(b) => self.craftable = Some(b),
+                None => return false,
+            },
+            "marketable" => match parse_bool(value) {
+                Some(b) => self.marketable = Some(b),
+                None => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "1" => Some(true),
+        "false" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// A search query split into free text and filters.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ParsedSearchQuery {
+    /// Everything that isn't a recognised filter, in its original order.
+    pub text: String,
+    pub filters: SearchFilters,
+}
+
+/// Split `ilvl:>=690 job:WHM cat:"Body" robe` into filters and the free
+/// text `robe`. Values may be double-quoted to include spaces. Tokens that
+/// look like filters but don't parse stay in the text.
+pub fn parse_search_query(query: &str) -> ParsedSearchQuery {
+    let mut parsed = ParsedSearchQuery::default();
+    let mut text = Vec::new();
+    for token in split_tokens(query) {
+        let is_filter = token.split_once(':').is_some_and(|(key, value)| {
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            !key.is_empty() && !value.is_empty() && parsed.filters.apply(key, value)
+        });
+        if !is_filter {
+            text.push(token);
+        }
+    }
+    parsed.text = text.join(" ");
+    parsed
+}
+
+/// Whitespace-separated tokens, keeping double-quoted runs (and their quotes)
+/// together.
+fn split_tokens(query: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut quoted = false;
+    for (i, c) in query.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        if c.is_whitespace() && !quoted {
+            if let Some(s) = start.take() {
+                tokens.push(&query[s..i]);
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(s) = start {
+        tokens.push(&query[s..]);
+    }
+    tokens
+}
+
+/// A `key:value` token for appending to a query, quoted when the value has
+/// spaces.
+pub fn format_filter(key: &str, value: &str) -> String {
+    if value.contains(char::is_whitespace) {
+        format!("{key}:\"{value}\"")
+    } else {
+        format!("{key}:{value}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_are_split_from_text() {
+        let parsed =
+            parse_search_query(r#"ilvl:>=690 job:WHM cat:"Body" craftable:true hq:yes robe"#);
+        assert_eq!(parsed.text, "robe");
+        assert_eq!(
+            parsed.filters,
+            SearchFilters {
+                item_level: Some(NumberFilter {
+                    min: Some(690),
+                    max: None
+                }),
+                jobs: vec!["WHM".to_string()],
+                categories: vec!["body".to_string()],
+                hq: Some(true),
+                craftable: Some(true),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn number_filters_parse_every_form() {
+        let parse = |v| NumberFilter::parse(v).unwrap();
+        assert_eq!(parse("90"), parse("=90"));
+        assert_eq!(parse("90").min, Some(90));
+        assert_eq!(parse(">90").min, Some(91));
+        assert_eq!(parse("<90").max, Some(89));
+        assert_eq!(
+            parse("600..700"),
+            NumberFilter {
+                min: Some(600),
+                max: Some(700)
+            }
+        );
+        assert_eq!(NumberFilter::parse("<0"), None);
+        assert_eq!(NumberFilter::parse("high"), None);
+    }
+
+    #[test]
+    fn repeated_filters_narrow_or_widen() {
+        let filters = parse_search_query("ilvl:>=600 ilvl:<=650 job:WHM,sch job:AST").filters;
+        assert_eq!(
+            filters.item_level,
+            Some(NumberFilter {
+                min: Some(600),
+                max: Some(650)
+            })
+        );
+        assert_eq!(filters.jobs, ["WHM", "SCH", "AST"]);
+    }
+
+    #[test]
+    fn unrecognised_tokens_stay_in_the_text() {
+        let parsed = parse_search_query(r#"Samurai's "Hakama of" ilvl:lots foo:bar"#);
+        assert_eq!(parsed.text, r#"Samurai's "Hakama of" ilvl:lots foo:bar"#);
+        assert_eq!(parsed.filters, SearchFilters::default());
+    }
+
+    #[test]
+    fn quoted_values_keep_their_spaces() {
+        let parsed = parse_search_query(r#"type:"job equipment" cat:"Ceramic Gear""#);
+        assert_eq!(parsed.filters.types, ["job equipment"]);
+        assert_eq!(parsed.filters.categories, ["ceramic gear"]);
+        assert_eq!(
+            format_filter("cat", "Ceramic Gear"),
+            r#"cat:"Ceramic Gear""#
+        );
+        assert_eq!(format_filter("job", "WHM"), "job:WHM");
+    }
+}
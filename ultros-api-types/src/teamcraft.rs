@@ -0,0 +1,416 @@
+//! Interchange between Ultros lists and FFXIV Teamcraft lists.
+//!
+//! Teamcraft accepts lists through its import link,
+//! `https://ffxivteamcraft.com/import/<base64>`, where the payload is
+//! `itemId,recipeId,quantity` entries joined by `;` and `recipeId` is `null`
+//! for items bought or gathered rather than crafted. The link has no room
+//! for progress, so exports carry what is still needed and the JSON export
+//! keeps `done` counts alongside. Both shapes import back, along with list
+//! JSON whose `finalItems` rows carry `id`, `amount`, `done`, `requiredHQ`
+//! and `recipeId`.
+//!
+//! This crate has no game data, so callers expand crafted rows into their
+//! ingredients with a recipe lookup. The web list view and the `/ffxiv list`
+//! commands share everything else.
+
+use base64::{
+    Engine,
+    engine::general_purpose::{STANDARD, URL_SAFE},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::list::ListItem;
+
+pub const IMPORT_LINK_PREFIX: &str = "https://ffxivteamcraft.com/import/";
+
+#[derive(Error, Debug)]
+pub enum TeamcraftError {
+    #[error("Nothing to import")]
+    Empty,
+    #[error(
+        "Teamcraft list links only point at Teamcraft's servers. Use the list's import link or JSON export instead"
+    )]
+    ListLink,
+    #[error("Invalid Teamcraft entry `{0}`, expected itemId,recipeId,quantity")]
+    InvalidEntry(String),
+    #[error("Invalid Teamcraft JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+}
+
+/// Whether Teamcraft plans to craft an item or get it some other way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Intent {
+    Buy,
+    Craft { recipe_id: i32 },
+}
+
+/// One row of a Teamcraft list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TeamcraftItem {
+    pub item_id: i32,
+    pub quantity: i32,
+    /// How many are already done. Import links don't carry this.
+    pub done: i32,
+    /// `None` when the row doesn't care about quality.
+    pub hq: Option<bool>,
+    pub intent: Intent,
+}
+
+/// What one craft of a recipe makes and takes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecipeParts {
+    pub amount_result: i32,
+    /// `(item_id, amount)` per craft.
+    pub ingredients: Vec<(i32, i32)>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ListRow {
+    id: i32,
+    amount: i32,
+    #[serde(default)]
+    done: i32,
+    #[serde(
+        default,
+        rename = "requiredHQ",
+        skip_serializing_if = "Option::is_none"
+    )]
+    required_hq: Option<bool>,
+    /// Teamcraft stores recipe ids as strings, but numbers are accepted too.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recipe_id: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ListExport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default)]
+    final_items: Vec<ListRow>,
+    /// Teamcraft's derived ingredient rows. Only read when there are no final
+    /// items, since they restate what the final items need.
+    #[serde(default, skip_serializing)]
+    items: Vec<ListRow>,
+}
+
+impl From<ListRow> for TeamcraftItem {
+    fn from(row: ListRow) -> Self {
+        let recipe_id = row.recipe_id.and_then(|recipe| match recipe {
+            Value::String(recipe) => recipe.parse().ok(),
+            Value::Number(recipe) => recipe.as_i64()?.try_into().ok(),
+            _ => None,
+        });
+        Self {
+            item_id: row.id,
+            quantity: row.amount,
+            done: row.done,
+            hq: row.required_hq.filter(|hq| *hq),
+            intent: recipe_id.map_or(Intent::Buy, |recipe_id| Intent::Craft { recipe_id }),
+        }
+    }
+}
+
+fn parse_entries(entries: &str) -> Result<Vec<TeamcraftItem>, TeamcraftError> {
+    entries
+        .split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let invalid = || TeamcraftError::InvalidEntry(entry.to_string());
+            let mut fields = entry.split(',').map(str::trim);
+            let (Some(item_id), Some(recipe_id), Some(quantity), None) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return Err(invalid());
+            };
+            let intent = match recipe_id {
+                "" | "null" | "undefined" => Intent::Buy,
+                recipe_id => Intent::Craft {
+                    recipe_id: recipe_id.parse().map_err(|_| invalid())?,
+                },
+            };
+            Ok(TeamcraftItem {
+                item_id: item_id.parse().map_err(|_| invalid())?,
+                quantity: quantity.parse().map_err(|_| invalid())?,
+                done: 0,
+                hq: None,
+                intent,
+            })
+        })
+        .collect()
+}
+
+/// Decode an import link or its bare payload. Links copied out of a browser
+/// may have the base64 padding percent-encoded.
+fn decode_import(input: &str) -> Option<String> {
+    let payload = input
+        .rsplit_once("/import/")
+        .map_or(input, |(_, payload)| payload)
+        .trim_end_matches('/')
+        .replace("%2B", "+")
+        .replace("%2F", "/")
+        .replace("%3D", "=");
+    let bytes = STANDARD
+        .decode(&payload)
+        .or_else(|_| URL_SAFE.decode(&payload))
+        .ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Parse anything Teamcraft hands out for a list: an import link, its base64
+/// payload, the decoded `itemId,recipeId,quantity;...` text, or list JSON.
+pub fn parse(input: &str) -> Result<Vec<TeamcraftItem>, TeamcraftError> {
+    let input = input.trim();
+    let items = if input.is_empty() {
+        return Err(TeamcraftError::Empty);
+    } else if input.starts_with('{') {
+        let export: ListExport = serde_json::from_str(input)?;
+        let rows = if export.final_items.is_empty() {
+            export.items
+        } else {
+            export.final_items
+        };
+        rows.into_iter().map(TeamcraftItem::from).collect()
+    } else if input.starts_with('[') {
+        let rows: Vec<ListRow> = serde_json::from_str(input)?;
+        rows.into_iter().map(TeamcraftItem::from).collect()
+    } else if input.contains("ffxivteamcraft.com/list/") {
+        return Err(TeamcraftError::ListLink);
+    } else if input.contains(',') {
+        parse_entries(input)?
+    } else {
+        let decoded =
+            decode_import(input).ok_or_else(|| TeamcraftError::InvalidEntry(input.to_string()))?;
+        parse_entries(&decoded)?
+    };
+    if items.is_empty() {
+        Err(TeamcraftError::Empty)
+    } else {
+        Ok(items)
+    }
+}
+
+/// Turn Teamcraft rows into list items. Bought rows keep their `done` count
+/// as acquired. Crafted rows become the ingredients for the crafts still to
+/// do, or the item itself when `recipe` doesn't know the recipe. Rows for the
+/// same item and quality are merged, since adding to a list expects one row
+/// per item.
+pub fn to_list_items(
+    items: &[TeamcraftItem],
+    recipe: impl Fn(i32) -> Option<RecipeParts>,
+) -> Vec<ListItem> {
+    let mut list_items: Vec<ListItem> = vec![];
+    let mut add = |item_id: i32, hq: Option<bool>, quantity: i32, acquired: i32| {
+        if quantity <= 0 {
+            return;
+        }
+        let acquired = acquired.clamp(0, quantity);
+        match list_items
+            .iter_mut()
+            .find(|item| item.item_id == item_id && item.hq == hq)
+        {
+            Some(item) => {
+                item.quantity = item.quantity.map(|q| q + quantity);
+                item.acquired = item.acquired.map(|a| a + acquired);
+            }
+            None => list_items.push(ListItem {
+                item_id,
+                hq,
+                quantity: Some(quantity),
+                acquired: Some(acquired),
+                ..Default::default()
+            }),
+        }
+    };
+    for item in items {
+        let parts = match item.intent {
+            Intent::Craft { recipe_id } => recipe(recipe_id),
+            Intent::Buy => None,
+        };
+        match parts {
+            Some(parts) => {
+                let remaining = (item.quantity - item.done).max(0);
+                let crafts =
+                    (remaining + parts.amount_result.max(1) - 1) / parts.amount_result.max(1);
+                for (ingredient, amount) in parts.ingredients {
+                    add(ingredient, None, amount * crafts, 0);
+                }
+            }
+            None => add(item.item_id, item.hq, item.quantity, item.done),
+        }
+    }
+    list_items
+}
+
+fn remaining(item: &ListItem) -> i32 {
+    let quantity = item.quantity.unwrap_or(1).max(1);
+    quantity - item.acquired.unwrap_or(0).clamp(0, quantity)
+}
+
+/// A Teamcraft import link for what is still needed on the list. Items that
+/// are fully acquired are left out.
+pub fn import_link(items: &[ListItem]) -> String {
+    let entries = items
+        .iter()
+        .filter(|item| remaining(item) > 0)
+        .map(|item| format!("{},null,{}", item.item_id, remaining(item)))
+        .collect::<Vec<_>>()
+        .join(";");
+    format!("{IMPORT_LINK_PREFIX}{}", STANDARD.encode(entries))
+}
+
+/// The list as Teamcraft list JSON, keeping acquired counts as `done`.
+pub fn export_json(name: &str, items: &[ListItem]) -> String {
+    let export = ListExport {
+        name: Some(name.to_string()),
+        final_items: items
+            .iter()
+            .map(|item| {
+                let amount = item.quantity.unwrap_or(1).max(1);
+                ListRow {
+                    id: item.item_id,
+                    amount,
+                    done: amount - remaining(item),
+                    required_hq: item.hq.filter(|hq| *hq),
+                    recipe_id: None,
+                }
+            })
+            .collect(),
+        items: vec![],
+    };
+    serde_json::to_string_pretty(&export).expect("list export serializes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list_item(item_id: i32, quantity: i32, acquired: i32, hq: Option<bool>) -> ListItem {
+        ListItem {
+            item_id,
+            hq,
+            quantity: Some(quantity),
+            acquired: Some(acquired),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn import_link_round_trips_remaining_quantities() {
+        let items = [
+            list_item(5111, 10, 4, None),
+            list_item(5057, 3, 3, Some(true)),
+            list_item(5333, 2, 0, Some(true)),
+        ];
+        let link = import_link(&items);
+        assert!(link.starts_with(IMPORT_LINK_PREFIX));
+        assert_eq!(
+            decode_import(&link).as_deref(),
+            Some("5111,null,6;5333,null,2")
+        );
+        let parsed = parse(&link.replace('=', "%3D")).unwrap();
+        assert_eq!(
+            parsed
+                .iter()
+                .map(|item| (item.item_id, item.quantity, item.intent))
+                .collect::<Vec<_>>(),
+            vec![(5111, 6, Intent::Buy), (5333, 2, Intent::Buy)]
+        );
+    }
+
+    #[test]
+    fn json_round_trips_progress_and_quality() {
+        let items = [
+            list_item(5111, 10, 4, None),
+            list_item(5333, 2, 1, Some(true)),
+        ];
+        let parsed = parse(&export_json("Gear", &items)).unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                TeamcraftItem {
+                    item_id: 5111,
+                    quantity: 10,
+                    done: 4,
+                    hq: None,
+                    intent: Intent::Buy,
+                },
+                TeamcraftItem {
+                    item_id: 5333,
+                    quantity: 2,
+                    done: 1,
+                    hq: Some(true),
+                    intent: Intent::Buy,
+                },
+            ]
+        );
+        assert_eq!(to_list_items(&parsed, |_| None), items);
+    }
+
+    #[test]
+    fn parse_reads_recipe_intent_and_rejects_list_links() {
+        let parsed = parse("36221,4321,3; 5111,null,20").unwrap();
+        assert_eq!(parsed[0].intent, Intent::Craft { recipe_id: 4321 });
+        assert_eq!(parsed[1].intent, Intent::Buy);
+
+        let json = r#"{"finalItems":[{"id":36221,"amount":2,"done":0,"recipeId":"4321","requiredHQ":true}],
+            "items":[{"id":5111,"amount":99}]}"#;
+        let parsed = parse(json).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].intent, Intent::Craft { recipe_id: 4321 });
+        assert_eq!(parsed[0].hq, Some(true));
+
+        assert!(matches!(
+            parse("https://ffxivteamcraft.com/list/abc123"),
+            Err(TeamcraftError::ListLink)
+        ));
+        assert!(matches!(parse("1,2"), Err(TeamcraftError::InvalidEntry(_))));
+        assert!(matches!(parse("  "), Err(TeamcraftError::Empty)));
+    }
+
+    #[test]
+    fn crafted_rows_expand_into_ingredients_for_the_crafts_left() {
+        let items = [
+            TeamcraftItem {
+                item_id: 36221,
+                quantity: 5,
+                done: 1,
+                hq: Some(true),
+                intent: Intent::Craft { recipe_id: 4321 },
+            },
+            TeamcraftItem {
+                item_id: 5111,
+                quantity: 4,
+                done: 0,
+                hq: None,
+                intent: Intent::Buy,
+            },
+            TeamcraftItem {
+                item_id: 999,
+                quantity: 1,
+                done: 0,
+                hq: None,
+                intent: Intent::Craft { recipe_id: 1 },
+            },
+        ];
+        let recipe = |recipe_id| {
+            (recipe_id == 4321).then(|| RecipeParts {
+                amount_result: 3,
+                ingredients: vec![(5111, 2), (5057, 1)],
+            })
+        };
+        // 4 still to craft at 3 per craft is 2 crafts.
+        assert_eq!(
+            to_list_items(&items, recipe),
+            vec![
+                list_item(5111, 8, 0, None),
+                list_item(5057, 2, 0, None),
+                list_item(999, 1, 0, None),
+            ]
+        );
+    }
+}
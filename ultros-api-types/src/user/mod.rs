@@ -1,7 +1,13 @@
+mod api_token;
 pub mod group;
+mod retainer_ledger;
+mod session;
 mod user_data;
 mod user_retainers;
 
+pub use api_token::*;
 pub use group::*;
+pub use retainer_ledger::*;
+pub use session::UserSession;
 pub use user_data::UserData;
 pub use user_retainers::*;
@@ -0,0 +1,109 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+/// The marketboard's cut of every sale, in percent. Matches the 95% the flip
+/// tools assume the seller keeps.
+pub const MARKET_TAX_PERCENT: i64 = 5;
+
+/// The tax withheld from a sale grossing `gross` gil.
+pub fn market_tax(gross: i64) -> i64 {
+    gross * MARKET_TAX_PERCENT / 100
+}
+
+/// Sales credited to the user's retainers, summed over one group.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LedgerTotals {
+    pub sales: i64,
+    pub units: i64,
+    /// What buyers paid.
+    pub gross: i64,
+    /// The marketboard's cut of `gross`.
+    pub tax: i64,
+    /// Mean seconds from a listing's last price change to its sale.
+    pub avg_seconds_to_sell: Option<i64>,
+}
+
+impl LedgerTotals {
+    /// What the retainers took home.
+    pub fn revenue(&self) -> i64 {
+        self.gross - self.tax
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RetainerLedgerRow {
+    pub retainer_id: i32,
+    pub retainer_name: String,
+    pub totals: LedgerTotals,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ItemLedgerRow {
+    pub item_id: i32,
+    pub totals: LedgerTotals,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WeekLedgerRow {
+    /// The Monday the week starts on.
+    pub week: NaiveDate,
+    pub totals: LedgerTotals,
+}
+
+/// One sale credited to a retainer.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RetainerSale {
+    pub retainer_id: i32,
+    pub world_id: i32,
+    pub item_id: i32,
+    pub hq: bool,
+    pub quantity: i32,
+    pub price_per_unit: i32,
+    pub gross: i64,
+    pub tax: i64,
+    pub listed_at: NaiveDateTime,
+    pub sold_at: NaiveDateTime,
+}
+
+/// Sales made by the user's retainers since `since`, broken down per
+/// retainer, per item and per week, with the most recent sales.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RetainerLedger {
+    pub since: NaiveDateTime,
+    pub total: LedgerTotals,
+    /// Highest revenue first.
+    pub retainers: Vec<RetainerLedgerRow>,
+    /// Highest revenue first.
+    pub items: Vec<ItemLedgerRow>,
+    /// Oldest week first.
+    pub weeks: Vec<WeekLedgerRow>,
+    /// Newest first.
+    pub recent: Vec<RetainerSale>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revenue_is_gross_after_the_market_cut() {
+        let totals = LedgerTotals {
+            sales: 2,
+            units: 3,
+            gross: 10_000,
+            tax: market_tax(10_000),
+            avg_seconds_to_sell: Some(60),
+        };
+        assert_eq!(totals.tax, 500);
+        assert_eq!(totals.revenue(), 9_500);
+        // Whole gil, rounded down.
+        assert_eq!(market_tax(19), 0);
+        assert_eq!(market_tax(39), 1);
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug, PartialEq, PartialOrd, Eq, Ord, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct UserData {
     // Discord snowflake. Serialized as a JSON string so the value survives a
     // round-trip through any JS engine — snowflakes routinely exceed 2^53 and
@@ -8,6 +9,7 @@ pub struct UserData {
     // deserializer accepts either a string or a JSON number to remain
     // compatible with older clients / cached payloads.
     #[serde(with = "u64_string")]
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
     pub id: u64,
     pub username: String,
     pub avatar: String,
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use crate::{ActiveListing, FfxivCharacter, retainer::Retainer};
 
 #[derive(Serialize, Deserialize, Default, Debug, PartialEq, PartialOrd, Eq, Ord, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct OwnedRetainer {
     pub id: i32,
     pub retainer_id: i32,
@@ -22,12 +23,14 @@ pub type UserRetainerListWithListings =
 
 /// List of all user retainers. User retainer are grouped by character
 #[derive(Serialize, Deserialize, Default, Debug, PartialEq, PartialOrd, Eq, Ord, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct UserRetainers {
     /// List of all the user's retainers. If no character is associated, it will be placed into the None.
     pub retainers: UserRetainerList,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct UserRetainerListings {
     /// List of all the user's retainers. If no character is associated, it will be placed into the None.
     pub retainers: UserRetainerListWithListings,
@@ -360,6 +360,7 @@ mod tests {
                 quantity: 1,
                 hq: false,
                 timestamp: NaiveDateTime::default(),
+                materia: vec![],
             },
             Retainer {
                 id: 7,
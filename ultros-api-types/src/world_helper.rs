@@ -83,6 +83,7 @@ impl From<OwnedResult> for AnySelector {
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum AnySelector {
     Region(i32),
     Datacenter(i32),
@@ -746,6 +747,7 @@ mod tests {
                 quantity: 1,
                 hq: false,
                 timestamp: chrono::Utc::now().naive_utc(),
+                materia: vec![],
             },
             ActiveListing {
                 id: 2,
@@ -756,6 +758,7 @@ mod tests {
                 quantity: 1,
                 hq: false,
                 timestamp: chrono::Utc::now().naive_utc(),
+                materia: vec![],
             },
         ];
         let mut excluded = std::collections::HashSet::new();
@@ -778,6 +781,7 @@ mod tests {
             quantity: 1,
             hq: false,
             timestamp: chrono::Utc::now().naive_utc(),
+            materia: vec![],
         }];
         let mut excluded = std::collections::HashSet::new();
         excluded.insert("Primal");
@@ -799,6 +803,7 @@ mod tests {
             quantity: 1,
             hq: false,
             timestamp: chrono::Utc::now().naive_utc(),
+            materia: vec![],
         }];
         let mut excluded = std::collections::HashSet::new();
         excluded.insert("Aether");
@@ -832,6 +837,7 @@ mod tests {
                 quantity: 1,
                 hq: false,
                 timestamp: chrono::Utc::now().naive_utc(),
+                materia: vec![],
             },
             ActiveListing {
                 id: 2,
@@ -842,6 +848,7 @@ mod tests {
                 quantity: 1,
                 hq: false,
                 timestamp: chrono::Utc::now().naive_utc(),
+                materia: vec![],
             },
         ];
         let mut excluded = std::collections::HashSet::new();
@@ -865,6 +872,7 @@ mod tests {
                 quantity: 1,
                 hq: false,
                 timestamp: chrono::Utc::now().naive_utc(),
+                materia: vec![],
             },
             ActiveListing {
                 id: 2,
@@ -875,6 +883,7 @@ mod tests {
                 quantity: 1,
                 hq: false,
                 timestamp: chrono::Utc::now().naive_utc(),
+                materia: vec![],
             },
         ];
         let mut excluded = std::collections::HashSet::new();
@@ -897,6 +906,7 @@ mod tests {
             quantity: 1,
             hq: false,
             timestamp: chrono::Utc::now().naive_utc(),
+            materia: vec![],
         }];
         let mut excluded = std::collections::HashSet::new();
         excluded.insert("ADAMANTOISE");
@@ -917,6 +927,7 @@ mod tests {
             quantity: 1,
             hq: false,
             timestamp: chrono::Utc::now().naive_utc(),
+            materia: vec![],
         }];
         let mut excluded = std::collections::HashSet::new();
         excluded.insert(100);
@@ -938,6 +949,7 @@ mod tests {
                 quantity: 1,
                 hq: false,
                 timestamp: chrono::Utc::now().naive_utc(),
+                materia: vec![],
             },
             "some extra data",
         )];
@@ -971,6 +983,7 @@ mod tests {
             quantity: 1,
             hq: false,
             timestamp: chrono::Utc::now().naive_utc(),
+            materia: vec![],
         }];
         let excluded = std::collections::HashSet::<i32>::new();
 
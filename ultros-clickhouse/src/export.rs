@@ -0,0 +1,450 @@
+//! File encodings for bulk sale-history exports.
+//!
+//! The rows come from [`crate::queries::export_sales`]; this module only turns
+//! them into bytes. Every format is produced incrementally: an
+//! [`ExportEncoder`] hands back a chunk whenever it has buffered enough, so an
+//! export of a region's worth of history never has to sit in memory at once.
+//!
+//! Each row type describes itself once through [`ExportRow`] — a fixed list
+//! of typed [`Column`]s and the matching [`Value`]s — and all three encoders
+//! are driven from that, so a column can't appear in the CSV and be missing
+//! from the Parquet schema.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use arrow_array::{
+    ArrayRef, RecordBatch,
+    builder::{
+        BooleanBuilder, Date32Builder, Float64Builder, Int64Builder, TimestampSecondBuilder,
+    },
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, NaiveDate, Utc};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+
+use crate::ClickHouseError;
+
+/// Text formats hand back a chunk once this many bytes are buffered.
+const TEXT_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Rows per Parquet row group. Each row group is written out as soon as it
+/// fills, which is what lets a Parquet export stream at all.
+const PARQUET_ROW_GROUP: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "csv" => Self::Csv,
+            "ndjson" | "jsonl" => Self::Ndjson,
+            "parquet" => Self::Parquet,
+            _ => return None,
+        })
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+            Self::Parquet => "parquet",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Ndjson => "application/x-ndjson",
+            Self::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Int,
+    Float,
+    Bool,
+    /// Seconds, UTC.
+    Timestamp,
+    /// A UTC calendar day.
+    Date,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    pub name: &'static str,
+    pub kind: ColumnKind,
+}
+
+impl Column {
+    pub const fn new(name: &'static str, kind: ColumnKind) -> Self {
+        Self { name, kind }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Timestamp(DateTime<Utc>),
+    Date(NaiveDate),
+}
+
+/// A row that can be written by an [`ExportEncoder`].
+pub trait ExportRow {
+    const COLUMNS: &'static [Column];
+
+    /// One value per entry in [`Self::COLUMNS`], in the same order and of the
+    /// matching kind.
+    fn values(&self) -> Vec<Value>;
+}
+
+/// Incremental encoder for one export.
+///
+/// Feed rows through [`push`](Self::push), forwarding whatever chunks it
+/// returns, then append the bytes from [`finish`](Self::finish). The chunks
+/// concatenated in order are the complete file.
+pub struct ExportEncoder<R> {
+    sink: Sink,
+    _row: PhantomData<fn(&R)>,
+}
+
+enum Sink {
+    Csv(Vec<u8>),
+    Ndjson(Vec<u8>),
+    Parquet(Box<ParquetSink>),
+}
+
+impl<R: ExportRow> ExportEncoder<R> {
+    pub fn new(format: ExportFormat) -> Result<Self, ClickHouseError> {
+        let sink = match format {
+            ExportFormat::Csv => {
+                let header = R::COLUMNS
+                    .iter()
+                    .map(|column| column.name)
+                    .collect::<Vec<_>>()
+                    .join(",");
+                Sink::Csv(format!("{header}\n").into_bytes())
+            }
+            ExportFormat::Ndjson => Sink::Ndjson(Vec::new()),
+            ExportFormat::Parquet => Sink::Parquet(Box::new(ParquetSink::new(R::COLUMNS)?)),
+        };
+        Ok(Self {
+            sink,
+            _row: PhantomData,
+        })
+    }
+
+    /// Encode one row, returning a chunk when enough has been buffered.
+    pub fn push(&mut self, row: &R) -> Result<Option<Vec<u8>>, ClickHouseError> {
+        let values = row.values();
+        match &mut self.sink {
+            Sink::Csv(buffer) => {
+                write_csv_line(buffer, &values);
+                Ok(take_full(buffer))
+            }
+            Sink::Ndjson(buffer) => {
+                write_json_line(buffer, R::COLUMNS, &values);
+                Ok(take_full(buffer))
+            }
+            Sink::Parquet(sink) => sink.push(&values),
+        }
+    }
+
+    /// Flush anything buffered and close the file.
+    pub fn finish(self) -> Result<Vec<u8>, ClickHouseError> {
+        match self.sink {
+            Sink::Csv(buffer) | Sink::Ndjson(buffer) => Ok(buffer),
+            Sink::Parquet(sink) => sink.finish(),
+        }
+    }
+}
+
+fn take_full(buffer: &mut Vec<u8>) -> Option<Vec<u8>> {
+    (buffer.len() >= TEXT_CHUNK_BYTES).then(|| std::mem::take(buffer))
+}
+
+fn write_value(out: &mut String, value: Value) {
+    use std::fmt::Write;
+
+    // Writing into a `String` can't fail.
+    let _ = match value {
+        Value::Int(v) => write!(out, "{v}"),
+        Value::Float(v) if v.is_finite() => write!(out, "{v}"),
+        Value::Float(_) => write!(out, "null"),
+        Value::Bool(v) => write!(out, "{v}"),
+        Value::Timestamp(v) => write!(out, "{}", v.format("%Y-%m-%dT%H:%M:%SZ")),
+        Value::Date(v) => write!(out, "{}", v.format("%Y-%m-%d")),
+    };
+}
+
+/// Every value is a number, a bool or a date, so nothing needs quoting.
+fn write_csv_line(buffer: &mut Vec<u8>, values: &[Value]) {
+    let mut line = String::new();
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            line.push(',');
+        }
+        match value {
+            Value::Float(v) if !v.is_finite() => {}
+            value => write_value(&mut line, *value),
+        }
+    }
+    line.push('\n');
+    buffer.extend_from_slice(line.as_bytes());
+}
+
+fn write_json_line(buffer: &mut Vec<u8>, columns: &[Column], values: &[Value]) {
+    let mut line = String::from("{");
+    for (i, (column, value)) in columns.iter().zip(values).enumerate() {
+        if i > 0 {
+            line.push(',');
+        }
+        line.push('"');
+        line.push_str(column.name);
+        line.push_str("\":");
+        let quoted = matches!(value, Value::Timestamp(_) | Value::Date(_));
+        if quoted {
+            line.push('"');
+        }
+        write_value(&mut line, *value);
+        if quoted {
+            line.push('"');
+        }
+    }
+    line.push_str("}\n");
+    buffer.extend_from_slice(line.as_bytes());
+}
+
+enum ColumnBuilder {
+    Int(Int64Builder),
+    Float(Float64Builder),
+    Bool(BooleanBuilder),
+    Timestamp(TimestampSecondBuilder),
+    Date(Date32Builder),
+}
+
+impl ColumnBuilder {
+    fn new(kind: ColumnKind) -> Self {
+        match kind {
+            ColumnKind::Int => Self::Int(Int64Builder::new()),
+            ColumnKind::Float => Self::Float(Float64Builder::new()),
+            ColumnKind::Bool => Self::Bool(BooleanBuilder::new()),
+            ColumnKind::Timestamp => {
+                Self::Timestamp(TimestampSecondBuilder::new().with_timezone("UTC"))
+            }
+            ColumnKind::Date => Self::Date(Date32Builder::new()),
+        }
+    }
+
+    fn data_type(kind: ColumnKind) -> DataType {
+        match kind {
+            ColumnKind::Int => DataType::Int64,
+            ColumnKind::Float => DataType::Float64,
+            ColumnKind::Bool => DataType::Boolean,
+            ColumnKind::Timestamp => DataType::Timestamp(TimeUnit::Second, Some("UTC".into())),
+            ColumnKind::Date => DataType::Date32,
+        }
+    }
+
+    fn append(&mut self, value: Value) -> Result<(), ClickHouseError> {
+        match (self, value) {
+            (Self::Int(b), Value::Int(v)) => b.append_value(v),
+            (Self::Float(b), Value::Float(v)) => b.append_value(v),
+            (Self::Bool(b), Value::Bool(v)) => b.append_value(v),
+            (Self::Timestamp(b), Value::Timestamp(v)) => b.append_value(v.timestamp()),
+            (Self::Date(b), Value::Date(v)) => b.append_value(
+                v.signed_duration_since(DateTime::UNIX_EPOCH.date_naive())
+                    .num_days() as i32,
+            ),
+            (_, value) => {
+                return Err(ClickHouseError::Export(format!(
+                    "value {value:?} doesn't match its column"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::Int(b) => Arc::new(b.finish()),
+            Self::Float(b) => Arc::new(b.finish()),
+            Self::Bool(b) => Arc::new(b.finish()),
+            Self::Timestamp(b) => Arc::new(b.finish()),
+            Self::Date(b) => Arc::new(b.finish()),
+        }
+    }
+}
+
+struct ParquetSink {
+    schema: SchemaRef,
+    columns: Vec<ColumnBuilder>,
+    rows: usize,
+    writer: ArrowWriter<Vec<u8>>,
+}
+
+impl ParquetSink {
+    fn new(columns: &[Column]) -> Result<Self, ClickHouseError> {
+        let schema = Arc::new(Schema::new(
+            columns
+                .iter()
+                .map(|c| Field::new(c.name, ColumnBuilder::data_type(c.kind), false))
+                .collect::<Vec<_>>(),
+        ));
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(PARQUET_ROW_GROUP)
+            .build();
+        let writer = ArrowWriter::try_new(Vec::new(), schema.clone(), Some(properties))
+            .map_err(|e| ClickHouseError::Export(e.to_string()))?;
+        Ok(Self {
+            schema,
+            columns: columns.iter().map(|c| ColumnBuilder::new(c.kind)).collect(),
+            rows: 0,
+            writer,
+        })
+    }
+
+    fn push(&mut self, values: &[Value]) -> Result<Option<Vec<u8>>, ClickHouseError> {
+        if values.len() != self.columns.len() {
+            return Err(ClickHouseError::Export(format!(
+                "row has {} values for {} columns",
+                values.len(),
+                self.columns.len()
+            )));
+        }
+        for (column, value) in self.columns.iter_mut().zip(values) {
+            column.append(*value)?;
+        }
+        self.rows += 1;
+        if self.rows < PARQUET_ROW_GROUP {
+            return Ok(None);
+        }
+        self.write_row_group()?;
+        // Parquet is written strictly front to back — only the footer refers
+        // back to earlier offsets, and the writer tracks those itself — so
+        // the bytes of a closed row group can be handed off right away.
+        Ok(Some(std::mem::take(self.writer.inner_mut())))
+    }
+
+    fn write_row_group(&mut self) -> Result<(), ClickHouseError> {
+        if self.rows == 0 {
+            return Ok(());
+        }
+        let arrays = self.columns.iter_mut().map(ColumnBuilder::finish).collect();
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)
+            .map_err(|e| ClickHouseError::Export(e.to_string()))?;
+        self.rows = 0;
+        self.writer
+            .write(&batch)
+            .and_then(|()| self.writer.flush())
+            .map_err(|e| ClickHouseError::Export(e.to_string()))
+    }
+
+    fn finish(mut self) -> Result<Vec<u8>, ClickHouseError> {
+        self.write_row_group()?;
+        self.writer
+            .into_inner()
+            .map_err(|e| ClickHouseError::Export(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestRow {
+        id: i64,
+        price: f64,
+        hq: bool,
+        at: DateTime<Utc>,
+        day: NaiveDate,
+    }
+
+    impl ExportRow for TestRow {
+        const COLUMNS: &'static [Column] = &[
+            Column::new("id", ColumnKind::Int),
+            Column::new("price", ColumnKind::Float),
+            Column::new("hq", ColumnKind::Bool),
+            Column::new("at", ColumnKind::Timestamp),
+            Column::new("day", ColumnKind::Date),
+        ];
+
+        fn values(&self) -> Vec<Value> {
+            vec![
+                Value::Int(self.id),
+                Value::Float(self.price),
+                Value::Bool(self.hq),
+                Value::Timestamp(self.at),
+                Value::Date(self.day),
+            ]
+        }
+    }
+
+    fn row(id: i64) -> TestRow {
+        let at = DateTime::from_timestamp(1_700_006_400 + id, 0).unwrap();
+        TestRow {
+            id,
+            price: 1.5,
+            hq: id % 2 == 0,
+            at,
+            day: at.date_naive(),
+        }
+    }
+
+    fn encode(format: ExportFormat, rows: impl IntoIterator<Item = TestRow>) -> Vec<u8> {
+        let mut encoder = ExportEncoder::new(format).unwrap();
+        let mut out = Vec::new();
+        for row in rows {
+            if let Some(chunk) = encoder.push(&row).unwrap() {
+                out.extend(chunk);
+            }
+        }
+        out.extend(encoder.finish().unwrap());
+        out
+    }
+
+    #[test]
+    fn text_formats_share_the_column_list() {
+        let csv = String::from_utf8(encode(ExportFormat::Csv, [row(0)])).unwrap();
+        assert_eq!(
+            csv,
+            "id,price,hq,at,day\n0,1.5,true,2023-11-15T00:00:00Z,2023-11-15\n"
+        );
+        let ndjson = String::from_utf8(encode(ExportFormat::Ndjson, [row(1)])).unwrap();
+        assert_eq!(
+            ndjson,
+            "{\"id\":1,\"price\":1.5,\"hq\":false,\"at\":\"2023-11-15T00:00:01Z\",\"day\":\"2023-11-15\"}\n"
+        );
+    }
+
+    #[test]
+    fn parquet_streams_row_groups_into_one_readable_file() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let rows = PARQUET_ROW_GROUP as i64 * 2 + 10;
+        let mut encoder = ExportEncoder::new(ExportFormat::Parquet).unwrap();
+        let mut chunks = Vec::new();
+        for id in 0..rows {
+            if let Some(chunk) = encoder.push(&row(id)).unwrap() {
+                chunks.push(chunk);
+            }
+        }
+        assert_eq!(chunks.len(), 2, "each full row group is handed off");
+        chunks.push(encoder.finish().unwrap());
+
+        let file = bytes::Bytes::from(chunks.concat());
+        let reader = SerializedFileReader::new(file).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.num_row_groups(), 3);
+        assert_eq!(metadata.file_metadata().num_rows(), rows);
+    }
+}
@@ -5,6 +5,7 @@
 //! - Typed row structs ([`rows`]) used by both writers and readers
 //! - The dual-write [`writer::Writer`] that mirrors sale events from the event bus
 //! - Read-side query helpers ([`queries`]) used by the analyzer
+//! - File encodings for bulk sale exports ([`export`])
 //! - One-shot backfill ([`backfill`]) from Postgres `sale_history`
 //! - Scheduled rollup refreshers ([`rollups`])
 //!
@@ -14,6 +15,7 @@
 //! Analyzer, FC Crafting). CH backs the deeper trend/historical math.
 
 pub mod backfill;
+pub mod export;
 pub mod quality_filter;
 pub mod queries;
 pub mod rollups;
@@ -34,6 +36,8 @@ pub enum ClickHouseError {
     Client(#[from] clickhouse::error::Error),
     #[error("Backfill error: {0}")]
     Backfill(String),
+    #[error("Export encoding error: {0}")]
+    Export(String),
 }
 
 /// A stable, low-cardinality label for *why* a ClickHouse call failed.
@@ -93,6 +97,8 @@ impl ClickHouseError {
             // Backfill failures wrap a Postgres-side message; there is no
             // ClickHouse status code to read.
             ClickHouseError::Backfill(_) => ClickHouseErrorKind::Other,
+            // Raised while writing the file, after ClickHouse has answered.
+            ClickHouseError::Export(_) => ClickHouseErrorKind::Other,
             ClickHouseError::Client(e) => classify_client_error(&e.to_string()),
         }
     }
@@ -5,13 +5,19 @@
 //! statistically sound numbers from `item_stats_window` + `item_quality_score`.
 //!
 //! The Market Pulse home-page tile uses [`market_pulse`].
+//!
+//! Bulk exports for offline analysis go through [`export_sales`].
 
 use clickhouse::Row;
+use futures::{StreamExt, stream::BoxStream};
 use serde::Deserialize;
+use ultros_api_types::alert_rule::MarketStat;
 use ultros_api_types::item_stats::ItemStatsVariant;
 use ultros_api_types::price_series::{HqFilter, SeriesGroup};
+use ultros_api_types::repricing::PriceBand;
 use ultros_api_types::trends::ConfidenceBand;
 
+use crate::export::{Column, ColumnKind, ExportEncoder, ExportFormat, ExportRow, Value};
 use crate::{ClickHouseClient, ClickHouseError};
 
 /// Rolled-up KPIs for one world: "today" (last 24h) + "yesterday"
@@ -436,33 +442,19 @@ impl DeepScan {
     /// breakpoints — good enough for a UI percentile chip without paying
     /// for a separate quantile query per item.
     pub fn price_percentile(&self, current_price: u32) -> u8 {
-        let breakpoints: [(u32, u8); 5] = [
-            (self.p10, 10),
-            (self.p25, 25),
-            (self.p50, 50),
-            (self.p75, 75),
-            (self.p90, 90),
-        ];
-        if current_price <= self.p10 {
-            return 0;
-        }
-        if current_price >= self.p90 {
-            return 100;
-        }
-        for w in breakpoints.windows(2) {
-            let (lo_p, lo_pct) = w[0];
-            let (hi_p, hi_pct) = w[1];
-            if current_price >= lo_p && current_price <= hi_p {
-                if hi_p == lo_p {
-                    return lo_pct;
-                }
-                let span = (hi_p - lo_p) as f32;
-                let delta = (current_price - lo_p) as f32;
-                let pct = lo_pct as f32 + (delta / span) * (hi_pct - lo_pct) as f32;
-                return pct.round() as u8;
-            }
+        self.price_band().percentile(current_price)
+    }
+
+    /// The cleaned percentile breakpoints, in the shape the repricing
+    /// suggestions take.
+    pub fn price_band(&self) -> PriceBand {
+        PriceBand {
+            p10: self.p10,
+            p25: self.p25,
+            p50: self.p50,
+            p75: self.p75,
+            p90: self.p90,
         }
-        50
     }
 }
 
@@ -647,6 +639,44 @@ fn weighted_mean_f32(values: &[f32], weights: &[u64]) -> f32 {
     (numerator / total) as f32
 }
 
+/// One [`MarketStat`] for an alert rule, folded across the per-world scans of
+/// one quality at one window.
+///
+/// Prices fold the same way [`aggregate_item_stats_variants`] does — weighted
+/// by each world's cleaned sample — and rates add across worlds before being
+/// divided by the window. Unlike the item view, a scope whose whole sample
+/// was filtered out has no price: an alert comparing against a made-up
+/// median is worse than one that waits for data, so that's `None` rather
+/// than a flat-mean fallback.
+pub fn rule_stat(scans: &[DeepScan], hq: bool, window_days: u16, stat: MarketStat) -> Option<f64> {
+    let group: Vec<&DeepScan> = scans
+        .iter()
+        .filter(|s| (s.hq != 0) == hq && s.window_days == window_days)
+        .collect();
+    if group.is_empty() || window_days == 0 {
+        return None;
+    }
+    let per_day = |total: u64| total as f64 / window_days as f64;
+    let price = |pick: fn(&DeepScan) -> u32| {
+        let cleaned: Vec<u64> = group.iter().map(|s| s.cleaned_sample_size as u64).collect();
+        if cleaned.iter().all(|c| *c == 0) {
+            return None;
+        }
+        let values: Vec<u32> = group.iter().map(|s| pick(s)).collect();
+        Some(weighted_mean_u32(&values, &cleaned) as f64)
+    };
+    match stat {
+        MarketStat::Median => price(|s| s.p50),
+        MarketStat::Vwap => price(|s| s.vwap),
+        MarketStat::P10 => price(|s| s.p10),
+        MarketStat::P25 => price(|s| s.p25),
+        MarketStat::P75 => price(|s| s.p75),
+        MarketStat::P90 => price(|s| s.p90),
+        MarketStat::SalesPerDay => Some(per_day(group.iter().map(|s| s.sample_size as u64).sum())),
+        MarketStat::UnitsPerDay => Some(per_day(group.iter().map(|s| s.unit_volume).sum())),
+    }
+}
+
 /// Single-item convenience wrapper.
 pub async fn deep_scan_one(
     ch: &ClickHouseClient,
@@ -990,6 +1020,239 @@ pub async fn price_density(
         .await?)
 }
 
+/// Row shape of a [`SalesExport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportGranularity {
+    /// One row per sale.
+    Sales,
+    /// One row per item, world, quality and UTC day, with OHLC, VWAP and
+    /// volume.
+    Daily,
+}
+
+/// Every sale of `item_ids` on `world_ids` within the half-open `[from, to)`
+/// window, optionally aggregated to days.
+#[derive(Debug, Clone)]
+pub struct SalesExport {
+    pub item_ids: Vec<i32>,
+    pub world_ids: Vec<i32>,
+    pub hq: HqFilter,
+    pub from: chrono::DateTime<chrono::Utc>,
+    pub to: chrono::DateTime<chrono::Utc>,
+    pub granularity: ExportGranularity,
+}
+
+/// One sale in an [`ExportGranularity::Sales`] export. Column order matches
+/// the SELECT.
+#[derive(Debug, Clone, Row, Deserialize)]
+pub struct ExportSaleRow {
+    pub item_id: i32,
+    pub world_id: i32,
+    pub hq: u8,
+    #[serde(with = "clickhouse::serde::chrono::datetime")]
+    pub sold_date: chrono::DateTime<chrono::Utc>,
+    pub price_per_item: u32,
+    pub quantity: u16,
+}
+
+impl ExportRow for ExportSaleRow {
+    const COLUMNS: &'static [Column] = &[
+        Column::new("item_id", ColumnKind::Int),
+        Column::new("world_id", ColumnKind::Int),
+        Column::new("hq", ColumnKind::Bool),
+        Column::new("sold_date", ColumnKind::Timestamp),
+        Column::new("price_per_item", ColumnKind::Int),
+        Column::new("quantity", ColumnKind::Int),
+    ];
+
+    fn values(&self) -> Vec<Value> {
+        vec![
+            Value::Int(self.item_id.into()),
+            Value::Int(self.world_id.into()),
+            Value::Bool(self.hq != 0),
+            Value::Timestamp(self.sold_date),
+            Value::Int(self.price_per_item.into()),
+            Value::Int(self.quantity.into()),
+        ]
+    }
+}
+
+/// One UTC day in an [`ExportGranularity::Daily`] export. Column order
+/// matches the SELECT.
+#[derive(Debug, Clone, Row, Deserialize)]
+pub struct ExportDailyRow {
+    pub item_id: i32,
+    pub world_id: i32,
+    pub hq: u8,
+    #[serde(with = "clickhouse::serde::chrono::date")]
+    pub day: chrono::NaiveDate,
+    pub open: u32,
+    pub high: u32,
+    pub low: u32,
+    pub close: u32,
+    /// `gil / units`.
+    pub vwap: f64,
+    pub units: u64,
+    pub gil: u64,
+    pub sales: u64,
+}
+
+impl ExportRow for ExportDailyRow {
+    const COLUMNS: &'static [Column] = &[
+        Column::new("item_id", ColumnKind::Int),
+        Column::new("world_id", ColumnKind::Int),
+        Column::new("hq", ColumnKind::Bool),
+        Column::new("day", ColumnKind::Date),
+        Column::new("open", ColumnKind::Int),
+        Column::new("high", ColumnKind::Int),
+        Column::new("low", ColumnKind::Int),
+        Column::new("close", ColumnKind::Int),
+        Column::new("vwap", ColumnKind::Float),
+        Column::new("units", ColumnKind::Int),
+        Column::new("gil", ColumnKind::Int),
+        Column::new("sales", ColumnKind::Int),
+    ];
+
+    fn values(&self) -> Vec<Value> {
+        vec![
+            Value::Int(self.item_id.into()),
+            Value::Int(self.world_id.into()),
+            Value::Bool(self.hq != 0),
+            Value::Date(self.day),
+            Value::Int(self.open.into()),
+            Value::Int(self.high.into()),
+            Value::Int(self.low.into()),
+            Value::Int(self.close.into()),
+            Value::Float(self.vwap),
+            Value::Int(self.units as i64),
+            Value::Int(self.gil as i64),
+            Value::Int(self.sales as i64),
+        ]
+    }
+}
+
+/// The `WHERE` clause of [`export_sales`]: [`window_predicate`] widened from
+/// one item to a set. Only numbers are interpolated.
+fn export_predicate(export: &SalesExport) -> String {
+    let list = |ids: &[i32]| {
+        ids.iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    format!(
+        "item_id IN ({items}) AND world_id IN ({worlds}) AND sold_date >= toDateTime({from_ts}) AND sold_date < toDateTime({to_ts}){hq_filter}",
+        items = list(&export.item_ids),
+        worlds = list(&export.world_ids),
+        from_ts = export.from.timestamp(),
+        to_ts = export.to.timestamp(),
+        hq_filter = hq_predicate(export.hq),
+    )
+}
+
+/// The encoded file, in chunks. Concatenated in order they're the whole file.
+pub type ExportStream = BoxStream<'static, Result<Vec<u8>, ClickHouseError>>;
+
+/// Stream `export` out of `sales` as a `format` file.
+///
+/// Rows are read off a ClickHouse cursor and encoded as they arrive, so the
+/// size of an export is bounded by what the caller is willing to download,
+/// not by memory. The query itself is only sent on the first poll; a failure
+/// part-way through ends the stream with an error after some chunks have
+/// already been produced.
+///
+/// Unlike [`price_series`] this reads with `FINAL`. An unmerged duplicate is
+/// noise in a chart bucket, but in an export it's a sale that never happened
+/// sitting in somebody's model; the item filter keeps the merge to the
+/// requested items' granules.
+///
+/// Rows are ordered `(item_id, hq, world_id, time)`, which is the table's
+/// sort key, so ClickHouse can read in order instead of sorting the export.
+pub fn export_sales(
+    ch: &ClickHouseClient,
+    export: &SalesExport,
+    format: ExportFormat,
+) -> Result<ExportStream, ClickHouseError> {
+    let empty = export.item_ids.is_empty() || export.world_ids.is_empty();
+    let predicate = export_predicate(export);
+    match export.granularity {
+        ExportGranularity::Sales => {
+            let sql = format!(
+                r#"
+                SELECT
+                    item_id,
+                    world_id,
+                    hq,
+                    sold_date,
+                    price_per_item,
+                    quantity
+                FROM sales FINAL
+                WHERE {predicate}
+                ORDER BY item_id, hq, world_id, sold_date
+                "#
+            );
+            encode_cursor::<ExportSaleRow>(ch, (!empty).then_some(sql), format)
+        }
+        ExportGranularity::Daily => {
+            let sql = format!(
+                r#"
+                SELECT
+                    item_id,
+                    world_id,
+                    hq,
+                    toDate(sold_date, 'UTC')                     AS day,
+                    toUInt32(argMin(price_per_item, sold_date))  AS open,
+                    toUInt32(max(price_per_item))                AS high,
+                    toUInt32(min(price_per_item))                AS low,
+                    toUInt32(argMax(price_per_item, sold_date))  AS close,
+                    toFloat64(sum(total_gil)) / greatest(sum(quantity), 1) AS vwap,
+                    toUInt64(sum(quantity))                      AS units,
+                    toUInt64(sum(total_gil))                     AS gil,
+                    toUInt64(count())                            AS sales
+                FROM sales FINAL
+                WHERE {predicate}
+                GROUP BY item_id, hq, world_id, day
+                ORDER BY item_id, hq, world_id, day
+                "#
+            );
+            encode_cursor::<ExportDailyRow>(ch, (!empty).then_some(sql), format)
+        }
+    }
+}
+
+/// Drive an [`ExportEncoder`] from a cursor over `sql`; `None` encodes an
+/// empty file without querying.
+fn encode_cursor<R>(
+    ch: &ClickHouseClient,
+    sql: Option<String>,
+    format: ExportFormat,
+) -> Result<ExportStream, ClickHouseError>
+where
+    R: ExportRow + clickhouse::RowOwned + clickhouse::RowRead + Send + 'static,
+{
+    let encoder = ExportEncoder::<R>::new(format)?;
+    let cursor = match sql {
+        Some(sql) => Some(ch.client().query(&sql).fetch::<R>()?),
+        None => None,
+    };
+    Ok(
+        futures::stream::try_unfold(Some((cursor, encoder)), |state| async move {
+            let Some((mut cursor, mut encoder)) = state else {
+                return Ok(None);
+            };
+            if let Some(rows) = cursor.as_mut() {
+                while let Some(row) = rows.next().await? {
+                    if let Some(chunk) = encoder.push(&row)? {
+                        return Ok(Some((chunk, Some((cursor, encoder)))));
+                    }
+                }
+            }
+            Ok(Some((encoder.finish()?, None)))
+        })
+        .boxed(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1040,6 +1303,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn export_predicate_widens_the_window_to_an_item_set() {
+        let export = SalesExport {
+            item_ids: vec![5057, 5058],
+            world_ids: vec![34, 35],
+            hq: HqFilter::Nq,
+            from: chrono::DateTime::from_timestamp(0, 0).unwrap(),
+            to: chrono::DateTime::from_timestamp(86_400, 0).unwrap(),
+            granularity: ExportGranularity::Daily,
+        };
+        assert_eq!(
+            export_predicate(&export),
+            "item_id IN (5057,5058) AND world_id IN (34,35) AND sold_date >= toDateTime(0) \
+             AND sold_date < toDateTime(86400) AND hq = 0"
+        );
+    }
+
+    #[test]
+    fn export_rows_describe_every_column() {
+        let at = chrono::DateTime::from_timestamp(1_700_006_400, 0).unwrap();
+        let sale = ExportSaleRow {
+            item_id: 1,
+            world_id: 2,
+            hq: 1,
+            sold_date: at,
+            price_per_item: 3,
+            quantity: 4,
+        };
+        assert_eq!(sale.values().len(), ExportSaleRow::COLUMNS.len());
+        let day = ExportDailyRow {
+            item_id: 1,
+            world_id: 2,
+            hq: 0,
+            day: at.date_naive(),
+            open: 1,
+            high: 2,
+            low: 1,
+            close: 2,
+            vwap: 1.5,
+            units: 2,
+            gil: 3,
+            sales: 2,
+        };
+        assert_eq!(day.values().len(), ExportDailyRow::COLUMNS.len());
+    }
+
     #[test]
     fn window_predicate_is_shared_shape_between_price_series_and_raw_sales() {
         // Pin the invariant the doc comments on `price_series` and
@@ -1249,6 +1558,58 @@ mod tests {
         assert_eq!(variants[0].vwap_30d, 200);
     }
 
+    #[test]
+    fn rule_stat_folds_the_matching_quality_and_window_only() {
+        let mut hq = scan(40, 1, 100, 100, 9000, "high");
+        hq.p50 = 9000;
+        let mut week = scan(41, 0, 100, 100, 700, "high");
+        week.window_days = 7;
+        let scans = [
+            scan(40, 0, 990, 990, 100, "high"),
+            scan(41, 0, 10, 10, 1000, "low"),
+            hq,
+            week,
+        ];
+        // Same weighting as the item view: (100*990 + 1000*10) / 1000.
+        assert_eq!(
+            rule_stat(&scans, false, 30, MarketStat::Median),
+            Some(109.0)
+        );
+        assert_eq!(
+            rule_stat(&scans, true, 30, MarketStat::Median),
+            Some(9000.0)
+        );
+        assert_eq!(rule_stat(&scans, false, 7, MarketStat::Vwap), Some(700.0));
+        assert_eq!(rule_stat(&scans, true, 7, MarketStat::Vwap), None);
+    }
+
+    #[test]
+    fn rule_stat_rates_sum_worlds_and_divide_by_the_window() {
+        let scans = [
+            scan(40, 0, 120, 100, 500, "high"),
+            scan(41, 0, 30, 20, 500, "high"),
+        ];
+        assert_eq!(
+            rule_stat(&scans, false, 30, MarketStat::SalesPerDay),
+            Some(5.0)
+        );
+        // fixture() carries 200 units per world.
+        assert_eq!(
+            rule_stat(&scans, false, 30, MarketStat::UnitsPerDay),
+            Some(400.0 / 30.0)
+        );
+    }
+
+    #[test]
+    fn rule_stat_has_no_price_when_every_sample_was_filtered() {
+        let scans = [scan(40, 0, 5, 0, 100, "unusable")];
+        assert_eq!(rule_stat(&scans, false, 30, MarketStat::Median), None);
+        assert_eq!(
+            rule_stat(&scans, false, 30, MarketStat::SalesPerDay),
+            Some(5.0 / 30.0)
+        );
+    }
+
     #[test]
     fn no_rows_yields_no_variants() {
         assert!(aggregate_item_stats_variants(&[]).is_empty());
@@ -184,6 +184,8 @@ impl UltrosDb {
                     created_at: Set(chrono::Utc::now()),
                     disabled_at: Set(None),
                     last_error: Set(None),
+                    schedule: Set(None),
+                    digest_sent_at: Set(None),
                 })
                 .exec_with_returning(&txn)
                 .await?
@@ -483,6 +485,8 @@ impl UltrosDb {
             created_at: Set(chrono::Utc::now()),
             disabled_at: Set(None),
             last_error: Set(None),
+            schedule: Set(None),
+            digest_sent_at: Set(None),
         })
         .exec_with_returning(&self.db)
         .await?;
@@ -518,6 +522,83 @@ impl UltrosDb {
         Ok(())
     }
 
+    /// Set when fires reach an endpoint. `None` sends them immediately; any
+    /// fires already queued go out with the next digest pass.
+    pub async fn set_endpoint_schedule(
+        &self,
+        owner: i64,
+        endpoint_id: i32,
+        schedule: Option<JsonValue>,
+    ) -> Result<()> {
+        let existing = notification_endpoint::Entity::find_by_id(endpoint_id)
+            .filter(notification_endpoint::Column::UserId.eq(owner))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow::Error::msg("endpoint not found"))?;
+        let mut active: notification_endpoint::ActiveModel = existing.into();
+        active.schedule = Set(schedule);
+        active.update(&self.db).await?;
+        Ok(())
+    }
+
+    /// Hold one alert fire for an endpoint's next digest summary.
+    pub async fn queue_digest_entry(
+        &self,
+        endpoint_id: i32,
+        alert_id: i32,
+        title: &str,
+        click_url: &str,
+    ) -> Result<()> {
+        alert_digest_entry::Entity::insert(alert_digest_entry::ActiveModel {
+            id: ActiveValue::default(),
+            endpoint_id: Set(endpoint_id),
+            alert_id: Set(alert_id),
+            title: Set(title.to_string()),
+            click_url: Set(click_url.to_string()),
+            queued_at: Set(chrono::Utc::now()),
+        })
+        .exec(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// Every deliverable endpoint with fires waiting for a digest, along with
+    /// those fires oldest first. Disabled endpoints keep their queue until
+    /// they are repaired.
+    pub async fn get_pending_digests(
+        &self,
+    ) -> Result<Vec<(notification_endpoint::Model, Vec<alert_digest_entry::Model>)>> {
+        Ok(notification_endpoint::Entity::find()
+            .filter(notification_endpoint::Column::DisabledAt.is_null())
+            .find_with_related(alert_digest_entry::Entity)
+            .filter(alert_digest_entry::Column::Id.is_not_null())
+            .order_by_asc(notification_endpoint::Column::Id)
+            .order_by_asc(alert_digest_entry::Column::QueuedAt)
+            .all(&self.db)
+            .await?)
+    }
+
+    /// Drop the entries a digest just delivered and stamp the endpoint so the
+    /// next interval is measured from now.
+    pub async fn finish_digest(&self, endpoint_id: i32, entry_ids: &[i64]) -> Result<()> {
+        use sea_orm::TransactionTrait;
+        let txn = self.db.begin().await?;
+        alert_digest_entry::Entity::delete_many()
+            .filter(alert_digest_entry::Column::Id.is_in(entry_ids.to_vec()))
+            .exec(&txn)
+            .await?;
+        notification_endpoint::Entity::update_many()
+            .col_expr(
+                notification_endpoint::Column::DigestSentAt,
+                Expr::value(chrono::Utc::now()),
+            )
+            .filter(notification_endpoint::Column::Id.eq(endpoint_id))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
     pub async fn delete_endpoint(&self, owner: i64, endpoint_id: i32) -> Result<()> {
         let existing = notification_endpoint::Entity::find_by_id(endpoint_id)
             .filter(notification_endpoint::Column::UserId.eq(owner))
@@ -665,6 +746,110 @@ impl UltrosDb {
         Ok(alert)
     }
 
+    /// Create an alert + alert_expression in a single transaction and bind the
+    /// supplied notification endpoints. The rule is stored as JSON; the caller
+    /// is expected to have validated it already.
+    pub async fn create_expression_alert(
+        &self,
+        owner: i64,
+        item_id: i32,
+        world_selector_json: JsonValue,
+        rule_json: JsonValue,
+        cooldown_seconds: i32,
+        endpoint_ids: &[i32],
+    ) -> Result<alert::Model> {
+        use sea_orm::TransactionTrait;
+        for &eid in endpoint_ids {
+            notification_endpoint::Entity::find_by_id(eid)
+                .filter(notification_endpoint::Column::UserId.eq(owner))
+                .one(&self.db)
+                .await?
+                .ok_or_else(|| anyhow::Error::msg(format!("endpoint {eid} not owned by user")))?;
+        }
+        let txn = self.db.begin().await?;
+        let alert = alert::Entity::insert(alert::ActiveModel {
+            id: ActiveValue::default(),
+            owner: Set(owner),
+            enabled: Set(true),
+            last_fired_at: Set(None),
+            cooldown_seconds: Set(cooldown_seconds),
+        })
+        .exec_with_returning(&txn)
+        .await?;
+        alert_expression::Entity::insert(alert_expression::ActiveModel {
+            id: ActiveValue::default(),
+            alert_id: Set(alert.id),
+            item_id: Set(item_id),
+            world_selector: Set(world_selector_json),
+            rule: Set(rule_json),
+        })
+        .exec(&txn)
+        .await?;
+        for &eid in endpoint_ids {
+            alert_notification_rule::Entity::insert(alert_notification_rule::ActiveModel {
+                alert_id: Set(alert.id),
+                endpoint_id: Set(eid),
+            })
+            .exec(&txn)
+            .await?;
+        }
+        txn.commit().await?;
+        Ok(alert)
+    }
+
+    /// Create an alert + alert_market_move in a single transaction and bind the
+    /// supplied notification endpoints. Like expression alerts, the rule is
+    /// stored as JSON and validated by the caller.
+    pub async fn create_market_move_alert(
+        &self,
+        owner: i64,
+        item_id: i32,
+        world_selector_json: JsonValue,
+        hq_only: bool,
+        rule_json: JsonValue,
+        cooldown_seconds: i32,
+        endpoint_ids: &[i32],
+    ) -> Result<alert::Model> {
+        use sea_orm::TransactionTrait;
+        for &eid in endpoint_ids {
+            notification_endpoint::Entity::find_by_id(eid)
+                .filter(notification_endpoint::Column::UserId.eq(owner))
+                .one(&self.db)
+                .await?
+                .ok_or_else(|| anyhow::Error::msg(format!("endpoint {eid} not owned by user")))?;
+        }
+        let txn = self.db.begin().await?;
+        let alert = alert::Entity::insert(alert::ActiveModel {
+            id: ActiveValue::default(),
+            owner: Set(owner),
+            enabled: Set(true),
+            last_fired_at: Set(None),
+            cooldown_seconds: Set(cooldown_seconds),
+        })
+        .exec_with_returning(&txn)
+        .await?;
+        alert_market_move::Entity::insert(alert_market_move::ActiveModel {
+            id: ActiveValue::default(),
+            alert_id: Set(alert.id),
+            item_id: Set(item_id),
+            world_selector: Set(world_selector_json),
+            hq_only: Set(hq_only),
+            rule: Set(rule_json),
+        })
+        .exec(&txn)
+        .await?;
+        for &eid in endpoint_ids {
+            alert_notification_rule::Entity::insert(alert_notification_rule::ActiveModel {
+                alert_id: Set(alert.id),
+                endpoint_id: Set(eid),
+            })
+            .exec(&txn)
+            .await?;
+        }
+        txn.commit().await?;
+        Ok(alert)
+    }
+
     /// Create an alert + alert_retainer_undercut in a single transaction and bind
     /// the supplied notification endpoints. This is the web/API path; legacy
     /// Discord commands still write `alert_discord_destination` as a fallback,
@@ -851,6 +1036,66 @@ impl UltrosDb {
             .collect())
     }
 
+    pub async fn get_user_expression_alerts(
+        &self,
+        owner: i64,
+    ) -> Result<Vec<(alert::Model, alert_expression::Model)>> {
+        let rows = alert::Entity::find()
+            .filter(alert::Column::Owner.eq(owner))
+            .find_with_related(alert_expression::Entity)
+            .all(&self.db)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .flat_map(|(a, ts)| ts.into_iter().map(move |t| (a.clone(), t)))
+            .collect())
+    }
+
+    /// Return all enabled expression alerts for the price tracker's index.
+    pub async fn get_all_active_expression_alerts(
+        &self,
+    ) -> Result<Vec<(alert::Model, alert_expression::Model)>> {
+        let rows = alert::Entity::find()
+            .filter(alert::Column::Enabled.eq(true))
+            .find_with_related(alert_expression::Entity)
+            .all(&self.db)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .flat_map(|(a, ts)| ts.into_iter().map(move |t| (a.clone(), t)))
+            .collect())
+    }
+
+    pub async fn get_user_market_move_alerts(
+        &self,
+        owner: i64,
+    ) -> Result<Vec<(alert::Model, alert_market_move::Model)>> {
+        let rows = alert::Entity::find()
+            .filter(alert::Column::Owner.eq(owner))
+            .find_with_related(alert_market_move::Entity)
+            .all(&self.db)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .flat_map(|(a, ts)| ts.into_iter().map(move |t| (a.clone(), t)))
+            .collect())
+    }
+
+    /// Return all enabled market-move alerts for the price tracker's index.
+    pub async fn get_all_active_market_move_alerts(
+        &self,
+    ) -> Result<Vec<(alert::Model, alert_market_move::Model)>> {
+        let rows = alert::Entity::find()
+            .filter(alert::Column::Enabled.eq(true))
+            .find_with_related(alert_market_move::Entity)
+            .all(&self.db)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .flat_map(|(a, ts)| ts.into_iter().map(move |t| (a.clone(), t)))
+            .collect())
+    }
+
     pub async fn get_all_active_list_update_alerts(
         &self,
     ) -> Result<Vec<(alert::Model, alert_list_update::Model)>> {
@@ -0,0 +1,103 @@
+//! Personal API tokens.
+//!
+//! Tokens are `ultros_` followed by 64 hex characters, so they can be told
+//! apart from session cookies and picked out by secret scanners. Only the
+//! SHA-256 is stored, as with sessions.
+
+use crate::UltrosDb;
+use crate::entity::{api_token, discord_user};
+use crate::sessions::{hash_session_token, new_session_token};
+use anyhow::{Result, anyhow};
+use chrono::{TimeDelta, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use ultros_api_types::user::{ApiTokenScope, format_scopes};
+
+pub const API_TOKEN_PREFIX: &str = "ultros_";
+
+/// How many tokens one user may hold at once.
+pub const MAX_API_TOKENS: u64 = 25;
+
+impl UltrosDb {
+    /// Mint a token for `user_id`. Returns the secret, which isn't stored.
+    pub async fn create_api_token(
+        &self,
+        user_id: u64,
+        name: String,
+        scopes: &[ApiTokenScope],
+        avatar_url: String,
+        expires_in: Option<TimeDelta>,
+    ) -> Result<(String, api_token::Model)> {
+        let existing = api_token::Entity::find()
+            .filter(api_token::Column::DiscordUserId.eq(user_id as i64))
+            .count(&self.db)
+            .await?;
+        if existing >= MAX_API_TOKENS {
+            return Err(anyhow!(
+                "A user may hold at most {MAX_API_TOKENS} API tokens"
+            ));
+        }
+        let secret = format!("{API_TOKEN_PREFIX}{}", new_session_token()?);
+        let now = Utc::now();
+        let token = api_token::ActiveModel {
+            id: NotSet,
+            token_hash: Set(hash_session_token(&secret)),
+            discord_user_id: Set(user_id as i64),
+            name: Set(name),
+            scopes: Set(format_scopes(scopes)),
+            avatar_url: Set(avatar_url),
+            created_at: Set(now),
+            expires_at: Set(expires_in.map(|expires_in| now + expires_in)),
+            last_used_at: Set(None),
+        }
+        .insert(&self.db)
+        .await?;
+        Ok((secret, token))
+    }
+
+    /// The unexpired token matching `secret`, with its user.
+    pub async fn get_api_token(
+        &self,
+        secret: &str,
+    ) -> Result<Option<(api_token::Model, discord_user::Model)>> {
+        let found = api_token::Entity::find()
+            .filter(api_token::Column::TokenHash.eq(hash_session_token(secret)))
+            .filter(
+                Condition::any()
+                    .add(api_token::Column::ExpiresAt.is_null())
+                    .add(api_token::Column::ExpiresAt.gt(Utc::now())),
+            )
+            .find_also_related(discord_user::Entity)
+            .one(&self.db)
+            .await?;
+        Ok(found.and_then(|(token, user)| Some((token, user?))))
+    }
+
+    pub async fn touch_api_token(&self, token_id: i64) -> Result<()> {
+        api_token::Entity::update_many()
+            .col_expr(api_token::Column::LastUsedAt, Expr::value(Utc::now()))
+            .filter(api_token::Column::Id.eq(token_id))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    /// All of `user_id`'s tokens, expired ones included, newest first.
+    pub async fn list_api_tokens(&self, user_id: u64) -> Result<Vec<api_token::Model>> {
+        Ok(api_token::Entity::find()
+            .filter(api_token::Column::DiscordUserId.eq(user_id as i64))
+            .order_by_desc(api_token::Column::CreatedAt)
+            .all(&self.db)
+            .await?)
+    }
+
+    /// False when the token wasn't `user_id`'s or was already gone.
+    pub async fn revoke_api_token(&self, user_id: u64, token_id: i64) -> Result<bool> {
+        let result = api_token::Entity::delete_many()
+            .filter(api_token::Column::Id.eq(token_id))
+            .filter(api_token::Column::DiscordUserId.eq(user_id as i64))
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+}
@@ -1,20 +1,22 @@
 use crate::{
     entity::{
-        self, datacenter, discord_user, final_fantasy_character, group_invite, list, list_activity,
-        list_invite, list_item, list_shared_group, list_shared_user, owned_retainers, region,
-        unknown_final_fantasy_character, user_group, user_group_member,
+        self, api_token, datacenter, discord_user, final_fantasy_character, group_invite, list,
+        list_activity, list_invite, list_item, list_shared_group, list_shared_user,
+        owned_retainers, region, retainer_sale, unknown_final_fantasy_character, user_group,
+        user_group_member,
     },
     world_data::world_cache::WorldCache,
 };
 use thiserror::Error;
 use ultros_api_types::{
-    ActiveListing, FfxivCharacter, SaleHistory, UnknownCharacter,
+    ActiveListing, FfxivCharacter, ListingMateria, SaleHistory, UnknownCharacter,
     list::{
         List, ListActivity, ListActivityKind, ListInvite, ListItem, ListSharedGroup, ListSharedUser,
     },
     retainer::Retainer,
     user::OwnedRetainer,
     user::group::{GroupInvite, UserGroup, UserGroupMember},
+    user::{ApiToken, RetainerSale, parse_scopes},
     world::{Datacenter, Region, World, WorldData},
     world_helper::AnySelector,
 };
@@ -36,7 +38,8 @@ impl From<entity::active_listing::Model> for ActiveListing {
             quantity,
             hq,
             timestamp,
-            // identity/cosmetic columns (listing_id, materia, …) are not part
+            materia,
+            // identity/cosmetic columns (listing_id, stain, …) are not part
             // of the public ActiveListing type yet
             ..
         } = value;
@@ -49,6 +52,23 @@ impl From<entity::active_listing::Model> for ActiveListing {
             quantity,
             hq,
             timestamp,
+            materia: materia
+                .map(|materia| {
+                    materia
+                        .0
+                        .into_iter()
+                        .map(
+                            |entity::active_listing::Materia {
+                                 slot_id,
+                                 materia_id,
+                             }| ListingMateria {
+                                materia_id,
+                                slot_id,
+                            },
+                        )
+                        .collect()
+                })
+                .unwrap_or_default(),
         }
     }
 }
@@ -296,6 +316,59 @@ impl From<owned_retainers::Model> for OwnedRetainer {
     }
 }
 
+impl From<retainer_sale::Model> for RetainerSale {
+    fn from(value: retainer_sale::Model) -> Self {
+        let retainer_sale::Model {
+            id: _,
+            retainer_id,
+            sale_history_id: _,
+            world_id,
+            item_id,
+            hq,
+            quantity,
+            price_per_unit,
+            gross,
+            tax,
+            listed_at,
+            sold_at,
+        } = value;
+        Self {
+            retainer_id,
+            world_id,
+            item_id,
+            hq,
+            quantity,
+            price_per_unit,
+            gross,
+            tax,
+            listed_at,
+            sold_at,
+        }
+    }
+}
+
+impl From<api_token::Model> for ApiToken {
+    fn from(value: api_token::Model) -> Self {
+        let api_token::Model {
+            id,
+            name,
+            scopes,
+            created_at,
+            expires_at,
+            last_used_at,
+            ..
+        } = value;
+        Self {
+            id,
+            name,
+            scopes: parse_scopes(&scopes),
+            created_at,
+            expires_at,
+            last_used_at,
+        }
+    }
+}
+
 impl TryFrom<list::Model> for List {
     type Error = ApiConversionError;
     fn try_from(value: list::Model) -> Result<Self, Self::Error> {
@@ -16,16 +16,22 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::alert_digest_entry::Entity")]
+    AlertDigestEntry,
     #[sea_orm(has_many = "super::alert_discord_destination::Entity")]
     AlertDiscordDestination,
     #[sea_orm(has_many = "super::alert_event::Entity")]
     AlertEvent,
+    #[sea_orm(has_many = "super::alert_expression::Entity")]
+    AlertExpression,
     #[sea_orm(has_many = "super::alert_item_threshold::Entity")]
     AlertItemThreshold,
     #[sea_orm(has_many = "super::alert_list_threshold::Entity")]
     AlertListThreshold,
     #[sea_orm(has_many = "super::alert_list_update::Entity")]
     AlertListUpdate,
+    #[sea_orm(has_many = "super::alert_market_move::Entity")]
+    AlertMarketMove,
     #[sea_orm(has_many = "super::alert_retainer_undercut::Entity")]
     AlertRetainerUndercut,
     #[sea_orm(
@@ -38,6 +44,12 @@ pub enum Relation {
     DiscordUser,
 }
 
+impl Related<super::alert_digest_entry::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AlertDigestEntry.def()
+    }
+}
+
 impl Related<super::alert_discord_destination::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::AlertDiscordDestination.def()
@@ -50,6 +62,12 @@ impl Related<super::alert_event::Entity> for Entity {
     }
 }
 
+impl Related<super::alert_expression::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AlertExpression.def()
+    }
+}
+
 impl Related<super::alert_item_threshold::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::AlertItemThreshold.def()
@@ -68,6 +86,12 @@ impl Related<super::alert_list_update::Entity> for Entity {
     }
 }
 
+impl Related<super::alert_market_move::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AlertMarketMove.def()
+    }
+}
+
 impl Related<super::alert_retainer_undercut::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::AlertRetainerUndercut.def()
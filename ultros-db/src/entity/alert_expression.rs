@@ -0,0 +1,38 @@
+//! `SeaORM` Entity. Hand-authored to mirror the `alert_item_threshold` shape.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "alert_expression")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub alert_id: i32,
+    pub item_id: i32,
+    #[sea_orm(column_type = "Json")]
+    pub world_selector: Json,
+    /// Serialized `ultros_api_types::alert_rule::AlertRule`.
+    #[sea_orm(column_type = "Json")]
+    pub rule: Json,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::alert::Entity",
+        from = "Column::AlertId",
+        to = "super::alert::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Alert,
+}
+
+impl Related<super::alert::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Alert.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -25,6 +25,10 @@ pub enum Relation {
     UserGroupMember,
     #[sea_orm(has_many = "super::list_shared_user::Entity")]
     ListSharedUser,
+    #[sea_orm(has_many = "super::user_session::Entity")]
+    UserSession,
+    #[sea_orm(has_many = "super::api_token::Entity")]
+    ApiToken,
 }
 
 impl Related<super::alert::Entity> for Entity {
@@ -63,4 +67,16 @@ impl Related<super::list_shared_user::Entity> for Entity {
     }
 }
 
+impl Related<super::user_session::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UserSession.def()
+    }
+}
+
+impl Related<super::api_token::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ApiToken.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -4,14 +4,18 @@ pub mod prelude;
 
 pub mod active_listing;
 pub mod alert;
+pub mod alert_digest_entry;
 pub mod alert_discord_destination;
 pub mod alert_event;
+pub mod alert_expression;
 pub mod alert_item_threshold;
 pub mod alert_list_threshold;
 pub mod alert_list_update;
+pub mod alert_market_move;
 pub mod alert_notification_rule;
 pub mod alert_price;
 pub mod alert_retainer_undercut;
+pub mod api_token;
 pub mod datacenter;
 pub mod discord_user;
 pub mod final_fantasy_character;
@@ -31,8 +35,10 @@ pub mod push_subscription;
 pub mod region;
 pub mod retainer;
 pub mod retainer_city;
+pub mod retainer_sale;
 pub mod sale_history;
 pub mod unknown_final_fantasy_character;
 pub mod user_group;
 pub mod user_group_member;
+pub mod user_session;
 pub mod world;
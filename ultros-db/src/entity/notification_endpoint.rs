@@ -20,6 +20,12 @@ pub struct Model {
     /// endpoints UI. Cleared alongside `disabled_at` when a delivery or an
     /// explicit Test succeeds, so it only ever describes a *current* outage.
     pub last_error: Option<String>,
+    /// Serialized `ultros_api_types::alert::DeliverySchedule`. `None` sends
+    /// every fire immediately.
+    #[sea_orm(column_type = "Json", nullable)]
+    pub schedule: Option<Json>,
+    /// When the last digest summary was sent to this endpoint.
+    pub digest_sent_at: Option<DateTimeUtc>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -32,6 +38,8 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     DiscordUser,
+    #[sea_orm(has_many = "super::alert_digest_entry::Entity")]
+    AlertDigestEntry,
     #[sea_orm(has_many = "super::alert_notification_rule::Entity")]
     AlertNotificationRule,
 }
@@ -42,6 +50,12 @@ impl Related<super::discord_user::Entity> for Entity {
     }
 }
 
+impl Related<super::alert_digest_entry::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AlertDigestEntry.def()
+    }
+}
+
 impl Related<super::alert_notification_rule::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::AlertNotificationRule.def()
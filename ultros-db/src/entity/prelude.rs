@@ -2,14 +2,18 @@
 
 pub use super::active_listing::Entity as ActiveListing;
 pub use super::alert::Entity as Alert;
+pub use super::alert_digest_entry::Entity as AlertDigestEntry;
 pub use super::alert_discord_destination::Entity as AlertDiscordDestination;
 pub use super::alert_event::Entity as AlertEvent;
+pub use super::alert_expression::Entity as AlertExpression;
 pub use super::alert_item_threshold::Entity as AlertItemThreshold;
 pub use super::alert_list_threshold::Entity as AlertListThreshold;
 pub use super::alert_list_update::Entity as AlertListUpdate;
+pub use super::alert_market_move::Entity as AlertMarketMove;
 pub use super::alert_notification_rule::Entity as AlertNotificationRule;
 pub use super::alert_price::Entity as AlertPrice;
 pub use super::alert_retainer_undercut::Entity as AlertRetainerUndercut;
+pub use super::api_token::Entity as ApiToken;
 pub use super::datacenter::Entity as Datacenter;
 pub use super::discord_user::Entity as DiscordUser;
 pub use super::final_fantasy_character::Entity as FinalFantasyCharacter;
@@ -29,8 +33,10 @@ pub use super::push_subscription::Entity as PushSubscription;
 pub use super::region::Entity as Region;
 pub use super::retainer::Entity as Retainer;
 pub use super::retainer_city::Entity as RetainerCity;
+pub use super::retainer_sale::Entity as RetainerSale;
 pub use super::sale_history::Entity as SaleHistory;
 pub use super::unknown_final_fantasy_character::Entity as UnknownFinalFantasyCharacter;
 pub use super::user_group::Entity as UserGroup;
 pub use super::user_group_member::Entity as UserGroupMember;
+pub use super::user_session::Entity as UserSession;
 pub use super::world::Entity as World;
@@ -19,6 +19,8 @@ pub enum Relation {
     ActiveListing,
     #[sea_orm(has_many = "super::owned_retainers::Entity")]
     OwnedRetainers,
+    #[sea_orm(has_many = "super::retainer_sale::Entity")]
+    RetainerSale,
     #[sea_orm(
         belongs_to = "super::retainer_city::Entity",
         from = "Column::RetainerCityId",
@@ -49,6 +51,12 @@ impl Related<super::owned_retainers::Entity> for Entity {
     }
 }
 
+impl Related<super::retainer_sale::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RetainerSale.def()
+    }
+}
+
 impl Related<super::retainer_city::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::RetainerCity.def()
@@ -0,0 +1,45 @@
+//! `SeaORM` Entity. Hand-authored to mirror the `retainer_sale` migration.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "retainer_sale")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub retainer_id: i32,
+    /// The `sale_history` row this sale was matched to. No foreign key: the
+    /// ledger is kept after sale history is pruned.
+    #[sea_orm(unique)]
+    pub sale_history_id: i32,
+    pub world_id: i32,
+    pub item_id: i32,
+    pub hq: bool,
+    pub quantity: i32,
+    pub price_per_unit: i32,
+    pub gross: i64,
+    pub tax: i64,
+    pub listed_at: DateTime,
+    pub sold_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::retainer::Entity",
+        from = "Column::RetainerId",
+        to = "super::retainer::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Retainer,
+}
+
+impl Related<super::retainer::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Retainer.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
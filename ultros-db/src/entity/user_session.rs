@@ -0,0 +1,40 @@
+//! `SeaORM` Entity. Hand-authored to mirror the `user_session` migration.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "user_session")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// Hex SHA-256 of the cookie token; the token itself is never stored.
+    #[sea_orm(unique)]
+    pub token_hash: String,
+    pub discord_user_id: i64,
+    pub avatar_url: String,
+    pub user_agent: Option<String>,
+    pub created_at: DateTimeUtc,
+    pub last_seen_at: DateTimeUtc,
+    pub expires_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::discord_user::Entity",
+        from = "Column::DiscordUserId",
+        to = "super::discord_user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    DiscordUser,
+}
+
+impl Related<super::discord_user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DiscordUser.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
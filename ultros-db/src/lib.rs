@@ -1,4 +1,5 @@
 mod alerts;
+pub mod api_tokens;
 pub mod common;
 pub mod common_type_conversions;
 mod discord;
@@ -7,8 +8,10 @@ mod ffxiv_character;
 pub mod listings;
 pub mod lists;
 pub mod recently_updated;
+pub mod retainer_ledger;
 pub mod retainers;
 pub mod sales;
+pub mod sessions;
 pub mod world_data;
 
 pub use sea_orm::ActiveValue;
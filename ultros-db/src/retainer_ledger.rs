@@ -0,0 +1,242 @@
+//! The retainer sales ledger: sales credited to tracked retainers, and the
+//! revenue, tax, volume and time-to-sell summaries built from them.
+//!
+//! Rows are written by the ledger service in the `ultros` crate, which pairs
+//! a tracked retainer's listing disappearing with the sale that removed it.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::UltrosDb;
+use crate::entity::{active_listing, owned_retainers, retainer, retainer_sale};
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveDateTime};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::*;
+use tracing::instrument;
+use ultros_api_types::user::{
+    ItemLedgerRow, LedgerTotals, RetainerLedger, RetainerLedgerRow, RetainerSale, WeekLedgerRow,
+};
+
+/// How many individual sales a ledger lists alongside its summaries.
+pub const LEDGER_RECENT_SALES: u64 = 50;
+
+/// The aggregate columns every ledger breakdown selects, after its key.
+/// Times are clamped at zero: `listed_at` is the listing's last review, which
+/// the matcher allows to trail the sale by a little clock drift.
+const TOTALS_COLUMNS: &str = r#"count(*)::bigint AS sales,
+    sum(s.quantity)::bigint AS units,
+    sum(s.gross)::bigint AS gross,
+    sum(s.tax)::bigint AS tax,
+    avg(greatest(extract(epoch FROM s.sold_at - s.listed_at), 0))::bigint AS avg_seconds_to_sell"#;
+
+/// Restricts `retainer_sale s` to the retainers user `$1` tracks, sold at or
+/// after `$2`.
+const OWNED_SINCE: &str = r#"FROM retainer_sale s
+    WHERE s.retainer_id IN (SELECT retainer_id FROM owned_retainers WHERE discord_id = $1)
+    AND s.sold_at >= $2"#;
+
+#[derive(Debug, FromQueryResult)]
+struct TotalsRow {
+    sales: i64,
+    units: Option<i64>,
+    gross: Option<i64>,
+    tax: Option<i64>,
+    avg_seconds_to_sell: Option<i64>,
+}
+
+impl From<TotalsRow> for LedgerTotals {
+    fn from(row: TotalsRow) -> Self {
+        LedgerTotals {
+            sales: row.sales,
+            units: row.units.unwrap_or_default(),
+            gross: row.gross.unwrap_or_default(),
+            tax: row.tax.unwrap_or_default(),
+            avg_seconds_to_sell: row.avg_seconds_to_sell,
+        }
+    }
+}
+
+/// Breakdown rows: a group key next to the totals columns.
+macro_rules! group_totals_row {
+    ($name:ident, $key:ty) => {
+        #[derive(Debug, FromQueryResult)]
+        struct $name {
+            key: $key,
+            sales: i64,
+            units: Option<i64>,
+            gross: Option<i64>,
+            tax: Option<i64>,
+            avg_seconds_to_sell: Option<i64>,
+        }
+
+        impl $name {
+            fn split(self) -> ($key, LedgerTotals) {
+                let totals = TotalsRow {
+                    sales: self.sales,
+                    units: self.units,
+                    gross: self.gross,
+                    tax: self.tax,
+                    avg_seconds_to_sell: self.avg_seconds_to_sell,
+                };
+                (self.key, totals.into())
+            }
+        }
+    };
+}
+
+group_totals_row!(IdTotalsRow, i32);
+group_totals_row!(WeekTotalsRow, NaiveDate);
+
+impl UltrosDb {
+    /// Every retainer at least one user tracks.
+    #[instrument(skip(self))]
+    pub async fn get_tracked_retainer_ids(&self) -> Result<HashSet<i32>> {
+        Ok(owned_retainers::Entity::find()
+            .select_only()
+            .column(owned_retainers::Column::RetainerId)
+            .distinct()
+            .into_tuple::<i32>()
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .collect())
+    }
+
+    /// The `(world_id, item_id)` of every active listing a tracked retainer
+    /// has up, i.e. the sales the ledger could possibly credit.
+    #[instrument(skip(self))]
+    pub async fn get_tracked_listing_keys(&self) -> Result<HashSet<(i32, i32)>> {
+        Ok(active_listing::Entity::find()
+            .select_only()
+            .column(active_listing::Column::WorldId)
+            .column(active_listing::Column::ItemId)
+            .distinct()
+            .filter(
+                active_listing::Column::RetainerId.in_subquery(
+                    sea_query::Query::select()
+                        .column(owned_retainers::Column::RetainerId)
+                        .from(owned_retainers::Entity)
+                        .to_owned(),
+                ),
+            )
+            .into_tuple::<(i32, i32)>()
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .collect())
+    }
+
+    /// Store matched sales. A sale that was already credited is skipped, so
+    /// replays are harmless. Returns how many rows were new.
+    #[instrument(skip(self, sales), fields(sales = sales.len()))]
+    pub async fn record_retainer_sales(
+        &self,
+        sales: Vec<retainer_sale::ActiveModel>,
+    ) -> Result<u64> {
+        if sales.is_empty() {
+            return Ok(0);
+        }
+        Ok(retainer_sale::Entity::insert_many(sales)
+            .on_conflict(
+                OnConflict::column(retainer_sale::Column::SaleHistoryId)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(&self.db)
+            .await?)
+    }
+
+    /// The ledger for every retainer `discord_user_id` tracks, from `since`.
+    #[instrument(skip(self))]
+    pub async fn get_retainer_ledger(
+        &self,
+        discord_user_id: u64,
+        since: NaiveDateTime,
+    ) -> Result<RetainerLedger> {
+        let values = || vec![(discord_user_id as i64).into(), since.into()];
+        let statement =
+            |sql: String| Statement::from_sql_and_values(DbBackend::Postgres, sql, values());
+
+        let total = TotalsRow::find_by_statement(statement(format!(
+            "SELECT {TOTALS_COLUMNS} {OWNED_SINCE}"
+        )))
+        .one(&self.db)
+        .await?
+        .map(LedgerTotals::from)
+        .unwrap_or_default();
+
+        let by_retainer = IdTotalsRow::find_by_statement(statement(format!(
+            "SELECT s.retainer_id AS key, {TOTALS_COLUMNS} {OWNED_SINCE}
+            GROUP BY s.retainer_id ORDER BY sum(s.gross) - sum(s.tax) DESC"
+        )))
+        .all(&self.db)
+        .await?
+        .into_iter()
+        .map(IdTotalsRow::split)
+        .collect::<Vec<_>>();
+        let names: HashMap<i32, String> = retainer::Entity::find()
+            .filter(retainer::Column::Id.is_in(by_retainer.iter().map(|(id, _)| *id)))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|retainer| (retainer.id, retainer.name))
+            .collect();
+        let retainers = by_retainer
+            .into_iter()
+            .map(|(retainer_id, totals)| RetainerLedgerRow {
+                retainer_id,
+                retainer_name: names.get(&retainer_id).cloned().unwrap_or_default(),
+                totals,
+            })
+            .collect();
+
+        let items = IdTotalsRow::find_by_statement(statement(format!(
+            "SELECT s.item_id AS key, {TOTALS_COLUMNS} {OWNED_SINCE}
+            GROUP BY s.item_id ORDER BY sum(s.gross) - sum(s.tax) DESC"
+        )))
+        .all(&self.db)
+        .await?
+        .into_iter()
+        .map(IdTotalsRow::split)
+        .map(|(item_id, totals)| ItemLedgerRow { item_id, totals })
+        .collect();
+
+        let weeks = WeekTotalsRow::find_by_statement(statement(format!(
+            "SELECT date_trunc('week', s.sold_at)::date AS key, {TOTALS_COLUMNS} {OWNED_SINCE}
+            GROUP BY key ORDER BY key"
+        )))
+        .all(&self.db)
+        .await?
+        .into_iter()
+        .map(WeekTotalsRow::split)
+        .map(|(week, totals)| WeekLedgerRow { week, totals })
+        .collect();
+
+        let owned = owned_retainers::Entity::find()
+            .select_only()
+            .column(owned_retainers::Column::RetainerId)
+            .filter(owned_retainers::Column::DiscordId.eq(discord_user_id as i64))
+            .into_tuple::<i32>()
+            .all(&self.db)
+            .await?;
+        let recent = retainer_sale::Entity::find()
+            .filter(retainer_sale::Column::RetainerId.is_in(owned))
+            .filter(retainer_sale::Column::SoldAt.gte(since))
+            .order_by_desc(retainer_sale::Column::SoldAt)
+            .limit(LEDGER_RECENT_SALES)
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(RetainerSale::from)
+            .collect();
+
+        Ok(RetainerLedger {
+            since,
+            total,
+            retainers,
+            items,
+            weeks,
+            recent,
+        })
+    }
+}
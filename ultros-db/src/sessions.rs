@@ -0,0 +1,186 @@
+//! Login sessions.
+//!
+//! The browser holds a random token; only its SHA-256 is stored, so a copy
+//! of this table can't be replayed as cookies. Sessions expire after
+//! [`SESSION_LIFETIME`] without use, and every touch pushes that out again.
+
+use crate::UltrosDb;
+use crate::entity::{discord_user, user_session};
+use anyhow::Result;
+use chrono::{TimeDelta, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use sha2::{Digest, Sha256};
+
+/// How long an unused session stays valid.
+pub const SESSION_LIFETIME: TimeDelta = TimeDelta::days(30);
+
+/// Hex SHA-256 of a session token, as stored in `user_session.token_hash`.
+pub fn hash_session_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+pub(crate) fn new_session_token() -> Result<String> {
+    let mut bytes = [0_u8; 32];
+    getrandom::fill(&mut bytes)?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+impl UltrosDb {
+    /// Start a session for `user_id`. Returns the token for the cookie.
+    pub async fn create_user_session(
+        &self,
+        user_id: u64,
+        avatar_url: String,
+        user_agent: Option<String>,
+    ) -> Result<(String, user_session::Model)> {
+        let token = new_session_token()?;
+        let session = self
+            .adopt_user_session(&token, user_id, avatar_url, user_agent)
+            .await?;
+        Ok((token, session))
+    }
+
+    /// Record a session for a token the browser already holds. Used for
+    /// cookies issued before sessions were stored, which carry the Discord
+    /// access token itself.
+    pub async fn adopt_user_session(
+        &self,
+        token: &str,
+        user_id: u64,
+        avatar_url: String,
+        user_agent: Option<String>,
+    ) -> Result<user_session::Model> {
+        let now = Utc::now();
+        let session = user_session::ActiveModel {
+            id: NotSet,
+            token_hash: Set(hash_session_token(token)),
+            discord_user_id: Set(user_id as i64),
+            avatar_url: Set(avatar_url),
+            user_agent: Set(user_agent),
+            created_at: Set(now),
+            last_seen_at: Set(now),
+            expires_at: Set(now + SESSION_LIFETIME),
+        };
+        Ok(user_session::Entity::insert(session)
+            .on_conflict(
+                sea_query::OnConflict::column(user_session::Column::TokenHash)
+                    .update_columns([
+                        user_session::Column::AvatarUrl,
+                        user_session::Column::LastSeenAt,
+                        user_session::Column::ExpiresAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec_with_returning(&self.db)
+            .await?)
+    }
+
+    /// The unexpired session `token` belongs to, with its user.
+    pub async fn get_user_session(
+        &self,
+        token: &str,
+    ) -> Result<Option<(user_session::Model, discord_user::Model)>> {
+        let found = user_session::Entity::find()
+            .filter(user_session::Column::TokenHash.eq(hash_session_token(token)))
+            .filter(user_session::Column::ExpiresAt.gt(Utc::now()))
+            .find_also_related(discord_user::Entity)
+            .one(&self.db)
+            .await?;
+        Ok(found.and_then(|(session, user)| Some((session, user?))))
+    }
+
+    /// Mark a session as used now and push its expiry out.
+    pub async fn touch_user_session(&self, session_id: i64) -> Result<()> {
+        let now = Utc::now();
+        user_session::Entity::update_many()
+            .col_expr(user_session::Column::LastSeenAt, Expr::value(now))
+            .col_expr(
+                user_session::Column::ExpiresAt,
+                Expr::value(now + SESSION_LIFETIME),
+            )
+            .filter(user_session::Column::Id.eq(session_id))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    /// `user_id`'s unexpired sessions, most recently used first.
+    pub async fn list_user_sessions(&self, user_id: u64) -> Result<Vec<user_session::Model>> {
+        Ok(user_session::Entity::find()
+            .filter(user_session::Column::DiscordUserId.eq(user_id as i64))
+            .filter(user_session::Column::ExpiresAt.gt(Utc::now()))
+            .order_by_desc(user_session::Column::LastSeenAt)
+            .all(&self.db)
+            .await?)
+    }
+
+    /// Sign one of `user_id`'s sessions out. False when it wasn't theirs or
+    /// was already gone.
+    pub async fn revoke_user_session(&self, user_id: u64, session_id: i64) -> Result<bool> {
+        let result = user_session::Entity::delete_many()
+            .filter(user_session::Column::Id.eq(session_id))
+            .filter(user_session::Column::DiscordUserId.eq(user_id as i64))
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Sign out whichever session `token` belongs to.
+    pub async fn revoke_user_session_token(&self, token: &str) -> Result<()> {
+        user_session::Entity::delete_many()
+            .filter(user_session::Column::TokenHash.eq(hash_session_token(token)))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    /// Sign out every session of `user_id` except `keep_session_id`.
+    /// Returns how many were removed.
+    pub async fn revoke_other_user_sessions(
+        &self,
+        user_id: u64,
+        keep_session_id: i64,
+    ) -> Result<u64> {
+        let result = user_session::Entity::delete_many()
+            .filter(user_session::Column::DiscordUserId.eq(user_id as i64))
+            .filter(user_session::Column::Id.ne(keep_session_id))
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// Drop sessions that have expired. Returns how many were removed.
+    pub async fn delete_expired_user_sessions(&self) -> Result<u64> {
+        let result = user_session::Entity::delete_many()
+            .filter(user_session::Column::ExpiresAt.lte(Utc::now()))
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_hash_is_hex_sha256() {
+        // printf 'abc' | sha256sum
+        assert_eq!(
+            hash_session_token("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn new_tokens_are_long_and_distinct() {
+        let a = new_session_token().unwrap();
+        let b = new_session_token().unwrap();
+        assert_eq!(a.len(), 64);
+        assert_ne!(a, b);
+    }
+}
@@ -19,17 +19,21 @@ use ultros_api_types::{
     },
     market_heat::MarketHeatResponse,
     market_pulse::MarketPulseDto,
+    patch_diff::PatchDiff,
     price_density::PriceDensity,
     price_series::{HqFilter, PriceSeries, SeriesGroup},
     recent_sales::RecentSales,
+    repricing::RetainerReprice,
     resale_quality::{ResaleQualityRequest, ResaleQualityResponse},
     result::JsonErrorWrapper,
     retainer::{Retainer, RetainerListings},
-    search::SearchResult,
+    route_planner::RoutePlan,
+    search::SearchPage,
     sparklines::{MoversResponse, SparklinesRequest, SparklinesResponse},
     trends::TrendsData,
     user::{
-        AssignRetainerCharacter, OwnedRetainer, UserData, UserRetainerListings, UserRetainers,
+        ApiToken, AssignRetainerCharacter, CreateApiToken, CreatedApiToken, OwnedRetainer,
+        RetainerLedger, UserData, UserRetainerListings, UserRetainers, UserSession,
         group::{
             CreateGroup, CreateGroupFromGuild, CreateGroupInvite, DiscordManageableGuild,
             GroupInvite, UserGroup, UserGroupMember,
@@ -40,9 +44,20 @@ use ultros_api_types::{
 use crate::error::{AppError, AppResult};
 use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
 
-pub(crate) async fn search(query: &str) -> AppResult<Vec<SearchResult>> {
+/// One page of matches for `query`, including any `key:value` filters, with
+/// facet counts over the whole result set. `lang` is the game-data language
+/// to match names in, e.g. `"ja"`.
+pub(crate) async fn search_page(
+    query: &str,
+    lang: &str,
+    offset: usize,
+    limit: usize,
+) -> AppResult<SearchPage> {
     let encoded_query = utf8_percent_encode(query, NON_ALPHANUMERIC).to_string();
-    fetch_api(&format!("/api/v1/search?q={encoded_query}")).await
+    fetch_api(&format!(
+        "/api/v1/search/page?q={encoded_query}&lang={lang}&offset={offset}&limit={limit}"
+    ))
+    .await
 }
 
 pub(crate) async fn get_listings(item_id: i32, world: &str) -> AppResult<CurrentlyShownItem> {
@@ -123,6 +138,31 @@ pub(crate) async fn delete_user() -> AppResult<()> {
     delete_api("/api/v1/current_user").await
 }
 
+pub(crate) async fn get_user_sessions() -> AppResult<Vec<UserSession>> {
+    fetch_api("/api/v1/sessions").await
+}
+
+pub(crate) async fn revoke_user_session(session_id: i64) -> AppResult<bool> {
+    delete_api(&format!("/api/v1/sessions/{session_id}")).await
+}
+
+/// Signs out every device except this one. Returns how many were signed out.
+pub(crate) async fn revoke_other_user_sessions() -> AppResult<u64> {
+    post_api("/api/v1/sessions/revoke_others", ()).await
+}
+
+pub(crate) async fn get_api_tokens() -> AppResult<Vec<ApiToken>> {
+    fetch_api("/api/v1/tokens").await
+}
+
+pub(crate) async fn create_api_token(request: CreateApiToken) -> AppResult<CreatedApiToken> {
+    post_api("/api/v1/tokens", request).await
+}
+
+pub(crate) async fn revoke_api_token(token_id: i64) -> AppResult<bool> {
+    delete_api(&format!("/api/v1/tokens/{token_id}")).await
+}
+
 /// Get analyzer data
 pub(crate) async fn get_cheapest_listings(world_name: &str) -> AppResult<CheapestListings> {
     fetch_api(&format!("/api/v1/cheapest/{}", world_name)).await
@@ -298,6 +338,25 @@ pub(crate) async fn get_market_heat(world_name: &str) -> AppResult<MarketHeatRes
     fetch_api(&format!("/api/v1/market_heat/{}", world_name)).await
 }
 
+/// What the current game data added over the previous one.
+pub(crate) async fn get_patch_diff() -> AppResult<PatchDiff> {
+    fetch_api("/api/v1/patch_diff").await
+}
+
+/// Budgeted shopping route across `world_name`'s data center. `horizon_days`
+/// is how long the purchases should take to sell; the server defaults it to 7.
+pub(crate) async fn get_route_plan(
+    world_name: &str,
+    budget: i64,
+    horizon_days: Option<f32>,
+) -> AppResult<RoutePlan> {
+    let mut query = format!("?budget={budget}");
+    if let Some(days) = horizon_days {
+        query.push_str(&format!("&horizon_days={days}"));
+    }
+    fetch_api(&format!("/api/v1/route_planner/{world_name}{query}")).await
+}
+
 pub(crate) async fn get_item_stats(world_name: &str, item_id: i32) -> AppResult<ItemStatsResponse> {
     fetch_api(&format!("/api/v1/item_stats/{}/{}", world_name, item_id)).await
 }
@@ -336,6 +395,17 @@ pub(crate) async fn get_user_retainer_listings() -> AppResult<UserRetainerListin
     fetch_api("/api/v1/user/retainer/listings").await
 }
 
+/// Sales credited to the logged in user's retainers over the last `days` days.
+pub(crate) async fn get_retainer_ledger(days: i64) -> AppResult<RetainerLedger> {
+    fetch_api(&format!("/api/v1/user/retainer/ledger?days={days}")).await
+}
+
+/// Every listing on the logged in user's retainers with the market data its
+/// relist suggestion is computed from.
+pub(crate) async fn get_retainer_reprice() -> AppResult<Vec<RetainerReprice>> {
+    fetch_api("/api/v1/user/retainer/reprice").await
+}
+
 #[derive(Deserialize, Serialize, Clone)]
 pub(crate) struct UndercutData {
     pub(crate) current: ActiveListing,
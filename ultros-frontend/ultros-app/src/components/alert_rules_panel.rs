@@ -190,6 +190,70 @@ pub fn AlertRulesPanel() -> impl IntoView {
                                                             "—".to_string(),
                                                             "—".to_string(),
                                                         ),
+                                                        // The rule itself decides quality, so the HQ
+                                                        // column has nothing to show.
+                                                        AlertTrigger::Expression {
+                                                            item_id,
+                                                            world_selector,
+                                                            ..
+                                                        } => {
+                                                            let name = tracked_data()
+                                                                .items
+                                                                .get(&ItemId(item_id))
+                                                                .map(|it| it.name.as_str().to_string())
+                                                                .unwrap_or_else(|| format!("Item {item_id}"));
+                                                            let world = match world_selector {
+                                                                ultros_api_types::world_helper::AnySelector::World(id) => {
+                                                                    format!("World({id})")
+                                                                }
+                                                                ultros_api_types::world_helper::AnySelector::Datacenter(id) => {
+                                                                    format!("DC({id})")
+                                                                }
+                                                                ultros_api_types::world_helper::AnySelector::Region(id) => {
+                                                                    format!("Region({id})")
+                                                                }
+                                                            };
+                                                            (
+                                                                name,
+                                                                t_string!(i18n, alerts_expression_rule).to_string(),
+                                                                world,
+                                                                "—".to_string(),
+                                                            )
+                                                        }
+                                                        AlertTrigger::MarketMove {
+                                                            item_id,
+                                                            world_selector,
+                                                            hq_only,
+                                                            rule,
+                                                        } => {
+                                                            let name = tracked_data()
+                                                                .items
+                                                                .get(&ItemId(item_id))
+                                                                .map(|it| it.name.as_str().to_string())
+                                                                .unwrap_or_else(|| format!("Item {item_id}"));
+                                                            let world = match world_selector {
+                                                                ultros_api_types::world_helper::AnySelector::World(id) => {
+                                                                    format!("World({id})")
+                                                                }
+                                                                ultros_api_types::world_helper::AnySelector::Datacenter(id) => {
+                                                                    format!("DC({id})")
+                                                                }
+                                                                ultros_api_types::world_helper::AnySelector::Region(id) => {
+                                                                    format!("Region({id})")
+                                                                }
+                                                            };
+                                                            let hq = if hq_only {
+                                                                t_string!(i18n, alerts_hq_any).to_string()
+                                                            } else {
+                                                                t_string!(i18n, alerts_any).to_string()
+                                                            };
+                                                            (
+                                                                name,
+                                                                t_string!(i18n, alerts_market_move_rule, percent = rule.percent, days = rule.window_days).to_string(),
+                                                                world,
+                                                                hq,
+                                                            )
+                                                        }
                                                     };
                                                     let endpoints_str = a
                                                         .endpoint_ids
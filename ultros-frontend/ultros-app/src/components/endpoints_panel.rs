@@ -1,6 +1,9 @@
 use icondata as i;
 use leptos::{prelude::*, task::spawn_local};
-use ultros_api_types::alert::{CreateEndpointRequest, Endpoint, EndpointMethod};
+use ultros_api_types::alert::{
+    CreateEndpointRequest, DeliverySchedule, Endpoint, EndpointMethod, WEBHOOK_TEMPLATE_VARIABLES,
+    WebhookHeader,
+};
 
 use crate::api::{
     create_endpoint, delete_endpoint, list_discord_writable_guilds, list_endpoints, test_endpoint,
@@ -170,6 +173,39 @@ pub fn EndpointsPanel() -> impl IntoView {
                                         EndpointMethod::WebPush { .. } => {
                                             t_string!(i18n, endpoints_method_web_push).to_string()
                                         }
+                                        EndpointMethod::GenericWebhook { signed: true, .. } => {
+                                            t_string!(i18n, endpoints_method_generic_webhook_signed)
+                                                .to_string()
+                                        }
+                                        EndpointMethod::GenericWebhook { .. } => {
+                                            t_string!(i18n, endpoints_method_generic_webhook).to_string()
+                                        }
+                                    };
+                                    let schedule: Option<String> = match e.schedule {
+                                        DeliverySchedule::Immediate => None,
+                                        DeliverySchedule::Digest { interval_minutes: 60 } => {
+                                            Some(t_string!(i18n, endpoints_delivery_hourly).to_string())
+                                        }
+                                        DeliverySchedule::Digest { interval_minutes: 1440 } => {
+                                            Some(t_string!(i18n, endpoints_delivery_daily).to_string())
+                                        }
+                                        DeliverySchedule::Digest { interval_minutes } => Some(
+                                            t_string!(i18n, endpoints_delivery_every, minutes = interval_minutes)
+                                                .to_string(),
+                                        ),
+                                        DeliverySchedule::QuietHours { start_hour, end_hour, .. } => Some(
+                                            t_string!(
+                                                i18n,
+                                                endpoints_delivery_quiet_hours,
+                                                start = start_hour,
+                                                end = end_hour,
+                                            )
+                                            .to_string(),
+                                        ),
+                                    };
+                                    let label = match schedule {
+                                        Some(schedule) => format!("{label} · {schedule}"),
+                                        None => label,
                                     };
                                     let id = e.id;
                                     // Delivery disabled this endpoint after Discord
@@ -225,6 +261,10 @@ fn EndpointCreateForm(#[prop(into)] on_created: Callback<()>) -> impl IntoView {
     let (selected_guild_id, set_selected_guild_id) = signal::<Option<i64>>(None);
     let (selected_channel_id, set_selected_channel_id) = signal::<Option<i64>>(None);
     let (webhook_url, set_webhook_url) = signal::<String>("".into());
+    let (webhook_template, set_webhook_template) = signal::<String>("".into());
+    let (webhook_headers, set_webhook_headers) = signal::<String>("".into());
+    let (webhook_secret, set_webhook_secret) = signal::<String>("".into());
+    let (digest_minutes, set_digest_minutes) = signal::<Option<i32>>(None);
     let (error, set_error) = signal::<Option<String>>(None);
     let toasts = use_toast();
 
@@ -279,12 +319,38 @@ fn EndpointCreateForm(#[prop(into)] on_created: Callback<()>) -> impl IntoView {
                 }
                 EndpointMethod::Webhook { url }
             }
+            "generic_webhook" => {
+                let url = webhook_url.get();
+                if url.trim().is_empty() {
+                    set_error.set(Some(
+                        t_string!(i18n, alert_drawer_err_webhook_required).to_string(),
+                    ));
+                    return;
+                }
+                let template = webhook_template.get();
+                let secret = webhook_secret.get();
+                EndpointMethod::GenericWebhook {
+                    url,
+                    template: (!template.trim().is_empty()).then_some(template),
+                    headers: parse_header_lines(&webhook_headers.get()),
+                    secret: (!secret.is_empty()).then_some(secret),
+                    signed: false,
+                }
+            }
             // DiscordDm uses the *current user's* discord id. We pass 0 here and let the
             // server fill it in if it sees method=DiscordDm with user_id=0; OR we read
             // the current user via a context. Simpler: pass 0; backend rewrites to user.id.
             _ => EndpointMethod::DiscordDm { user_id: 0 },
         };
-        let req = CreateEndpointRequest { name: n, method };
+        let schedule = match digest_minutes.get() {
+            Some(interval_minutes) => DeliverySchedule::Digest { interval_minutes },
+            None => DeliverySchedule::Immediate,
+        };
+        let req = CreateEndpointRequest {
+            name: n,
+            method,
+            schedule,
+        };
         spawn_local(async move {
             match create_endpoint(req).await {
                 Ok(_) => {
@@ -313,12 +379,14 @@ fn EndpointCreateForm(#[prop(into)] on_created: Callback<()>) -> impl IntoView {
                         set_method_kind.set(match v.as_str() {
                             "discord_channel" => "discord_channel",
                             "webhook" => "webhook",
+                            "generic_webhook" => "generic_webhook",
                             _ => "discord_dm",
                         });
                     }>
                     <option value="discord_dm">{t!(i18n, endpoints_discord_dm_me)}</option>
                     <option value="discord_channel">{t!(i18n, endpoints_discord_channel)}</option>
                     <option value="webhook">{t!(i18n, endpoints_webhook_url)}</option>
+                    <option value="generic_webhook">{t!(i18n, endpoints_generic_webhook)}</option>
                 </select>
             </div>
             <Show when=move || method_kind.get() == "discord_channel">
@@ -423,6 +491,53 @@ fn EndpointCreateForm(#[prop(into)] on_created: Callback<()>) -> impl IntoView {
                         on:input=move |e| set_webhook_url.set(event_target_value(&e)) />
                 </div>
             </Show>
+            <Show when=move || method_kind.get() == "generic_webhook">
+                <div class="space-y-3">
+                    <div class="space-y-1">
+                        <label class="text-sm font-semibold" for="endpoint-generic-url">{t!(i18n, endpoints_webhook_url)}</label>
+                        <input id="endpoint-generic-url" class="input w-full" placeholder="https://"
+                            prop:value=webhook_url
+                            on:input=move |e| set_webhook_url.set(event_target_value(&e)) />
+                    </div>
+                    <div class="space-y-1">
+                        <label class="text-sm font-semibold" for="endpoint-generic-template">{t!(i18n, endpoints_webhook_template_label)}</label>
+                        <textarea id="endpoint-generic-template" class="input w-full font-mono text-sm" rows="5"
+                            placeholder=r#"{"text": "{{item_name}}: {{price}} gil on {{world_name}}"}"#
+                            prop:value=webhook_template
+                            on:input=move |e| set_webhook_template.set(event_target_value(&e))></textarea>
+                        <p class="text-xs opacity-70">
+                            {t!(i18n, endpoints_webhook_variables_label)}" "
+                            {WEBHOOK_TEMPLATE_VARIABLES
+                                .iter()
+                                .map(|name| format!("{{{{{name}}}}}"))
+                                .collect::<Vec<_>>()
+                                .join(", ")}
+                        </p>
+                    </div>
+                    <div class="space-y-1">
+                        <label class="text-sm font-semibold" for="endpoint-generic-headers">{t!(i18n, endpoints_webhook_headers_label)}</label>
+                        <textarea id="endpoint-generic-headers" class="input w-full font-mono text-sm" rows="2"
+                            placeholder="Authorization: Bearer …"
+                            prop:value=webhook_headers
+                            on:input=move |e| set_webhook_headers.set(event_target_value(&e))></textarea>
+                    </div>
+                    <div class="space-y-1">
+                        <label class="text-sm font-semibold" for="endpoint-generic-secret">{t!(i18n, endpoints_webhook_secret_label)}</label>
+                        <input id="endpoint-generic-secret" type="password" class="input w-full" autocomplete="off"
+                            prop:value=webhook_secret
+                            on:input=move |e| set_webhook_secret.set(event_target_value(&e)) />
+                    </div>
+                </div>
+            </Show>
+            <div class="space-y-1">
+                <label class="text-sm font-semibold" for="endpoint-delivery">{t!(i18n, endpoints_delivery_label)}</label>
+                <select id="endpoint-delivery" class="input w-full"
+                    on:change=move |e| set_digest_minutes.set(event_target_value(&e).parse().ok())>
+                    <option value="">{t!(i18n, endpoints_delivery_immediate)}</option>
+                    <option value="60">{t!(i18n, endpoints_delivery_hourly)}</option>
+                    <option value="1440">{t!(i18n, endpoints_delivery_daily)}</option>
+                </select>
+            </div>
             <Show when=move || error.get().is_some()>
                 <div class="text-sm text-red-500">{move || error.get().unwrap_or_default()}</div>
             </Show>
@@ -432,3 +547,18 @@ fn EndpointCreateForm(#[prop(into)] on_created: Callback<()>) -> impl IntoView {
         </div>
     }
 }
+
+/// Parse the headers textarea: one `Name: value` per line, blank lines
+/// skipped. Malformed names are left for the server to reject.
+fn parse_header_lines(text: &str) -> Vec<WebhookHeader> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (name, value) = line.split_once(':').unwrap_or((line, ""));
+            WebhookHeader {
+                name: name.trim().to_string(),
+                value: value.trim().to_string(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,401 @@
+//! Fills the on-hand inventory from the exports of other inventory trackers:
+//! the Allagan Tools CSV export and the Teamcraft inventory JSON. Each
+//! export lists items per character and retainer, so quantities are summed
+//! per item before they reach `OnHandMap`.
+
+use std::collections::HashMap;
+
+use crate::components::on_hand_input::OnHandMap;
+use crate::global_state::xiv_data::tracked_data;
+use crate::i18n::{t, t_string, use_i18n};
+use leptos::{either::Either, prelude::*};
+use serde_json::Value;
+use thiserror::Error;
+use xiv_gen::ItemId;
+
+/// Allagan Tools shows high quality items with this glyph after the name.
+const HQ_GLYPH: char = '\u{E03C}';
+
+const ID_COLUMNS: &[&str] = &["id", "item id", "itemid"];
+const NAME_COLUMNS: &[&str] = &["name", "item name", "item"];
+const QUANTITY_COLUMNS: &[&str] = &[
+    "quantity",
+    "qty",
+    "total quantity available",
+    "total",
+    "count",
+];
+
+#[derive(Error, Debug)]
+pub enum InventoryImportError {
+    #[error("The export is empty")]
+    Empty,
+    #[error("No {0} column in the CSV header")]
+    MissingColumn(&'static str),
+    #[error("Invalid Teamcraft JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+}
+
+/// How an export names an item, before it is matched against the game data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ItemRef {
+    Id(i32),
+    Name(String),
+}
+
+/// Split one CSV line, honouring quoted fields and `""` escapes.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+fn find_column(header: &[String], candidates: &[&str]) -> Option<usize> {
+    candidates.iter().find_map(|candidate| {
+        header
+            .iter()
+            .position(|column| column.trim().eq_ignore_ascii_case(candidate))
+    })
+}
+
+/// Quantities may carry thousands separators, e.g. `"1,234"`.
+fn parse_quantity(raw: &str) -> Option<i32> {
+    raw.chars()
+        .filter(|c| !matches!(c, ',' | '.' | ' '))
+        .collect::<String>()
+        .parse()
+        .ok()
+}
+
+/// Parse an Allagan Tools CSV export. Columns are found by header name, so
+/// any table layout works as long as it has a quantity column and an item id
+/// or name column. Rows that don't parse are skipped.
+fn parse_allagan_csv(csv: &str) -> Result<Vec<(ItemRef, i32)>, InventoryImportError> {
+    let mut lines = csv.lines().filter(|line| !line.trim().is_empty());
+    let header = split_csv_line(
+        lines
+            .next()
+            .ok_or(InventoryImportError::Empty)?
+            .trim_start_matches('\u{feff}'),
+    );
+    let quantity = find_column(&header, QUANTITY_COLUMNS)
+        .ok_or(InventoryImportError::MissingColumn("quantity"))?;
+    let id = find_column(&header, ID_COLUMNS);
+    let name = find_column(&header, NAME_COLUMNS);
+    if id.is_none() && name.is_none() {
+        return Err(InventoryImportError::MissingColumn("item name or id"));
+    }
+    Ok(lines
+        .filter_map(|line| {
+            let fields = split_csv_line(line);
+            let quantity = parse_quantity(fields.get(quantity)?)?;
+            let item = id
+                .and_then(|id| fields.get(id)?.trim().parse().ok())
+                .map(ItemRef::Id)
+                .or_else(|| {
+                    let name = fields.get(name?)?.replace(HQ_GLYPH, "");
+                    let name = name.trim();
+                    (!name.is_empty()).then(|| ItemRef::Name(name.to_string()))
+                })?;
+            Some((item, quantity))
+        })
+        .collect())
+}
+
+/// Parse a Teamcraft inventory export. Teamcraft nests items by character,
+/// retainer and container, so every object carrying an `itemId` and a
+/// `quantity` counts as one stack wherever it sits.
+fn parse_teamcraft_json(json: &str) -> Result<Vec<(ItemRef, i32)>, InventoryImportError> {
+    fn collect(value: &Value, stacks: &mut Vec<(ItemRef, i32)>) {
+        match value {
+            Value::Object(object) => {
+                let item_id = object.get("itemId").and_then(Value::as_i64);
+                let quantity = object.get("quantity").and_then(Value::as_i64);
+                if let (Some(item_id), Some(quantity)) = (item_id, quantity) {
+                    if let (Ok(item_id), Ok(quantity)) =
+                        (i32::try_from(item_id), i32::try_from(quantity))
+                    {
+                        stacks.push((ItemRef::Id(item_id), quantity));
+                    }
+                } else {
+                    object.values().for_each(|value| collect(value, stacks));
+                }
+            }
+            Value::Array(values) => values.iter().for_each(|value| collect(value, stacks)),
+            _ => {}
+        }
+    }
+    let value: Value = serde_json::from_str(json)?;
+    let mut stacks = vec![];
+    collect(&value, &mut stacks);
+    Ok(stacks)
+}
+
+/// Pick the parser from the shape of the export.
+fn parse_export(export: &str) -> Result<Vec<(ItemRef, i32)>, InventoryImportError> {
+    let export = export.trim();
+    if export.is_empty() {
+        Err(InventoryImportError::Empty)
+    } else if export.starts_with('{') || export.starts_with('[') {
+        parse_teamcraft_json(export)
+    } else {
+        parse_allagan_csv(export)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct ResolvedInventory {
+    /// Total quantity per item id, summed across every character and retainer.
+    items: HashMap<i32, i32>,
+    /// Names or ids the game data didn't recognise.
+    unknown: Vec<String>,
+}
+
+fn resolve(
+    stacks: Vec<(ItemRef, i32)>,
+    mut lookup: impl FnMut(&ItemRef) -> Option<i32>,
+) -> ResolvedInventory {
+    let mut inventory = ResolvedInventory::default();
+    for (item, quantity) in stacks {
+        if quantity <= 0 {
+            continue;
+        }
+        match lookup(&item) {
+            Some(item_id) => {
+                let total = inventory.items.entry(item_id).or_default();
+                *total = total.saturating_add(quantity);
+            }
+            None => {
+                let label = match item {
+                    ItemRef::Id(id) => id.to_string(),
+                    ItemRef::Name(name) => name,
+                };
+                if !inventory.unknown.contains(&label) {
+                    inventory.unknown.push(label);
+                }
+            }
+        }
+    }
+    inventory
+}
+
+/// Match exported items against the game data, by id or by exact name.
+fn resolve_with_game_data(stacks: Vec<(ItemRef, i32)>) -> ResolvedInventory {
+    let items = &tracked_data().items;
+    let mut by_name: Option<HashMap<&str, i32>> = None;
+    resolve(stacks, |item| match item {
+        ItemRef::Id(id) => items.contains_key(&ItemId(*id)).then_some(*id),
+        ItemRef::Name(name) => by_name
+            .get_or_insert_with(|| {
+                items
+                    .iter()
+                    .map(|(ItemId(id), item)| (item.name.as_str(), *id))
+                    .collect()
+            })
+            .get(name.as_str())
+            .copied(),
+    })
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ImportMode {
+    Replace,
+    Add,
+}
+
+/// Textarea importer that writes an Allagan Tools or Teamcraft export into
+/// the shared on-hand inventory.
+#[component]
+pub fn InventoryImport() -> impl IntoView {
+    let i18n = use_i18n();
+    let on_hand = use_context::<OnHandMap>().expect("OnHandMap not provided");
+    let (export, set_export) = signal(String::new());
+    let parsed = Memo::new(move |_| {
+        export.with(|export| {
+            parse_export(export)
+                .map(resolve_with_game_data)
+                .map_err(|e| e.to_string())
+        })
+    });
+    let import = move |mode: ImportMode| {
+        let Ok(inventory) = parsed.get_untracked() else {
+            return;
+        };
+        on_hand.0.update(|map| {
+            if mode == ImportMode::Replace {
+                map.clear();
+            }
+            for (item_id, quantity) in inventory.items {
+                let total = map.entry(item_id).or_default();
+                *total = total.saturating_add(quantity);
+            }
+        });
+        set_export(String::new());
+    };
+    let ready = move || parsed.with(|parsed| parsed.as_ref().is_ok_and(|i| !i.items.is_empty()));
+
+    view! {
+        <div class="flex flex-col gap-2">
+            <label for="inventory-import-textarea" class="text-xs text-[color:var(--color-text-muted)]">
+                {t!(i18n, on_hand_import_instructions)}
+            </label>
+            <textarea
+                id="inventory-import-textarea"
+                class="input h-32 font-mono text-xs"
+                prop:value=export
+                on:input=move |input| set_export(event_target_value(&input))
+            ></textarea>
+            {move || {
+                export
+                    .with(|export| !export.trim().is_empty())
+                    .then(|| {
+                        parsed
+                            .with(|parsed| match parsed {
+                                Ok(inventory) => {
+                                    Either::Left(
+                                        view! {
+                                            <div class="text-xs text-[color:var(--color-text-muted)]">
+                                                <div>
+                                                    {t_string!(i18n, on_hand_import_ready, count = inventory.items.len())
+                                                        .to_string()}
+                                                </div>
+                                                {(!inventory.unknown.is_empty())
+                                                    .then(|| {
+                                                        view! {
+                                                            <div class="text-amber-300">
+                                                                {t_string!(
+                                                                    i18n, on_hand_import_unknown, count = inventory.unknown.len()
+                                                                )
+                                                                    .to_string()} " " {inventory.unknown.join(", ")}
+                                                            </div>
+                                                        }
+                                                    })}
+                                            </div>
+                                        },
+                                    )
+                                }
+                                Err(e) => {
+                                    Either::Right(
+                                        view! {
+                                            <div class="text-xs text-red-300">
+                                                {t!(i18n, on_hand_import_error_prefix)} " " {e.clone()}
+                                            </div>
+                                        },
+                                    )
+                                }
+                            })
+                    })
+            }}
+            <div class="flex flex-row gap-2">
+                <button
+                    class="btn-secondary text-xs"
+                    disabled=move || !ready()
+                    on:click=move |_| import(ImportMode::Replace)
+                >
+                    {t!(i18n, on_hand_import_replace)}
+                </button>
+                <button
+                    class="btn-secondary text-xs"
+                    disabled=move || !ready()
+                    on:click=move |_| import(ImportMode::Add)
+                >
+                    {t!(i18n, on_hand_import_add)}
+                </button>
+            </div>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_sums_rows_and_strips_the_hq_glyph() {
+        let csv = "\u{feff}\"Name\",\"Quantity\",\"Source\"\n\
+                   \"Darksteel Ore\",\"99\",\"Alice - Saddlebag\"\n\
+                   \"Darksteel Ore\",\"1,200\",\"Retainer Bob\"\n\
+                   \"Rarefied \"\"Special\"\" Nugget\u{E03C}\",\"3\",\"Retainer Bob\"\n\
+                   \"Broken row\"\n";
+        let stacks = parse_allagan_csv(csv).unwrap();
+        assert_eq!(
+            stacks,
+            vec![
+                (ItemRef::Name("Darksteel Ore".into()), 99),
+                (ItemRef::Name("Darksteel Ore".into()), 1200),
+                (ItemRef::Name("Rarefied \"Special\" Nugget".into()), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_prefers_the_id_column_and_needs_a_quantity() {
+        let csv = "Item ID,Name,Total Quantity Available\n5111,Darksteel Ore,12\n";
+        assert_eq!(
+            parse_allagan_csv(csv).unwrap(),
+            vec![(ItemRef::Id(5111), 12)]
+        );
+        assert!(matches!(
+            parse_allagan_csv("Name,Source\nDarksteel Ore,Alice\n"),
+            Err(InventoryImportError::MissingColumn("quantity"))
+        ));
+    }
+
+    #[test]
+    fn teamcraft_json_collects_nested_stacks() {
+        let json = r#"{
+            "items": {
+                "1234:Alice": { "0": [{ "itemId": 5111, "quantity": 10, "hq": false }] },
+                "1234:Retainer Bob": { "10000": [
+                    { "itemId": 5111, "quantity": 5, "hq": true },
+                    { "itemId": 5057, "quantity": 2 }
+                ]}
+            }
+        }"#;
+        let mut stacks = parse_export(json).unwrap();
+        stacks.sort_by_key(|(item, quantity)| (format!("{item:?}"), *quantity));
+        assert_eq!(
+            stacks,
+            vec![
+                (ItemRef::Id(5057), 2),
+                (ItemRef::Id(5111), 5),
+                (ItemRef::Id(5111), 10),
+            ]
+        );
+        assert!(matches!(
+            parse_export("{ not json"),
+            Err(InventoryImportError::InvalidJson(_))
+        ));
+    }
+
+    #[test]
+    fn resolve_sums_per_item_and_reports_unknowns_once() {
+        let stacks = vec![
+            (ItemRef::Id(5111), 10),
+            (ItemRef::Name("Darksteel Ore".into()), 5),
+            (ItemRef::Name("Not An Item".into()), 1),
+            (ItemRef::Name("Not An Item".into()), 2),
+            (ItemRef::Id(5057), 0),
+        ];
+        let inventory = resolve(stacks, |item| match item {
+            ItemRef::Id(id) => Some(*id),
+            ItemRef::Name(name) => (name == "Darksteel Ore").then_some(5111),
+        });
+        assert_eq!(inventory.items, HashMap::from([(5111, 15)]));
+        assert_eq!(inventory.unknown, vec!["Not An Item".to_string()]);
+    }
+}
@@ -5,7 +5,7 @@ use crate::global_state::xiv_data::tracked_data;
 use crate::i18n::{t, t_string, use_i18n};
 use icondata as i;
 use leptos::prelude::*;
-use ultros_api_types::{ActiveListing, list::ListItem};
+use ultros_api_types::{ActiveListing, fill_cost::optimal_fill, list::ListItem};
 use xiv_gen::ItemId;
 
 use crate::components::gil::*;
@@ -22,7 +22,10 @@ struct WorldPrice {
     item_count: usize,
 }
 
-/// Find the cheapest listings for a given item based on quantity and HQ preference
+/// Find the cheapest set of listings covering `quantity`, honoring the HQ
+/// preference and exclusions. Whole stacks only; see
+/// [`ultros_api_types::fill_cost::optimal_fill`] for why this isn't simply
+/// cheapest-unit-first.
 fn get_cheapest_listing(
     mut listings: Vec<ActiveListing>,
     quantity: i32,
@@ -31,8 +34,6 @@ fn get_cheapest_listing(
     excluded_datacenters: &HashSet<String>,
     world_helper: Option<&WorldHelper>,
 ) -> Vec<ActiveListing> {
-    // ⚡ Bolt: Filter out unwanted listings before sorting.
-    // This reduces the array size N significantly, making the O(N log N) sorting step much faster.
     listings.retain(|listing| {
         if listing.is_excluded(excluded_worlds) {
             return false;
@@ -48,18 +49,9 @@ fn get_cheapest_listing(
             true
         }
     });
-    listings.sort_unstable_by_key(|listing| listing.price_per_unit);
 
-    let quantity_needed = quantity;
-    let mut current_quantity = 0;
-    listings
-        .into_iter()
-        .take_while(|listing| {
-            let needed_more = current_quantity < quantity_needed;
-            current_quantity += listing.quantity;
-            needed_more
-        })
-        .collect::<Vec<_>>()
+    let (chosen, _) = optimal_fill(&listings, quantity);
+    chosen.into_iter().map(|i| listings[i].clone()).collect()
 }
 
 /// Calculate the total price and breakdown by world for all items in the list
@@ -380,6 +372,7 @@ mod tests {
                 .unwrap()
                 .and_hms_opt(0, 0, 0)
                 .unwrap(),
+            materia: vec![],
         }
     }
 
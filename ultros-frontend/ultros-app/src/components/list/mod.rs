@@ -4,3 +4,4 @@ pub mod list_item_row;
 pub mod list_settings_drawer;
 pub mod list_summary;
 pub mod share_list_modal;
+pub mod teamcraft_interchange;
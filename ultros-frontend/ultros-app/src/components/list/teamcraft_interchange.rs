@@ -0,0 +1,132 @@
+use crate::api::bulk_add_item_to_list;
+use crate::components::{clipboard::Clipboard, crafting_cost::IngredientsIter, loading::Loading};
+use crate::global_state::xiv_data::tracked_data;
+use crate::i18n::{t, t_string, use_i18n};
+use leptos::{either::Either, prelude::*};
+use ultros_api_types::{
+    list::ListItem,
+    teamcraft::{self, RecipeParts},
+};
+use xiv_gen::{ItemId, RecipeId};
+
+/// Ingredients of a Teamcraft recipe row, from the game data.
+fn recipe_parts(recipe_id: i32) -> Option<RecipeParts> {
+    let recipe = tracked_data().recipes.get(&RecipeId(recipe_id))?;
+    Some(RecipeParts {
+        amount_result: recipe.amount_result,
+        ingredients: IngredientsIter::new(recipe)
+            .map(|(ItemId(item_id), amount)| (item_id, amount))
+            .collect(),
+    })
+}
+
+fn parse_import(input: &str) -> Result<Vec<ListItem>, teamcraft::TeamcraftError> {
+    teamcraft::parse(input).map(|items| teamcraft::to_list_items(&items, recipe_parts))
+}
+
+/// Adds a Teamcraft import link or list JSON to the list.
+#[component]
+pub fn TeamcraftImporter<R>(list_id: Signal<i32>, refresh: R) -> impl IntoView
+where
+    R: Fn() + 'static + Copy + Send + Sync,
+{
+    let i18n = use_i18n();
+    let (input, set_input) = signal(String::new());
+    let add_items_to_list = Action::new(move |items: &Vec<ListItem>| {
+        let items = items.clone();
+        async move {
+            let result = bulk_add_item_to_list(list_id(), items).await;
+            refresh();
+            result
+        }
+    });
+    let parsed_items = move || {
+        input.with(|input| {
+            (!input.trim().is_empty()).then(|| match parse_import(input) {
+                Ok(items) => Either::Left(view! {
+                    <span>{t_string!(i18n, teamcraft_items_ready, count = items.len()).to_string()}</span>
+                }),
+                Err(e) => Either::Right(view! { <span class="text-red-300">{e.to_string()}</span> }),
+            })
+        })
+    };
+    view! {
+        <div class="flex flex-col gap-2">
+            <h3 class="font-bold text-brand-200">{t!(i18n, teamcraft_import_heading)}</h3>
+            <label for="teamcraft-import-textarea" class="text-sm text-[color:var(--color-text-muted)]">
+                {t!(i18n, teamcraft_import_instructions)}
+            </label>
+            <textarea
+                id="teamcraft-import-textarea"
+                class="input h-32 font-mono text-xs"
+                on:input=move |ev| set_input(event_target_value(&ev))
+            ></textarea>
+            <div class="text-xs">{parsed_items}</div>
+            <button
+                class="btn-primary self-start"
+                on:click=move |_| {
+                    if let Ok(items) = parse_import(&input.get_untracked()) {
+                        add_items_to_list.dispatch(items);
+                    }
+                }
+            >
+                {t!(i18n, teamcraft_bulk_add)}
+            </button>
+            {move || add_items_to_list.pending()().then(|| view! { <Loading /> })}
+            <div class="text-xs">
+                {move || {
+                    add_items_to_list
+                        .value()
+                        .with(|result| {
+                            result
+                                .as_ref()
+                                .map(|result| match result {
+                                    Ok(_) => Either::Left(view! { <span>{t!(i18n, teamcraft_added_success)}</span> }),
+                                    Err(e) => Either::Right(view! {
+                                        <span class="text-red-300">
+                                            {t!(i18n, teamcraft_error_prefix)} " " {e.to_string()}
+                                        </span>
+                                    }),
+                                })
+                        })
+                }}
+            </div>
+        </div>
+    }
+}
+
+/// The list as a Teamcraft import link and as list JSON with progress.
+#[component]
+pub fn TeamcraftExport(
+    #[prop(into)] list_name: Signal<String>,
+    #[prop(into)] items: Signal<Vec<ListItem>>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let link = Signal::derive(move || items.with(|items| teamcraft::import_link(items)));
+    let json = Signal::derive(move || {
+        list_name.with(|name| items.with(|items| teamcraft::export_json(name, items)))
+    });
+    view! {
+        <div class="flex flex-col gap-2">
+            <h3 class="font-bold text-brand-200">{t!(i18n, teamcraft_export_heading)}</h3>
+            <p class="text-sm text-[color:var(--color-text-muted)]">
+                {t!(i18n, teamcraft_export_instructions)}
+            </p>
+            <div class="flex flex-row items-center gap-2">
+                <a class="btn-secondary" href=link target="_blank" rel="noopener noreferrer">
+                    {t!(i18n, teamcraft_open_link)}
+                </a>
+                <Clipboard clipboard_text=link />
+            </div>
+            <label for="teamcraft-export-json" class="text-xs text-[color:var(--color-text-muted)]">
+                {t!(i18n, teamcraft_export_json_label)}
+            </label>
+            <textarea
+                id="teamcraft-export-json"
+                class="input h-32 font-mono text-xs"
+                readonly
+                prop:value=json
+            ></textarea>
+        </div>
+    }
+}
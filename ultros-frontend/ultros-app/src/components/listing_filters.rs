@@ -123,6 +123,7 @@ mod tests {
                 .unwrap()
                 .and_hms_opt(0, 0, 0)
                 .unwrap(),
+            materia: vec![],
         }
     }
 
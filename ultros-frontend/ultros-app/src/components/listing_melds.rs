@@ -0,0 +1,17 @@
+use crate::i18n::*;
+use leptos::prelude::*;
+use ultros_api_types::ListingMateria;
+
+/// How many materia are melded into a listing.
+#[component]
+pub fn ListingMelds(materia: Vec<ListingMateria>) -> impl IntoView {
+    let i18n = use_i18n();
+    let count = materia.len();
+    (count > 0).then(|| {
+        view! {
+            <span class="inline-flex items-center rounded-full px-2 py-0.5 text-xs font-medium border border-[color:var(--color-outline)] text-[color:var(--color-text)]">
+                {t!(i18n, listings_meld_count, count = count)}
+            </span>
+        }
+    })
+}
@@ -1,4 +1,5 @@
 use super::gil::*;
+use super::listing_melds::*;
 use super::relative_time::*;
 use crate::components::{datacenter_name::*, world_name::*};
 use crate::i18n::*;
@@ -38,6 +39,11 @@ pub fn ListingsTable(
         listings.sort_unstable_by_key(|(listing, _)| listing.price_per_unit);
         listings
     });
+    // Melded gear gets its own column, left out for everything else so plain
+    // items keep the familiar table.
+    let has_melds = Memo::new(move |_| {
+        sorted_listings.with(|listings| listings.iter().any(|(l, _)| !l.materia.is_empty()))
+    });
     // This memo handles the cheap slicing/view logic.
     // When `show_more` toggles, we re-slice the already sorted list instead of re-sorting everything.
     let listings = Memo::new(move |_| {
@@ -54,6 +60,10 @@ pub fn ListingsTable(
                     <th scope="col">{t!(i18n, listings_col_price)}</th>
                     <th scope="col">{t!(i18n, listings_col_qty)}</th>
                     <th scope="col">{t!(i18n, listings_col_total)}</th>
+                    {move || {
+                        has_melds()
+                            .then(|| view! { <th scope="col">{t!(i18n, listings_col_melds)}</th> })
+                    }}
                     <th scope="col">{t!(i18n, listings_col_retainer)}</th>
                     <th scope="col">{t!(i18n, listings_col_world)}</th>
                     <th scope="col">{t!(i18n, listings_col_datacenter)}</th>
@@ -89,6 +99,7 @@ pub fn ListingsTable(
                     key=move |(listing, _retainer)| listing.id
                     children=move |(listing, retainer)| {
                         let total = listing.price_per_unit * listing.quantity;
+                        let materia = listing.materia.clone();
                         view! {
                             <tr>
                                 <td>
@@ -98,6 +109,17 @@ pub fn ListingsTable(
                                 <td>
                                     <Gil amount=total />
                                 </td>
+                                {move || {
+                                    let materia = materia.clone();
+                                    has_melds()
+                                        .then(move || {
+                                            view! {
+                                                <td>
+                                                    <ListingMelds materia />
+                                                </td>
+                                            }
+                                        })
+                                }}
                                 <td>
                                     <A href=format!(
                                         "/retainers/listings/{}",
@@ -122,7 +144,7 @@ pub fn ListingsTable(
                         .then(|| {
                             view! {
                                 <tr>
-                                    <td colspan=7>
+                                    <td colspan=move || if has_melds() { 8 } else { 7 }>
                                         <button
                                             class="btn w-full"
                                             on:click=move |_| set_show_more(true)
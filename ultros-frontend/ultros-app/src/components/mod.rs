@@ -29,6 +29,7 @@ pub mod grouped_nav_accordion;
 pub mod history_panel;
 pub mod hover_card;
 pub mod icon;
+pub(crate) mod inventory_import;
 pub(crate) mod invite_link;
 pub mod item_icon;
 pub mod item_tooltip;
@@ -38,6 +39,7 @@ pub mod language_picker;
 pub mod list;
 pub mod list_subscribe_drawer;
 pub(crate) mod listing_filters;
+pub(crate) mod listing_melds;
 pub(crate) mod listing_quality;
 pub mod listings_panel;
 pub mod listings_table;
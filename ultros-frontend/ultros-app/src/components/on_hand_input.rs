@@ -8,6 +8,7 @@
 #![allow(dead_code)]
 
 use crate::components::crafting_cost::OnHand;
+use crate::components::inventory_import::InventoryImport;
 use crate::i18n::{t, t_string, use_i18n};
 use leptos::prelude::*;
 use std::cell::RefCell;
@@ -192,8 +193,8 @@ pub fn OnHandQuantity(
     }
 }
 
-/// Collapsible global panel listing every tracked item, with a reset button.
-/// Mounted on the analyzer routes.
+/// Collapsible global panel listing every tracked item, with a reset button
+/// and the inventory export importer. Mounted on the analyzer and list routes.
 #[component]
 pub fn OnHandPanel() -> impl IntoView {
     let i18n = use_i18n();
@@ -209,14 +210,14 @@ pub fn OnHandPanel() -> impl IntoView {
                     on:click=move |_| on_hand.0.update(|m| m.clear())
                     disabled=is_empty
                 >
-                    "Reset"
+                    {t!(i18n, on_hand_reset)}
                 </button>
             </div>
             <Show
                 when=move || !is_empty()
-                fallback=|| view! {
+                fallback=move || view! {
                     <div class="text-xs text-[color:var(--color-text-muted)]">
-                        "Set on-hand counts on individual ingredient rows."
+                        {t!(i18n, on_hand_empty_hint)}
                     </div>
                 }
             >
@@ -224,11 +225,19 @@ pub fn OnHandPanel() -> impl IntoView {
                     let count = on_hand.0.with(|m| m.len());
                     view! {
                         <div class="text-xs text-[color:var(--color-text-muted)]">
-                            {format!("{} items tracked", count)}
+                            {t_string!(i18n, on_hand_items_tracked, count = count).to_string()}
                         </div>
                     }
                 }}
             </Show>
+            <details class="mt-2">
+                <summary class="cursor-pointer text-xs text-brand-300">
+                    {t!(i18n, on_hand_import_summary)}
+                </summary>
+                <div class="mt-2">
+                    <InventoryImport />
+                </div>
+            </details>
         </div>
     }
 }
@@ -109,6 +109,7 @@ mod tests {
             quantity,
             hq,
             timestamp: Utc::now().naive_utc(),
+            materia: vec![],
         }
     }
 
@@ -9,7 +9,7 @@ use leptos::{html::Input, prelude::*, task::spawn_local};
 use leptos_router::{NavigateOptions, hooks::use_navigate};
 use std::sync::Arc;
 use std::sync::LazyLock;
-use ultros_api_types::search::SearchResult;
+use ultros_api_types::search::{SearchFacet, SearchResult, format_filter, parse_search_query};
 use web_sys::KeyboardEvent;
 
 static STATIC_PAGES: LazyLock<Vec<SearchResult>> = LazyLock::new(|| {
@@ -86,6 +86,14 @@ static STATIC_PAGES: LazyLock<Vec<SearchResult>> = LazyLock::new(|| {
             icon_id: None,
             category: Some("Market Analysis".to_string()),
         },
+        SearchResult {
+            score: 100.0,
+            title: "Route Planner Help".to_string(),
+            result_type: "Help".to_string(),
+            url: "/help/route-planner".to_string(),
+            icon_id: None,
+            category: Some("Market Analysis".to_string()),
+        },
         SearchResult {
             score: 100.0,
             title: "Recipe Analyzer Help".to_string(),
@@ -94,6 +102,14 @@ static STATIC_PAGES: LazyLock<Vec<SearchResult>> = LazyLock::new(|| {
             icon_id: None,
             category: Some("Crafting".to_string()),
         },
+        SearchResult {
+            score: 100.0,
+            title: "New This Patch Help".to_string(),
+            result_type: "Help".to_string(),
+            url: "/help/new-this-patch".to_string(),
+            icon_id: None,
+            category: Some("Crafting".to_string()),
+        },
         SearchResult {
             score: 100.0,
             title: "Venture Analyzer Help".to_string(),
@@ -126,6 +142,12 @@ static STATIC_PAGES: LazyLock<Vec<SearchResult>> = LazyLock::new(|| {
 /// `"<name> (<abbreviation>)"`, so clicking one lands on that job's gear.
 const JOB_EXAMPLES: [&str; 3] = ["SAM", "WHM", "BLM"];
 
+/// Backend results fetched per keystroke.
+const SEARCH_PAGE_SIZE: usize = 10;
+
+/// Category chips offered above the results for narrowing the search.
+const MAX_CATEGORY_CHIPS: usize = 6;
+
 fn get_static_pages() -> &'static [SearchResult] {
     &STATIC_PAGES
 }
@@ -167,10 +189,12 @@ pub fn SearchBox(#[prop(optional)] autofocus: bool) -> impl IntoView {
     let (active, set_active) = signal(false);
     let (loading, set_loading) = signal(false);
 
-    use crate::api::search as api_search;
+    use crate::api::search_page as api_search_page;
 
     // Search results and request tracking
     let (search_results, set_search_results) = signal::<Vec<Arc<SearchResult>>>(Vec::new());
+    // Item categories across every match, for narrowing with a `cat:` filter.
+    let (category_facets, set_category_facets) = signal::<Vec<SearchFacet>>(Vec::new());
     let (search_id, set_search_id) = signal(0usize);
 
     // Keyboard navigation focus handling
@@ -202,6 +226,8 @@ pub fn SearchBox(#[prop(optional)] autofocus: bool) -> impl IntoView {
     // Debounced search effect with cancellation via serial search_id
     Effect::new(move |_| {
         let s = search.get();
+        // Tracked so switching language re-runs the search in the new one.
+        let lang = i18n.get_locale().as_str();
         set_search_id.update(|n| *n += 1);
         let current_id = search_id.get_untracked();
 
@@ -214,13 +240,16 @@ pub fn SearchBox(#[prop(optional)] autofocus: bool) -> impl IntoView {
 
             if s.trim().is_empty() {
                 set_search_results.set(vec![]);
+                set_category_facets.set(vec![]);
                 return;
             }
 
-            let s_lower = s.to_lowercase();
+            // Tool pages only match on the free text; `job:WHM` alone
+            // shouldn't list every page.
+            let s_lower = parse_search_query(&s).text.to_lowercase();
             let mut matched_pages: Vec<SearchResult> = get_static_pages()
                 .iter()
-                .filter(|p| p.title.to_lowercase().contains(&s_lower))
+                .filter(|p| !s_lower.is_empty() && p.title.to_lowercase().contains(&s_lower))
                 .cloned()
                 .collect();
 
@@ -232,12 +261,13 @@ pub fn SearchBox(#[prop(optional)] autofocus: bool) -> impl IntoView {
             });
 
             set_loading.set(true);
-            match api_search(&s).await {
-                Ok(mut results) => {
+            match api_search_page(&s, lang, 0, SEARCH_PAGE_SIZE).await {
+                Ok(mut page) => {
                     if search_outcome(search_id, current_id) == SearchOutcome::Commit {
                         // Prepend static pages to the backend results
                         let mut final_results = matched_pages;
-                        final_results.append(&mut results);
+                        final_results.append(&mut page.results);
+                        set_category_facets.set(page.facets.categories);
 
                         let results = final_results.into_iter().map(Arc::new).collect();
                         set_search_results.set(results);
@@ -248,6 +278,7 @@ pub fn SearchBox(#[prop(optional)] autofocus: bool) -> impl IntoView {
                     if search_outcome(search_id, current_id) == SearchOutcome::Commit {
                         log::error!("Search failed: {}", e);
                         // Even if backend fails, show matched static pages
+                        set_category_facets.set(vec![]);
                         let results = matched_pages.into_iter().map(Arc::new).collect();
                         set_search_results.set(results);
                         set_loading.set(false);
@@ -456,6 +487,10 @@ pub fn SearchBox(#[prop(optional)] autofocus: bool) -> impl IntoView {
                     <Icon icon=i::FaWrenchSolid attr:class="text-[color:var(--color-text-muted)]" />
                     <span>{t!(i18n, search_hint_tools)}</span>
                 </div>
+                <div class="flex items-center gap-2 text-sm">
+                    <Icon icon=i::FaFilterSolid attr:class="text-[color:var(--color-text-muted)]" />
+                    <span class="font-mono text-xs">{t!(i18n, search_hint_filters)}</span>
+                </div>
             </div>
 
             // Search Results
@@ -478,6 +513,46 @@ pub fn SearchBox(#[prop(optional)] autofocus: bool) -> impl IntoView {
                     </div>
                 </Show>
 
+                // One category across every match narrows nothing, so the
+                // chips only show when there's a choice to make.
+                <Show when=move || category_facets.with(|facets| facets.len() > 1)>
+                    <div class="flex flex-wrap gap-1 p-2 mb-1 bg-[color:var(--color-background-elevated)] border border-[color:var(--color-outline)] rounded-md shadow-lg">
+                        {move || {
+                            category_facets
+                                .get()
+                                .into_iter()
+                                .take(MAX_CATEGORY_CHIPS)
+                                .map(|facet| {
+                                    let category = facet.value.clone();
+                                    let label = t_string!(i18n, search_narrow_by_category, category = facet.value.clone())
+                                        .to_string();
+                                    view! {
+                                        <button
+                                            class="px-2 py-0.5 rounded-full text-xs border border-[color:var(--color-outline)] text-[color:var(--color-text-muted)] hover:text-[color:var(--color-text)] hover:border-[color:var(--brand-ring)] transition-colors focus-visible:ring-2 focus-visible:ring-[color:var(--brand-ring)] focus:outline-none"
+                                            aria-label=label
+                                            on:mousedown=|e: web_sys::MouseEvent| e.prevent_default()
+                                            on:click=move |_| {
+                                                let filter = format_filter("cat", &category);
+                                                set_search.update(|s| {
+                                                    *s = format!("{} {filter}", s.trim());
+                                                });
+                                                if let Some(input) = text_input.get() {
+                                                    let _ = input.focus();
+                                                }
+                                                set_active(true);
+                                            }
+                                        >
+                                            {facet.value}
+                                            " "
+                                            <span class="opacity-70">{facet.count}</span>
+                                        </button>
+                                    }
+                                })
+                                .collect_view()
+                        }}
+                    </div>
+                </Show>
+
                 <div
                     class="scroll-panel content-auto contain-layout contain-paint will-change-scroll forced-layer cis-42"
                     class:hidden=move || search_results.with(|v| v.is_empty())
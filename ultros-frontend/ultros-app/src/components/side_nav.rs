@@ -168,6 +168,13 @@ pub fn SideNav() -> impl IntoView {
                 >
                     {t!(i18n, vendor_resale)}
                 </SideNavItem>
+                <SideNavItem
+                    href=with_world("/route-planner?world={world}", "/route-planner")
+                    section="route-planner"
+                    icon=i::FaRouteSolid
+                >
+                    {t!(i18n, route_planner)}
+                </SideNavItem>
                 <SideNavItem
                     href=with_world("/recipe-analyzer?world={world}", "/recipe-analyzer")
                     section="recipe-analyzer"
@@ -175,6 +182,13 @@ pub fn SideNav() -> impl IntoView {
                 >
                     {t!(i18n, recipe_analyzer)}
                 </SideNavItem>
+                <SideNavItem
+                    href="/new-this-patch".to_string()
+                    section="new-this-patch"
+                    icon=i::FaWandMagicSparklesSolid
+                >
+                    {t!(i18n, patch_diff)}
+                </SideNavItem>
                 <SideNavItem
                     href=with_world("/fc-crafting-analyzer/{world}", "/fc-crafting-analyzer")
                     section="fc-crafting-analyzer"
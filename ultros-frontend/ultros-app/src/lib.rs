@@ -47,8 +47,10 @@ use crate::{
         list_view::*,
         lists::*,
         not_found::NotFound,
+        patch_diff::*,
         recipe_analyzer::*,
         retainers::*,
+        route_planner::*,
         scrip_sources::*,
         settings::*,
         trends::*,
@@ -397,6 +399,14 @@ pub fn Footer() -> impl IntoView {
                     >
                         <Icon icon=i::BsInfoCircle width="1.2em" height="1.2em" /><span>{t!(i18n, about)}</span>
                     </A>
+                    // served by axum, not the router
+                    <a
+                        href="/api/docs"
+                        rel="external"
+                        class="btn-ghost opacity-80 hover:opacity-100"
+                    >
+                        <Icon icon=i::BsCodeSlash width="1.2em" height="1.2em" /><span>{t!(i18n, api_docs)}</span>
+                    </a>
                 </div>
                 <div class="divider opacity-50"></div>
                 <div class="text-center space-y-3 muted text-sm max-w-3xl mx-auto opacity-75 hover:opacity-100 transition-opacity">
@@ -512,6 +522,8 @@ pub fn AppInner(cookies: Cookies) -> impl IntoView {
                         <ParentRoute path=path!("retainers") view=Retainers>
                             <Route path=path!("edit") view=EditRetainers />
                             <Route path=path!("undercuts") view=RetainerUndercuts />
+                            <Route path=path!("reprice") view=RetainerRepricing />
+                            <Route path=path!("ledger") view=RetainerSalesLedger />
                             <Route path=path!("listings") view=RetainerListings />
                             <Route path=path!("listings/:id") view=SingleRetainerListings />
                             <Route path=path!("") view=RetainersBasePath />
@@ -556,7 +568,9 @@ pub fn AppInner(cookies: Cookies) -> impl IntoView {
                         <Route path=path!("flip-finder/:world") view=AnalyzerWorldView />
                         <Route path=path!("vendor-resale") view=VendorResale />
                         <Route path=path!("vendor-resale/:world") view=VendorWorldView />
+                        <Route path=path!("route-planner") view=RoutePlanner />
                         <Route path=path!("recipe-analyzer") view=RecipeAnalyzer />
+                        <Route path=path!("new-this-patch") view=NewThisPatch />
                         <Route path=path!("fc-crafting-analyzer") view=FCCraftingAnalyzer />
                         <Route path=path!("fc-crafting-analyzer/:world") view=FCCraftingAnalyzer />
                         <Route path=path!("leve-analyzer") view=LeveAnalyzer />
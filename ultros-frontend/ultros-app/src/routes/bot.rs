@@ -62,6 +62,7 @@ pub fn BotGuide() -> impl IntoView {
                         ("/ffxiv retainer remove owned_retainer_id:<name>", t_string!(i18n, bot_cmd_retainer_remove_desc).to_string()),
                         ("/ffxiv retainer check_listings", t_string!(i18n, bot_cmd_retainer_check_listings_desc).to_string()),
                         ("/ffxiv retainer check_undercuts", t_string!(i18n, bot_cmd_retainer_check_undercuts_desc).to_string()),
+                        ("/ffxiv retainer ledger days:<1-365>", t_string!(i18n, bot_cmd_retainer_ledger_desc).to_string()),
                         ("/ffxiv retainer add_undercut_alert margin_percent:<0-200>", t_string!(i18n, bot_cmd_retainer_add_undercut_alert_desc).to_string()),
                         ("/ffxiv retainer remove_undercut_alert", t_string!(i18n, bot_cmd_retainer_remove_undercut_alert_desc).to_string()),
                     ]
@@ -3,7 +3,7 @@ use crate::components::crafting_cost::{
     CRYSTAL_SEARCH_CATEGORY, CraftingCostOptions, EmptyOnHand, OnHand, ShardsMode,
     compute_ingredient_cost,
 };
-use crate::components::on_hand_input::{ActiveListBanner, LocalOnHand, OnHandMap};
+use crate::components::on_hand_input::{ActiveListBanner, LocalOnHand, OnHandMap, OnHandPanel};
 use crate::global_state::cookies::Cookies;
 use crate::global_state::craft_options::{self, CraftOptions};
 use crate::global_state::xiv_data::tracked_data;
@@ -251,6 +251,11 @@ fn FCCraftingAnalyzerTable(
         };
         let on_hand_map = use_context::<OnHandMap>();
         let use_on_hand = use_on_hand_enabled();
+        // Re-run when the inventory changes, e.g. after an import; the
+        // per-row snapshots below read it untracked.
+        if use_on_hand && let Some(m) = on_hand_map {
+            m.0.track();
+        }
 
         let mut results = Vec::new();
 
@@ -399,6 +404,7 @@ fn FCCraftingAnalyzerTable(
     view! {
         <div class="flex flex-col gap-6">
             <ActiveListBanner />
+            <OnHandPanel />
             <Toolbar>
                 <ToolbarField label=t_string!(i18n, fc_crafting_filter_profit_min_label).to_string()>
                     <input
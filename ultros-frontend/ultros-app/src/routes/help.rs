@@ -121,6 +121,35 @@ pub const HELP_TOPICS: &[HelpTopic] = &[
         ],
         image: None,
     },
+    HelpTopic {
+        slug: "route-planner",
+        title: "Route Planner",
+        category: "Market analysis",
+        summary: "Plan a budgeted shopping trip across your data center to resell at home.",
+        purpose: "Use this when one flip at a time is too slow and you have gil to put to work.",
+        inputs: &[
+            "Home world",
+            "Budget",
+            "Sell-through horizon in days",
+            "Every listing of each candidate item in the data center",
+            "Recent sales on the home world",
+        ],
+        assumptions: &[
+            "Whole listings are bought; the market board does not split stacks.",
+            "Each recent sale counts as one unit, so stackable materials are undercounted.",
+            "The 5% market board tax is subtracted from the sale price.",
+        ],
+        results: &[
+            "Stops are ordered home world first, then by expected profit.",
+            "A bracketed quantity is how much of a stack is expected to sell in the horizon.",
+            "Expected profit values unsold surplus at zero.",
+        ],
+        next_actions: &[
+            "Check the item page before buying expensive stacks.",
+            "Shorten the horizon if you want to turn the gil over quickly.",
+        ],
+        image: None,
+    },
     HelpTopic {
         slug: "recipe-analyzer",
         title: "Recipe Analyzer",
@@ -150,6 +179,31 @@ pub const HELP_TOPICS: &[HelpTopic] = &[
         ],
         image: None,
     },
+    HelpTopic {
+        slug: "new-this-patch",
+        title: "New This Patch",
+        category: "Crafting",
+        summary: "See the items, recipes and shop changes the latest game update added.",
+        purpose: "Use this on patch day to decide which materials to buy before the new recipes drive their prices up.",
+        inputs: &[
+            "The game data before and after the update",
+            "Current cheapest listings in your price zone",
+        ],
+        assumptions: &[
+            "The report is rebuilt when Ultros picks up new game data, usually within a day of a patch.",
+            "Materials that are themselves new this patch are left out of the stock-up table.",
+        ],
+        results: &[
+            "Mats to stock up on are existing items the new recipes use, most widely used first.",
+            "Amounts are per single craft of each recipe.",
+            "Vendor price changes compare the gil shop price before and after.",
+        ],
+        next_actions: &[
+            "Open an ingredient's market page to check its price history.",
+            "Run a new recipe through the Recipe Analyzer once it has sales.",
+        ],
+        image: None,
+    },
     HelpTopic {
         slug: "leve-analyzer",
         title: "Leve Analyzer",
@@ -455,6 +455,7 @@ mod tests {
                 quantity,
                 hq,
                 timestamp: chrono::Utc::now().naive_utc(),
+                materia: vec![],
             },
             Retainer {
                 id,
@@ -44,7 +44,7 @@ use xiv_gen::{
 /// `abbreviation`, or it resolves on English data and nowhere else.
 ///
 /// The ids are the `ClassJob` sheet's row ids and the order matches
-/// [`job_category_lookup`]'s destructure exactly, because the
+/// `ClassJobCategory::includes_job`'s destructure exactly, because the
 /// `ClassJobCategory` sheet's per-job columns are laid out in `ClassJob` id
 /// order. `canonical_acronym_matches_english_abbreviation` pins the table
 /// against the shipped English pack, so a game-data bump that renumbers or
@@ -118,105 +118,12 @@ pub(crate) fn job_category_lookup(
     class_job_category: &ClassJobCategory,
     job_acronym: &str,
 ) -> bool {
-    let lower_case = job_acronym.to_lowercase();
-    // this is kind of dumb, but this should give a compile time error whenever a job changes.
-    let ClassJobCategory {
-        key_id: _,
-        name: _,
-        adv,
-        gla,
-        pgl,
-        mrd,
-        lnc,
-        arc,
-        cnj,
-        thm,
-        crp,
-        bsm,
-        arm,
-        gsm,
-        ltw,
-        wvr,
-        alc,
-        cul,
-        min,
-        btn,
-        fsh,
-        pld,
-        mnk,
-        war,
-        drg,
-        brd,
-        whm,
-        blm,
-        acn,
-        smn,
-        sch,
-        rog,
-        nin,
-        mch,
-        drk,
-        ast,
-        sam,
-        rdm,
-        blu,
-        gnb,
-        dnc,
-        rpr,
-        sge,
-        vpr,
-        pct,
-        ..
-    } = class_job_category;
-    match lower_case.as_str() {
-        "adv" => *adv,
-        "gla" => *gla,
-        "pgl" => *pgl,
-        "mrd" => *mrd,
-        "lnc" => *lnc,
-        "arc" => *arc,
-        "cnj" => *cnj,
-        "thm" => *thm,
-        "crp" => *crp,
-        "bsm" => *bsm,
-        "arm" => *arm,
-        "gsm" => *gsm,
-        "ltw" => *ltw,
-        "wvr" => *wvr,
-        "alc" => *alc,
-        "cul" => *cul,
-        "min" => *min,
-        "btn" => *btn,
-        "fsh" => *fsh,
-        "pld" => *pld,
-        "mnk" => *mnk,
-        "war" => *war,
-        "drg" => *drg,
-        "brd" => *brd,
-        "whm" => *whm,
-        "blm" => *blm,
-        "acn" => *acn,
-        "smn" => *smn,
-        "sch" => *sch,
-        "rog" => *rog,
-        "nin" => *nin,
-        "mch" => *mch,
-        "drk" => *drk,
-        "ast" => *ast,
-        "sam" => *sam,
-        "rdm" => *rdm,
-        "blu" => *blu,
-        "gnb" => *gnb,
-        "dnc" => *dnc,
-        "rpr" => *rpr,
-        "sge" => *sge,
-        "vpr" => *vpr,
-        "pct" => *pct,
-        _ => {
+    class_job_category
+        .includes_job(job_acronym)
+        .unwrap_or_else(|| {
             tracing::warn!(job_acronym, "Unknown job acronym");
             false
-        }
-    }
+        })
 }
 
 /// Filter `data.items` to entries matching the given canonical job acronym.
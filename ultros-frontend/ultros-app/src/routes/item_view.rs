@@ -2186,6 +2186,7 @@ mod tests {
                 quantity: 1,
                 hq,
                 timestamp: chrono::Utc::now().naive_utc(),
+                materia: vec![],
             },
             Arc::new(Retainer {
                 id,
@@ -1,5 +1,5 @@
 use std::cmp::Reverse;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::str::FromStr;
 
@@ -25,14 +25,19 @@ use crate::components::{
     add_recipe_to_current_list::AddRecipeToCurrentListModal,
     item_icon::*,
     list::{
-        auto_mark_purchases::AutoMarkPurchases, buying_view::BuyingView,
-        list_item_row::ListItemRow, list_settings_drawer::ListSettingsDrawer, list_summary::*,
+        auto_mark_purchases::AutoMarkPurchases,
+        buying_view::BuyingView,
+        list_item_row::ListItemRow,
+        list_settings_drawer::ListSettingsDrawer,
+        list_summary::*,
+        teamcraft_interchange::{TeamcraftExport, TeamcraftImporter},
     },
     list_subscribe_drawer::ListSubscribeDrawer,
     loading::*,
     make_place_importer::*,
     meta::{MetaDescription, MetaRobotsNoIndex, MetaTitle},
     modal::Modal,
+    on_hand_input::{OnHandMap, OnHandPanel},
     realtime_status::RealtimeStatus,
     tooltip::*,
 };
@@ -49,6 +54,8 @@ enum MenuState {
     None,
     // Recipe and item search are now handled by modals
     MakePlace,
+    Inventory,
+    Teamcraft,
 }
 
 fn filter_excluded_worlds(
@@ -208,6 +215,30 @@ fn remaining_quantity(item: &ListItem) -> i32 {
     quantity.saturating_sub(item.acquired.unwrap_or(0).clamp(0, quantity))
 }
 
+/// Rows whose acquired count should rise to cover what the on-hand inventory
+/// already holds. An item listed on several rows (e.g. HQ and NQ) draws on
+/// one shared pool, and acquired counts never go down.
+fn acquired_from_inventory(
+    items: &[(ListItem, Vec<ActiveListing>)],
+    owned: &HashMap<i32, i32>,
+) -> Vec<ListItem> {
+    let mut pool = owned.clone();
+    items
+        .iter()
+        .filter_map(|(item, _)| {
+            let available = pool.get_mut(&item.item_id)?;
+            let quantity = item.quantity.unwrap_or(1).max(1);
+            let acquired = item.acquired.unwrap_or(0).clamp(0, quantity);
+            let covered = (*available).min(quantity);
+            *available -= covered.max(acquired).min(*available);
+            (covered > acquired).then(|| ListItem {
+                acquired: Some(covered),
+                ..item.clone()
+            })
+        })
+        .collect()
+}
+
 /// Cheapest per-unit price among the listings that match the item's quality
 /// requirement — mirrors what the price column displays.
 fn cheapest_price_per_unit(item: &ListItem, listings: &[ActiveListing]) -> Option<i32> {
@@ -516,6 +547,30 @@ pub fn ListView() -> impl IntoView {
     });
 
     let (menu, set_menu) = signal(MenuState::None);
+    let on_hand = use_context::<OnHandMap>().expect("OnHandMap not provided");
+    // Raise acquired counts to what the imported inventory already covers,
+    // updating the loaded rows right away like auto-marked purchases.
+    let mark_owned_acquired = move || {
+        let owned = on_hand.0.get_untracked();
+        list_view.update(|data| {
+            if let Some(Ok((list, items))) = data {
+                if !ListCapabilities::from(list.permission).can_write {
+                    return;
+                }
+                let updated = acquired_from_inventory(items, &owned);
+                for (item, _) in items.iter_mut() {
+                    if let Some(next) = updated.iter().find(|next| next.id == item.id) {
+                        item.acquired = next.acquired;
+                    }
+                }
+                for item in updated {
+                    leptos::task::spawn_local(async move {
+                        let _ = edit_list_item(item).await;
+                    });
+                }
+            }
+        });
+    };
     let (item_modal_open, set_item_modal_open) = signal(false);
     let (recipe_modal_open, set_recipe_modal_open) = signal(false);
     let (subscribe_open, set_subscribe_open) = signal(false);
@@ -688,8 +743,38 @@ pub fn ListView() -> impl IntoView {
                                         <span>{t!(i18n, list_view_make_place)}</span>
                                     </button>
                                 </Tooltip>
+                                <Tooltip tooltip_text=t_string!(i18n, list_view_tooltip_inventory).to_string()>
+                                    <button
+                                        class="btn-secondary"
+                                        class:active=move || menu() == MenuState::Inventory
+                                        on:click=move |_| set_menu(
+                                            match menu() {
+                                                MenuState::Inventory => MenuState::None,
+                                                _ => MenuState::Inventory,
+                                            },
+                                        )
+                                    >
+                                        <Icon icon=i::BiPackageRegular />
+                                        <span>{t!(i18n, list_view_inventory)}</span>
+                                    </button>
+                                </Tooltip>
                             </>
                         </Show>
+                        <Tooltip tooltip_text=t_string!(i18n, list_view_tooltip_teamcraft).to_string()>
+                            <button
+                                class="btn-secondary"
+                                class:active=move || menu() == MenuState::Teamcraft
+                                on:click=move |_| set_menu(
+                                    match menu() {
+                                        MenuState::Teamcraft => MenuState::None,
+                                        _ => MenuState::Teamcraft,
+                                    },
+                                )
+                            >
+                                <Icon icon=i::BiShareAltRegular />
+                                <span>{t!(i18n, list_view_teamcraft)}</span>
+                            </button>
+                        </Tooltip>
                     </div>
 
                     <div class="flex flex-wrap gap-2 self-start lg:self-auto">
@@ -1019,7 +1104,68 @@ pub fn ListView() -> impl IntoView {
                                     refresh=move || { list_view.refetch() }
                                 />
                             </section>
-                        },
+                        }
+                            .into_any(),
+                    )
+                }
+                MenuState::Inventory => {
+                    Some(
+                        view! {
+                            <section class="panel rounded-lg p-4 flex flex-col gap-3">
+                                <OnHandPanel />
+                                <div class="flex flex-row items-center gap-3">
+                                    <button
+                                        class="btn-primary"
+                                        disabled=move || {
+                                            on_hand.0.with(|m| m.is_empty())
+                                        }
+                                        on:click=move |_| mark_owned_acquired()
+                                    >
+                                        {t!(i18n, list_view_mark_owned_acquired)}
+                                    </button>
+                                    <span class="text-xs text-[color:var(--color-text-muted)]">
+                                        {t!(i18n, list_view_mark_owned_acquired_hint)}
+                                    </span>
+                                </div>
+                            </section>
+                        }
+                            .into_any(),
+                    )
+                }
+                MenuState::Teamcraft => {
+                    let list_name = Signal::derive(move || {
+                        list_view
+                            .with(|data| {
+                                data.as_ref()
+                                    .and_then(|result| result.as_ref().ok())
+                                    .map(|(list, _)| list.list.name.clone())
+                            })
+                            .unwrap_or_default()
+                    });
+                    let items = Signal::derive(move || {
+                        list_view
+                            .with(|data| {
+                                data.as_ref()
+                                    .and_then(|result| result.as_ref().ok())
+                                    .map(|(_, items)| {
+                                        items.iter().map(|(item, _)| item.clone()).collect()
+                                    })
+                            })
+                            .unwrap_or_default()
+                    });
+                    Some(
+                        view! {
+                            <section class="panel rounded-lg p-4 grid gap-6 lg:grid-cols-2">
+                                <Show when=move || view_caps.with(|c| c.can_write)>
+                                    <TeamcraftImporter
+                                        list_id=list_id.into()
+                                        refresh=move || { list_view.refetch() }
+                                    />
+                                </Show>
+                                <TeamcraftExport list_name items />
+                            </section>
+                        }
+                            .into_any(),
                     )
                 }
             }}
@@ -1569,6 +1715,7 @@ mod tests {
                 .unwrap()
                 .and_hms_opt(0, 0, 0)
                 .unwrap(),
+            materia: vec![],
         }
     }
 
@@ -1802,4 +1949,30 @@ mod tests {
             vec![2, 3, 1]
         );
     }
+
+    #[test]
+    fn inventory_fills_rows_from_a_shared_pool() {
+        let row = |id: i32, item_id: i32, quantity: i32, acquired: i32| {
+            let mut item = list_item(id);
+            item.item_id = item_id;
+            item.quantity = Some(quantity);
+            item.acquired = Some(acquired);
+            (item, vec![])
+        };
+        let items = vec![
+            row(1, 7, 10, 2),
+            row(2, 7, 5, 0),
+            row(3, 8, 3, 3),
+            row(4, 9, 1, 0),
+        ];
+        let owned = HashMap::from([(7, 12), (8, 1)]);
+        let updated = acquired_from_inventory(&items, &owned);
+        assert_eq!(
+            updated
+                .iter()
+                .map(|item| (item.id, item.acquired))
+                .collect::<Vec<_>>(),
+            vec![(1, Some(10)), (2, Some(2))]
+        );
+    }
 }
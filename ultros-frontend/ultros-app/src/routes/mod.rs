@@ -23,8 +23,10 @@ pub mod leve_analyzer;
 pub mod list_view;
 pub mod lists;
 pub mod not_found;
+pub mod patch_diff;
 pub mod recipe_analyzer;
 pub mod retainers;
+pub mod route_planner;
 pub mod scrip_sources;
 pub mod settings;
 pub mod trends;
@@ -0,0 +1,291 @@
+use crate::api::get_patch_diff;
+use crate::components::meta::{MetaDescription, MetaTitle};
+use crate::components::{
+    cheapest_price::CheapestPrice, gil::*, item_icon::*, skeleton::BoxSkeleton, tool_help::*,
+};
+use crate::global_state::xiv_data::tracked_data;
+use crate::i18n::*;
+use crate::routes::recipe_analyzer::craft_type_acronym;
+use leptos::prelude::*;
+use ultros_api_types::patch_diff::{DiffRecipe, IngredientDemand, PatchDiff, PriceChange};
+use xiv_gen::ItemId;
+
+/// Localized name when the item is in our game data, otherwise `fallback`
+/// (the English name the report was written with).
+fn item_name(item_id: i32, fallback: Option<&str>) -> String {
+    tracked_data()
+        .items
+        .get(&ItemId(item_id))
+        .map(|i| i.name.as_str().to_string())
+        .or_else(|| fallback.map(str::to_string))
+        .unwrap_or_else(|| item_id.to_string())
+}
+
+#[component]
+fn ItemLink(item_id: i32, #[prop(optional)] fallback: Option<String>) -> impl IntoView {
+    view! {
+        <a
+            class="flex flex-row items-center gap-2 hover:text-brand-300 transition-colors"
+            href=format!("/item/{}", item_id)
+        >
+            <ItemIcon item_id=item_id icon_size=IconSize::Small />
+            <span class="font-semibold">{item_name(item_id, fallback.as_deref())}</span>
+        </a>
+    }
+}
+
+#[component]
+fn Section(#[prop(into)] title: String, count: usize, children: Children) -> impl IntoView {
+    view! {
+        <section class="panel p-4 rounded-2xl flex flex-col gap-3">
+            <h2 class="text-lg font-bold text-[color:var(--brand-fg)]">
+                {title} " "
+                <span class="text-sm font-medium text-[color:var(--color-text-muted)]">
+                    "(" {count} ")"
+                </span>
+            </h2>
+            <div class="overflow-x-auto">{children()}</div>
+        </section>
+    }
+}
+
+#[component]
+fn IngredientDemandTable(demand: Vec<IngredientDemand>) -> impl IntoView {
+    let i18n = use_i18n();
+    view! {
+        <Section title=t_string!(i18n, patch_diff_demand).to_string() count=demand.len()>
+            <p class="text-sm text-[color:var(--color-text-muted)] mb-2">
+                {t!(i18n, patch_diff_demand_help)}
+            </p>
+            <table class="w-full text-sm">
+                <thead>
+                    <tr class="text-left text-[color:var(--color-text-muted)]">
+                        <th class="p-2">{t!(i18n, patch_diff_col_item)}</th>
+                        <th class="p-2 text-right">{t!(i18n, patch_diff_col_recipes)}</th>
+                        <th class="p-2 text-right">{t!(i18n, patch_diff_col_amount)}</th>
+                        <th class="p-2 text-right">{t!(i18n, patch_diff_col_cheapest)}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {demand
+                        .into_iter()
+                        .map(|d| view! {
+                            <tr class="border-t border-[color:var(--color-outline)]">
+                                <td class="p-2"><ItemLink item_id=d.item_id /></td>
+                                <td class="p-2 text-right">{d.recipes}</td>
+                                <td class="p-2 text-right">{d.amount}</td>
+                                <td class="p-2 text-right">
+                                    <CheapestPrice item_id=ItemId(d.item_id) />
+                                </td>
+                            </tr>
+                        })
+                        .collect_view()}
+                </tbody>
+            </table>
+        </Section>
+    }
+}
+
+#[component]
+fn NewRecipesTable(recipes: Vec<DiffRecipe>) -> impl IntoView {
+    let i18n = use_i18n();
+    view! {
+        <Section title=t_string!(i18n, patch_diff_new_recipes).to_string() count=recipes.len()>
+            <table class="w-full text-sm">
+                <thead>
+                    <tr class="text-left text-[color:var(--color-text-muted)]">
+                        <th class="p-2">{t!(i18n, patch_diff_col_item)}</th>
+                        <th class="p-2">{t!(i18n, patch_diff_col_job)}</th>
+                        <th class="p-2">{t!(i18n, patch_diff_col_ingredients)}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {recipes
+                        .into_iter()
+                        .map(|recipe| view! {
+                            <tr class="border-t border-[color:var(--color-outline)] align-top">
+                                <td class="p-2">
+                                    <div class="flex flex-row items-center gap-1">
+                                        <ItemLink item_id=recipe.item_id />
+                                        {(recipe.amount_result > 1)
+                                            .then(|| format!("× {}", recipe.amount_result))}
+                                    </div>
+                                </td>
+                                <td class="p-2 whitespace-nowrap">
+                                    {craft_type_acronym(recipe.craft_type)} " " {recipe.level}
+                                </td>
+                                <td class="p-2">
+                                    <div class="flex flex-row flex-wrap gap-x-4 gap-y-1">
+                                        {recipe
+                                            .ingredients
+                                            .into_iter()
+                                            .map(|ingredient| view! {
+                                                <div class="flex flex-row items-center gap-1">
+                                                    <ItemLink item_id=ingredient.item_id />
+                                                    "× " {ingredient.amount}
+                                                </div>
+                                            })
+                                            .collect_view()}
+                                    </div>
+                                </td>
+                            </tr>
+                        })
+                        .collect_view()}
+                </tbody>
+            </table>
+        </Section>
+    }
+}
+
+#[component]
+fn VendorPriceTable(changes: Vec<PriceChange>) -> impl IntoView {
+    let i18n = use_i18n();
+    view! {
+        <Section title=t_string!(i18n, patch_diff_vendor_prices).to_string() count=changes.len()>
+            <table class="w-full text-sm">
+                <thead>
+                    <tr class="text-left text-[color:var(--color-text-muted)]">
+                        <th class="p-2">{t!(i18n, patch_diff_col_item)}</th>
+                        <th class="p-2 text-right">{t!(i18n, patch_diff_col_before)}</th>
+                        <th class="p-2 text-right">{t!(i18n, patch_diff_col_after)}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {changes
+                        .into_iter()
+                        .map(|change| view! {
+                            <tr class="border-t border-[color:var(--color-outline)]">
+                                <td class="p-2"><ItemLink item_id=change.item_id /></td>
+                                <td class="p-2 text-right"><Gil amount=change.before /></td>
+                                <td class="p-2 text-right"><Gil amount=change.after /></td>
+                            </tr>
+                        })
+                        .collect_view()}
+                </tbody>
+            </table>
+        </Section>
+    }
+}
+
+#[component]
+fn PatchDiffView(diff: PatchDiff) -> impl IntoView {
+    let i18n = use_i18n();
+    if diff.is_empty() {
+        return view! {
+            <ActionableEmptyState
+                title=t_string!(i18n, patch_diff_empty_title).to_string()
+                body=t_string!(i18n, patch_diff_empty_body).to_string()
+            />
+        }
+        .into_any();
+    }
+    let demand = diff.ingredient_demand();
+    let short = |revision: &str| revision.chars().take(10).collect::<String>();
+    let (from, to) = (short(&diff.from), short(&diff.to));
+    let stats = [
+        (
+            t_string!(i18n, patch_diff_new_items).to_string(),
+            diff.new_items.len(),
+        ),
+        (
+            t_string!(i18n, patch_diff_new_recipes).to_string(),
+            diff.new_recipes.len(),
+        ),
+        (
+            t_string!(i18n, patch_diff_changed_recipes).to_string(),
+            diff.changed_recipes.len(),
+        ),
+        (
+            t_string!(i18n, patch_diff_new_vendor_items).to_string(),
+            diff.new_vendor_items.len(),
+        ),
+        (
+            t_string!(i18n, patch_diff_new_shop_entries).to_string(),
+            diff.new_special_shop_entries.len(),
+        ),
+    ];
+    view! {
+        <div class="flex flex-col gap-4">
+            <div class="panel p-4 rounded-2xl flex flex-row flex-wrap gap-6">
+                {stats
+                    .into_iter()
+                    .map(|(label, count)| view! {
+                        <div class="flex flex-col">
+                            <span class="text-xs text-[color:var(--color-text-muted)]">{label}</span>
+                            <span>{count}</span>
+                        </div>
+                    })
+                    .collect_view()}
+                <div class="flex flex-col ml-auto text-right">
+                    <span class="text-xs text-[color:var(--color-text-muted)]">
+                        {t!(i18n, patch_diff_revisions)}
+                    </span>
+                    <code class="text-xs">{from} " → " {to}</code>
+                </div>
+            </div>
+            {(!demand.is_empty()).then(|| view! { <IngredientDemandTable demand=demand /> })}
+            {(!diff.new_recipes.is_empty())
+                .then(|| view! { <NewRecipesTable recipes=diff.new_recipes /> })}
+            {(!diff.new_items.is_empty()).then(|| {
+                let items = diff.new_items;
+                view! {
+                    <Section title=t_string!(i18n, patch_diff_new_items).to_string() count=items.len()>
+                        <table class="w-full text-sm">
+                            <tbody>
+                                {items
+                                    .into_iter()
+                                    .map(|item| view! {
+                                        <tr class="border-t border-[color:var(--color-outline)]">
+                                            <td class="p-2">
+                                                <ItemLink item_id=item.item_id fallback=item.name />
+                                            </td>
+                                            <td class="p-2 text-right">
+                                                <CheapestPrice item_id=ItemId(item.item_id) />
+                                            </td>
+                                        </tr>
+                                    })
+                                    .collect_view()}
+                            </tbody>
+                        </table>
+                    </Section>
+                }
+            })}
+            {(!diff.vendor_price_changes.is_empty())
+                .then(|| view! { <VendorPriceTable changes=diff.vendor_price_changes /> })}
+        </div>
+    }
+    .into_any()
+}
+
+#[component]
+pub fn NewThisPatch() -> impl IntoView {
+    let i18n = use_i18n();
+    let diff = Resource::new(|| (), |_| get_patch_diff());
+    view! {
+        <div class="flex flex-col gap-4">
+            <MetaTitle title=move || t_string!(i18n, patch_diff_meta_title).to_string() />
+            <MetaDescription text=move || t_string!(i18n, patch_diff_meta_desc).to_string() />
+            <ToolHeader
+                title=t_string!(i18n, patch_diff).to_string()
+                summary=t_string!(i18n, patch_diff_tool_summary).to_string()
+                context=t_string!(i18n, patch_diff_tool_context).to_string()
+                help_href="/help/new-this-patch"
+                help_body=t_string!(i18n, patch_diff_tool_help).to_string()
+            />
+            <Suspense fallback=move || view! { <BoxSkeleton /> }>
+                {move || {
+                    diff.get().map(|diff| match diff {
+                        Ok(diff) => view! { <PatchDiffView diff=diff /> }.into_any(),
+                        Err(e) => view! {
+                            <div class="text-red-400">
+                                {t!(i18n, patch_diff_error)} " " {e.to_string()}
+                            </div>
+                        }
+                        .into_any(),
+                    })
+                }}
+            </Suspense>
+        </div>
+    }
+    .into_any()
+}
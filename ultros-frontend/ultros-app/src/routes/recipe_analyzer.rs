@@ -2,7 +2,7 @@ use crate::components::crafting_cost::{
     CraftingCostOptions, EmptyOnHand, ShardsMode, compute_cost,
 };
 use crate::components::meta::{MetaDescription, MetaTitle};
-use crate::components::on_hand_input::{ActiveListBanner, LocalOnHand, OnHandMap};
+use crate::components::on_hand_input::{ActiveListBanner, LocalOnHand, OnHandMap, OnHandPanel};
 use crate::components::related_items::is_shard_item;
 use crate::global_state::craft_options::{self, CraftOptions};
 use crate::global_state::xiv_data::tracked_data;
@@ -64,7 +64,7 @@ struct RecipeProfitData {
 
 /// Acronym for a `Recipe::craft_type`, matching the `CraftType` sheet order.
 /// Empty for anything outside the eight crafters.
-fn craft_type_acronym(craft_type: i32) -> &'static str {
+pub(crate) fn craft_type_acronym(craft_type: i32) -> &'static str {
     match craft_type {
         0 => "CRP",
         1 => "BSM",
@@ -272,6 +272,11 @@ fn RecipeAnalyzerTable(
         };
         let on_hand_map = use_context::<OnHandMap>();
         let use_on_hand = use_on_hand_enabled();
+        // Re-run when the inventory changes, e.g. after an import; the
+        // per-row snapshots below read it untracked.
+        if use_on_hand && let Some(m) = on_hand_map {
+            m.0.track();
+        }
 
         for recipe in recipes.values() {
             // Filter by job and level
@@ -469,6 +474,7 @@ fn RecipeAnalyzerTable(
     view! {
         <div class="flex flex-col gap-6">
             <ActiveListBanner />
+            <OnHandPanel />
             // Primary filter toolbar
             <Toolbar>
                 <ToolbarField label=t_string!(i18n, recipe_analyzer_filter_profit_min_label).to_string()>
@@ -1,13 +1,15 @@
 use crate::api::{
-    UndercutData, get_login, get_retainer_listings, get_retainer_undercuts,
-    get_user_retainer_listings,
+    UndercutData, get_login, get_retainer_ledger, get_retainer_listings, get_retainer_reprice,
+    get_retainer_undercuts, get_user_retainer_listings,
 };
 use crate::components::alert_drawer::{AlertDrawer, AlertKind};
 use crate::components::clipboard::Clipboard;
 use crate::components::gil::*;
 use crate::components::icon::Icon;
+use crate::components::relative_time::RelativeToNow;
 use crate::components::skeleton::BoxSkeleton;
 use crate::components::tool_help::ActionableEmptyState;
+use crate::components::toolbar::{Toolbar, ToolbarField, ToolbarPills};
 use crate::components::{item_icon::*, loading::*, meta::*, world_name::*};
 use crate::global_state::use_world_display_name;
 use crate::global_state::xiv_data::tracked_data;
@@ -18,7 +20,12 @@ use icondata as i;
 use leptos::either::Either;
 use leptos::prelude::*;
 use leptos_router::*;
-use ultros_api_types::{ActiveListing, FfxivCharacter, Retainer, world_helper::AnySelector};
+use ultros_api_types::{
+    ActiveListing, FfxivCharacter, Retainer,
+    repricing::{DEFAULT_URGENCY, ListingReprice, RepriceInputs, RetainerReprice},
+    user::{LedgerTotals, RetainerLedger},
+    world_helper::AnySelector,
+};
 use xiv_gen::{ItemId, ItemSortCategoryId};
 
 #[derive(PartialOrd, Ord, Eq, PartialEq, Debug)]
@@ -560,6 +567,487 @@ pub fn RetainerListings() -> impl IntoView {
     }.into_any()
 }
 
+/// Average time to sell, in the largest whole unit that fits.
+fn format_time_to_sell(seconds: Option<i64>) -> String {
+    match seconds {
+        None => "—".to_string(),
+        Some(s) if s < 3600 => format!("{}m", s / 60),
+        Some(s) if s < 86400 => format!("{}h", s / 3600),
+        Some(s) => format!("{}d", s / 86400),
+    }
+}
+
+/// The sales, units, gil and time-to-sell cells every ledger table ends with.
+#[component]
+fn LedgerTotalsCells(totals: LedgerTotals) -> impl IntoView {
+    view! {
+        <td>{totals.sales}</td>
+        <td>{totals.units}</td>
+        <td>
+            <GenericGil<i64> amount=totals.gross />
+        </td>
+        <td>
+            <GenericGil<i64> amount=totals.tax />
+        </td>
+        <td>
+            <GenericGil<i64> amount=totals.revenue() />
+        </td>
+        <td>{format_time_to_sell(totals.avg_seconds_to_sell)}</td>
+    }
+}
+
+#[component]
+fn LedgerTotalsHeader() -> impl IntoView {
+    let i18n = use_i18n();
+    view! {
+        <th scope="col">{t!(i18n, retainers_ledger_sales)}</th>
+        <th scope="col">{t!(i18n, retainers_ledger_units)}</th>
+        <th scope="col">{t!(i18n, retainers_ledger_gross)}</th>
+        <th scope="col">{t!(i18n, retainers_ledger_tax)}</th>
+        <th scope="col">{t!(i18n, retainers_ledger_revenue)}</th>
+        <th scope="col">{t!(i18n, retainers_ledger_time_to_sell)}</th>
+    }
+}
+
+#[component]
+fn LedgerItemLink(item_id: i32) -> impl IntoView {
+    let i18n = use_i18n();
+    match tracked_data().items.get(&ItemId(item_id)) {
+        Some(item) => Either::Left(view! {
+            <A attr:class="flex flex-row" href=format!("/item/{item_id}")>
+                <ItemIcon icon_size=IconSize::Small item_id />
+                {item.name.as_str()}
+            </A>
+        }),
+        None => Either::Right(view! { {t!(i18n, retainers_item_not_found)} }),
+    }
+}
+
+#[component]
+fn LedgerTables(ledger: RetainerLedger) -> impl IntoView {
+    let i18n = use_i18n();
+    if ledger.total.sales == 0 {
+        return view! {
+            <div class="panel p-4 rounded-xl text-center opacity-70">
+                {t!(i18n, retainers_ledger_empty)}
+            </div>
+        }
+        .into_any();
+    }
+    let retainer_names: std::collections::HashMap<i32, String> = ledger
+        .retainers
+        .iter()
+        .map(|row| (row.retainer_id, row.retainer_name.clone()))
+        .collect();
+    let total = ledger.total;
+    let retainers = ledger
+        .retainers
+        .into_iter()
+        .map(|row| {
+            view! {
+                <tr>
+                    <td>{row.retainer_name}</td>
+                    <LedgerTotalsCells totals=row.totals />
+                </tr>
+            }
+        })
+        .collect_view();
+    let items = ledger
+        .items
+        .into_iter()
+        .map(|row| {
+            view! {
+                <tr>
+                    <td>
+                        <LedgerItemLink item_id=row.item_id />
+                    </td>
+                    <LedgerTotalsCells totals=row.totals />
+                </tr>
+            }
+        })
+        .collect_view();
+    let weeks = ledger
+        .weeks
+        .into_iter()
+        .rev()
+        .map(|row| {
+            view! {
+                <tr>
+                    <td>{row.week.format("%Y-%m-%d").to_string()}</td>
+                    <LedgerTotalsCells totals=row.totals />
+                </tr>
+            }
+        })
+        .collect_view();
+    let recent = ledger
+        .recent
+        .into_iter()
+        .map(|sale| {
+            view! {
+                <tr>
+                    <td>{sale.hq.then_some(t!(i18n, retainers_hq))}</td>
+                    <td>
+                        <LedgerItemLink item_id=sale.item_id />
+                    </td>
+                    <td>{retainer_names.get(&sale.retainer_id).cloned()}</td>
+                    <td>
+                        <Gil amount=sale.price_per_unit />
+                    </td>
+                    <td>{sale.quantity}</td>
+                    <td>
+                        <GenericGil<i64> amount=sale.gross - sale.tax />
+                    </td>
+                    <td>
+                        <RelativeToNow timestamp=sale.sold_at />
+                    </td>
+                </tr>
+            }
+        })
+        .collect_view();
+    view! {
+        <div class="panel p-4 rounded-xl">
+            <table class="w-full">
+                <thead>
+                    <tr>
+                        <LedgerTotalsHeader />
+                    </tr>
+                </thead>
+                <tbody>
+                    <tr>
+                        <LedgerTotalsCells totals=total />
+                    </tr>
+                </tbody>
+            </table>
+        </div>
+        <div class="panel p-4 rounded-xl">
+            <span class="content-title">{t!(i18n, retainers_ledger_by_retainer)}</span>
+            <table class="w-full">
+                <thead>
+                    <tr>
+                        <th scope="col">{t!(i18n, retainers_ledger_retainer)}</th>
+                        <LedgerTotalsHeader />
+                    </tr>
+                </thead>
+                <tbody>{retainers}</tbody>
+            </table>
+        </div>
+        <div class="panel p-4 rounded-xl">
+            <span class="content-title">{t!(i18n, retainers_ledger_by_week)}</span>
+            <table class="w-full">
+                <thead>
+                    <tr>
+                        <th scope="col">{t!(i18n, retainers_ledger_week_of)}</th>
+                        <LedgerTotalsHeader />
+                    </tr>
+                </thead>
+                <tbody>{weeks}</tbody>
+            </table>
+        </div>
+        <div class="panel p-4 rounded-xl">
+            <span class="content-title">{t!(i18n, retainers_ledger_by_item)}</span>
+            <table class="w-full">
+                <thead>
+                    <tr>
+                        <th scope="col">{t!(i18n, retainers_item)}</th>
+                        <LedgerTotalsHeader />
+                    </tr>
+                </thead>
+                <tbody>{items}</tbody>
+            </table>
+        </div>
+        <div class="panel p-4 rounded-xl">
+            <span class="content-title">{t!(i18n, retainers_ledger_recent)}</span>
+            <table class="w-full">
+                <thead>
+                    <tr>
+                        <th scope="col">{t!(i18n, retainers_hq)}</th>
+                        <th scope="col">{t!(i18n, retainers_item)}</th>
+                        <th scope="col">{t!(i18n, retainers_ledger_retainer)}</th>
+                        <th scope="col">{t!(i18n, retainers_price_per_unit)}</th>
+                        <th scope="col">{t!(i18n, retainers_quantity)}</th>
+                        <th scope="col">{t!(i18n, retainers_ledger_revenue)}</th>
+                        <th scope="col">{t!(i18n, retainers_ledger_sold)}</th>
+                    </tr>
+                </thead>
+                <tbody>{recent}</tbody>
+            </table>
+        </div>
+    }
+    .into_any()
+}
+
+#[component]
+pub fn RetainerSalesLedger() -> impl IntoView {
+    let i18n = use_i18n();
+    let login = Resource::new(|| (), |_| async move { get_login().await });
+    let (days, set_days) = signal(30_i64);
+    let ledger = Resource::new(
+        move || (login.get().map(|res| res.is_ok()).unwrap_or(false), days()),
+        move |(logged_in, days)| async move {
+            if logged_in {
+                get_retainer_ledger(days).await
+            } else {
+                Err(crate::error::AppError::ApiError(
+                    ultros_api_types::result::ApiError::NotAuthenticated,
+                ))
+            }
+        },
+    );
+    let pill_active_class = "px-3 py-1.5 rounded-full text-xs font-semibold border transition-colors bg-[color:color-mix(in_srgb,var(--brand-ring)_18%,transparent)] text-[color:var(--color-text)] border-[color:color-mix(in_srgb,var(--brand-ring)_40%,var(--color-outline))]";
+    let pill_inactive_class = "px-3 py-1.5 rounded-full text-xs font-semibold border transition-colors bg-transparent text-[color:var(--color-text-muted)] hover:text-[color:var(--color-text)] border-transparent";
+    let window_pill = move |window: i64, label: String| {
+        view! {
+            <button
+                aria-pressed=move || (days() == window).to_string()
+                class=move || if days() == window { pill_active_class } else { pill_inactive_class }
+                on:click=move |_| set_days.set(window)
+            >
+                {label}
+            </button>
+        }
+    };
+    view! {
+        <MetaTitle title=t_string!(i18n, retainers_ledger_title).to_string() />
+        <Suspense fallback=move || {
+            view! { <Loading /> }
+        }>
+            {move || {
+                match login.get() {
+                    None => view! { <Loading /> }.into_any(),
+                    Some(Err(_)) => {
+                        view! {
+                            <ActionableEmptyState
+                                title=t_string!(i18n, retainers_empty_title).to_string()
+                                body=t_string!(i18n, retainers_empty_body).to_string()
+                                action_href="/login?next=/retainers/ledger"
+                                action_label=t_string!(i18n, sign_in_discord).to_string()
+                                action_external=true
+                                secondary_action_href="/bot"
+                                secondary_action_label=t_string!(i18n, retainers_empty_secondary_label).to_string()
+                            />
+                        }.into_any()
+                    }
+                    Some(Ok(_)) => {
+                        view! {
+                            <span class="content-title">{t!(i18n, retainers_ledger_title)}</span>
+                            <br />
+                            <span>{t!(i18n, retainers_ledger_description)}</span>
+                            <Toolbar>
+                                <ToolbarField label=t_string!(i18n, retainers_ledger_window).to_string()>
+                                    <ToolbarPills>
+                                        {window_pill(7, t_string!(i18n, retainers_ledger_window_7d).to_string())}
+                                        {window_pill(30, t_string!(i18n, retainers_ledger_window_30d).to_string())}
+                                        {window_pill(90, t_string!(i18n, retainers_ledger_window_90d).to_string())}
+                                        {window_pill(365, t_string!(i18n, retainers_ledger_window_365d).to_string())}
+                                    </ToolbarPills>
+                                </ToolbarField>
+                            </Toolbar>
+                            <div class="flex flex-col gap-4">
+                                {move || {
+                                    ledger
+                                        .get()
+                                        .map(|ledger| match ledger {
+                                            Ok(ledger) => Either::Left(view! { <LedgerTables ledger /> }),
+                                            Err(e) => Either::Right(view! {
+                                                <div>
+                                                    {t!(i18n, retainers_unable_to_get)} <br /> {e.to_string()}
+                                                </div>
+                                            }),
+                                        })
+                                }}
+                            </div>
+                        }.into_any()
+                    }
+                }
+            }}
+        </Suspense>
+    }
+}
+
+#[component]
+fn RepriceRow(
+    listing: ActiveListing,
+    inputs: RepriceInputs,
+    urgency: ReadSignal<u8>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let suggestion = Memo::new(move |_| inputs.suggest(urgency()));
+    view! {
+        <tr>
+            <td>{listing.hq.then_some(t!(i18n, retainers_hq))}</td>
+            <td>
+                <LedgerItemLink item_id=listing.item_id />
+            </td>
+            <td>
+                <Gil amount=listing.price_per_unit />
+            </td>
+            <td>{listing.quantity}</td>
+            {move || match suggestion() {
+                Some(suggestion) => Either::Left(view! {
+                    <td>
+                        <div class="flex flex-row">
+                            <Gil amount=suggestion.price />
+                            <Clipboard clipboard_text=suggestion.price.to_string() />
+                        </div>
+                    </td>
+                    <td>{suggestion.position}</td>
+                    <td>
+                        {suggestion
+                            .est_days_to_sell
+                            .map(|days| format_time_to_sell(Some((days * 86_400.0) as i64)))
+                            .unwrap_or_else(|| "—".to_string())}
+                    </td>
+                    <td>
+                        {suggestion
+                            .percentile
+                            .map(|percentile| percentile.to_string())
+                            .unwrap_or_else(|| "—".to_string())}
+                    </td>
+                }),
+                None => Either::Right(view! {
+                    <td colspan="4" class="opacity-70">
+                        {t!(i18n, retainers_reprice_no_data)}
+                    </td>
+                }),
+            }}
+        </tr>
+    }
+}
+
+#[component]
+fn RetainerRepriceTable(reprice: RetainerReprice, urgency: ReadSignal<u8>) -> impl IntoView {
+    let i18n = use_i18n();
+    let mut listings = reprice.listings;
+    listings.sort_by_key(|l| ItemSortKey::from(&l.listing));
+    let retainer = reprice.retainer;
+    view! {
+        <div class="panel p-4 rounded-xl">
+            <span class="content-title">
+                {retainer.name} " - " <WorldName id=AnySelector::World(retainer.world_id) />
+            </span>
+            <table class="w-full">
+                <thead>
+                    <tr>
+                        <th scope="col">{t!(i18n, retainers_hq)}</th>
+                        <th scope="col">{t!(i18n, retainers_item)}</th>
+                        <th scope="col">{t!(i18n, retainers_reprice_current)}</th>
+                        <th scope="col">{t!(i18n, retainers_quantity)}</th>
+                        <th scope="col">{t!(i18n, retainers_reprice_suggested)}</th>
+                        <th scope="col">{t!(i18n, retainers_reprice_position)}</th>
+                        <th scope="col">{t!(i18n, retainers_reprice_days_to_sell)}</th>
+                        <th scope="col">{t!(i18n, retainers_reprice_percentile)}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {listings
+                        .into_iter()
+                        .map(|ListingReprice { listing, inputs }| {
+                            view! { <RepriceRow listing inputs urgency /> }
+                        })
+                        .collect_view()}
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
+#[component]
+pub fn RetainerRepricing() -> impl IntoView {
+    let i18n = use_i18n();
+    let login = Resource::new(|| (), |_| async move { get_login().await });
+    let (urgency, set_urgency) = signal(DEFAULT_URGENCY);
+    let reprice = Resource::new(
+        move || login.get().map(|res| res.is_ok()).unwrap_or(false),
+        move |logged_in| async move {
+            if logged_in {
+                get_retainer_reprice().await
+            } else {
+                Err(crate::error::AppError::ApiError(
+                    ultros_api_types::result::ApiError::NotAuthenticated,
+                ))
+            }
+        },
+    );
+    view! {
+        <MetaTitle title=t_string!(i18n, retainers_reprice_title).to_string() />
+        <Suspense fallback=move || {
+            view! { <Loading /> }
+        }>
+            {move || {
+                match login.get() {
+                    None => view! { <Loading /> }.into_any(),
+                    Some(Err(_)) => {
+                        view! {
+                            <ActionableEmptyState
+                                title=t_string!(i18n, retainers_empty_title).to_string()
+                                body=t_string!(i18n, retainers_empty_body).to_string()
+                                action_href="/login?next=/retainers/reprice"
+                                action_label=t_string!(i18n, sign_in_discord).to_string()
+                                action_external=true
+                                secondary_action_href="/bot"
+                                secondary_action_label=t_string!(i18n, retainers_empty_secondary_label).to_string()
+                            />
+                        }.into_any()
+                    }
+                    Some(Ok(_)) => {
+                        view! {
+                            <span class="content-title">{t!(i18n, retainers_reprice_title)}</span>
+                            <br />
+                            <span>{t!(i18n, retainers_reprice_description)}</span>
+                            <Toolbar>
+                                <ToolbarField label=t_string!(i18n, retainers_reprice_pricing).to_string()>
+                                    <div class="flex flex-row items-center gap-2 text-xs">
+                                        <span>{t!(i18n, retainers_reprice_max_price)}</span>
+                                        <input
+                                            type="range"
+                                            min="0"
+                                            max="100"
+                                            class="accent-brand-300"
+                                            prop:value=move || urgency().to_string()
+                                            on:input=move |event| {
+                                                if let Ok(value) = event_target_value(&event).parse() {
+                                                    set_urgency.set(value);
+                                                }
+                                            }
+                                        />
+                                        <span>{t!(i18n, retainers_reprice_fast_sale)}</span>
+                                    </div>
+                                </ToolbarField>
+                            </Toolbar>
+                            <div class="flex flex-col gap-4">
+                                {move || {
+                                    reprice
+                                        .get()
+                                        .map(|reprice| match reprice {
+                                            Ok(retainers) if retainers.iter().all(|r| r.listings.is_empty()) => {
+                                                view! {
+                                                    <div class="panel p-4 rounded-xl text-center opacity-70">
+                                                        {t!(i18n, retainers_reprice_empty)}
+                                                    </div>
+                                                }.into_any()
+                                            }
+                                            Ok(retainers) => retainers
+                                                .into_iter()
+                                                .filter(|r| !r.listings.is_empty())
+                                                .map(|reprice| view! { <RetainerRepriceTable reprice urgency /> })
+                                                .collect_view()
+                                                .into_any(),
+                                            Err(e) => view! {
+                                                <div>
+                                                    {t!(i18n, retainers_unable_to_get)} <br /> {e.to_string()}
+                                                </div>
+                                            }.into_any(),
+                                        })
+                                }}
+                            </div>
+                        }.into_any()
+                    }
+                }
+            }}
+        </Suspense>
+    }
+}
+
 #[component]
 pub fn Retainers() -> impl IntoView {
     let i18n = use_i18n();
@@ -579,6 +1067,14 @@ pub fn Retainers() -> impl IntoView {
                 <Icon height="1.25em" width="1.25em" icon=i::AiExclamationOutlined />
                 <span>{t!(i18n, retainers_undercuts_tab)}</span>
             </A>
+            <A exact=true attr:class="nav-link" href="/retainers/reprice">
+                <Icon height="1.25em" width="1.25em" icon=i::FaMoneyBillTrendUpSolid />
+                <span>{t!(i18n, retainers_reprice_tab)}</span>
+            </A>
+            <A exact=true attr:class="nav-link" href="/retainers/ledger">
+                <Icon height="1.25em" width="1.25em" icon=i::FaCoinsSolid />
+                <span>{t!(i18n, retainers_ledger_tab)}</span>
+            </A>
         </div>
         <div class="main-content">
             <div class="container mx-auto">
@@ -614,6 +1110,7 @@ mod test {
                 quantity: 1,
                 hq: true,
                 timestamp: NaiveDateTime::MIN,
+                materia: vec![],
             })
             .collect();
         let original = item_vec.clone();
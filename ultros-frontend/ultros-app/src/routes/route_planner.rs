@@ -0,0 +1,300 @@
+use crate::api::get_route_plan;
+use crate::components::meta::{MetaDescription, MetaTitle};
+use crate::components::{
+    gil::*,
+    item_icon::*,
+    skeleton::BoxSkeleton,
+    tool_help::*,
+    toolbar::{Toolbar, ToolbarField},
+    world_name::WorldName,
+    world_picker::WorldOnlyPicker,
+};
+use crate::global_state::xiv_data::tracked_data;
+use crate::global_state::{LocalWorldData, home_world::use_home_world};
+use crate::i18n::*;
+use leptos::prelude::*;
+use leptos_router::{
+    NavigateOptions,
+    hooks::{query_signal, use_navigate, use_query_map},
+};
+use ultros_api_types::{
+    route_planner::{RoutePlan, RouteStop},
+    world_helper::AnySelector,
+};
+
+const DEFAULT_BUDGET: i64 = 1_000_000;
+const DEFAULT_HORIZON_DAYS: f32 = 7.0;
+
+#[component]
+fn RouteStopCard(stop: RouteStop, index: usize, home_world_id: i32) -> impl IntoView {
+    let i18n = use_i18n();
+    let items = &tracked_data().items;
+    let is_home = stop.world_id == home_world_id;
+    view! {
+        <section class="panel p-4 rounded-2xl flex flex-col gap-3">
+            <div class="flex flex-row flex-wrap items-center justify-between gap-2">
+                <h2 class="text-lg font-bold text-[color:var(--brand-fg)] flex flex-row items-center gap-2">
+                    <span>{index + 1} "."</span>
+                    <WorldName id=AnySelector::World(stop.world_id) />
+                    {is_home.then(|| view! {
+                        <span class="text-xs font-medium text-[color:var(--color-text-muted)]">
+                            {t!(i18n, route_planner_home_stop)}
+                        </span>
+                    })}
+                </h2>
+                <div class="flex flex-row gap-4 text-sm">
+                    <div class="flex flex-row gap-1 items-center">
+                        {t!(i18n, route_planner_stop_cost)}
+                        <GenericGil amount=stop.cost />
+                    </div>
+                    <div class="flex flex-row gap-1 items-center">
+                        {t!(i18n, route_planner_expected_profit)}
+                        <GenericGil amount=stop.expected_profit />
+                    </div>
+                </div>
+            </div>
+            <div class="overflow-x-auto">
+                <table class="w-full text-sm">
+                    <thead>
+                        <tr class="text-left text-[color:var(--color-text-muted)]">
+                            <th class="p-2">{t!(i18n, route_planner_col_item)}</th>
+                            <th class="p-2 text-right">{t!(i18n, route_planner_col_quantity)}</th>
+                            <th class="p-2 text-right">{t!(i18n, route_planner_col_price)}</th>
+                            <th class="p-2 hidden md:table-cell">{t!(i18n, route_planner_col_retainer)}</th>
+                            <th class="p-2 text-right">{t!(i18n, route_planner_col_profit)}</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {stop
+                            .purchases
+                            .into_iter()
+                            .map(|purchase| {
+                                let name = items
+                                    .get(&xiv_gen::ItemId(purchase.item_id))
+                                    .map(|i| i.name.as_str().to_string())
+                                    .unwrap_or_else(|| t_string!(i18n, unknown).to_string());
+                                let partially_sellable = purchase.sellable_quantity < purchase.quantity;
+                                view! {
+                                    <tr class="border-t border-[color:var(--color-outline)]">
+                                        <td class="p-2">
+                                            <a
+                                                class="flex flex-row items-center gap-2 hover:text-brand-300 transition-colors"
+                                                href=format!("/item/{}", purchase.item_id)
+                                            >
+                                                <ItemIcon item_id=purchase.item_id icon_size=IconSize::Small />
+                                                <span class="font-semibold">{name}</span>
+                                                {purchase.hq.then_some("HQ")}
+                                            </a>
+                                        </td>
+                                        <td class="p-2 text-right">
+                                            {purchase.quantity}
+                                            {partially_sellable.then(|| view! {
+                                                <span
+                                                    class="text-xs text-[color:var(--color-text-muted)] cursor-help"
+                                                    title=move || t_string!(i18n, route_planner_partial_tooltip).to_string()
+                                                >
+                                                    " (" {purchase.sellable_quantity} ")"
+                                                </span>
+                                            })}
+                                        </td>
+                                        <td class="p-2 text-right">
+                                            <Gil amount=purchase.price_per_unit />
+                                        </td>
+                                        <td class="p-2 hidden md:table-cell">
+                                            {purchase.retainer_name.clone().unwrap_or_default()}
+                                        </td>
+                                        <td class="p-2 text-right">
+                                            <GenericGil amount=purchase.expected_profit />
+                                        </td>
+                                    </tr>
+                                }
+                            })
+                            .collect_view()}
+                    </tbody>
+                </table>
+            </div>
+        </section>
+    }
+    .into_any()
+}
+
+#[component]
+fn RoutePlanView(plan: RoutePlan) -> impl IntoView {
+    let i18n = use_i18n();
+    if plan.stops.is_empty() {
+        return view! {
+            <ActionableEmptyState
+                title=t_string!(i18n, route_planner_empty_title).to_string()
+                body=t_string!(i18n, route_planner_empty_body).to_string()
+            />
+        }
+        .into_any();
+    }
+    let home_world_id = plan.home_world_id;
+    view! {
+        <div class="flex flex-col gap-4">
+            <div class="panel p-4 rounded-2xl flex flex-row flex-wrap gap-6">
+                <div class="flex flex-col">
+                    <span class="text-xs text-[color:var(--color-text-muted)]">{t!(i18n, route_planner_total_cost)}</span>
+                    <GenericGil amount=plan.total_cost />
+                </div>
+                <div class="flex flex-col">
+                    <span class="text-xs text-[color:var(--color-text-muted)]">{t!(i18n, route_planner_expected_profit)}</span>
+                    <GenericGil amount=plan.expected_profit />
+                </div>
+                <div class="flex flex-col">
+                    <span class="text-xs text-[color:var(--color-text-muted)]">{t!(i18n, route_planner_stops)}</span>
+                    <span>{plan.stops.len()}</span>
+                </div>
+            </div>
+            {plan
+                .stops
+                .into_iter()
+                .enumerate()
+                .map(|(index, stop)| view! { <RouteStopCard stop=stop index=index home_world_id=home_world_id /> })
+                .collect_view()}
+        </div>
+    }
+    .into_any()
+}
+
+#[component]
+pub fn RoutePlanner() -> impl IntoView {
+    let i18n = use_i18n();
+    let query = use_query_map();
+    let (home_world, _) = use_home_world();
+    let nav = use_navigate();
+    let (budget, set_budget) = query_signal::<i64>("budget");
+    let (horizon_days, set_horizon_days) = query_signal::<f32>("days");
+
+    let worlds = use_context::<LocalWorldData>()
+        .expect("Should always have local world data")
+        .0
+        .unwrap();
+    let initial_world = query.with_untracked(|p| {
+        let binding = p.get("world");
+        let world = binding.as_deref().unwrap_or_default();
+        worlds
+            .lookup_world_by_name(world)
+            .and_then(|w| w.as_world().cloned())
+    });
+    let (selected_world, set_selected_world) = signal(initial_world);
+
+    Effect::new(move |_| {
+        if selected_world.get_untracked().is_none()
+            && let Some(home) = home_world.get()
+        {
+            set_selected_world(Some(home));
+        }
+    });
+
+    // Keep `?world=` in step with the picker so a plan is shareable.
+    Effect::new(move |_| {
+        if let Some(world) = selected_world.get() {
+            let current_query = query.get_untracked();
+            if current_query.get("world").as_deref() != Some(world.name.as_str()) {
+                let mut q = current_query;
+                q.insert("world".to_string(), world.name.clone());
+                nav(
+                    &q.to_query_string(),
+                    NavigateOptions {
+                        scroll: false,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+    });
+
+    let plan = Resource::new(
+        move || {
+            (
+                selected_world.get().map(|w| w.name),
+                budget().unwrap_or(DEFAULT_BUDGET),
+                horizon_days(),
+            )
+        },
+        move |(world, budget, horizon_days)| async move {
+            match world {
+                Some(world) => Some(get_route_plan(&world, budget, horizon_days).await),
+                None => None,
+            }
+        },
+    );
+
+    view! {
+        <div class="flex flex-col gap-4">
+            <MetaTitle title=move || t_string!(i18n, route_planner_meta_title).to_string() />
+            <MetaDescription text=move || t_string!(i18n, route_planner_meta_desc).to_string() />
+            <ToolHeader
+                title=t_string!(i18n, route_planner).to_string()
+                summary=t_string!(i18n, route_planner_tool_summary).to_string()
+                context=t_string!(i18n, route_planner_tool_context).to_string()
+                help_href="/help/route-planner"
+                help_body=t_string!(i18n, route_planner_tool_help).to_string()
+            />
+            <Toolbar>
+                <ToolbarField label=t_string!(i18n, world).to_string()>
+                    <WorldOnlyPicker
+                        current_world=selected_world.into()
+                        set_current_world=set_selected_world.into()
+                    />
+                </ToolbarField>
+                <ToolbarField label=t_string!(i18n, route_planner_budget).to_string()>
+                    <input
+                        class="input input-sm w-36"
+                        min=0
+                        step=100000
+                        type="number"
+                        prop:value=move || budget().unwrap_or(DEFAULT_BUDGET)
+                        on:change=move |input| {
+                            let value = event_target_value(&input);
+                            if let Ok(budget) = value.parse::<i64>() {
+                                set_budget(Some(budget))
+                            } else if value.is_empty() {
+                                set_budget(None);
+                            }
+                        }
+                    />
+                </ToolbarField>
+                <ToolbarField label=t_string!(i18n, route_planner_horizon_days).to_string()>
+                    <input
+                        class="input input-sm w-24"
+                        min=1
+                        max=30
+                        step=1
+                        type="number"
+                        prop:value=move || horizon_days().unwrap_or(DEFAULT_HORIZON_DAYS)
+                        on:change=move |input| {
+                            let value = event_target_value(&input);
+                            if let Ok(days) = value.parse::<f32>() {
+                                set_horizon_days(Some(days))
+                            } else if value.is_empty() {
+                                set_horizon_days(None);
+                            }
+                        }
+                    />
+                </ToolbarField>
+            </Toolbar>
+            <CalculationSummary
+                title=t_string!(i18n, route_planner_calc_title).to_string()
+                formula=t_string!(i18n, route_planner_calc_formula).to_string()
+                details=t_string!(i18n, route_planner_calc_details).to_string()
+            />
+            <Suspense fallback=move || view! { <BoxSkeleton /> }>
+                {move || {
+                    plan.get().flatten().map(|plan| match plan {
+                        Ok(plan) => view! { <RoutePlanView plan=plan /> }.into_any(),
+                        Err(e) => view! {
+                            <div class="text-red-400">
+                                {t!(i18n, route_planner_error)} " " {e.to_string()}
+                            </div>
+                        }
+                        .into_any(),
+                    })
+                }}
+            </Suspense>
+        </div>
+    }
+    .into_any()
+}
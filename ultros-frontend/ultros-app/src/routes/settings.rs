@@ -1,10 +1,12 @@
 use crate::api::{
-    claim_character, delete_user, get_characters, search_characters, unclaim_character,
+    claim_character, create_api_token, delete_user, get_api_tokens, get_characters,
+    get_user_sessions, revoke_api_token, revoke_other_user_sessions, revoke_user_session,
+    search_characters, unclaim_character,
 };
 use crate::components::meta::{MetaDescription, MetaRobotsNoIndex, MetaTitle};
 use crate::components::{
-    ad::*, crafter_settings::CrafterSettings, loading::*, toggle::Toggle, world_name::*,
-    world_picker::*,
+    ad::*, crafter_settings::CrafterSettings, loading::*, relative_time::RelativeToNow,
+    toggle::Toggle, world_name::*, world_picker::*,
 };
 use crate::error::AppResult;
 use crate::global_state::cookies::Cookies;
@@ -22,6 +24,7 @@ use leptos::task::spawn_local;
 use icondata as i;
 use log::info;
 use ultros_api_types::FfxivCharacter;
+use ultros_api_types::user::{ApiTokenScope, CreateApiToken};
 use ultros_api_types::world_helper::AnySelector;
 
 #[component]
@@ -272,6 +275,374 @@ fn AdChoice() -> impl IntoView {
     }.into_any()
 }
 
+#[component]
+fn Sessions() -> impl IntoView {
+    let revoke_session = Action::new(move |id: &i64| revoke_user_session(*id));
+    let revoke_others = Action::new(move |_: &()| revoke_other_user_sessions());
+    let sessions = Resource::new(
+        move || (revoke_session.version()(), revoke_others.version()()),
+        move |_| get_user_sessions(),
+    );
+    let i18n = use_i18n();
+
+    view! {
+        <div class="p-6 rounded-xl bg-gradient-to-br from-brand-950/10 to-black/20
+        border border-white/10 ">
+            <div class="flex items-center justify-between mb-2">
+                <h2 class="text-2xl font-bold text-brand-300">{t!(i18n, sessions)}</h2>
+                <button
+                    class="px-4 py-2 rounded-lg bg-brand-900/30 hover:bg-brand-800/40
+                    border border-white/10 hover:border-brand-300/30
+                    transition-all duration-300 text-gray-200 hover:text-brand-300"
+                    on:click=move |_| {
+                        let _ = revoke_others.dispatch(());
+                    }
+                >
+                    {t!(i18n, sessions_sign_out_others)}
+                </button>
+            </div>
+            <p class="text-gray-400 mb-6">{t!(i18n, sessions_desc)}</p>
+            <Suspense fallback=move || {
+                view! {
+                    <div class="flex items-center justify-center p-8">
+                        <Loading />
+                    </div>
+                }
+            }>
+                {move || {
+                    sessions
+                        .get()
+                        .map(|sessions| match sessions {
+                            Ok(sessions) => {
+                                Either::Left(
+                                    view! {
+                                        <div class="space-y-3">
+                                            {sessions
+                                                .into_iter()
+                                                .map(|session| {
+                                                    let id = session.id;
+                                                    let device = session
+                                                        .user_agent
+                                                        .unwrap_or_else(|| {
+                                                            t_string!(i18n, session_unknown_device).to_string()
+                                                        });
+                                                    view! {
+                                                        <div class="flex items-center justify-between gap-4 p-4
+                                                        rounded-lg bg-brand-950/30 border border-white/5">
+                                                            <div class="min-w-0">
+                                                                <div class="text-brand-200 truncate" title=device.clone()>
+                                                                    {device}
+                                                                </div>
+                                                                <div class="text-sm text-gray-400">
+                                                                    {t!(i18n, session_last_seen)} " "
+                                                                    <RelativeToNow timestamp=session
+                                                                        .last_seen_at
+                                                                        .naive_utc() />
+                                                                </div>
+                                                            </div>
+                                                            {if session.current {
+                                                                Either::Left(
+                                                                    view! {
+                                                                        <span class="shrink-0 text-sm text-brand-300">
+                                                                            {t!(i18n, session_this_device)}
+                                                                        </span>
+                                                                    },
+                                                                )
+                                                            } else {
+                                                                Either::Right(
+                                                                    view! {
+                                                                        <button
+                                                                            class="shrink-0 px-3 py-1 rounded-lg text-sm
+                                                                            text-gray-400 hover:text-red-400
+                                                                            hover:bg-red-900/30 border border-transparent
+                                                                            hover:border-red-800/30 transition-all duration-200"
+                                                                            on:click=move |_| {
+                                                                                let _ = revoke_session.dispatch(id);
+                                                                            }
+                                                                        >
+                                                                            {t!(i18n, session_sign_out)}
+                                                                        </button>
+                                                                    },
+                                                                )
+                                                            }}
+                                                        </div>
+                                                    }
+                                                })
+                                                .collect::<Vec<_>>()}
+                                        </div>
+                                    },
+                                )
+                            }
+                            Err(e) => {
+                                Either::Right(
+                                    view! {
+                                        <div class="p-4 rounded-lg bg-red-900/20 border border-red-800/30 text-red-400">
+                                            {t!(i18n, unable_to_fetch_sessions)} " " {e.to_string()}
+                                        </div>
+                                    },
+                                )
+                            }
+                        })
+                }}
+            </Suspense>
+        </div>
+    }
+    .into_any()
+}
+
+/// Expiry choices offered when creating an API token, in days.
+const API_TOKEN_EXPIRY_DAYS: [Option<u32>; 4] = [Some(30), Some(90), Some(365), None];
+
+#[component]
+fn ApiTokens() -> impl IntoView {
+    let i18n = use_i18n();
+    let (name, set_name) = signal(String::new());
+    let (scopes, set_scopes) = signal(Vec::<ApiTokenScope>::new());
+    let (expires_in_days, set_expires_in_days) = signal(API_TOKEN_EXPIRY_DAYS[1]);
+    let create_token = Action::new(move |request: &CreateApiToken| create_api_token(request.clone()));
+    let revoke_token = Action::new(move |id: &i64| revoke_api_token(*id));
+    let tokens = Resource::new(
+        move || (create_token.version()(), revoke_token.version()()),
+        move |_| get_api_tokens(),
+    );
+    let can_create = move || !name().trim().is_empty() && !scopes().is_empty();
+
+    view! {
+        <div class="p-6 rounded-xl bg-gradient-to-br from-brand-950/10 to-black/20
+        border border-white/10 ">
+            <h2 class="text-2xl font-bold text-brand-300 mb-2">{t!(i18n, api_tokens)}</h2>
+            <p class="text-gray-400 mb-6">{t!(i18n, api_tokens_desc)}</p>
+
+            <div class="flex flex-col gap-4 p-4 mb-6 rounded-lg bg-brand-950/30 border border-white/5">
+                <label class="flex flex-col gap-1">
+                    <span class="text-sm text-gray-400">{t!(i18n, api_token_name)}</span>
+                    <input
+                        class="input w-full"
+                        maxlength="64"
+                        prop:value=name
+                        on:input=move |ev| set_name(event_target_value(&ev))
+                    />
+                </label>
+                <div class="flex flex-col gap-1">
+                    <span class="text-sm text-gray-400">{t!(i18n, api_token_scopes)}</span>
+                    <div class="flex flex-wrap gap-4">
+                        {ApiTokenScope::ALL
+                            .into_iter()
+                            .map(|scope| {
+                                let id = format!("api-token-scope-{}", scope.as_str().replace(':', "-"));
+                                view! {
+                                    <label class="flex items-center gap-2" for=id.clone()>
+                                        <input
+                                            type="checkbox"
+                                            id=id
+                                            class="checkbox"
+                                            prop:checked=move || scopes().contains(&scope)
+                                            on:change=move |ev| {
+                                                let checked = event_target_checked(&ev);
+                                                set_scopes
+                                                    .update(|scopes| {
+                                                        scopes.retain(|s| *s != scope);
+                                                        if checked {
+                                                            scopes.push(scope);
+                                                        }
+                                                    });
+                                            }
+                                        />
+                                        <code>{scope.as_str()}</code>
+                                    </label>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </div>
+                </div>
+                <label class="flex flex-col gap-1">
+                    <span class="text-sm text-gray-400">{t!(i18n, api_token_expiry)}</span>
+                    <select
+                        class="input input-sm w-48"
+                        on:change=move |ev| {
+                            set_expires_in_days(event_target_value(&ev).parse::<u32>().ok());
+                        }
+                        prop:value=move || {
+                            expires_in_days().map(|days| days.to_string()).unwrap_or_default()
+                        }
+                    >
+                        {API_TOKEN_EXPIRY_DAYS
+                            .into_iter()
+                            .map(|days| {
+                                let label = match days {
+                                    Some(days) => {
+                                        t_string!(i18n, api_token_expiry_days, days = days).to_string()
+                                    }
+                                    None => t_string!(i18n, api_token_never_expires).to_string(),
+                                };
+                                view! {
+                                    <option value=days.map(|days| days.to_string()).unwrap_or_default()>
+                                        {label}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <div>
+                    <button
+                        class="px-4 py-2 rounded-lg bg-brand-900/30 hover:bg-brand-800/40
+                        border border-white/10 hover:border-brand-300/30
+                        transition-all duration-300 text-gray-200 hover:text-brand-300
+                        disabled:opacity-50 disabled:cursor-not-allowed"
+                        disabled=move || !can_create()
+                        on:click=move |_| {
+                            if can_create() {
+                                let _ = create_token
+                                    .dispatch(CreateApiToken {
+                                        name: name.get_untracked().trim().to_string(),
+                                        scopes: scopes.get_untracked(),
+                                        expires_in_days: expires_in_days.get_untracked(),
+                                    });
+                                set_name(String::new());
+                            }
+                        }
+                    >
+                        {t!(i18n, api_token_create)}
+                    </button>
+                </div>
+                {move || {
+                    create_token
+                        .value()
+                        .get()
+                        .map(|created| match created {
+                            Ok(created) => {
+                                Either::Left(
+                                    view! {
+                                        <div class="p-4 rounded-lg bg-green-900/20 border border-green-800/30">
+                                            <p class="text-green-300 mb-2">
+                                                {t!(i18n, api_token_created_copy)}
+                                            </p>
+                                            <code class="block break-all select-all text-brand-200">
+                                                {created.secret}
+                                            </code>
+                                        </div>
+                                    },
+                                )
+                            }
+                            Err(e) => {
+                                Either::Right(
+                                    view! {
+                                        <div class="p-4 rounded-lg bg-red-900/20 border border-red-800/30 text-red-400">
+                                            {e.to_string()}
+                                        </div>
+                                    },
+                                )
+                            }
+                        })
+                }}
+            </div>
+
+            <Suspense fallback=move || {
+                view! {
+                    <div class="flex items-center justify-center p-8">
+                        <Loading />
+                    </div>
+                }
+            }>
+                {move || {
+                    tokens
+                        .get()
+                        .map(|tokens| match tokens {
+                            Ok(tokens) if tokens.is_empty() => {
+                                EitherOf3::A(
+                                    view! {
+                                        <div class="text-center p-8 text-gray-400">
+                                            {t!(i18n, no_api_tokens)}
+                                        </div>
+                                    },
+                                )
+                            }
+                            Ok(tokens) => {
+                                EitherOf3::B(
+                                    view! {
+                                        <div class="space-y-3">
+                                            {tokens
+                                                .into_iter()
+                                                .map(|token| {
+                                                    let id = token.id;
+                                                    let scopes = token
+                                                        .scopes
+                                                        .iter()
+                                                        .map(|scope| scope.as_str())
+                                                        .collect::<Vec<_>>()
+                                                        .join(" ");
+                                                    let expires = token
+                                                        .expires_at
+                                                        .map(|expires| {
+                                                            t_string!(
+                                                                i18n, api_token_expires_on, date = expires.format("%Y-%m-%d").to_string()
+                                                            )
+                                                                .to_string()
+                                                        });
+                                                    view! {
+                                                        <div class="flex items-center justify-between gap-4 p-4
+                                                        rounded-lg bg-brand-950/30 border border-white/5">
+                                                            <div class="min-w-0">
+                                                                <div class="text-brand-200 truncate">{token.name}</div>
+                                                                <code class="text-sm text-gray-300">{scopes}</code>
+                                                                <div class="text-sm text-gray-400 flex flex-wrap gap-x-3">
+                                                                    {match token.last_used_at {
+                                                                        Some(last_used) => {
+                                                                            Either::Left(
+                                                                                view! {
+                                                                                    <span>
+                                                                                        {t!(i18n, api_token_last_used)} " "
+                                                                                        <RelativeToNow timestamp=last_used.naive_utc() />
+                                                                                    </span>
+                                                                                },
+                                                                            )
+                                                                        }
+                                                                        None => {
+                                                                            Either::Right(
+                                                                                view! { <span>{t!(i18n, api_token_never_used)}</span> },
+                                                                            )
+                                                                        }
+                                                                    }}
+                                                                    {expires.map(|expires| view! { <span>{expires}</span> })}
+                                                                </div>
+                                                            </div>
+                                                            <button
+                                                                class="shrink-0 px-3 py-1 rounded-lg text-sm
+                                                                text-gray-400 hover:text-red-400
+                                                                hover:bg-red-900/30 border border-transparent
+                                                                hover:border-red-800/30 transition-all duration-200"
+                                                                on:click=move |_| {
+                                                                    let _ = revoke_token.dispatch(id);
+                                                                }
+                                                            >
+                                                                {t!(i18n, api_token_revoke)}
+                                                            </button>
+                                                        </div>
+                                                    }
+                                                })
+                                                .collect::<Vec<_>>()}
+                                        </div>
+                                    },
+                                )
+                            }
+                            Err(e) => {
+                                EitherOf3::C(
+                                    view! {
+                                        <div class="p-4 rounded-lg bg-red-900/20 border border-red-800/30 text-red-400">
+                                            {t!(i18n, unable_to_fetch_api_tokens)} " " {e.to_string()}
+                                        </div>
+                                    },
+                                )
+                            }
+                        })
+                }}
+            </Suspense>
+        </div>
+    }
+    .into_any()
+}
+
 #[component]
 fn DeleteUser() -> impl IntoView {
     let (confirmed, set_confirmed) = signal(false);
@@ -465,6 +836,9 @@ pub fn Profile() -> impl IntoView {
                     </Suspense>
                 </div>
 
+                <Sessions />
+                <ApiTokens />
+
                 // Delete Account Section
                 <DeleteUser />
             </div>
@@ -6,8 +6,9 @@ use tokio_util::sync::CancellationToken;
 use tracing::error;
 use ultros_api_types::{
     user::OwnedRetainer,
-    websocket::{ListEventData, ListingEventData},
+    websocket::{ListEventData, ListingEventData, SaleEventData},
 };
+use ultros_clickhouse::ClickHouseClient;
 use ultros_db::{
     UltrosDb,
     entity::{alert, alert_retainer_undercut},
@@ -15,7 +16,9 @@ use ultros_db::{
 };
 
 use crate::event::{EventBus, EventType};
+use crate::repricing::Repricer;
 
+use super::digest;
 use super::list_update_alert_tracker::ListUpdateAlertListener;
 use super::price_alert_tracker::PriceAlertListener;
 use super::undercut_alert::{RetainerAlertListener, RetainerAlertTx};
@@ -28,9 +31,11 @@ pub(crate) struct AlertManager {
 }
 
 impl AlertManager {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn start_manager(
         ultros_db: UltrosDb,
         (retainers, listings): (EventBus<OwnedRetainer>, EventBus<ListingEventData>),
+        sales: EventBus<SaleEventData>,
         (mut alerts, mut undercuts): (
             EventBus<alert::Model>,
             EventBus<alert_retainer_undercut::Model>,
@@ -39,6 +44,8 @@ impl AlertManager {
         ctx: serenity_prelude::Context,
         token: CancellationToken,
         world_cache: Arc<WorldCache>,
+        ch: ClickHouseClient,
+        repricer: Repricer,
     ) {
         // start all alerts we know about from the db, then use the alert busses to monitor for new alerts being spawned
         let mut manager = AlertManager {
@@ -61,6 +68,7 @@ impl AlertManager {
                                     &ctx,
                                     listings.resubscribe(),
                                     retainers.resubscribe(),
+                                    &repricer,
                                 )
                                 .await;
                         }
@@ -72,16 +80,19 @@ impl AlertManager {
         match PriceAlertListener::start(
             ultros_db.clone(),
             listings.resubscribe(),
+            sales,
             alerts.resubscribe(),
             lists.resubscribe(),
             ctx.clone(),
             world_cache,
+            ch,
         )
         .await
         {
             Ok(listener) => manager.price_alerts = Some(listener),
             Err(e) => error!("failed to start price alert listener: {e}"),
         }
+        digest::spawn_digest_sender(ultros_db.clone(), ctx.clone(), token.clone());
         match ListUpdateAlertListener::start(
             ultros_db.clone(),
             lists.resubscribe(),
@@ -117,6 +128,7 @@ impl AlertManager {
                                                             &ctx,
                                                             listings.resubscribe(),
                                                             retainers.resubscribe(),
+                                                            &repricer,
                                                         )
                                                         .await;
                                                 }
@@ -143,6 +155,7 @@ impl AlertManager {
                                                 &ctx,
                                                 listings.resubscribe(),
                                                 retainers.resubscribe(),
+                                                &repricer,
                                             )
                                             .await;
                                     }
@@ -165,6 +178,7 @@ impl AlertManager {
         ctx: &serenity_prelude::Context,
         listings: EventBus<ListingEventData>,
         active_retainers: EventBus<OwnedRetainer>,
+        repricer: &Repricer,
     ) {
         let alert_retainer_undercut::Model {
             id,
@@ -179,6 +193,7 @@ impl AlertManager {
             listings,
             active_retainers,
             ctx.clone(),
+            repricer.clone(),
         )
         .await
         {
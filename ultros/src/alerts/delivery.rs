@@ -4,9 +4,12 @@ use poise::serenity_prelude::{
 };
 use serde::Deserialize;
 use std::sync::{Arc, OnceLock};
-use tracing::error;
+use tracing::{error, warn};
+use ultros_api_types::alert::{DeliverySchedule, WebhookHeader};
 use ultros_db::UltrosDb;
 
+use crate::alerts::webhook::{TemplateContext, WebhookRejected, send_generic_webhook};
+
 /// Process-wide handle to the running Discord client's `serenity::Context`.
 ///
 /// The bot owns the live context, but web handlers (`/test`, `/resend`) also need to send
@@ -73,6 +76,31 @@ pub(crate) enum EndpointConfig {
     Webhook { url: String },
     #[serde(rename = "WebPush")]
     WebPush { subscription_id: i32 },
+    #[serde(rename = "GenericWebhook")]
+    GenericWebhook {
+        url: String,
+        #[serde(default)]
+        template: Option<String>,
+        #[serde(default)]
+        headers: Vec<WebhookHeader>,
+        #[serde(default)]
+        secret: Option<String>,
+    },
+}
+
+/// Structured details about a fire, for endpoint methods that can use more
+/// than a title and body (today, generic webhook templates). Every field is
+/// optional: a list update has no price, a digest has no single item.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AlertFields {
+    pub(crate) alert_id: Option<i32>,
+    pub(crate) item_id: Option<i32>,
+    pub(crate) item_name: Option<String>,
+    pub(crate) world_id: Option<i32>,
+    pub(crate) world_name: Option<String>,
+    pub(crate) price: Option<i32>,
+    pub(crate) listing_id: Option<i64>,
+    pub(crate) retainer_name: Option<String>,
 }
 
 /// Parse a notification endpoint row's `(method, config)` pair into a typed [`EndpointConfig`].
@@ -106,26 +134,23 @@ pub(crate) fn parse_endpoint_config(
 /// (e.g. `/retainers/undercuts` for undercut alerts); use `/alerts` when no
 /// more specific destination applies. Other endpoint methods ignore it — their
 /// bodies already carry full links.
+///
+/// `fields` feeds generic webhook templates; everything else ignores it.
 pub(crate) async fn deliver_to_endpoint(
     endpoint: &ultros_db::entity::notification_endpoint::Model,
     title: &str,
     body: &str,
     click_url: &str,
+    fields: &AlertFields,
     db: &UltrosDb,
     ctx: &serenity_prelude::Context,
 ) -> Result<()> {
-    let parsed = parse_endpoint_config(&endpoint.method, &endpoint.config)?;
-    match parsed {
+    match parse_endpoint_config(&endpoint.method, &endpoint.config)? {
         EndpointConfig::DiscordChannel { channel_id } => {
             send_to_channel(channel_id, title, body, ctx).await
         }
         EndpointConfig::DiscordDm { user_id } => send_dm(user_id, title, body, ctx).await,
-        EndpointConfig::Webhook { url } => send_webhook(&url, title, body).await,
-        EndpointConfig::WebPush { subscription_id } => {
-            let cfg = get_web_push_config()
-                .ok_or_else(|| anyhow!("web push not configured on this deployment"))?;
-            send_webpush(subscription_id, title, body, click_url, db, cfg).await
-        }
+        parsed => deliver_parsed_non_discord(parsed, title, body, click_url, fields, db).await,
     }
 }
 
@@ -138,9 +163,23 @@ pub(crate) async fn deliver_non_discord_endpoint(
     title: &str,
     body: &str,
     click_url: &str,
+    fields: &AlertFields,
     db: &UltrosDb,
 ) -> Result<()> {
     let parsed = parse_endpoint_config(&endpoint.method, &endpoint.config)?;
+    deliver_parsed_non_discord(parsed, title, body, click_url, fields, db).await
+}
+
+/// The delivery paths shared by [`deliver_to_endpoint`] and
+/// [`deliver_non_discord_endpoint`]: everything that doesn't go through the bot.
+async fn deliver_parsed_non_discord(
+    parsed: EndpointConfig,
+    title: &str,
+    body: &str,
+    click_url: &str,
+    fields: &AlertFields,
+    db: &UltrosDb,
+) -> Result<()> {
     match parsed {
         EndpointConfig::DiscordChannel { .. } | EndpointConfig::DiscordDm { .. } => {
             Err(anyhow!("Discord endpoints require the bot to be connected"))
@@ -151,9 +190,45 @@ pub(crate) async fn deliver_non_discord_endpoint(
                 .ok_or_else(|| anyhow!("web push not configured on this deployment"))?;
             send_webpush(subscription_id, title, body, click_url, db, cfg).await
         }
+        EndpointConfig::GenericWebhook {
+            url,
+            template,
+            headers,
+            secret,
+        } => {
+            let message = TemplateContext {
+                title,
+                body,
+                click_url,
+                fields,
+                fired_at: chrono::Utc::now(),
+            };
+            send_generic_webhook(
+                &url,
+                template.as_deref(),
+                &headers,
+                secret.as_deref(),
+                &message,
+            )
+            .await
+        }
     }
 }
 
+/// The endpoint's delivery schedule. A row that fails to parse is treated as
+/// immediate: a broken schedule should never swallow alerts.
+pub(crate) fn endpoint_schedule(
+    endpoint: &ultros_db::entity::notification_endpoint::Model,
+) -> DeliverySchedule {
+    let Some(schedule) = &endpoint.schedule else {
+        return DeliverySchedule::Immediate;
+    };
+    serde_json::from_value(schedule.clone()).unwrap_or_else(|e| {
+        warn!(endpoint_id = endpoint.id, "bad delivery schedule: {e}");
+        DeliverySchedule::Immediate
+    })
+}
+
 /// Whether a Discord API rejection can ever succeed on a later retry.
 ///
 /// Discord answers a failed REST call with an HTTP status plus a numeric JSON
@@ -183,7 +258,8 @@ fn is_permanent_discord_failure(status: u16, discord_code: isize) -> bool {
 
 /// Pull a permanent-failure reason out of an error returned by
 /// [`deliver_to_endpoint`], if the underlying cause was Discord rejecting the
-/// destination for good.
+/// destination for good, or a generic webhook refusing a request with a
+/// status that retrying won't change.
 ///
 /// The delivery helpers surface serenity errors through `anyhow`, so walk the
 /// source chain rather than matching only the top-level error.
@@ -191,6 +267,9 @@ pub(crate) fn permanent_failure_reason(err: &anyhow::Error) -> Option<String> {
     use poise::serenity_prelude::HttpError;
 
     for cause in err.chain() {
+        if let Some(rejected) = cause.downcast_ref::<WebhookRejected>() {
+            return Some(rejected.to_string());
+        }
         let Some(serenity_prelude::Error::Http(HttpError::UnsuccessfulRequest(resp))) =
             cause.downcast_ref::<serenity_prelude::Error>()
         else {
@@ -223,12 +302,15 @@ pub(crate) enum DispatchOutcome {
 ///
 /// Endpoints that Discord rejects permanently are disabled as a side effect, so
 /// the next fire skips them entirely. A successful delivery clears any
-/// previously recorded failure.
+/// previously recorded failure. Endpoints on a digest schedule queue the fire
+/// for their next summary instead (see `alerts::digest`); a queued fire counts
+/// as delivered.
 pub(crate) async fn dispatch_alert_detailed(
     alert_id: i32,
     title: &str,
     body: &str,
     click_url: &str,
+    fields: &AlertFields,
     db: &UltrosDb,
     ctx: &serenity_prelude::Context,
 ) -> DispatchOutcome {
@@ -250,9 +332,31 @@ pub(crate) async fn dispatch_alert_detailed(
     let mut permanent_reason: Option<String> = None;
     let mut any_ok = false;
     let mut any_transient = false;
+    let now = chrono::Utc::now();
+    let fields = AlertFields {
+        alert_id: Some(alert_id),
+        ..fields.clone()
+    };
 
     for endpoint in endpoints {
-        match deliver_to_endpoint(&endpoint, title, body, click_url, db, ctx).await {
+        if endpoint_schedule(&endpoint).holds_at(now) {
+            match db
+                .queue_digest_entry(endpoint.id, alert_id, title, click_url)
+                .await
+            {
+                Ok(()) => any_ok = true,
+                Err(e) => {
+                    error!(
+                        "failed to queue digest entry for alert {alert_id} on endpoint {}: {e}",
+                        endpoint.id
+                    );
+                    any_transient = true;
+                    last_err = Some(e);
+                }
+            }
+            continue;
+        }
+        match deliver_to_endpoint(&endpoint, title, body, click_url, &fields, db, ctx).await {
             Ok(()) => {
                 any_ok = true;
                 // Only touch the DB when there is actually stale failure state
@@ -313,10 +417,11 @@ pub(crate) async fn dispatch_alert(
     title: &str,
     body: &str,
     click_url: &str,
+    fields: &AlertFields,
     db: &UltrosDb,
     ctx: &serenity_prelude::Context,
 ) -> Result<()> {
-    match dispatch_alert_detailed(alert_id, title, body, click_url, db, ctx).await {
+    match dispatch_alert_detailed(alert_id, title, body, click_url, fields, db, ctx).await {
         DispatchOutcome::Delivered => Ok(()),
         DispatchOutcome::TransientFailure(e) => Err(e),
         DispatchOutcome::PermanentFailure(reason) => Err(anyhow!("{reason}")),
@@ -554,6 +659,20 @@ mod tests {
         assert!(permanent_failure_reason(&nested).is_none());
     }
 
+    #[test]
+    fn rejected_generic_webhooks_are_permanent() {
+        let err: anyhow::Error = WebhookRejected {
+            status: 410,
+            body: "gone".into(),
+        }
+        .into();
+        let nested = err.context("delivering to endpoint 3");
+        assert_eq!(
+            permanent_failure_reason(&nested).as_deref(),
+            Some("webhook returned 410: gone")
+        );
+    }
+
     #[test]
     fn push_payload_carries_the_callers_click_url() {
         let payload = build_push_payload("Undercut Alert", "body", "/retainers/undercuts").unwrap();
@@ -601,6 +720,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parses_generic_webhook_with_optional_fields_missing() {
+        let cfg = json!({ "url": "https://example.com/hook" });
+        let parsed = parse_endpoint_config("GenericWebhook", &cfg).unwrap();
+        assert_eq!(
+            parsed,
+            EndpointConfig::GenericWebhook {
+                url: "https://example.com/hook".to_string(),
+                template: None,
+                headers: vec![],
+                secret: None,
+            }
+        );
+    }
+
     #[test]
     fn parse_endpoint_ignores_method_field_already_present_in_config() {
         // The splicing overwrites any existing "method" key in the config object —
@@ -0,0 +1,157 @@
+//! Scheduled delivery of held alert fires.
+//!
+//! Endpoints on a digest or quiet-hours schedule don't receive fires as they
+//! happen: `dispatch_alert_detailed` queues them in `alert_digest_entry`
+//! instead. This task wakes once a minute, finds endpoints whose summary is
+//! due, and sends each one a single message covering everything queued.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, warn};
+use ultros_db::{
+    UltrosDb,
+    entity::{alert_digest_entry, notification_endpoint},
+};
+
+use crate::alerts::delivery::{
+    AlertFields, deliver_to_endpoint, endpoint_schedule, permanent_failure_reason,
+};
+
+/// How often queued fires are checked. Schedules are expressed in whole
+/// minutes and hours, so finer resolution buys nothing.
+const DIGEST_TICK: Duration = Duration::from_secs(60);
+
+/// Most fires listed individually in one summary. A Discord embed
+/// description caps out at 4096 characters; the rest are counted instead.
+const MAX_DIGEST_LINES: usize = 25;
+
+/// Sends due digests until `token` is cancelled.
+pub(crate) fn spawn_digest_sender(
+    db: UltrosDb,
+    ctx: serenity_prelude::Context,
+    token: CancellationToken,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(DIGEST_TICK);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = interval.tick() => send_due_digests(&db, &ctx, Utc::now()).await,
+            }
+        }
+    });
+}
+
+async fn send_due_digests(db: &UltrosDb, ctx: &serenity_prelude::Context, now: DateTime<Utc>) {
+    let pending = match db.get_pending_digests().await {
+        Ok(pending) => pending,
+        Err(e) => {
+            error!("failed to load pending alert digests: {e}");
+            return;
+        }
+    };
+    for (endpoint, entries) in pending {
+        let Some(oldest) = entries.first() else {
+            continue;
+        };
+        let since = endpoint.digest_sent_at.unwrap_or(oldest.queued_at);
+        if !endpoint_schedule(&endpoint).summary_due(since, now) {
+            continue;
+        }
+        send_digest(db, ctx, &endpoint, &entries).await;
+    }
+}
+
+async fn send_digest(
+    db: &UltrosDb,
+    ctx: &serenity_prelude::Context,
+    endpoint: &notification_endpoint::Model,
+    entries: &[alert_digest_entry::Model],
+) {
+    let (title, body) = format_digest_message(entries);
+    // A summary spans many items, so there are no per-fire details to template.
+    let fields = AlertFields::default();
+    match deliver_to_endpoint(endpoint, &title, &body, "/alerts", &fields, db, ctx).await {
+        Ok(()) => {
+            let ids: Vec<i64> = entries.iter().map(|e| e.id).collect();
+            if let Err(e) = db.finish_digest(endpoint.id, &ids).await {
+                error!("failed to clear digest for endpoint {}: {e}", endpoint.id);
+            }
+        }
+        Err(e) => match permanent_failure_reason(&e) {
+            Some(reason) => {
+                warn!("disabling endpoint {} for digest: {reason}", endpoint.id);
+                if let Err(e) = db
+                    .disable_endpoint_for_delivery_failure(endpoint.id, &reason)
+                    .await
+                {
+                    error!("failed to disable endpoint {}: {e}", endpoint.id);
+                }
+            }
+            // Left queued; the next tick retries.
+            None => warn!("digest delivery failed for endpoint {}: {e}", endpoint.id),
+        },
+    }
+}
+
+/// Build the title + body of one summary covering `entries`, oldest first.
+/// Pure.
+pub(crate) fn format_digest_message(entries: &[alert_digest_entry::Model]) -> (String, String) {
+    let count = entries.len();
+    let title = if count == 1 {
+        "🗞️ 1 alert fired".to_string()
+    } else {
+        format!("🗞️ {count} alerts fired")
+    };
+    let mut body = String::new();
+    for entry in entries.iter().take(MAX_DIGEST_LINES) {
+        body.push_str("• ");
+        body.push_str(&entry.title);
+        body.push('\n');
+    }
+    if count > MAX_DIGEST_LINES {
+        body.push_str(&format!("…and {} more\n", count - MAX_DIGEST_LINES));
+    }
+    body.push_str("https://ultros.app/alerts");
+    (title, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: i64, title: &str) -> alert_digest_entry::Model {
+        alert_digest_entry::Model {
+            id,
+            endpoint_id: 1,
+            alert_id: 1,
+            title: title.to_string(),
+            click_url: "/item/1".to_string(),
+            queued_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn digest_lists_each_fire() {
+        let (title, body) = format_digest_message(&[
+            entry(1, "🎯 Cordial dropped to 500 gil"),
+            entry(2, "🎯 Potion dropped to 20 gil"),
+        ]);
+        assert_eq!(title, "🗞️ 2 alerts fired");
+        assert!(body.starts_with("• 🎯 Cordial dropped to 500 gil\n• 🎯 Potion"));
+        assert!(body.ends_with("ultros.app/alerts"));
+    }
+
+    #[test]
+    fn digest_counts_fires_past_the_line_limit() {
+        let entries: Vec<_> = (0..40).map(|i| entry(i, "fire")).collect();
+        let (title, body) = format_digest_message(&entries);
+        assert_eq!(title, "🗞️ 40 alerts fired");
+        assert_eq!(body.matches("• fire").count(), MAX_DIGEST_LINES);
+        assert!(body.contains("…and 15 more"));
+    }
+}
@@ -9,7 +9,10 @@ use ultros_api_types::websocket::ListEventData;
 use ultros_db::{UltrosDb, entity::alert};
 
 use crate::{
-    alerts::{delivery::dispatch_alert, price_alert_tracker::is_off_cooldown_at},
+    alerts::{
+        delivery::{AlertFields, dispatch_alert},
+        price_alert_tracker::is_off_cooldown_at,
+    },
     event::{EventBus, EventType},
 };
 
@@ -170,8 +173,12 @@ async fn handle_list_event(
             rule.list_id
         );
         let click_url = format!("/list/{}", rule.list_id);
+        let fields = AlertFields {
+            item_id,
+            ..Default::default()
+        };
         let delivery_result =
-            dispatch_alert(rule.alert_id, &title, &body, &click_url, db, ctx).await;
+            dispatch_alert(rule.alert_id, &title, &body, &click_url, &fields, db, ctx).await;
         let delivered = delivery_result.is_ok();
         let delivery_error = delivery_result.err().map(|e| e.to_string());
 
@@ -1,6 +1,8 @@
 pub mod alert_manager;
 pub(crate) mod delivery;
+pub(crate) mod digest;
 pub(crate) mod list_update_alert_tracker;
 pub(crate) mod price_alert_tracker;
 #[allow(unused)]
 pub mod undercut_alert;
+pub(crate) mod webhook;
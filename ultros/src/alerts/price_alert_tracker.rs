@@ -1,25 +1,32 @@
 use std::{
-    collections::{HashMap, HashSet},
-    sync::Arc,
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::{Arc, Weak},
+    time::Duration,
 };
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use itertools::Itertools;
 use poise::serenity_prelude;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify, mpsc};
 use tracing::{error, info, instrument, warn};
 use ultros_api_types::{
-    ActiveListing,
-    websocket::{ListEventData, ListingEventData},
+    ActiveListing, Retainer, SaleHistory,
+    alert_rule::{AlertRule, MarketMoveRule, MarketStat, MoveReference, MoveSource, RuleInputs},
+    websocket::{ListEventData, ListingEventData, SaleEventData},
     world_helper::AnySelector as ApiAnySelector,
 };
+use ultros_clickhouse::{ClickHouseClient, queries::DeepScan};
 use ultros_db::{
     UltrosDb,
-    entity::{alert, alert_item_threshold, alert_list_threshold},
+    entity::{
+        alert, alert_expression, alert_item_threshold, alert_list_threshold, alert_market_move,
+    },
+    listings::ListingSummary,
     world_data::world_cache::{AnySelector as DbAnySelector, WorldCache},
 };
 
-use crate::alerts::delivery::dispatch_alert;
+use crate::alerts::delivery::{AlertFields, dispatch_alert};
 use crate::event::{EventBus, EventType};
 
 /// True when an alert with the given `last_fired_at` is free to fire again given `cooldown_seconds`
@@ -103,6 +110,197 @@ pub(crate) fn list_rule_matches_listing(
     is_off_cooldown_at(rule.last_fired_at, rule.cooldown_seconds, now)
 }
 
+/// How often rollup stats are refetched for expression rules. The shortest
+/// rollup window refreshes every 15 minutes, so five minutes keeps rules close
+/// to current without a ClickHouse query per listing.
+const EXPRESSION_STATS_TTL: Duration = Duration::from_secs(300);
+
+/// Pending cheapest-listing checks. Bounded so a slow database sheds checks
+/// rather than letting the backlog grow; the next listing event on the item
+/// checks it again.
+const CHEAPEST_QUEUE: usize = 1024;
+
+/// Returns true if `listing` is in scope for an expression `rule` and the rule
+/// is off cooldown at `now`. The rule itself is evaluated separately, once its
+/// market statistics are at hand. Market-move rules watch their own source
+/// rather than each listing, so they never apply here.
+pub(crate) fn expression_rule_applies_to_listing(
+    rule: &ExpressionActiveRule,
+    listing: &ActiveListing,
+    now: DateTime<Utc>,
+) -> bool {
+    rule.market_move.is_none()
+        && rule.world_id_set.contains(&listing.world_id)
+        && is_off_cooldown_at(rule.last_fired_at, rule.cooldown_seconds, now)
+}
+
+/// Returns true if a change from `source` on `world_id` is in scope for a
+/// market-move `rule` and the rule is off cooldown at `now`.
+pub(crate) fn market_move_rule_applies(
+    rule: &ExpressionActiveRule,
+    source: MoveSource,
+    world_id: i32,
+    now: DateTime<Utc>,
+) -> bool {
+    rule.market_move.is_some_and(|m| m.source == source)
+        && rule.world_id_set.contains(&world_id)
+        && is_off_cooldown_at(rule.last_fired_at, rule.cooldown_seconds, now)
+}
+
+/// The price a rule is evaluated against: a new listing, the cheapest listing
+/// across the rule's worlds, or a sale.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RuleObservation {
+    pub(crate) price_per_unit: i32,
+    pub(crate) quantity: i32,
+    pub(crate) hq: bool,
+}
+
+impl From<&ActiveListing> for RuleObservation {
+    fn from(listing: &ActiveListing) -> Self {
+        Self {
+            price_per_unit: listing.price_per_unit,
+            quantity: listing.quantity,
+            hq: listing.hq,
+        }
+    }
+}
+
+impl From<&SaleHistory> for RuleObservation {
+    fn from(sale: &SaleHistory) -> Self {
+        Self {
+            price_per_unit: sale.price_per_item,
+            quantity: sale.quantity,
+            hq: sale.hq,
+        }
+    }
+}
+
+/// One observation plus the rollup rows for its item across the rule's worlds.
+struct ObservedRuleInputs<'a> {
+    observed: RuleObservation,
+    scans: &'a [DeepScan],
+}
+
+impl RuleInputs for ObservedRuleInputs<'_> {
+    fn listing_price(&self) -> i32 {
+        self.observed.price_per_unit
+    }
+
+    fn listing_quantity(&self) -> i32 {
+        self.observed.quantity
+    }
+
+    fn listing_hq(&self) -> bool {
+        self.observed.hq
+    }
+
+    fn stat(&self, stat: MarketStat, window_days: u16) -> Option<f64> {
+        ultros_clickhouse::queries::rule_stat(self.scans, self.observed.hq, window_days, stat)
+    }
+}
+
+/// Build the Discord embed title + body for an expression-alert firing. Pure.
+pub(crate) fn format_expression_alert_message(
+    item_name: &str,
+    item_id: i32,
+    matched_price: i32,
+    hq: bool,
+) -> (String, String) {
+    let quality = if hq { "HQ" } else { "NQ" };
+    let title = format!("📈 {item_name} ({quality}) matched your rule at {matched_price} gil");
+    let body = format!("https://ultros.app/item/{item_id}");
+    (title, body)
+}
+
+/// Build the Discord embed title + body for a market-move alert firing.
+/// `reference` is the statistic the price moved away from, when known. Pure.
+pub(crate) fn format_market_move_alert_message(
+    item_name: &str,
+    item_id: i32,
+    rule: &MarketMoveRule,
+    observed: RuleObservation,
+    reference: Option<(MarketStat, f64)>,
+) -> (String, String) {
+    let price = observed.price_per_unit;
+    let fell = reference.is_some_and(|(_, value)| (price as f64) < value);
+    let (icon, verb) = match (rule.source, fell) {
+        (MoveSource::CheapestListing, true) => ("📉", "dropped to"),
+        (MoveSource::CheapestListing, false) => ("📈", "spiked to"),
+        (MoveSource::LatestSale, true) => ("📉", "sold low at"),
+        (MoveSource::LatestSale, false) => ("📈", "sold high at"),
+    };
+    let quality = if observed.hq { " (HQ)" } else { "" };
+    let title = format!("{icon} {item_name}{quality} {verb} {price} gil");
+    let link = format!("https://ultros.app/item/{item_id}");
+    let Some((stat, value)) = reference.filter(|(_, value)| *value > 0.0) else {
+        return (title, link);
+    };
+    let stat_name = match stat {
+        MarketStat::P25 => "25th percentile",
+        MarketStat::P75 => "75th percentile",
+        _ => "median",
+    };
+    let percent = ((price as f64 - value) / value * 100.0).abs().round();
+    let direction = if fell { "below" } else { "above" };
+    let body = format!(
+        "{percent}% {direction} the {}-day {stat_name} of {} gil\n{link}",
+        rule.window_days,
+        value.round()
+    );
+    (title, body)
+}
+
+/// The statistic a market-move rule measured `observed` against: the median,
+/// or whichever edge of the band the price crossed.
+fn market_move_reference(
+    rule: &MarketMoveRule,
+    observed: RuleObservation,
+    scans: &[DeepScan],
+) -> Option<(MarketStat, f64)> {
+    let stat = |stat| {
+        ultros_clickhouse::queries::rule_stat(scans, observed.hq, rule.window_days, stat)
+            .map(|value| (stat, value))
+    };
+    match rule.reference {
+        MoveReference::Median => stat(MarketStat::Median),
+        MoveReference::Band => {
+            let low = stat(MarketStat::P25)?;
+            if (observed.price_per_unit as f64) < low.1 {
+                Some(low)
+            } else {
+                stat(MarketStat::P75)
+            }
+        }
+    }
+}
+
+/// Template details for something observed at `price` on `world_id`. The item
+/// is filled in when the alert is delivered.
+fn observed_fields(world_id: i32, price: i32, world_cache: &WorldCache) -> AlertFields {
+    AlertFields {
+        world_id: Some(world_id),
+        world_name: world_cache
+            .lookup_selector(&DbAnySelector::World(world_id))
+            .ok()
+            .map(|w| w.get_name().to_string()),
+        price: Some(price),
+        ..Default::default()
+    }
+}
+
+fn listing_fields(
+    listing: &ActiveListing,
+    retainer: &Retainer,
+    world_cache: &WorldCache,
+) -> AlertFields {
+    AlertFields {
+        listing_id: Some(listing.id.into()),
+        retainer_name: Some(retainer.name.clone()),
+        ..observed_fields(listing.world_id, listing.price_per_unit, world_cache)
+    }
+}
+
 /// Look up an item's name in the embedded xiv-gen data, falling back to `"Item {id}"` if missing.
 pub(crate) fn resolve_item_name(item_id: i32) -> String {
     xiv_gen_db::data()
@@ -140,6 +338,25 @@ pub(crate) struct ListActiveRule {
     pub(crate) list_name: String,
 }
 
+/// An enabled expression or market-move alert, with its world selector
+/// resolved and the rollup windows its rule reads precomputed. Market-move
+/// alerts are compiled into an [`AlertRule`] and keep the original for
+/// deciding what to watch and for the message.
+#[derive(Debug, Clone)]
+pub(crate) struct ExpressionActiveRule {
+    pub(crate) alert_id: i32,
+    pub(crate) item_id: i32,
+    pub(crate) rule: AlertRule,
+    pub(crate) market_move: Option<MarketMoveRule>,
+    /// Only set for market-move alerts; expression rules carry their own
+    /// `hq` guard.
+    pub(crate) hq_only: bool,
+    pub(crate) cooldown_seconds: i32,
+    pub(crate) last_fired_at: Option<DateTime<Utc>>,
+    pub(crate) world_id_set: HashSet<i32>,
+    pub(crate) windows: BTreeSet<u16>,
+}
+
 #[derive(Debug, Default)]
 struct TrackerState {
     by_item: HashMap<i32, Vec<ActiveRule>>,
@@ -148,6 +365,13 @@ struct TrackerState {
     /// list_item) so the incoming-listing path doesn't have to do any DB
     /// queries.
     by_item_list_rules: HashMap<i32, Vec<ListActiveRule>>,
+    by_item_expression_rules: HashMap<i32, Vec<ExpressionActiveRule>>,
+    /// Rollup rows per expression alert, kept filled by
+    /// [`prefetch_expression_stats`]. Cleared on every refresh, since a
+    /// changed rule may read other windows.
+    expression_stats: HashMap<i32, Arc<Vec<DeepScan>>>,
+    /// Wakes the prefetcher when the rules change.
+    rules_changed: Arc<Notify>,
 }
 
 impl TrackerState {
@@ -161,35 +385,7 @@ impl TrackerState {
             if !a.enabled {
                 continue;
             }
-            // Deserialize and resolve the world_selector to a flat set of world IDs.
-            let world_id_set: HashSet<i32> =
-                match serde_json::from_value::<ApiAnySelector>(t.world_selector.clone()) {
-                    Ok(api_selector) => {
-                        let selector: ultros_db::world_data::world_cache::AnySelector =
-                            api_selector.into();
-                        match world_cache.lookup_selector(&selector) {
-                            Ok(result) => world_cache
-                                .get_all_worlds_in(&result)
-                                .unwrap_or_default()
-                                .into_iter()
-                                .collect(),
-                            Err(e) => {
-                                warn!(
-                                    alert_id = a.id,
-                                    "could not resolve world_selector for alert: {e}"
-                                );
-                                HashSet::new()
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        warn!(
-                            alert_id = a.id,
-                            "could not deserialize world_selector for alert: {e}"
-                        );
-                        HashSet::new()
-                    }
-                };
+            let world_id_set = resolve_world_selector(a.id, &t.world_selector, world_cache);
             self.by_item.entry(t.item_id).or_default().push(ActiveRule {
                 alert_id: a.id,
                 item_id: t.item_id,
@@ -202,6 +398,75 @@ impl TrackerState {
         }
     }
 
+    fn refresh_expression_rules_from(
+        &mut self,
+        alerts: &[(alert::Model, alert_expression::Model)],
+        market_moves: &[(alert::Model, alert_market_move::Model)],
+        world_cache: &WorldCache,
+    ) {
+        self.by_item_expression_rules.clear();
+        self.expression_stats.clear();
+        self.rules_changed.notify_one();
+        for (a, t) in market_moves {
+            if !a.enabled {
+                continue;
+            }
+            let market_move = match serde_json::from_value::<MarketMoveRule>(t.rule.clone()) {
+                Ok(rule) => rule,
+                Err(e) => {
+                    warn!(
+                        alert_id = a.id,
+                        "could not deserialize market move rule: {e}"
+                    );
+                    continue;
+                }
+            };
+            let rule = market_move.to_rule(t.hq_only);
+            let world_id_set = resolve_world_selector(a.id, &t.world_selector, world_cache);
+            self.by_item_expression_rules
+                .entry(t.item_id)
+                .or_default()
+                .push(ExpressionActiveRule {
+                    alert_id: a.id,
+                    item_id: t.item_id,
+                    windows: rule.stat_windows(),
+                    rule,
+                    market_move: Some(market_move),
+                    hq_only: t.hq_only,
+                    cooldown_seconds: a.cooldown_seconds,
+                    last_fired_at: a.last_fired_at.map(|dt| dt.with_timezone(&Utc)),
+                    world_id_set,
+                });
+        }
+        for (a, t) in alerts {
+            if !a.enabled {
+                continue;
+            }
+            let rule = match serde_json::from_value::<AlertRule>(t.rule.clone()) {
+                Ok(rule) => rule,
+                Err(e) => {
+                    warn!(alert_id = a.id, "could not deserialize alert rule: {e}");
+                    continue;
+                }
+            };
+            let world_id_set = resolve_world_selector(a.id, &t.world_selector, world_cache);
+            self.by_item_expression_rules
+                .entry(t.item_id)
+                .or_default()
+                .push(ExpressionActiveRule {
+                    alert_id: a.id,
+                    item_id: t.item_id,
+                    windows: rule.stat_windows(),
+                    rule,
+                    market_move: None,
+                    hq_only: false,
+                    cooldown_seconds: a.cooldown_seconds,
+                    last_fired_at: a.last_fired_at.map(|dt| dt.with_timezone(&Utc)),
+                    world_id_set,
+                });
+        }
+    }
+
     /// Pre-compute the list-threshold index. One DB roundtrip per enabled
     /// (alert, list) pair to fetch the list row and its priced items. Cost:
     /// O(active list-alerts) at refresh; O(1) at dispatch.
@@ -286,6 +551,38 @@ impl TrackerState {
     }
 }
 
+/// Deserialize a stored world selector and resolve it to a flat set of world
+/// IDs. Failures are logged and yield an empty set, so the rule never fires.
+fn resolve_world_selector(
+    alert_id: i32,
+    world_selector: &serde_json::Value,
+    world_cache: &WorldCache,
+) -> HashSet<i32> {
+    match serde_json::from_value::<ApiAnySelector>(world_selector.clone()) {
+        Ok(api_selector) => {
+            let selector: DbAnySelector = api_selector.into();
+            match world_cache.lookup_selector(&selector) {
+                Ok(result) => world_cache
+                    .get_all_worlds_in(&result)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect(),
+                Err(e) => {
+                    warn!(alert_id, "could not resolve world_selector for alert: {e}");
+                    HashSet::new()
+                }
+            }
+        }
+        Err(e) => {
+            warn!(
+                alert_id,
+                "could not deserialize world_selector for alert: {e}"
+            );
+            HashSet::new()
+        }
+    }
+}
+
 pub(crate) struct PriceAlertListener {
     /// Held to keep the channel sender alive — when `PriceAlertListener` is
     /// dropped, the corresponding `stop_rx.recv()` in the spawned task returns
@@ -296,14 +593,26 @@ pub(crate) struct PriceAlertListener {
 }
 
 impl PriceAlertListener {
-    #[instrument(skip(ultros_db, listings, alert_events, list_events, ctx, world_cache))]
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(skip(
+        ultros_db,
+        listings,
+        sales,
+        alert_events,
+        list_events,
+        ctx,
+        world_cache,
+        ch
+    ))]
     pub(crate) async fn start(
         ultros_db: UltrosDb,
         mut listings: EventBus<ListingEventData>,
+        mut sales: EventBus<SaleEventData>,
         mut alert_events: EventBus<alert::Model>,
         mut list_events: EventBus<ListEventData>,
         ctx: serenity_prelude::Context,
         world_cache: Arc<WorldCache>,
+        ch: ClickHouseClient,
     ) -> Result<Self> {
         let state = Arc::new(Mutex::new(TrackerState::default()));
         let (initial, initial_list) =
@@ -316,6 +625,22 @@ impl PriceAlertListener {
 
         let (stop_tx, mut stop_rx) = tokio::sync::mpsc::channel::<()>(1);
 
+        let rules_changed = state.lock().await.rules_changed.clone();
+        tokio::spawn(prefetch_expression_stats(
+            Arc::downgrade(&state),
+            rules_changed,
+            ch,
+        ));
+
+        let (cheapest_tx, cheapest_rx) = mpsc::channel(CHEAPEST_QUEUE);
+        tokio::spawn(recheck_cheapest(
+            cheapest_rx,
+            state.clone(),
+            ultros_db.clone(),
+            ctx.clone(),
+            world_cache.clone(),
+        ));
+
         let state_for_loop = state.clone();
         let db_for_loop = ultros_db.clone();
         let world_cache_for_loop = world_cache.clone();
@@ -358,8 +683,13 @@ impl PriceAlertListener {
                     msg = listings.recv() => {
                         match msg {
                             Ok(event) => {
-                                if let EventType::Add(added) = event {
-                                    handle_added(&added, &state_for_loop, &db_for_loop, &ctx).await;
+                                if let EventType::Add(added) = &event {
+                                    handle_added(added, &state_for_loop, &db_for_loop, &ctx, &world_cache_for_loop).await;
+                                }
+                                // Removals can raise the cheapest price as
+                                // much as additions can lower it.
+                                if matches!(event, EventType::Add(_) | EventType::Remove(_)) {
+                                    queue_cheapest_check(event.as_ref(), &state_for_loop, &cheapest_tx).await;
                                 }
                             }
                             Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
@@ -368,6 +698,18 @@ impl PriceAlertListener {
                             Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
                         }
                     }
+                    msg = sales.recv() => {
+                        match msg {
+                            Ok(EventType::Add(added)) => {
+                                handle_sales(&added, &state_for_loop, &db_for_loop, &ctx, &world_cache_for_loop).await;
+                            }
+                            Ok(_) => {}
+                            Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                                error!("price-alert tracker lagged, dropped {n} sale events");
+                            }
+                            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                        }
+                    }
                 }
             }
         });
@@ -386,9 +728,12 @@ async fn refresh_state_from_db(
 )> {
     let threshold_alerts = db.get_all_active_threshold_alerts().await?;
     let list_threshold_alerts = db.get_all_active_list_threshold_alerts().await?;
+    let expression_alerts = db.get_all_active_expression_alerts().await?;
+    let market_move_alerts = db.get_all_active_market_move_alerts().await?;
     {
         let mut guard = state.lock().await;
         guard.refresh_from(&threshold_alerts, world_cache);
+        guard.refresh_expression_rules_from(&expression_alerts, &market_move_alerts, world_cache);
         guard
             .refresh_list_rules_from(&list_threshold_alerts, db, world_cache)
             .await;
@@ -401,21 +746,28 @@ async fn handle_added(
     state: &Arc<Mutex<TrackerState>>,
     db: &UltrosDb,
     ctx: &serenity_prelude::Context,
+    world_cache: &WorldCache,
 ) {
     let now = Utc::now();
-    let mut to_fire: Vec<(ActiveRule, i32)> = vec![];
-    let mut to_fire_list: Vec<(ListActiveRule, i32)> = vec![];
+    let mut to_fire: Vec<(ActiveRule, i32, AlertFields)> = vec![];
+    let mut to_fire_list: Vec<(ListActiveRule, i32, AlertFields)> = vec![];
+    let mut expression_candidates: Vec<(ExpressionActiveRule, RuleObservation, AlertFields)> =
+        vec![];
 
     {
         let mut guard = state.lock().await;
-        for (listing, _retainer) in &added.listings {
+        for (listing, retainer) in &added.listings {
             if let Some(rules) = guard.by_item.get_mut(&listing.item_id) {
                 for rule in rules.iter_mut() {
                     if !rule_matches_listing(rule, listing, now) {
                         continue;
                     }
                     rule.last_fired_at = Some(now);
-                    to_fire.push((rule.clone(), listing.price_per_unit));
+                    to_fire.push((
+                        rule.clone(),
+                        listing.price_per_unit,
+                        listing_fields(listing, retainer, world_cache),
+                    ));
                 }
             }
             if let Some(list_rules) = guard.by_item_list_rules.get_mut(&listing.item_id) {
@@ -434,7 +786,11 @@ async fn handle_added(
                         // Already firing this alert for this listing; skip.
                         continue;
                     }
-                    to_fire_list.push((rule.clone(), listing.price_per_unit));
+                    to_fire_list.push((
+                        rule.clone(),
+                        listing.price_per_unit,
+                        listing_fields(listing, retainer, world_cache),
+                    ));
                 }
                 // Apply the cooldown update to every row sharing an alert_id
                 // we just fired, regardless of which row triggered.
@@ -444,10 +800,21 @@ async fn handle_added(
                     }
                 }
             }
+            if let Some(rules) = guard.by_item_expression_rules.get(&listing.item_id) {
+                for rule in rules {
+                    if expression_rule_applies_to_listing(rule, listing, now) {
+                        expression_candidates.push((
+                            rule.clone(),
+                            listing.into(),
+                            listing_fields(listing, retainer, world_cache),
+                        ));
+                    }
+                }
+            }
         }
     }
 
-    for (rule, matched_price) in to_fire {
+    for (rule, matched_price, fields) in to_fire {
         let item_name = resolve_item_name(rule.item_id);
         let (title, body) = format_threshold_alert_message(
             &item_name,
@@ -457,8 +824,13 @@ async fn handle_added(
         );
 
         let click_url = format!("/item/{}", rule.item_id);
+        let fields = AlertFields {
+            item_id: Some(rule.item_id),
+            item_name: Some(item_name),
+            ..fields
+        };
         let delivery_result =
-            dispatch_alert(rule.alert_id, &title, &body, &click_url, db, ctx).await;
+            dispatch_alert(rule.alert_id, &title, &body, &click_url, &fields, db, ctx).await;
         let delivered = delivery_result.is_ok();
         let delivery_error = delivery_result.err().map(|e| e.to_string());
 
@@ -486,7 +858,7 @@ async fn handle_added(
         }
     }
 
-    for (rule, matched_price) in to_fire_list {
+    for (rule, matched_price, fields) in to_fire_list {
         let item_name = resolve_item_name(rule.item_id);
         let (title, body) = format_list_threshold_alert_message(
             &rule.list_name,
@@ -498,8 +870,13 @@ async fn handle_added(
         );
 
         let click_url = format!("/list/{}", rule.list_id);
+        let fields = AlertFields {
+            item_id: Some(rule.item_id),
+            item_name: Some(item_name),
+            ..fields
+        };
         let delivery_result =
-            dispatch_alert(rule.alert_id, &title, &body, &click_url, db, ctx).await;
+            dispatch_alert(rule.alert_id, &title, &body, &click_url, &fields, db, ctx).await;
         let delivered = delivery_result.is_ok();
         let delivery_error = delivery_result.err().map(|e| e.to_string());
 
@@ -526,6 +903,359 @@ async fn handle_added(
             );
         }
     }
+
+    fire_expression_candidates(expression_candidates, state, db, ctx, now).await;
+}
+
+/// Hands a listing event to [`recheck_cheapest`] when some market-move rule
+/// watches the cheapest listing of its item on its world. Only the rule
+/// lookup happens here; the order-book read stays off the event loop.
+async fn queue_cheapest_check(
+    event: &ListingEventData,
+    state: &Arc<Mutex<TrackerState>>,
+    queue: &mpsc::Sender<(i32, i32)>,
+) {
+    let now = Utc::now();
+    let watched = state
+        .lock()
+        .await
+        .by_item_expression_rules
+        .get(&event.item_id)
+        .is_some_and(|rules| {
+            rules.iter().any(|r| {
+                market_move_rule_applies(r, MoveSource::CheapestListing, event.world_id, now)
+            })
+        });
+    if watched && queue.try_send((event.item_id, event.world_id)).is_err() {
+        warn!(
+            item_id = event.item_id,
+            "cheapest listing queue is full, skipping a market-move check"
+        );
+    }
+}
+
+/// Works through queued cheapest-listing checks, checking each item and world
+/// once per batch however many events touched it. Exits once the listener
+/// loop drops its sender.
+async fn recheck_cheapest(
+    mut queue: mpsc::Receiver<(i32, i32)>,
+    state: Arc<Mutex<TrackerState>>,
+    db: UltrosDb,
+    ctx: serenity_prelude::Context,
+    world_cache: Arc<WorldCache>,
+) {
+    let mut batch = Vec::new();
+    while queue.recv_many(&mut batch, CHEAPEST_QUEUE).await > 0 {
+        for (item_id, world_id) in batch.drain(..).unique() {
+            handle_cheapest_changed(item_id, world_id, &state, &db, &ctx, &world_cache).await;
+        }
+    }
+}
+
+/// Re-check cheapest-listing market-move rules for the item and world a
+/// listing event touched.
+async fn handle_cheapest_changed(
+    item_id: i32,
+    world_id: i32,
+    state: &Arc<Mutex<TrackerState>>,
+    db: &UltrosDb,
+    ctx: &serenity_prelude::Context,
+    world_cache: &WorldCache,
+) {
+    let now = Utc::now();
+    let rules: Vec<ExpressionActiveRule> = {
+        let guard = state.lock().await;
+        let Some(rules) = guard.by_item_expression_rules.get(&item_id) else {
+            return;
+        };
+        rules
+            .iter()
+            .filter(|r| market_move_rule_applies(r, MoveSource::CheapestListing, world_id, now))
+            .cloned()
+            .collect()
+    };
+    if rules.is_empty() {
+        return;
+    }
+    // One order-book read covers every rule on the item; each then takes the
+    // cheapest row among its own worlds.
+    let worlds: Vec<i32> = rules
+        .iter()
+        .flat_map(|rule| rule.world_id_set.iter().copied())
+        .unique()
+        .collect();
+    let summaries = match db.cheapest_listings_for_items(&worlds, &[item_id]).await {
+        Ok(summaries) => summaries,
+        Err(e) => {
+            warn!(item_id, "cheapest listing lookup failed: {e}");
+            return;
+        }
+    };
+    let mut candidates = vec![];
+    for rule in rules {
+        // Nothing listed is not a price; wait for the next listing.
+        let Some(cheapest) = cheapest_for_rule(&rule, &summaries) else {
+            continue;
+        };
+        // Quantity isn't tracked per cheapest price, and market-move rules
+        // never read it.
+        let observed = RuleObservation {
+            price_per_unit: cheapest.price_per_unit,
+            quantity: 0,
+            hq: cheapest.hq,
+        };
+        let fields = observed_fields(cheapest.world_id, cheapest.price_per_unit, world_cache);
+        candidates.push((rule, observed, fields));
+    }
+    fire_expression_candidates(candidates, state, db, ctx, now).await;
+}
+
+/// The cheapest listing a market-move rule watches: the lowest row among its
+/// own worlds, HQ only when the rule is.
+fn cheapest_for_rule<'a>(
+    rule: &ExpressionActiveRule,
+    summaries: &'a [ListingSummary],
+) -> Option<&'a ListingSummary> {
+    summaries
+        .iter()
+        .filter(|s| rule.world_id_set.contains(&s.world_id) && (s.hq || !rule.hq_only))
+        .min_by_key(|s| s.price_per_unit)
+}
+
+/// Check latest-sale market-move rules against each new sale.
+async fn handle_sales(
+    added: &SaleEventData,
+    state: &Arc<Mutex<TrackerState>>,
+    db: &UltrosDb,
+    ctx: &serenity_prelude::Context,
+    world_cache: &WorldCache,
+) {
+    let now = Utc::now();
+    let mut candidates = vec![];
+    {
+        let guard = state.lock().await;
+        for (sale, _buyer) in &added.sales {
+            let Some(rules) = guard.by_item_expression_rules.get(&sale.sold_item_id) else {
+                continue;
+            };
+            for rule in rules {
+                if market_move_rule_applies(rule, MoveSource::LatestSale, sale.world_id, now) {
+                    candidates.push((
+                        rule.clone(),
+                        sale.into(),
+                        observed_fields(sale.world_id, sale.price_per_item, world_cache),
+                    ));
+                }
+            }
+        }
+    }
+    fire_expression_candidates(candidates, state, db, ctx, now).await;
+}
+
+/// Evaluate expression and market-move candidates and deliver the ones that
+/// match, at most once per alert. Rules read prefetched stats, so this runs
+/// outside the state lock and only marks a rule fired once it has actually
+/// matched.
+async fn fire_expression_candidates(
+    candidates: Vec<(ExpressionActiveRule, RuleObservation, AlertFields)>,
+    state: &Arc<Mutex<TrackerState>>,
+    db: &UltrosDb,
+    ctx: &serenity_prelude::Context,
+    now: DateTime<Utc>,
+) {
+    let mut to_fire: Vec<(
+        ExpressionActiveRule,
+        RuleObservation,
+        Arc<Vec<DeepScan>>,
+        AlertFields,
+    )> = vec![];
+    for (rule, observed, fields) in candidates {
+        if to_fire
+            .iter()
+            .any(|(fired, _, _, _)| fired.alert_id == rule.alert_id)
+        {
+            continue;
+        }
+        let Some(scans) = expression_stats(&rule, state).await else {
+            continue;
+        };
+        let inputs = ObservedRuleInputs {
+            observed,
+            scans: &scans,
+        };
+        if rule.rule.matches(&inputs) {
+            to_fire.push((rule, observed, scans, fields));
+        }
+    }
+    if to_fire.is_empty() {
+        return;
+    }
+    {
+        let mut guard = state.lock().await;
+        for (fired, _, _, _) in &to_fire {
+            if let Some(rules) = guard.by_item_expression_rules.get_mut(&fired.item_id) {
+                for rule in rules.iter_mut().filter(|r| r.alert_id == fired.alert_id) {
+                    rule.last_fired_at = Some(now);
+                }
+            }
+        }
+    }
+
+    for (rule, observed, scans, fields) in to_fire {
+        let item_name = resolve_item_name(rule.item_id);
+        let (title, body) = match &rule.market_move {
+            Some(market_move) => format_market_move_alert_message(
+                &item_name,
+                rule.item_id,
+                market_move,
+                observed,
+                market_move_reference(market_move, observed, &scans),
+            ),
+            None => format_expression_alert_message(
+                &item_name,
+                rule.item_id,
+                observed.price_per_unit,
+                observed.hq,
+            ),
+        };
+
+        let click_url = format!("/item/{}", rule.item_id);
+        let fields = AlertFields {
+            item_id: Some(rule.item_id),
+            item_name: Some(item_name),
+            ..fields
+        };
+        let delivery_result =
+            dispatch_alert(rule.alert_id, &title, &body, &click_url, &fields, db, ctx).await;
+        let delivered = delivery_result.is_ok();
+        let delivery_error = delivery_result.err().map(|e| e.to_string());
+
+        if let Err(e) = db
+            .record_alert_event(
+                rule.alert_id,
+                rule.item_id,
+                None,
+                Some(observed.price_per_unit),
+                delivered,
+                delivery_error,
+            )
+            .await
+        {
+            error!(
+                "failed to record alert_event for expression-alert {}: {e}",
+                rule.alert_id
+            );
+        }
+        if delivered && let Err(e) = db.update_alert_last_fired(rule.alert_id).await {
+            error!(
+                "failed to update last_fired_at for expression-alert {}: {e}",
+                rule.alert_id
+            );
+        }
+    }
+}
+
+/// Rollup rows for `rule`'s item across its worlds and windows, as last
+/// prefetched. `None` until the first fetch for a new or changed rule lands,
+/// or when ClickHouse couldn't be reached; the rule then waits rather than
+/// firing on missing data.
+async fn expression_stats(
+    rule: &ExpressionActiveRule,
+    state: &Arc<Mutex<TrackerState>>,
+) -> Option<Arc<Vec<DeepScan>>> {
+    if rule.windows.is_empty() {
+        return Some(Arc::default());
+    }
+    state
+        .lock()
+        .await
+        .expression_stats
+        .get(&rule.alert_id)
+        .cloned()
+}
+
+/// Keeps `expression_stats` filled for every expression and market-move rule
+/// so listing and sale events never wait on ClickHouse. Refetches every
+/// [`EXPRESSION_STATS_TTL`], and straight away when the rules change. Exits
+/// once the tracker is dropped.
+async fn prefetch_expression_stats(
+    state: Weak<Mutex<TrackerState>>,
+    rules_changed: Arc<Notify>,
+    ch: ClickHouseClient,
+) {
+    loop {
+        let Some(tracker) = state.upgrade() else {
+            return;
+        };
+        let rules: Vec<ExpressionActiveRule> = tracker
+            .lock()
+            .await
+            .by_item_expression_rules
+            .values()
+            .flatten()
+            .filter(|rule| !rule.windows.is_empty())
+            .cloned()
+            .collect();
+        drop(tracker);
+        for rule in rules {
+            let scans = fetch_expression_stats(&rule, &ch).await;
+            let Some(tracker) = state.upgrade() else {
+                return;
+            };
+            let mut guard = tracker.lock().await;
+            // The rules may have been refreshed while this fetch ran; only
+            // keep stats that still answer the rule as it now stands.
+            let current = guard
+                .by_item_expression_rules
+                .get(&rule.item_id)
+                .and_then(|rules| rules.iter().find(|r| r.alert_id == rule.alert_id))
+                .is_some_and(|r| r.windows == rule.windows && r.world_id_set == rule.world_id_set);
+            match scans {
+                Some(scans) if current => {
+                    guard
+                        .expression_stats
+                        .insert(rule.alert_id, Arc::new(scans));
+                }
+                _ => {
+                    guard.expression_stats.remove(&rule.alert_id);
+                }
+            }
+        }
+        tokio::select! {
+            _ = rules_changed.notified() => {}
+            _ = tokio::time::sleep(EXPRESSION_STATS_TTL) => {}
+        }
+    }
+}
+
+async fn fetch_expression_stats(
+    rule: &ExpressionActiveRule,
+    ch: &ClickHouseClient,
+) -> Option<Vec<DeepScan>> {
+    let requests: Vec<(i32, u8, i32)> = rule
+        .world_id_set
+        .iter()
+        .flat_map(|world_id| {
+            [
+                (rule.item_id, 0u8, *world_id),
+                (rule.item_id, 1u8, *world_id),
+            ]
+        })
+        .collect();
+    let mut scans = Vec::new();
+    for window_days in &rule.windows {
+        match ultros_clickhouse::queries::deep_scan_batch(ch, *window_days, &requests).await {
+            Ok(rows) => scans.extend(rows),
+            Err(e) => {
+                warn!(
+                    alert_id = rule.alert_id,
+                    window_days, "expression alert stats query failed: {e}"
+                );
+                return None;
+            }
+        }
+    }
+    Some(scans)
 }
 
 #[cfg(test)]
@@ -555,6 +1285,7 @@ mod test {
             quantity: 1,
             hq,
             timestamp: NaiveDateTime::default(),
+            materia: vec![],
         }
     }
 
@@ -671,6 +1402,235 @@ mod test {
         assert!(rule_matches_listing(&r, &l, now));
     }
 
+    // ---------- expression rules ----------
+
+    fn expression_rule(worlds: &[i32]) -> ExpressionActiveRule {
+        use ultros_api_types::alert_rule::{RuleComparison, RuleTerm};
+        let rule = AlertRule::Compare {
+            left: RuleTerm::ListingPrice,
+            cmp: RuleComparison::Lt,
+            right: RuleTerm::Scaled {
+                percent: 60,
+                of: Box::new(RuleTerm::Stat {
+                    stat: MarketStat::Median,
+                    window_days: 7,
+                }),
+            },
+        };
+        ExpressionActiveRule {
+            alert_id: 2,
+            item_id: 42,
+            windows: rule.stat_windows(),
+            rule,
+            market_move: None,
+            hq_only: false,
+            cooldown_seconds: 3600,
+            last_fired_at: None,
+            world_id_set: worlds.iter().copied().collect(),
+        }
+    }
+
+    fn week_scan(world_id: i32, hq: bool, p50: u32) -> DeepScan {
+        DeepScan {
+            item_id: 42,
+            hq: hq as u8,
+            world_id,
+            window_days: 7,
+            vwap: p50,
+            p50,
+            p10: p50,
+            p25: p50,
+            p75: p50,
+            p90: p50,
+            median_abs_deviation: 0,
+            sample_size: 70,
+            cleaned_sample_size: 70,
+            excluded_count: 0,
+            unit_volume: 70,
+            gil_volume: 70 * p50 as u64,
+            unique_buyers: 10,
+            quality_score: 80,
+            confidence_band_raw: "high".to_string(),
+            launder_suspicion_pct: 0.0,
+        }
+    }
+
+    #[test]
+    fn expression_rule_scope_checks_world_and_cooldown() {
+        let now = Utc::now();
+        let mut r = expression_rule(&[1]);
+        assert!(expression_rule_applies_to_listing(
+            &r,
+            &listing(1, 50, false),
+            now
+        ));
+        assert!(!expression_rule_applies_to_listing(
+            &r,
+            &listing(2, 50, false),
+            now
+        ));
+        r.last_fired_at = Some(now - Duration::seconds(60));
+        assert!(!expression_rule_applies_to_listing(
+            &r,
+            &listing(1, 50, false),
+            now
+        ));
+    }
+
+    #[test]
+    fn expression_rule_reads_stats_for_the_listing_quality() {
+        let r = expression_rule(&[1]);
+        let scans = [week_scan(1, false, 100), week_scan(1, true, 1000)];
+        let hq = listing(1, 500, true);
+        let nq = listing(1, 500, false);
+        // 500 is under 60% of the HQ median but well over the NQ one.
+        assert!(r.rule.matches(&ObservedRuleInputs {
+            observed: (&hq).into(),
+            scans: &scans,
+        }));
+        assert!(!r.rule.matches(&ObservedRuleInputs {
+            observed: (&nq).into(),
+            scans: &scans,
+        }));
+    }
+
+    #[test]
+    fn expression_rule_without_stats_does_not_fire() {
+        let r = expression_rule(&[1]);
+        let l = listing(1, 1, false);
+        assert!(!r.rule.matches(&ObservedRuleInputs {
+            observed: (&l).into(),
+            scans: &[],
+        }));
+    }
+
+    // ---------- market-move rules ----------
+
+    fn market_move_rule(source: MoveSource, worlds: &[i32]) -> ExpressionActiveRule {
+        use ultros_api_types::alert_rule::MoveDirection;
+        let market_move = MarketMoveRule {
+            source,
+            direction: MoveDirection::Either,
+            reference: MoveReference::Median,
+            percent: 25,
+            window_days: 7,
+        };
+        let rule = market_move.to_rule(false);
+        ExpressionActiveRule {
+            alert_id: 3,
+            item_id: 42,
+            windows: rule.stat_windows(),
+            rule,
+            market_move: Some(market_move),
+            hq_only: false,
+            cooldown_seconds: 3600,
+            last_fired_at: None,
+            world_id_set: worlds.iter().copied().collect(),
+        }
+    }
+
+    #[test]
+    fn cheapest_for_rule_stays_within_its_worlds_and_quality() {
+        let summary = |world_id, hq, price_per_unit| ListingSummary {
+            item_id: 42,
+            hq,
+            price_per_unit,
+            world_id,
+        };
+        let summaries = [
+            summary(1, false, 100),
+            summary(2, false, 50),
+            summary(1, true, 300),
+        ];
+        let mut r = market_move_rule(MoveSource::CheapestListing, &[1]);
+        assert_eq!(
+            cheapest_for_rule(&r, &summaries).map(|s| s.price_per_unit),
+            Some(100)
+        );
+        r.hq_only = true;
+        assert_eq!(
+            cheapest_for_rule(&r, &summaries).map(|s| s.price_per_unit),
+            Some(300)
+        );
+        r.world_id_set = [3].into_iter().collect();
+        assert!(cheapest_for_rule(&r, &summaries).is_none());
+    }
+
+    #[test]
+    fn market_move_rules_only_watch_their_source() {
+        let now = Utc::now();
+        let cheapest = market_move_rule(MoveSource::CheapestListing, &[1]);
+        assert!(market_move_rule_applies(
+            &cheapest,
+            MoveSource::CheapestListing,
+            1,
+            now
+        ));
+        assert!(!market_move_rule_applies(
+            &cheapest,
+            MoveSource::LatestSale,
+            1,
+            now
+        ));
+        assert!(!market_move_rule_applies(
+            &cheapest,
+            MoveSource::CheapestListing,
+            2,
+            now
+        ));
+        // Market-move rules never fire per listing like expressions do.
+        assert!(!expression_rule_applies_to_listing(
+            &cheapest,
+            &listing(1, 1, false),
+            now
+        ));
+        assert!(!market_move_rule_applies(
+            &expression_rule(&[1]),
+            MoveSource::CheapestListing,
+            1,
+            now
+        ));
+    }
+
+    #[test]
+    fn market_move_fires_on_a_sale_far_from_the_median() {
+        let r = market_move_rule(MoveSource::LatestSale, &[1]);
+        let scans = [week_scan(1, false, 1000)];
+        let sale = |price_per_item| RuleObservation {
+            price_per_unit: price_per_item,
+            quantity: 1,
+            hq: false,
+        };
+        for (price, fires) in [(700, true), (900, false), (1200, false), (1300, true)] {
+            assert_eq!(
+                r.rule.matches(&ObservedRuleInputs {
+                    observed: sale(price),
+                    scans: &scans,
+                }),
+                fires,
+                "{price}"
+            );
+        }
+    }
+
+    #[test]
+    fn market_move_message_reports_direction_and_distance() {
+        let r = market_move_rule(MoveSource::CheapestListing, &[1]);
+        let market_move = r.market_move.unwrap();
+        let observed = RuleObservation {
+            price_per_unit: 600,
+            quantity: 0,
+            hq: false,
+        };
+        let scans = [week_scan(1, false, 1000)];
+        let reference = market_move_reference(&market_move, observed, &scans);
+        let (title, body) =
+            format_market_move_alert_message("Cordial", 6141, &market_move, observed, reference);
+        assert!(title.contains("dropped to 600 gil"));
+        assert!(body.contains("40% below the 7-day median of 1000 gil"));
+        assert!(body.contains("ultros.app/item/6141"));
+    }
+
     // ---------- format_threshold_alert_message ----------
 
     #[test]
@@ -8,12 +8,17 @@ use futures::future::{self, Either};
 use poise::serenity_prelude::{self, Color, UserId};
 use serde::Serialize;
 use tracing::{debug, error, instrument, warn};
-use ultros_api_types::{user::OwnedRetainer, websocket::ListingEventData};
+use ultros_api_types::{
+    repricing::RepriceSuggestion, user::OwnedRetainer, websocket::ListingEventData,
+};
 use ultros_db::UltrosDb;
 
 use crate::{
-    alerts::delivery::{DispatchOutcome, dispatch_alert_detailed, permanent_failure_reason},
+    alerts::delivery::{
+        AlertFields, DispatchOutcome, dispatch_alert_detailed, permanent_failure_reason,
+    },
     event::{EventBus, EventType},
+    repricing::Repricer,
 };
 
 /// Returns true when `competitor_price` undercuts `our_lowest_price` by strictly more than
@@ -32,6 +37,19 @@ pub(crate) fn is_undercut_by_more_than_margin(
     threshold > competitor_price
 }
 
+/// The "what to relist at" line of an undercut message.
+fn relist_line(suggestion: &RepriceSuggestion) -> String {
+    let mut line = format!("\nSuggested relist: **{} gil**", suggestion.price);
+    if suggestion.position > 0 {
+        line.push_str(&format!(", {} listings still cheaper", suggestion.position));
+    }
+    if let Some(days) = suggestion.est_days_to_sell {
+        line.push_str(&format!(", sells in ~{days:.1} days"));
+    }
+    line.push('\n');
+    line
+}
+
 pub(crate) struct RetainerAlertListener {
     pub(crate) retainer_alert_id: i32,
     pub(crate) cancellation_sender: tokio::sync::mpsc::Sender<RetainerAlertTx>,
@@ -159,6 +177,8 @@ pub(crate) struct UndercutRetainer {
 #[derive(Debug)]
 pub(crate) struct Undercut {
     pub(crate) item_id: i32,
+    pub(crate) world_id: i32,
+    pub(crate) hq: bool,
     pub(crate) undercut_retainers: Vec<UndercutRetainer>,
 }
 
@@ -299,6 +319,8 @@ impl UndercutTracker {
                             .unwrap_or_default();
                         return Ok(Some(Undercut {
                             item_id: added.item_id,
+                            world_id: added.world_id,
+                            hq: added.hq,
                             undercut_retainers: retainers,
                         }));
                     }
@@ -311,7 +333,8 @@ impl UndercutTracker {
 }
 
 impl RetainerAlertListener {
-    #[instrument(skip(ultros_db, listings, ctx))]
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(skip(ultros_db, listings, ctx, repricer))]
     pub(crate) async fn create_listener(
         retainer_alert_id: i32,
         alert_id: i32,
@@ -320,6 +343,7 @@ impl RetainerAlertListener {
         mut listings: EventBus<ListingEventData>,
         active_retainers: EventBus<OwnedRetainer>,
         ctx: serenity_prelude::Context,
+        repricer: Repricer,
     ) -> Result<Self> {
         let alert = ultros_db
             .get_alert(alert_id)
@@ -360,6 +384,8 @@ impl RetainerAlertListener {
                                 None => {}
                                 Some(Undercut {
                                     item_id,
+                                    world_id,
+                                    hq,
                                     undercut_retainers,
                                 }) => {
                                     let items = &xiv_gen_db::data().items;
@@ -370,10 +396,30 @@ impl RetainerAlertListener {
                                             .collect::<Vec<_>>()
                                             .join(", ");
                                         let item_name = &item.name;
+                                        let relist = match repricer
+                                            .suggest_relist(discord_user, world_id, item_id, hq)
+                                            .await
+                                        {
+                                            Ok(suggestion) => suggestion
+                                                .map(|s| relist_line(&s))
+                                                .unwrap_or_default(),
+                                            Err(e) => {
+                                                warn!(
+                                                    "no relist suggestion for undercut alert: {e}"
+                                                );
+                                                String::new()
+                                            }
+                                        };
                                         let undercut_msg = format!(
-                                            "Your retainers {retainer_names} have been undercut on {item_name}\n\nhttps://ultros.app/retainers/undercuts"
+                                            "Your retainers {retainer_names} have been undercut on {item_name}\n{relist}\nhttps://ultros.app/retainers/reprice"
                                         );
                                         let title = "Undercut Alert";
+                                        let fields = AlertFields {
+                                            item_id: Some(item_id),
+                                            item_name: Some(item_name.to_string()),
+                                            retainer_name: Some(retainer_names),
+                                            ..Default::default()
+                                        };
                                         let mut delivered = false;
                                         let mut delivery_error = None;
                                         // Tracks whether every destination failed
@@ -386,7 +432,8 @@ impl RetainerAlertListener {
                                             alert_id,
                                             title,
                                             &undercut_msg,
-                                            "/retainers/undercuts",
+                                            "/retainers/reprice",
+                                            &fields,
                                             &ultros_db,
                                             &ctx,
                                         )
@@ -501,6 +548,29 @@ impl RetainerAlertListener {
 mod tests {
     use super::*;
 
+    #[test]
+    fn relist_line_leaves_out_what_it_doesnt_know() {
+        let suggestion = RepriceSuggestion {
+            price: 1_499,
+            position: 0,
+            est_days_to_sell: None,
+            percentile: None,
+        };
+        assert_eq!(
+            relist_line(&suggestion),
+            "\nSuggested relist: **1499 gil**\n"
+        );
+        let suggestion = RepriceSuggestion {
+            position: 2,
+            est_days_to_sell: Some(1.5),
+            ..suggestion
+        };
+        assert_eq!(
+            relist_line(&suggestion),
+            "\nSuggested relist: **1499 gil**, 2 listings still cheaper, sells in ~1.5 days\n"
+        );
+    }
+
     // ---------- is_undercut_by_more_than_margin ----------
 
     #[test]
@@ -0,0 +1,415 @@
+//! Generic HTTP webhook delivery.
+//!
+//! Where the `Webhook` method posts a Discord embed, a `GenericWebhook` posts
+//! whatever JSON its owner templated, to any https URL. Each delivery is
+//! optionally signed so the receiver can tell it came from us:
+//!
+//! ```text
+//! X-Ultros-Timestamp: 1760659200
+//! X-Ultros-Signature: sha256=<hex HMAC-SHA256 of "{timestamp}.{body}">
+//! ```
+//!
+//! Rate limits, timeouts and 5xx responses are retried a few times with
+//! backoff before giving up for this fire. Any other rejection surfaces as
+//! [`WebhookRejected`], which the dispatcher treats as permanent and disables
+//! the endpoint for, the same way it handles a deleted Discord channel.
+
+use std::{
+    net::SocketAddr,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac, digest::KeyInit};
+use hyper_014::client::connect::dns::Name;
+use serde_json::Value;
+use sha2::Sha256;
+use ultros_api_types::alert::{
+    WEBHOOK_TEMPLATE_VARIABLES, WebhookHeader, webhook_template_placeholders,
+};
+
+use crate::{alerts::delivery::AlertFields, web::api::endpoint_validation::is_internal_ip};
+
+pub(crate) const SIGNATURE_HEADER: &str = "X-Ultros-Signature";
+pub(crate) const TIMESTAMP_HEADER: &str = "X-Ultros-Timestamp";
+
+/// Largest template accepted, in bytes.
+pub(crate) const MAX_TEMPLATE_BYTES: usize = 8 * 1024;
+
+/// Attempts per fire, including the first.
+const MAX_ATTEMPTS: u32 = 3;
+/// Wait before the first retry; doubles for each one after.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+/// Most of a rejection's response body kept in the endpoint's failure reason.
+const MAX_REJECTION_BODY_CHARS: usize = 200;
+
+/// The payload sent when an endpoint has no template of its own.
+const DEFAULT_TEMPLATE: &str = r#"{
+    "title": "{{title}}",
+    "body": "{{body}}",
+    "url": "{{url}}",
+    "alert_id": "{{alert_id}}",
+    "item_id": "{{item_id}}",
+    "item_name": "{{item_name}}",
+    "world_id": "{{world_id}}",
+    "world_name": "{{world_name}}",
+    "price": "{{price}}",
+    "listing_id": "{{listing_id}}",
+    "retainer_name": "{{retainer_name}}",
+    "fired_at": "{{fired_at}}"
+}"#;
+
+/// The receiver answered with a status that retrying won't change: the URL is
+/// gone, auth was revoked, or it doesn't accept the templated payload.
+#[derive(Debug, thiserror::Error)]
+#[error("webhook returned {status}: {body}")]
+pub(crate) struct WebhookRejected {
+    pub(crate) status: u16,
+    pub(crate) body: String,
+}
+
+/// Everything a template can reference for one delivery.
+pub(crate) struct TemplateContext<'a> {
+    pub(crate) title: &'a str,
+    pub(crate) body: &'a str,
+    pub(crate) click_url: &'a str,
+    pub(crate) fields: &'a AlertFields,
+    pub(crate) fired_at: DateTime<Utc>,
+}
+
+impl TemplateContext<'_> {
+    /// The value of one template variable; `None` for an unknown name.
+    fn lookup(&self, name: &str) -> Option<Value> {
+        let fields = self.fields;
+        Some(match name {
+            "title" => self.title.into(),
+            "body" => self.body.into(),
+            "url" => format!("https://ultros.app{}", self.click_url).into(),
+            "alert_id" => fields.alert_id.into(),
+            "item_id" => fields.item_id.into(),
+            "item_name" => fields.item_name.clone().into(),
+            "world_id" => fields.world_id.into(),
+            "world_name" => fields.world_name.clone().into(),
+            "price" => fields.price.into(),
+            "listing_id" => fields.listing_id.into(),
+            "retainer_name" => fields.retainer_name.clone().into(),
+            "fired_at" => self.fired_at.to_rfc3339().into(),
+            _ => return None,
+        })
+    }
+
+    fn render_string(&self, text: &str) -> Value {
+        if let Some(name) = text.strip_prefix("{{").and_then(|t| t.strip_suffix("}}"))
+            && !name.contains("{{")
+            && let Some(value) = self.lookup(name)
+        {
+            return value;
+        }
+        let mut out = text.to_string();
+        for name in webhook_template_placeholders(text) {
+            let Some(value) = self.lookup(name) else {
+                continue;
+            };
+            let value = match value {
+                Value::String(s) => s,
+                Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            out = out.replace(&format!("{{{{{name}}}}}"), &value);
+        }
+        Value::String(out)
+    }
+
+    fn render_value(&self, value: &Value) -> Value {
+        match value {
+            Value::String(s) => self.render_string(s),
+            Value::Array(items) => items.iter().map(|v| self.render_value(v)).collect(),
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), self.render_value(v)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    /// Render `template` (or the default payload) into the request body.
+    pub(crate) fn render(&self, template: Option<&str>) -> Result<Vec<u8>> {
+        let template: Value = serde_json::from_str(template.unwrap_or(DEFAULT_TEMPLATE))
+            .map_err(|e| anyhow!("webhook template is not valid JSON: {e}"))?;
+        Ok(serde_json::to_vec(&self.render_value(&template))?)
+    }
+}
+
+/// Check a user-supplied template: bounded size, valid JSON, and only known
+/// variables. Returns a human-readable reason on failure.
+pub(crate) fn validate_template(template: &str) -> Result<(), String> {
+    if template.len() > MAX_TEMPLATE_BYTES {
+        return Err(format!(
+            "template must be at most {MAX_TEMPLATE_BYTES} bytes"
+        ));
+    }
+    let parsed: Value =
+        serde_json::from_str(template).map_err(|e| format!("template is not valid JSON: {e}"))?;
+    let mut strings = vec![];
+    collect_strings(&parsed, &mut strings);
+    for text in strings {
+        if let Some(unknown) = webhook_template_placeholders(text)
+            .into_iter()
+            .find(|name| !WEBHOOK_TEMPLATE_VARIABLES.contains(name))
+        {
+            return Err(format!("unknown template variable {{{{{unknown}}}}}"));
+        }
+    }
+    Ok(())
+}
+
+fn collect_strings<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+    match value {
+        Value::String(s) => out.push(s),
+        Value::Array(items) => items.iter().for_each(|v| collect_strings(v, out)),
+        Value::Object(map) => map.values().for_each(|v| collect_strings(v, out)),
+        _ => {}
+    }
+}
+
+/// `sha256=<hex>` signature of `{timestamp}.{body}` under `secret`.
+pub(crate) fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = <Hmac<Sha256> as KeyInit>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+    format!("sha256={hex}")
+}
+
+/// Whether a failed response is worth another attempt.
+fn is_retryable_status(status: u16) -> bool {
+    status == 408 || status == 429 || status >= 500
+}
+
+/// Shared by every generic webhook delivery so repeat sends to a receiver
+/// reuse its connections. Redirects are refused: the URL was validated,
+/// wherever it points wasn't. Names resolve through [`PublicResolver`], so a
+/// host that was public when the endpoint was saved can't later be pointed at
+/// the server's own network.
+static WEBHOOK_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .redirect(reqwest::redirect::Policy::none())
+        .dns_resolver(Arc::new(PublicResolver))
+        .build()
+        .expect("webhook HTTP client should build")
+});
+
+/// System DNS lookup that drops loopback, private and link-local answers.
+/// Runs on every new connection, so rebinding a name after validation gains
+/// nothing.
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((name.as_str(), 0)).await?;
+            let addrs: reqwest::dns::Addrs =
+                Box::new(public_addrs(name.as_str(), addrs)?.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// The public addresses among `addrs`, or an error when there are none.
+fn public_addrs(
+    host: &str,
+    addrs: impl IntoIterator<Item = SocketAddr>,
+) -> Result<Vec<SocketAddr>> {
+    let public: Vec<_> = addrs
+        .into_iter()
+        .filter(|addr| !is_internal_ip(addr.ip()))
+        .collect();
+    if public.is_empty() {
+        return Err(anyhow!("{host} does not resolve to a public address"));
+    }
+    Ok(public)
+}
+
+/// Render and POST one delivery, retrying transient failures.
+pub(crate) async fn send_generic_webhook(
+    url: &str,
+    template: Option<&str>,
+    headers: &[WebhookHeader],
+    secret: Option<&str>,
+    message: &TemplateContext<'_>,
+) -> Result<()> {
+    let payload = message.render(template)?;
+    let client = &*WEBHOOK_CLIENT;
+
+    let mut last_err = anyhow!("webhook was not attempted");
+    for attempt in 0..MAX_ATTEMPTS {
+        if attempt > 0 {
+            tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).await;
+        }
+        let mut request = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        for header in headers {
+            request = request.header(header.name.as_str(), header.value.as_str());
+        }
+        if let Some(secret) = secret {
+            // Re-signed per attempt so a receiver's replay window sees the
+            // time of this request, not of the first one.
+            let timestamp = Utc::now().timestamp();
+            request = request
+                .header(TIMESTAMP_HEADER, timestamp.to_string())
+                .header(SIGNATURE_HEADER, sign(secret, timestamp, &payload));
+        }
+        let resp = match request.body(payload.clone()).send().await {
+            Ok(resp) => resp,
+            Err(e) => {
+                last_err = anyhow!("webhook request failed: {e}");
+                continue;
+            }
+        };
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let body: String = resp
+            .text()
+            .await
+            .unwrap_or_default()
+            .chars()
+            .take(MAX_REJECTION_BODY_CHARS)
+            .collect();
+        if !is_retryable_status(status.as_u16()) {
+            return Err(WebhookRejected {
+                status: status.as_u16(),
+                body,
+            }
+            .into());
+        }
+        last_err = anyhow!("webhook returned {status}: {body}");
+    }
+    Err(last_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn fields() -> AlertFields {
+        AlertFields {
+            alert_id: Some(9),
+            item_id: Some(5057),
+            item_name: Some("Cordial".into()),
+            world_id: Some(34),
+            world_name: Some("Brynhildr".into()),
+            price: Some(480),
+            listing_id: Some(123),
+            retainer_name: Some("Sells-a-lot".into()),
+        }
+    }
+
+    fn render(template: Option<&str>, fields: &AlertFields) -> Value {
+        let message = TemplateContext {
+            title: "🎯 Cordial dropped to 480 gil",
+            body: "body",
+            click_url: "/item/5057",
+            fields,
+            fired_at: Utc.with_ymd_and_hms(2026, 10, 17, 12, 0, 0).unwrap(),
+        };
+        serde_json::from_slice(&message.render(template).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn whole_string_placeholders_keep_their_type() {
+        let out = render(
+            Some(r#"{"price": "{{price}}", "name": "{{item_name}}", "nested": ["{{world_id}}"]}"#),
+            &fields(),
+        );
+        assert_eq!(
+            out,
+            json!({"price": 480, "name": "Cordial", "nested": [34]})
+        );
+    }
+
+    #[test]
+    fn embedded_placeholders_are_spliced_as_text() {
+        let out = render(
+            Some(
+                r#"{"text": "{{item_name}} at {{price}} gil on {{world_name}} ({{listing_id}})"}"#,
+            ),
+            &fields(),
+        );
+        assert_eq!(
+            out,
+            json!({"text": "Cordial at 480 gil on Brynhildr (123)"})
+        );
+    }
+
+    #[test]
+    fn missing_details_render_as_null_or_empty() {
+        let out = render(
+            Some(r#"{"retainer": "{{retainer_name}}", "text": "by {{retainer_name}}"}"#),
+            &AlertFields::default(),
+        );
+        assert_eq!(out, json!({"retainer": null, "text": "by "}));
+    }
+
+    #[test]
+    fn default_template_carries_every_variable() {
+        let out = render(None, &fields());
+        for name in WEBHOOK_TEMPLATE_VARIABLES {
+            assert!(out.get(name).is_some(), "default payload lacks {name}");
+        }
+        assert_eq!(out["url"], json!("https://ultros.app/item/5057"));
+        assert_eq!(out["fired_at"], json!("2026-10-17T12:00:00+00:00"));
+    }
+
+    #[test]
+    fn validation_rejects_unknown_variables_and_bad_json() {
+        assert!(validate_template(DEFAULT_TEMPLATE).is_ok());
+        assert!(validate_template(r#"{"x": "{{price}} {{nope}}"}"#).is_err());
+        assert!(validate_template(r#"{"x": "{{price}}""#).is_err());
+        let huge = format!(r#"{{"x": "{}"}}"#, "a".repeat(MAX_TEMPLATE_BYTES));
+        assert!(validate_template(&huge).is_err());
+    }
+
+    #[test]
+    fn signature_matches_a_known_hmac() {
+        // printf '1760659200.{"a":1}' | openssl dgst -sha256 -hmac secret
+        assert_eq!(
+            sign("secret", 1760659200, br#"{"a":1}"#),
+            "sha256=106533b2fad96b5a80375b1d2038dbfc7be3bf68fc54395eab86df1a181f6e11"
+        );
+    }
+
+    #[test]
+    fn only_rate_limits_timeouts_and_server_errors_are_retried() {
+        assert!(is_retryable_status(429));
+        assert!(is_retryable_status(408));
+        assert!(is_retryable_status(503));
+        assert!(!is_retryable_status(400));
+        assert!(!is_retryable_status(401));
+        assert!(!is_retryable_status(404));
+        assert!(!is_retryable_status(301));
+    }
+
+    #[test]
+    fn resolved_internal_addresses_are_dropped() {
+        let public: SocketAddr = "203.0.113.7:0".parse().unwrap();
+        let private: SocketAddr = "10.0.0.5:0".parse().unwrap();
+        let loopback: SocketAddr = "[::1]:0".parse().unwrap();
+        assert_eq!(
+            public_addrs("hooks.example", [private, public, loopback]).unwrap(),
+            vec![public]
+        );
+        assert!(public_addrs("rebound.example", [private, loopback]).is_err());
+    }
+}
@@ -30,6 +30,7 @@ use crate::trend_candidates::{TrendCandidate, select_trend_candidates};
 use thiserror::Error;
 use tokio::sync::RwLock;
 use tokio_util::sync::CancellationToken;
+use ultros_api_types::repricing::SellThrough;
 use ultros_api_types::trends::{TrendItem, TrendsData};
 use ultros_db::world_data::world_cache::{AnySelector, WorldCache};
 
@@ -1389,56 +1390,9 @@ impl AnalyzerService {
             .await
             .item_map
             .iter()
-            .map(|(i, values)| (i, values, values.iter().collect::<SoldWithin>()))
-            .flat_map(|(item, values, sold_within)| {
-                let mut prices: smallvec::SmallVec<[i32; SALE_HISTORY_SIZE]> = values
-                    .iter()
-                    .filter(|sale| {
-                        resale_options
-                            .filter_sale
-                            .as_ref()
-                            .map(|sale_within| {
-                                let sale_within = Duration::from(sale_within);
-                                now.signed_duration_since(sale.sale_date).lt(&sale_within)
-                            })
-                            .unwrap_or(true)
-                    })
-                    .map(|sale| sale.price_per_item)
-                    .collect();
-                if prices.is_empty() {
-                    return None;
-                }
-                let price_low = *prices.iter().min()?;
-                let price_high = *prices.iter().max()?;
-                // Lower-middle median: the upper-middle pick resolves a
-                // two-sale laundering pair to the higher of the two.
-                // ⚡ Bolt: Optimization: Use select_nth_unstable instead of sort_unstable for median calculation.
-                // This reduces time complexity from O(N log N) to O(N).
-                let median = crate::resale_eligibility::conservative_median(&mut prices);
-
-                // Velocity uses the whole buffer, not the filtered window: it
-                // is a rate estimate, and `sold_within` already carries the
-                // windowed view.
-                let span_days = values
-                    .iter()
-                    .map(|s| s.sale_date)
-                    .min()
-                    .map(|oldest| now.signed_duration_since(oldest).num_seconds() as f32 / 86_400.0)
-                    .unwrap_or(0.0);
-                let velocity_per_day =
-                    crate::resale_eligibility::velocity_per_day(values.len(), span_days);
-
-                Some((
-                    *item,
-                    SaleHistoryStats {
-                        median,
-                        sold_within,
-                        price_low,
-                        price_high,
-                        buffer_sale_count: values.len().min(u8::MAX as usize) as u8,
-                        velocity_per_day,
-                    },
-                ))
+            .flat_map(|(item, values)| {
+                let stats = sale_history_stats(values, resale_options.filter_sale.as_ref(), now)?;
+                Some((*item, stats))
             })
             .collect();
 
@@ -1624,6 +1578,125 @@ impl AnalyzerService {
         Some(possible_sales)
     }
 
+    /// Items worth hauling from `datacenter_id` back to `world_id`, judged on
+    /// the data center's cheapest listing alone. The route planner fetches
+    /// the full listing depth for whatever this returns, so the list is
+    /// capped at `max_candidates`, ranked by the profit the floor listing
+    /// alone would make across the items' sell-through caps.
+    pub(crate) async fn get_route_candidates(
+        &self,
+        world_id: i32,
+        datacenter_id: i32,
+        horizon_days: f32,
+        policy: crate::resale_eligibility::EligibilityPolicy,
+        max_candidates: usize,
+    ) -> Result<Vec<RouteCandidate>, AnalyzerError> {
+        if !self.initiated.load(Ordering::Relaxed) {
+            return Err(AnalyzerError::Uninitialized);
+        }
+        let now = Utc::now().naive_utc();
+        let sale_history = self
+            .recent_sale_history
+            .get(&world_id)
+            .ok_or(AnalyzerError::NotFound)?
+            .read()
+            .await;
+        let datacenter = self
+            .cheapest_items
+            .get(&AnySelector::Datacenter(datacenter_id))
+            .ok_or(AnalyzerError::NotFound)?
+            .read()
+            .await;
+        let home_listings = self
+            .cheapest_items
+            .get(&AnySelector::World(world_id))
+            .ok_or(AnalyzerError::NotFound)?
+            .read()
+            .await;
+        let game_data = xiv_gen_db::data();
+        let mut candidates: Vec<_> = datacenter
+            .item_map
+            .iter()
+            .flat_map(|(item_key, cheapest)| {
+                let values = sale_history.item_map.get(item_key)?;
+                let stats = sale_history_stats(values, None, now)?;
+                let est_sale_price = estimate_sale_price(
+                    stats.median,
+                    home_listings.item_map.get(item_key).map(|l| l.price),
+                );
+                let (floor_profit, return_on_investment) =
+                    flip_profit_and_roi(est_sale_price, cheapest.price)?;
+                if floor_profit <= 0 {
+                    return None;
+                }
+                let vendor_price = game_data
+                    .items
+                    .get(&xiv_gen::ItemId(item_key.item_id))
+                    .map(|i| i.price_mid)
+                    .unwrap_or(0);
+                if !policy.accepts(&crate::resale_eligibility::Candidate {
+                    est_sale_price,
+                    return_on_investment,
+                    velocity_per_day: stats.velocity_per_day,
+                    buffer_sale_count: stats.buffer_sale_count,
+                    vendor_price,
+                }) {
+                    return None;
+                }
+                Some(RouteCandidate {
+                    item_id: item_key.item_id,
+                    hq: item_key.hq,
+                    net_sale_price: (est_sale_price as f32 * POST_TAX_MULTIPLIER) as i32,
+                    sellable_units: crate::route_planner::sellable_units(
+                        stats.velocity_per_day,
+                        horizon_days,
+                    ),
+                    floor_profit,
+                })
+            })
+            .collect();
+        candidates
+            .sort_unstable_by_key(|c| Reverse(c.floor_profit as i64 * c.sellable_units as i64));
+        candidates.truncate(max_candidates);
+        Ok(candidates)
+    }
+
+    /// Recent sell-through on `world_id` for each of `items` that has sold
+    /// lately, for the repricing suggestions.
+    pub(crate) async fn get_sell_through(
+        &self,
+        world_id: i32,
+        items: impl IntoIterator<Item = ItemKey>,
+    ) -> Result<BTreeMap<ItemKey, SellThrough>, AnalyzerError> {
+        if !self.initiated.load(Ordering::Relaxed) {
+            return Err(AnalyzerError::Uninitialized);
+        }
+        let now = Utc::now().naive_utc();
+        let sale_history = self
+            .recent_sale_history
+            .get(&world_id)
+            .ok_or(AnalyzerError::NotFound)?
+            .read()
+            .await;
+        Ok(items
+            .into_iter()
+            .filter_map(|item_key| {
+                let values = sale_history.item_map.get(&item_key)?;
+                let stats = sale_history_stats(values, None, now)?;
+                Some((
+                    item_key,
+                    SellThrough {
+                        median: stats.median,
+                        low: stats.price_low,
+                        high: stats.price_high,
+                        velocity_per_day: stats.velocity_per_day,
+                        sale_count: stats.buffer_sale_count,
+                    },
+                ))
+            })
+            .collect())
+    }
+
     /// process listings in bulk.
     async fn add_listings(
         &self,
@@ -2012,6 +2085,56 @@ impl<'a> FromIterator<&'a SaleSummary> for SoldWithin {
     }
 }
 
+/// Buffer statistics for one item, or `None` when no sale falls inside
+/// `filter_sale`'s window.
+fn sale_history_stats(
+    values: &[SaleSummary],
+    filter_sale: Option<&SoldWithin>,
+    now: NaiveDateTime,
+) -> Option<SaleHistoryStats> {
+    let sold_within = values.iter().collect::<SoldWithin>();
+    let mut prices: smallvec::SmallVec<[i32; SALE_HISTORY_SIZE]> = values
+        .iter()
+        .filter(|sale| {
+            filter_sale
+                .map(|sale_within| {
+                    let sale_within = Duration::from(sale_within);
+                    now.signed_duration_since(sale.sale_date).lt(&sale_within)
+                })
+                .unwrap_or(true)
+        })
+        .map(|sale| sale.price_per_item)
+        .collect();
+    if prices.is_empty() {
+        return None;
+    }
+    let price_low = *prices.iter().min()?;
+    let price_high = *prices.iter().max()?;
+    // Lower-middle median: the upper-middle pick resolves a
+    // two-sale laundering pair to the higher of the two.
+    let median = crate::resale_eligibility::conservative_median(&mut prices);
+
+    // Velocity uses the whole buffer, not the filtered window: it
+    // is a rate estimate, and `sold_within` already carries the
+    // windowed view.
+    let span_days = values
+        .iter()
+        .map(|s| s.sale_date)
+        .min()
+        .map(|oldest| now.signed_duration_since(oldest).num_seconds() as f32 / 86_400.0)
+        .unwrap_or(0.0);
+    let velocity_per_day = crate::resale_eligibility::velocity_per_day(values.len(), span_days);
+
+    Some(SaleHistoryStats {
+        median,
+        sold_within,
+        price_low,
+        price_high,
+        buffer_sale_count: values.len().min(u8::MAX as usize) as u8,
+        velocity_per_day,
+    })
+}
+
 /// Per-item statistics derived from the bounded recent-sales buffer.
 /// Everything here has 100% coverage — no ClickHouse dependency.
 #[derive(Debug, Clone, Copy)]
@@ -2027,6 +2150,18 @@ pub(crate) struct SaleHistoryStats {
     pub(crate) velocity_per_day: Option<f32>,
 }
 
+/// An item the route planner should look at listing depth for.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RouteCandidate {
+    pub(crate) item_id: i32,
+    pub(crate) hq: bool,
+    /// Post-tax gil per unit on the home world.
+    pub(crate) net_sale_price: i32,
+    pub(crate) sellable_units: i32,
+    /// Post-tax profit per unit when bought at the data center floor.
+    pub(crate) floor_profit: i32,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct ResaleStats {
     pub(crate) profit: i32,
@@ -2102,6 +2237,7 @@ mod test {
             quantity: 1,
             hq,
             timestamp: chrono::DateTime::from_timestamp(0, 0).unwrap().naive_utc(),
+            materia: vec![],
         }
     }
 
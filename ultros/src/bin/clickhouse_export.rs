@@ -0,0 +1,148 @@
+//! Export sale history from ClickHouse to a file, for offline analysis.
+//!
+//! Usage:
+//!   cargo run --bin clickhouse_export -- --items 5057,5058 --world Aether
+//!   cargo run --bin clickhouse_export -- --items 5057 --world 34,35 \
+//!       --from 2026-01-01 --to 2026-07-01 --format parquet --daily --out sales.parquet
+//!
+//! Options:
+//!   --items   comma separated item ids (required)
+//!   --world   a world, datacenter or region name, or comma separated world ids
+//!             (required; names are resolved through Postgres)
+//!   --from    first day, YYYY-MM-DD (default: 30 days before --to)
+//!   --to      day after the last, YYYY-MM-DD (default: now)
+//!   --format  csv, ndjson or parquet (default: csv)
+//!   --daily   one row per item, world, quality and UTC day with OHLC, VWAP
+//!             and volume, instead of one row per sale
+//!   --hq / --nq  only high or normal quality sales
+//!   --out     output file (default: stdout)
+//!
+//! Same query and encodings as `/api/v1/export/sales`, without its item and
+//! window limits.
+
+use std::env;
+use std::io::Write;
+
+use anyhow::{Context, Result, anyhow, bail};
+use futures::TryStreamExt;
+use ultros_api_types::price_series::HqFilter;
+use ultros_clickhouse::{
+    ClickHouseClient,
+    export::ExportFormat,
+    queries::{ExportGranularity, SalesExport, export_sales},
+};
+use ultros_db::{UltrosDb, world_data::world_cache::WorldCache};
+
+struct Args {
+    export: SalesExport,
+    format: ExportFormat,
+    out: Option<String>,
+}
+
+fn parse_ids(value: &str) -> Result<Vec<i32>, std::num::ParseIntError> {
+    value.split(',').map(|id| id.trim().parse()).collect()
+}
+
+fn parse_day(value: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    let day = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .with_context(|| format!("{value} isn't a YYYY-MM-DD date"))?;
+    Ok(day.and_time(chrono::NaiveTime::MIN).and_utc())
+}
+
+async fn resolve_worlds(world: &str) -> Result<Vec<i32>> {
+    if let Ok(ids) = parse_ids(world) {
+        return Ok(ids);
+    }
+    let db = UltrosDb::connect().await?;
+    let world_cache = WorldCache::new(&db).await;
+    let selected = world_cache.lookup_value_by_name(world)?;
+    world_cache
+        .get_all_worlds_in(&selected)
+        .ok_or_else(|| anyhow!("{world} has no worlds"))
+}
+
+async fn parse_args() -> Result<Args> {
+    let mut items = None;
+    let mut world = None;
+    let mut from = None;
+    let mut to = None;
+    let mut format = ExportFormat::Csv;
+    let mut granularity = ExportGranularity::Sales;
+    let mut hq = HqFilter::Any;
+    let mut out = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{arg} needs a value"));
+        match arg.as_str() {
+            "--items" => items = Some(parse_ids(&value()?).context("--items")?),
+            "--world" => world = Some(value()?),
+            "--from" => from = Some(parse_day(&value()?)?),
+            "--to" => to = Some(parse_day(&value()?)?),
+            "--format" => {
+                let value = value()?;
+                format =
+                    ExportFormat::parse(&value).ok_or_else(|| anyhow!("unknown format {value}"))?;
+            }
+            "--out" => out = Some(value()?),
+            "--daily" => granularity = ExportGranularity::Daily,
+            "--hq" => hq = HqFilter::Hq,
+            "--nq" => hq = HqFilter::Nq,
+            _ => bail!("unknown argument {arg}"),
+        }
+    }
+
+    let item_ids = items.ok_or_else(|| anyhow!("--items is required"))?;
+    let world = world.ok_or_else(|| anyhow!("--world is required"))?;
+    let world_ids = resolve_worlds(&world).await?;
+    let to = to.unwrap_or_else(chrono::Utc::now);
+    let from = from.unwrap_or_else(|| to - chrono::Duration::days(30));
+    if from >= to {
+        bail!("--from must be before --to");
+    }
+    Ok(Args {
+        export: SalesExport {
+            item_ids,
+            world_ids,
+            hq,
+            from,
+            to,
+            granularity,
+        },
+        format,
+        out,
+    })
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let _ = dotenvy::dotenv();
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+
+    let args = parse_args().await?;
+    let ch = ClickHouseClient::from_env();
+
+    let mut out: Box<dyn Write> = match &args.out {
+        Some(path) => Box::new(std::io::BufWriter::new(
+            std::fs::File::create(path).with_context(|| format!("creating {path}"))?,
+        )),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let mut chunks = export_sales(&ch, &args.export, args.format)?;
+    let mut bytes = 0;
+    while let Some(chunk) = chunks.try_next().await? {
+        bytes += chunk.len();
+        out.write_all(&chunk)?;
+    }
+    out.flush()?;
+    if let Some(path) = &args.out {
+        eprintln!("wrote {bytes} bytes to {path}");
+    }
+    Ok(())
+}
@@ -0,0 +1,664 @@
+//! Recursive buy-or-craft solver behind `/api/v1/craft_plan`.
+//!
+//! Kept free of the database so the decisions are unit-testable: the handler
+//! builds a [`Catalog`] of the recipe tree from the game data, reads the
+//! order book of every item in it, and this module picks.
+//!
+//! Every node is priced both ways. Buying fills the quantity past on-hand
+//! stock from the order book (or a gil shop, when allowed); crafting prices
+//! each recipe's ingredients the same way, recursively. The cheaper complete
+//! option wins. Crafts are tried against a copy of the on-hand pool so a
+//! losing recipe doesn't spend stock the winner needed.
+//!
+//! Quantities multiply on the way down the tree and every node runs a fill
+//! solve sized by its quantity, so a plan has a budget of nodes and units;
+//! going over it refuses the plan rather than grinding through it.
+
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, HashMap, HashSet},
+};
+
+use ultros_api_types::{
+    ActiveListing, Retainer,
+    craft_plan::{
+        CraftPlan, CraftPlanRequest, CraftStep, PlanNode, PlanSource, Shortfall, VendorPurchase,
+        WorldPurchase, WorldShoppingList,
+    },
+    fill_cost::{FillCost, FillCostQuery, optimal_fill},
+};
+use xiv_gen::{ItemId, RecipeLevelTableId};
+
+/// `ItemSearchCategory` of crystals, shards and clusters.
+const CRYSTAL_SEARCH_CATEGORY: i32 = 59;
+
+/// Most nodes one plan may price.
+const MAX_PLAN_NODES: usize = 5_000;
+
+/// Most units, summed over every priced node, one plan may ask for.
+const MAX_PLAN_UNITS: i64 = 500_000;
+
+#[derive(Debug, thiserror::Error)]
+#[error("the recipe tree is too large to plan at this quantity")]
+pub(crate) struct PlanTooLarge;
+
+pub(crate) type OrderBook = Vec<(ActiveListing, Option<Retainer>)>;
+
+#[derive(Debug, Clone)]
+pub(crate) struct PlannerRecipe {
+    pub(crate) recipe_id: i32,
+    pub(crate) craft_type: i32,
+    /// Crafter level the recipe needs.
+    pub(crate) level: i32,
+    pub(crate) amount_result: i32,
+    /// `(item_id, amount)` per craft.
+    pub(crate) ingredients: Vec<(i32, i32)>,
+}
+
+/// The slice of the game data one plan can touch.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Catalog {
+    /// Every item in the recipe tree, the requested one included.
+    pub(crate) items: HashSet<i32>,
+    pub(crate) recipes: HashMap<i32, Vec<PlannerRecipe>>,
+    /// Gil shop price per unit.
+    pub(crate) vendor_prices: HashMap<i32, i32>,
+    pub(crate) crystals: HashSet<i32>,
+}
+
+impl Catalog {
+    /// Walks the recipe tree under `item_id` down to `max_depth` crafts.
+    pub(crate) fn from_game_data(data: &xiv_gen::Data, item_id: i32, max_depth: u8) -> Self {
+        let mut by_output: HashMap<i32, Vec<&xiv_gen::Recipe>> = HashMap::new();
+        for recipe in data.recipes.values() {
+            if recipe.item_result > 0 && recipe.amount_result > 0 {
+                by_output
+                    .entry(recipe.item_result)
+                    .or_default()
+                    .push(recipe);
+            }
+        }
+        let sold_by_vendor: HashSet<i32> = data
+            .gil_shop_items
+            .values()
+            .flatten()
+            .map(|shop_item| shop_item.item)
+            .collect();
+
+        let mut catalog = Catalog::default();
+        let mut frontier = vec![item_id];
+        for depth in 0..=max_depth {
+            let mut next = vec![];
+            for item_id in frontier {
+                if !catalog.items.insert(item_id) {
+                    continue;
+                }
+                let item = data.items.get(&ItemId(item_id));
+                if item.is_some_and(|i| i.item_search_category == CRYSTAL_SEARCH_CATEGORY) {
+                    catalog.crystals.insert(item_id);
+                }
+                if let Some(price) = item
+                    .filter(|i| i.price_mid > 0 && sold_by_vendor.contains(&item_id))
+                    .map(|i| i.price_mid as i32)
+                {
+                    catalog.vendor_prices.insert(item_id, price);
+                }
+                if depth == max_depth {
+                    continue;
+                }
+                let recipes: Vec<PlannerRecipe> = by_output
+                    .get(&item_id)
+                    .into_iter()
+                    .flatten()
+                    .map(|recipe| PlannerRecipe {
+                        recipe_id: recipe.key_id.0,
+                        craft_type: recipe.craft_type,
+                        level: data
+                            .recipe_level_tables
+                            .get(&RecipeLevelTableId(recipe.recipe_level_table))
+                            .map(|table| table.class_job_level as i32)
+                            .unwrap_or_default(),
+                        amount_result: recipe.amount_result,
+                        ingredients: recipe
+                            .ingredient
+                            .iter()
+                            .zip(recipe.amount_ingredient)
+                            .filter(|(item_id, amount)| **item_id > 0 && *amount > 0)
+                            .map(|(item_id, amount)| (*item_id, amount))
+                            .collect(),
+                    })
+                    .collect();
+                next.extend(
+                    recipes
+                        .iter()
+                        .flat_map(|r| r.ingredients.iter().map(|(i, _)| *i)),
+                );
+                if !recipes.is_empty() {
+                    catalog.recipes.insert(item_id, recipes);
+                }
+            }
+            frontier = next;
+        }
+        catalog
+    }
+}
+
+struct Planner<'a> {
+    catalog: &'a Catalog,
+    request: &'a CraftPlanRequest,
+    books: HashMap<i32, Vec<ActiveListing>>,
+    max_depth: u8,
+    /// Nodes priced and units asked for so far.
+    spent: Cell<(usize, i64)>,
+}
+
+/// A priced way of obtaining one node's quantity past on-hand.
+struct Sourcing {
+    source: PlanSource,
+    cost: i64,
+    complete: bool,
+    craft: Option<CraftStep>,
+    pool: Option<HashMap<i32, i32>>,
+}
+
+impl Planner<'_> {
+    /// Charges one node of `quantity` units; false once the plan is over.
+    fn charge(&self, quantity: i32) -> bool {
+        let (nodes, units) = self.spent.get();
+        let spent = (nodes + 1, units + quantity as i64);
+        self.spent.set(spent);
+        spent.0 <= MAX_PLAN_NODES && spent.1 <= MAX_PLAN_UNITS
+    }
+
+    fn over_budget(&self) -> bool {
+        let (nodes, units) = self.spent.get();
+        nodes > MAX_PLAN_NODES || units > MAX_PLAN_UNITS
+    }
+
+    fn can_craft(&self, recipe: &PlannerRecipe) -> bool {
+        self.request.crafter_levels.as_ref().is_none_or(|levels| {
+            usize::try_from(recipe.craft_type)
+                .ok()
+                .and_then(|job| levels.get(job))
+                .is_some_and(|level| *level >= recipe.level)
+        })
+    }
+
+    fn market_cost(&self, item_id: i32, quantity: i32) -> Option<i64> {
+        let book = self.books.get(&item_id)?;
+        let (chosen, complete) = optimal_fill(book, quantity);
+        complete.then(|| {
+            chosen
+                .into_iter()
+                .map(|i| book[i].price_per_unit as i64 * book[i].quantity as i64)
+                .sum()
+        })
+    }
+
+    fn vendor_cost(&self, item_id: i32, quantity: i32) -> Option<i64> {
+        if !self.request.use_vendors {
+            return None;
+        }
+        let price = self.catalog.vendor_prices.get(&item_id)?;
+        Some(*price as i64 * quantity as i64)
+    }
+
+    fn craft(
+        &self,
+        recipe: &PlannerRecipe,
+        need: i32,
+        depth: u8,
+        path: &mut Vec<i32>,
+        pool: &HashMap<i32, i32>,
+    ) -> Sourcing {
+        let crafts = (need - 1) / recipe.amount_result + 1;
+        let mut trial = pool.clone();
+        let ingredients: Vec<PlanNode> = recipe
+            .ingredients
+            .iter()
+            .map(|(item_id, amount)| match amount.checked_mul(crafts) {
+                Some(quantity) => self.node(*item_id, quantity, depth + 1, path, &mut trial),
+                None => PlanNode {
+                    item_id: *item_id,
+                    quantity: i32::MAX,
+                    from_on_hand: 0,
+                    source: PlanSource::Unavailable,
+                    cost: 0,
+                    buy_cost: None,
+                    complete: false,
+                    craft: None,
+                },
+            })
+            .collect();
+        Sourcing {
+            source: PlanSource::Craft,
+            cost: ingredients.iter().map(|i| i.cost).sum(),
+            complete: ingredients.iter().all(|i| i.complete),
+            craft: Some(CraftStep {
+                recipe_id: recipe.recipe_id,
+                craft_type: recipe.craft_type,
+                crafts,
+                amount_result: recipe.amount_result,
+                ingredients,
+            }),
+            pool: Some(trial),
+        }
+    }
+
+    fn node(
+        &self,
+        item_id: i32,
+        quantity: i32,
+        depth: u8,
+        path: &mut Vec<i32>,
+        pool: &mut HashMap<i32, i32>,
+    ) -> PlanNode {
+        let held = pool.entry(item_id).or_default();
+        let from_on_hand = (*held).clamp(0, quantity);
+        *held -= from_on_hand;
+        let need = quantity - from_on_hand;
+        let leaf = |source, cost, buy_cost, complete| PlanNode {
+            item_id,
+            quantity,
+            from_on_hand,
+            source,
+            cost,
+            buy_cost,
+            complete,
+            craft: None,
+        };
+        if need == 0 {
+            return leaf(PlanSource::OnHand, 0, Some(0), true);
+        }
+        if !self.charge(need) {
+            return leaf(PlanSource::Unavailable, 0, None, false);
+        }
+        if !self.request.include_crystals && self.catalog.crystals.contains(&item_id) {
+            return leaf(PlanSource::Excluded, 0, None, true);
+        }
+
+        let buy = [
+            (PlanSource::Market, self.market_cost(item_id, need)),
+            (PlanSource::Vendor, self.vendor_cost(item_id, need)),
+        ]
+        .into_iter()
+        .filter_map(|(source, cost)| Some((source, cost?)))
+        .min_by_key(|(_, cost)| *cost);
+
+        let mut best_craft: Option<Sourcing> = None;
+        if depth < self.max_depth && !path.contains(&item_id) {
+            path.push(item_id);
+            for recipe in self
+                .catalog
+                .recipes
+                .get(&item_id)
+                .into_iter()
+                .flatten()
+                .filter(|recipe| self.can_craft(recipe))
+            {
+                let craft = self.craft(recipe, need, depth, path, pool);
+                // Complete beats incomplete, then cheaper beats dearer.
+                if best_craft
+                    .as_ref()
+                    .is_none_or(|best| (!craft.complete, craft.cost) < (!best.complete, best.cost))
+                {
+                    best_craft = Some(craft);
+                }
+            }
+            path.pop();
+        }
+
+        let chosen = match (buy, best_craft) {
+            (Some((_, buy_cost)), Some(craft)) if craft.complete && craft.cost < buy_cost => craft,
+            (Some((source, cost)), _) => Sourcing {
+                source,
+                cost,
+                complete: true,
+                craft: None,
+                pool: None,
+            },
+            (None, Some(craft)) => craft,
+            (None, None) => return leaf(PlanSource::Unavailable, 0, None, false),
+        };
+        if let Some(trial) = chosen.pool {
+            *pool = trial;
+        }
+        PlanNode {
+            item_id,
+            quantity,
+            from_on_hand,
+            source: chosen.source,
+            cost: chosen.cost,
+            buy_cost: buy.map(|(_, cost)| cost),
+            complete: chosen.complete,
+            craft: chosen.craft,
+        }
+    }
+}
+
+/// What the plan buys or can't source, summed per item across the tree.
+#[derive(Default)]
+struct Demand {
+    market: BTreeMap<i32, i32>,
+    vendor: BTreeMap<i32, i32>,
+    unavailable: BTreeMap<i32, i32>,
+}
+
+impl Demand {
+    fn collect(&mut self, node: &PlanNode) {
+        let need = node.quantity - node.from_on_hand;
+        match node.source {
+            PlanSource::Market => *self.market.entry(node.item_id).or_default() += need,
+            PlanSource::Vendor => *self.vendor.entry(node.item_id).or_default() += need,
+            PlanSource::Unavailable => {
+                let short = self.unavailable.entry(node.item_id).or_default();
+                *short = short.saturating_add(need);
+            }
+            PlanSource::Craft => {
+                for child in node.craft.iter().flat_map(|step| &step.ingredients) {
+                    self.collect(child);
+                }
+            }
+            PlanSource::OnHand | PlanSource::Excluded => {}
+        }
+    }
+}
+
+/// Plans `request` over `books`, the order book of every item in
+/// `catalog.items` across the worlds being shopped.
+pub(crate) fn plan_craft(
+    catalog: &Catalog,
+    request: &CraftPlanRequest,
+    books: &HashMap<i32, OrderBook>,
+) -> Result<CraftPlan, PlanTooLarge> {
+    let planner = Planner {
+        catalog,
+        request,
+        books: books
+            .iter()
+            .map(|(item_id, book)| (*item_id, book.iter().map(|(l, _)| l.clone()).collect()))
+            .collect(),
+        max_depth: request.depth(),
+        spent: Cell::new((0, 0)),
+    };
+    let mut pool = request.on_hand.clone();
+    let root = planner.node(request.item_id, request.quantity, 0, &mut vec![], &mut pool);
+    if planner.over_budget() {
+        return Err(PlanTooLarge);
+    }
+
+    let mut demand = Demand::default();
+    demand.collect(&root);
+
+    let mut worlds: BTreeMap<i32, Vec<WorldPurchase>> = BTreeMap::new();
+    for (&item_id, &quantity) in &demand.market {
+        let fill = FillCost::solve(
+            FillCostQuery {
+                item_id,
+                hq: None,
+                quantity,
+            },
+            books.get(&item_id).cloned().unwrap_or_default(),
+        );
+        // Branches that each fit the book can still overrun it together.
+        if !fill.complete {
+            *demand.unavailable.entry(item_id).or_default() += quantity - fill.filled_quantity;
+        }
+        for stop in fill.stops {
+            let purchases = worlds.entry(stop.world_id).or_default();
+            match purchases.iter_mut().find(|p| p.item_id == item_id) {
+                Some(purchase) => {
+                    purchase.quantity += stop.quantity;
+                    purchase.cost += stop.cost;
+                    purchase.stops.push(stop);
+                }
+                None => purchases.push(WorldPurchase {
+                    item_id,
+                    quantity: stop.quantity,
+                    cost: stop.cost,
+                    stops: vec![stop],
+                }),
+            }
+        }
+    }
+    let shopping: Vec<WorldShoppingList> = worlds
+        .into_iter()
+        .map(|(world_id, purchases)| WorldShoppingList {
+            world_id,
+            cost: purchases.iter().map(|p| p.cost).sum(),
+            purchases,
+        })
+        .collect();
+    let vendor: Vec<VendorPurchase> = demand
+        .vendor
+        .iter()
+        .map(|(&item_id, &quantity)| {
+            let unit_price = catalog.vendor_prices[&item_id];
+            VendorPurchase {
+                item_id,
+                quantity,
+                unit_price,
+                cost: unit_price as i64 * quantity as i64,
+            }
+        })
+        .collect();
+
+    Ok(CraftPlan {
+        total_cost: shopping.iter().map(|w| w.cost).sum::<i64>()
+            + vendor.iter().map(|v| v.cost).sum::<i64>(),
+        root,
+        shopping,
+        vendor,
+        unavailable: demand
+            .unavailable
+            .into_iter()
+            .filter(|(_, quantity)| *quantity > 0)
+            .map(|(item_id, quantity)| Shortfall { item_id, quantity })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    const SWORD: i32 = 10;
+    const INGOT: i32 = 20;
+    const ORE: i32 = 30;
+    const CRYSTAL: i32 = 2;
+    /// Blacksmith, in `CraftType` order.
+    const BSM: i32 = 1;
+
+    fn listing(
+        id: i32,
+        world_id: i32,
+        item_id: i32,
+        price_per_unit: i32,
+        quantity: i32,
+    ) -> (ActiveListing, Option<Retainer>) {
+        (
+            ActiveListing {
+                id,
+                world_id,
+                item_id,
+                retainer_id: id,
+                price_per_unit,
+                quantity,
+                hq: false,
+                timestamp: NaiveDateTime::default(),
+                materia: vec![],
+            },
+            None,
+        )
+    }
+
+    fn recipe(
+        recipe_id: i32,
+        level: i32,
+        amount_result: i32,
+        ingredients: Vec<(i32, i32)>,
+    ) -> PlannerRecipe {
+        PlannerRecipe {
+            recipe_id,
+            craft_type: BSM,
+            level,
+            amount_result,
+            ingredients,
+        }
+    }
+
+    /// A sword from two ingots and a crystal; an ingot (two per craft) from
+    /// three ore and a crystal.
+    fn catalog() -> Catalog {
+        Catalog {
+            items: HashSet::from([SWORD, INGOT, ORE, CRYSTAL]),
+            recipes: HashMap::from([
+                (
+                    SWORD,
+                    vec![recipe(1, 50, 1, vec![(INGOT, 2), (CRYSTAL, 1)])],
+                ),
+                (INGOT, vec![recipe(2, 40, 2, vec![(ORE, 3), (CRYSTAL, 1)])]),
+            ]),
+            vendor_prices: HashMap::from([(ORE, 15)]),
+            crystals: HashSet::from([CRYSTAL]),
+        }
+    }
+
+    fn request(quantity: i32) -> CraftPlanRequest {
+        CraftPlanRequest {
+            item_id: SWORD,
+            quantity,
+            ..Default::default()
+        }
+    }
+
+    fn books(listings: Vec<(ActiveListing, Option<Retainer>)>) -> HashMap<i32, OrderBook> {
+        let mut books: HashMap<i32, OrderBook> = HashMap::new();
+        for listing in listings {
+            books.entry(listing.0.item_id).or_default().push(listing);
+        }
+        books
+    }
+
+    #[test]
+    fn crafts_when_the_ingredients_are_cheaper() {
+        // Four ingots bought means taking the stack of ten at 100, 1000 in
+        // all; crafting them takes 6 ore at 10.
+        let books = books(vec![
+            listing(1, 1, SWORD, 1000, 2),
+            listing(2, 1, INGOT, 100, 10),
+            listing(3, 2, ORE, 10, 99),
+        ]);
+        let plan = plan_craft(&catalog(), &request(2), &books).unwrap();
+
+        assert_eq!(plan.root.source, PlanSource::Craft);
+        assert_eq!(plan.root.buy_cost, Some(2000));
+        assert_eq!(plan.crafts(), vec![(INGOT, 2, 2), (SWORD, 1, 2)]);
+        assert_eq!(plan.total_cost, 990);
+        assert_eq!(plan.shopping.len(), 1);
+        assert_eq!(plan.shopping[0].world_id, 2);
+        assert!(plan.is_complete());
+    }
+
+    #[test]
+    fn buys_when_crafting_costs_more() {
+        let books = books(vec![
+            listing(1, 1, SWORD, 100, 1),
+            listing(2, 1, INGOT, 100, 10),
+        ]);
+        let plan = plan_craft(&catalog(), &request(1), &books).unwrap();
+
+        assert_eq!(plan.root.source, PlanSource::Market);
+        assert!(plan.root.craft.is_none());
+        assert_eq!(plan.total_cost, 100);
+    }
+
+    #[test]
+    fn on_hand_stock_is_spent_first() {
+        let books = books(vec![listing(1, 1, INGOT, 100, 10)]);
+        let mut request = request(2);
+        request.on_hand = HashMap::from([(INGOT, 3)]);
+        let plan = plan_craft(&catalog(), &request, &books).unwrap();
+
+        let ingots = &plan.root.craft.as_ref().unwrap().ingredients[0];
+        assert_eq!((ingots.quantity, ingots.from_on_hand), (4, 3));
+        assert_eq!(plan.total_cost, 1000);
+    }
+
+    #[test]
+    fn recipes_above_the_crafter_levels_are_skipped() {
+        let books = books(vec![
+            listing(1, 1, SWORD, 1000, 1),
+            listing(2, 1, INGOT, 100, 10),
+            listing(3, 1, ORE, 10, 99),
+        ]);
+        let mut request = request(1);
+        request.crafter_levels = Some(vec![90, 45]);
+        let plan = plan_craft(&catalog(), &request, &books).unwrap();
+
+        // The sword needs level 50, so it's bought.
+        assert_eq!(plan.root.source, PlanSource::Market);
+        request.crafter_levels = Some(vec![0, 50]);
+        let plan = plan_craft(&catalog(), &request, &books).unwrap();
+        assert_eq!(plan.crafts(), vec![(INGOT, 2, 1), (SWORD, 1, 1)]);
+    }
+
+    #[test]
+    fn vendors_only_when_allowed() {
+        let books = books(vec![listing(1, 1, ORE, 20, 3)]);
+        let plan = plan_craft(&catalog(), &request(1), &books).unwrap();
+        assert_eq!(plan.total_cost, 60);
+        assert!(plan.vendor.is_empty());
+
+        let mut request = request(1);
+        request.use_vendors = true;
+        let plan = plan_craft(&catalog(), &request, &books).unwrap();
+        assert_eq!(plan.total_cost, 45);
+        assert!(plan.shopping.is_empty());
+        assert_eq!(plan.vendor[0].unit_price, 15);
+    }
+
+    #[test]
+    fn crystals_are_left_out_unless_asked_for() {
+        let books = books(vec![
+            listing(1, 1, ORE, 10, 3),
+            listing(2, 1, CRYSTAL, 5, 2),
+        ]);
+        let plan = plan_craft(&catalog(), &request(1), &books).unwrap();
+        assert_eq!(plan.total_cost, 30);
+
+        let mut request = request(1);
+        request.include_crystals = true;
+        let plan = plan_craft(&catalog(), &request, &books).unwrap();
+        assert_eq!(plan.total_cost, 40);
+    }
+
+    #[test]
+    fn oversized_trees_are_refused() {
+        let mut catalog = catalog();
+        catalog
+            .recipes
+            .insert(INGOT, vec![recipe(2, 40, 1, vec![(ORE, 999)])]);
+        let books = books(vec![listing(1, 1, ORE, 10, 99)]);
+
+        assert!(plan_craft(&catalog, &request(1), &books).is_ok());
+        assert!(plan_craft(&catalog, &request(999), &books).is_err());
+    }
+
+    #[test]
+    fn missing_ingredients_are_reported() {
+        let books = books(vec![listing(1, 1, ORE, 10, 2)]);
+        let plan = plan_craft(&catalog(), &request(1), &books).unwrap();
+
+        assert!(!plan.root.complete);
+        assert_eq!(
+            plan.unavailable,
+            vec![Shortfall {
+                item_id: ORE,
+                quantity: 3
+            }]
+        );
+        assert!(!plan.is_complete());
+    }
+}
@@ -0,0 +1,132 @@
+use std::fmt::Write;
+
+use anyhow::anyhow;
+use poise::CreateReply;
+use poise::serenity_prelude::CreateEmbed;
+use ultros_api_types::craft_plan::CraftPlanRequest;
+use ultros_api_types::world_helper::AnySelector;
+use ultros_db::world_data::world_cache::AnySelector as DbAnySelector;
+
+use crate::discord::ffxiv::helpers;
+use crate::discord::ffxiv::helpers::{discord_locale_to_xiv_language, localized_item_name};
+use crate::web::api::craft_plan::{MAX_PLAN_QUANTITY, craft_plan as plan};
+
+use super::{Context, Error, ULTROS_COLOR};
+
+/// Embed descriptions are capped at 4096 characters.
+const DESCRIPTION_LIMIT: usize = 4000;
+
+/// Cheapest way to get an item: what to buy on which world and what to craft
+#[poise::command(slash_command, prefix_command)]
+pub(crate) async fn craft_plan(
+    ctx: Context<'_>,
+    #[description = "Item to make"]
+    #[autocomplete = "helpers::autocomplete_item"]
+    item: String,
+    #[description = "How many (default 1)"] quantity: Option<i32>,
+    #[description = "World/DC/region to shop in (default: your home world)"] world: Option<String>,
+    #[description = "Buy ingredients from gil shops when cheaper (default: no)"]
+    use_vendors: Option<bool>,
+    #[description = "Price crystals, shards and clusters too (default: no)"]
+    include_crystals: Option<bool>,
+) -> Result<(), Error> {
+    let quantity = quantity.unwrap_or(1);
+    if !(1..=MAX_PLAN_QUANTITY).contains(&quantity) {
+        ctx.say(format!(
+            "Quantity must be between 1 and {MAX_PLAN_QUANTITY}."
+        ))
+        .await?;
+        return Ok(());
+    }
+    let item_id = helpers::resolve_item_id(&item).ok_or_else(|| anyhow!("unknown item: {item}"))?;
+    ctx.defer().await?;
+    let world_cache = &ctx.data().world_cache;
+    let worlds = match world {
+        Some(name) => {
+            let result = world_cache
+                .lookup_value_by_name(&name)
+                .map_err(|e| anyhow!("unknown world/datacenter/region '{name}': {e}"))?;
+            world_cache
+                .get_all_worlds_in(&result)
+                .ok_or_else(|| anyhow!("no worlds in '{name}'"))?
+        }
+        None => match helpers::user_home_world_selector(&ctx).await? {
+            AnySelector::World(world_id) => vec![world_id],
+            _ => return Err(anyhow!("home world selector wasn't a world").into()),
+        },
+    };
+    let request = CraftPlanRequest {
+        item_id,
+        quantity,
+        use_vendors: use_vendors.unwrap_or(false),
+        include_crystals: include_crystals.unwrap_or(false),
+        ..Default::default()
+    };
+    let plan = plan(&ctx.data().db, &worlds, &request).await?;
+
+    let lang = discord_locale_to_xiv_language(ctx.locale());
+    let name = |item_id: i32| localized_item_name(item_id, lang);
+    let mut description = format!("**Total: {} gil**\n", plan.total_cost);
+    let crafts = plan.crafts();
+    if !crafts.is_empty() {
+        description.push_str("\n__Craft__\n");
+        for (item_id, _, crafts) in crafts {
+            let _ = writeln!(description, "{} × {crafts}", name(item_id));
+        }
+    }
+    for list in &plan.shopping {
+        let world_name = world_cache
+            .lookup_selector(&DbAnySelector::World(list.world_id))
+            .map(|w| w.get_name().to_string())
+            .unwrap_or_else(|_| list.world_id.to_string());
+        let _ = writeln!(description, "\n__Buy on {world_name}__ ({} gil)", list.cost);
+        for purchase in &list.purchases {
+            let _ = writeln!(
+                description,
+                "{} × {} — {} gil",
+                name(purchase.item_id),
+                purchase.quantity,
+                purchase.cost
+            );
+        }
+    }
+    if !plan.vendor.is_empty() {
+        description.push_str("\n__Buy from vendors__\n");
+        for purchase in &plan.vendor {
+            let _ = writeln!(
+                description,
+                "{} × {} — {} gil",
+                name(purchase.item_id),
+                purchase.quantity,
+                purchase.cost
+            );
+        }
+    }
+    if !plan.is_complete() {
+        description.push_str("\n__Couldn't source__\n");
+        for shortfall in &plan.unavailable {
+            let _ = writeln!(
+                description,
+                "{} × {}",
+                name(shortfall.item_id),
+                shortfall.quantity
+            );
+        }
+    }
+    if description.len() > DESCRIPTION_LIMIT {
+        let cut = description.floor_char_boundary(DESCRIPTION_LIMIT);
+        description.truncate(cut);
+        description.push('…');
+    }
+
+    ctx.send(
+        CreateReply::default().embed(
+            CreateEmbed::new()
+                .color(ULTROS_COLOR)
+                .title(format!("{} × {quantity}", name(item_id)))
+                .description(description),
+        ),
+    )
+    .await?;
+    Ok(())
+}
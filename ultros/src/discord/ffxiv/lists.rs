@@ -5,9 +5,13 @@ use crate::discord::ffxiv::helpers::{
 };
 use anyhow::anyhow;
 use itertools::Itertools;
-use poise::serenity_prelude::User;
-use ultros_api_types::list::ListPermission;
-use ultros_db::world_data::world_cache::AnySelector;
+use poise::serenity_prelude::{CreateAttachment, User};
+use ultros_api_types::{
+    list::{ListItem, ListPermission},
+    teamcraft::{self, RecipeParts},
+};
+use ultros_db::{entity::list_item, world_data::world_cache::AnySelector};
+use xiv_gen::RecipeId;
 #[poise::command(
     slash_command,
     prefix_command,
@@ -20,7 +24,9 @@ use ultros_db::world_data::world_cache::AnySelector;
         "show_lists",
         "share_user",
         "create_invite",
-        "redeem_invite"
+        "redeem_invite",
+        "import_teamcraft",
+        "export_teamcraft"
     )
 )]
 pub(crate) async fn list(ctx: Context<'_>) -> Result<(), Error> {
@@ -430,6 +436,104 @@ async fn show_list(
     Ok(())
 }
 
+/// Ingredients of a Teamcraft recipe row, from the game data.
+fn recipe_parts(recipe_id: i32) -> Option<RecipeParts> {
+    let recipe = xiv_gen_db::data().recipes.get(&RecipeId(recipe_id))?;
+    Some(RecipeParts {
+        amount_result: recipe.amount_result,
+        ingredients: recipe
+            .ingredient
+            .iter()
+            .zip(recipe.amount_ingredient)
+            .filter(|(item_id, _)| **item_id != 0)
+            .map(|(item_id, amount)| (*item_id, amount))
+            .collect(),
+    })
+}
+
+/// Add a Teamcraft import link or list JSON to a list
+#[poise::command(slash_command, prefix_command)]
+async fn import_teamcraft(
+    ctx: Context<'_>,
+    #[description = "name of the list to add the items to"]
+    #[autocomplete = "autocomplete_list_name"]
+    list_name: String,
+    #[description = "Teamcraft import link, import text or list JSON"] teamcraft: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let author_id = ctx.author().id.get() as i64;
+    let list = resolve_list(&ctx, author_id, &list_name)
+        .await?
+        .ok_or(anyhow!("List not found"))?;
+    let items = teamcraft::to_list_items(&teamcraft::parse(&teamcraft)?, recipe_parts);
+    let count = items.len();
+    ctx.data()
+        .db
+        .add_items_to_list(
+            &list,
+            author_id,
+            items.into_iter().map(|item| {
+                list_item::Model::from(ListItem {
+                    list_id: list.id,
+                    ..item
+                })
+            }),
+        )
+        .await?;
+    ctx.send(
+        poise::CreateReply::default().embed(
+            poise::serenity_prelude::CreateEmbed::new()
+                .title("Teamcraft list imported")
+                .description(format!("{count} items added to list {}", list.name)),
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Export a list to Teamcraft, with acquired progress
+#[poise::command(slash_command, prefix_command)]
+async fn export_teamcraft(
+    ctx: Context<'_>,
+    #[description = "list to export"]
+    #[autocomplete = "autocomplete_list_name"]
+    list_name: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let author_id = ctx.author().id.get() as i64;
+    let list = resolve_list(&ctx, author_id, &list_name)
+        .await?
+        .ok_or(anyhow!("List not found"))?;
+    let items: Vec<ListItem> = ctx
+        .data()
+        .db
+        .get_list_items(list.id, author_id)
+        .await?
+        .into_iter()
+        .map(ListItem::from)
+        .collect();
+    let link = teamcraft::import_link(&items);
+    // Embed descriptions cap out at 4096 characters; long lists only get the file.
+    let description = if link.len() <= 4000 {
+        format!("[Open in Teamcraft]({link})\nThe attached JSON keeps acquired progress.")
+    } else {
+        "The list is too long for an import link here. The attached JSON keeps acquired progress."
+            .to_string()
+    };
+    let json = teamcraft::export_json(&list.name, &items);
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(
+                poise::serenity_prelude::CreateEmbed::new()
+                    .title(format!("{} for Teamcraft", list.name))
+                    .description(description),
+            )
+            .attachment(CreateAttachment::bytes(json, "teamcraft.json")),
+    )
+    .await?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
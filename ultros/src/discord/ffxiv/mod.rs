@@ -3,6 +3,7 @@ pub mod admin;
 mod alert;
 mod analyze;
 mod character;
+mod craft_plan;
 mod helpers;
 mod item_prices;
 mod lists;
@@ -12,6 +13,7 @@ use admin::rescan_market;
 use alert::alert;
 use analyze::analyze;
 use character::character;
+use craft_plan::craft_plan;
 use item_prices::prices;
 use lists::list;
 use poise::serenity_prelude::Color;
@@ -28,6 +30,7 @@ pub(crate) const ULTROS_COLOR: Color = Color::DARK_PURPLE;
         "analyze",
         "list",
         "prices",
+        "craft_plan",
         "rescan_market",
         "alert"
     )
@@ -18,6 +18,7 @@ use super::{Context, Error};
         "remove",
         "check_listings",
         "check_undercuts",
+        "ledger",
         "add_undercut_alert",
         "remove_undercut_alert"
     )
@@ -33,7 +34,7 @@ pub(crate) async fn retainer(ctx: Context<'_>) -> Result<(), Error> {
                      1. Verify your character at https://ultros.app\n\
                      2. `/ffxiv retainer add` — claim one of your retainers\n\
                      3. `/ffxiv retainer add_undercut_alert` — get alerts in this channel\n\n\
-                     **See also:** `/ffxiv retainer list`, `check_listings`, `check_undercuts`.",
+                     **See also:** `/ffxiv retainer list`, `check_listings`, `check_undercuts`, `ledger`.",
                 ),
         ),
     )
@@ -263,6 +264,95 @@ async fn check_listings(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+fn format_time_to_sell(seconds: Option<i64>) -> String {
+    match seconds {
+        None => "-".to_string(),
+        Some(s) if s < 3600 => format!("{}m", s / 60),
+        Some(s) if s < 86400 => format!("{}h", s / 3600),
+        Some(s) => format!("{}d", s / 86400),
+    }
+}
+
+/// Shows what your retainers have sold: revenue after tax, units and time to sell
+#[poise::command(slash_command)]
+async fn ledger(
+    ctx: Context<'_>,
+    #[description = "How many days back to look (1-365, default 30)"] days: Option<i64>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let days = days.unwrap_or(30).clamp(1, 365);
+    let since = chrono::Utc::now().naive_utc() - chrono::Duration::days(days);
+    let ledger = ctx
+        .data()
+        .db
+        .get_retainer_ledger(ctx.author().id.get(), since)
+        .await?;
+    if ledger.total.sales == 0 {
+        ctx.say(format!(
+            "No sales credited to your retainers in the last {days} days. \
+             Sales are matched as they happen, so only retainers you've added are tracked."
+        ))
+        .await?;
+        return Ok(());
+    }
+    let user_lang = discord_locale_to_xiv_language(ctx.locale());
+    let total = &ledger.total;
+    let summary = format!(
+        "**{}** sales, **{}** units over the last {days} days\n\
+         Gross **{}** gil, tax **{}** gil, revenue **{}** gil\n\
+         Average time to sell: **{}**",
+        total.sales,
+        total.units,
+        total.gross,
+        total.tax,
+        total.revenue(),
+        format_time_to_sell(total.avg_seconds_to_sell)
+    );
+
+    let mut retainers = format!(
+        "```{:<20} {:>6} {:>12} {:>6}\n",
+        "Retainer", "Sales", "Revenue", "Sells"
+    );
+    // Embed fields hold 1024 characters, so both tables are cut short.
+    for row in ledger.retainers.iter().take(15) {
+        let _ = writeln!(
+            retainers,
+            "{:<20} {:>6} {:>12} {:>6}",
+            row.retainer_name,
+            row.totals.sales,
+            row.totals.revenue(),
+            format_time_to_sell(row.totals.avg_seconds_to_sell)
+        );
+    }
+    retainers += "```";
+
+    let mut items = format!("```{:<30} {:>6} {:>12}\n", "Item name", "Units", "Revenue");
+    for row in ledger.items.iter().take(10) {
+        let item_name = localized_item_name(row.item_id, user_lang);
+        let _ = writeln!(
+            items,
+            "{:<30} {:>6} {:>12}",
+            item_name,
+            row.totals.units,
+            row.totals.revenue()
+        );
+    }
+    items += "```";
+
+    ctx.send(
+        poise::CreateReply::default().embed(
+            poise::serenity_prelude::CreateEmbed::new()
+                .title("Retainer ledger")
+                .description(summary)
+                .field("Per retainer", retainers, false)
+                .field("Top items", items, false)
+                .color(Color::from_rgb(123, 0, 123)),
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
 /// Adds a retainer to your profile (requires a verified FFXIV character)
 #[poise::command(slash_command)]
 async fn add(
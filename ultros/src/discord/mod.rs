@@ -13,6 +13,7 @@ use crate::{
     analyzer_service::AnalyzerService,
     event::{EventReceivers, EventSenders},
     item_update_service::UpdateService,
+    repricing::Repricer,
 };
 
 type Error = Box<dyn std::error::Error + Send + Sync>;
@@ -119,11 +120,14 @@ pub(crate) async fn start_discord(
                 tokio::spawn(AlertManager::start_manager(
                     db.clone(),
                     item_events,
+                    event_receivers.history.resubscribe(),
                     alert_events,
                     event_receivers.lists.resubscribe(),
                     ctx.clone(),
                     setup_token,
                     world_cache.clone(),
+                    ch_client.clone(),
+                    Repricer::new(db.clone(), analyzer_service.clone(), ch_client.clone()),
                 ));
                 Ok(Data {
                     db,
@@ -15,7 +15,7 @@ use ultros_db::{
     entity::{listing_last_updated::Model, world},
     world_data::world_cache::WorldCache,
 };
-use universalis::{UniversalisClient, WorldId, WorldItemRecencyView};
+use universalis::{MarketDataSource, WorldId, WorldItemRecencyView};
 
 use crate::event::{EventProducer, EventType};
 
@@ -33,7 +33,9 @@ const FULL_SWEEP_COOLDOWN: Duration = Duration::from_secs(6 * 60 * 60);
 pub(crate) struct UpdateService {
     pub(crate) db: UltrosDb,
     pub(crate) world_cache: Arc<WorldCache>,
-    pub(crate) universalis: UniversalisClient,
+    /// Live Universalis in production, a [`universalis::replay::ReplaySource`]
+    /// when running against captured traffic.
+    pub(crate) source: Arc<dyn MarketDataSource>,
     pub(crate) listings: EventProducer<ListingEventData>,
     pub(crate) sales: EventProducer<SaleEventData>,
     /// Per-world timestamp of the last saturation-triggered full sweep.
@@ -186,7 +188,7 @@ impl UpdateService {
         world: &world::Model,
     ) -> Result<Vec<WorldItemRecencyView>, anyhow::Error> {
         let recently_updated = self
-            .universalis
+            .source
             .recently_updated_items(
                 universalis::WorldOrDatacenter::World(&world.name),
                 RECENTLY_UPDATED_WINDOW,
@@ -214,7 +216,7 @@ impl UpdateService {
         let world_id = WorldId(*id);
         for item_ids in item_ids.chunks(100) {
             let market_data = self
-                .universalis
+                .source
                 .marketboard_current_data(world_name, item_ids)
                 .await?;
             info!("missing data {item_ids:?}");
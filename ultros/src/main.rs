@@ -3,6 +3,7 @@
 pub(crate) mod alerts;
 pub(crate) mod analyzer_service;
 pub(crate) mod character_claim;
+pub(crate) mod craft_plan;
 mod discord;
 pub(crate) mod event;
 mod fd_limit;
@@ -11,7 +12,10 @@ mod item_update_service;
 pub mod leptos;
 #[cfg(feature = "profiling")]
 pub mod profiling;
+pub(crate) mod repricing;
 pub(crate) mod resale_eligibility;
+pub(crate) mod retainer_ledger;
+pub(crate) mod route_planner;
 pub(crate) mod search_service;
 pub(crate) mod trend_candidates;
 pub(crate) mod utils;
@@ -31,6 +35,7 @@ use character_claim::CharacterClaimService;
 use discord::start_discord;
 use dotenvy::dotenv;
 use event::{EventProducer, EventType, create_event_busses};
+use futures::StreamExt;
 use std::collections::HashSet;
 use std::sync::Arc;
 #[cfg(all(not(target_env = "msvc"), feature = "jemalloc"))]
@@ -45,9 +50,11 @@ use ultros_api_types::world::WorldData;
 use ultros_api_types::world_helper::WorldHelper;
 use ultros_db::UltrosDb;
 use ultros_db::world_data::world_cache::WorldCache;
+use universalis::journal::JournalConfig;
+use universalis::replay::{ReplayPace, ReplaySource};
 use universalis::websocket::SocketRx;
-use universalis::websocket::event_types::{EventChannel, SubscribeMode, WSMessage};
-use universalis::{DataCentersView, UniversalisClient, WebsocketClient, WorldId, WorldsView};
+use universalis::websocket::event_types::{EventChannel, WSMessage};
+use universalis::{DataCentersView, MarketDataSource, UniversalisClient, WorldId, WorldsView};
 use web::oauth::{AuthUserCache, DiscordAuthConfig, OAuthScope};
 #[cfg(all(not(target_env = "msvc"), feature = "jemalloc"))]
 #[global_allocator]
@@ -74,30 +81,78 @@ struct Config {
     discord_token: String,
 }
 
+/// Picks where ingest reads market data from. Setting `ULTROS_REPLAY_FILE`
+/// runs the whole pipeline against a recorded session (see
+/// [`universalis::replay`]) instead of the live Universalis API; pointing it at
+/// a directory replays a websocket journal captured with `ULTROS_CAPTURE_DIR`,
+/// paced by `ULTROS_REPLAY_SPEED` (unset: as fast as ingest keeps up, `1`: real
+/// time, `10`: ten times faster). Worlds and datacenters still come from the
+/// live API on startup, so an offline run needs a database that has been primed
+/// once.
+fn market_data_source(live: &UniversalisClient) -> Result<Arc<dyn MarketDataSource>> {
+    match std::env::var("ULTROS_REPLAY_FILE")
+        .ok()
+        .filter(|s| !s.is_empty())
+    {
+        Some(path) => {
+            let replay = if std::path::Path::new(&path).is_dir() {
+                ReplaySource::open_journal(&path)?
+            } else {
+                ReplaySource::open(&path)?
+            };
+            let pace = std::env::var("ULTROS_REPLAY_SPEED")
+                .ok()
+                .and_then(|speed| speed.parse::<f64>().ok())
+                .filter(|speed| speed.is_finite() && *speed > 0.0)
+                .map(ReplayPace::Scaled)
+                .unwrap_or_default();
+            warn!(
+                path,
+                events = replay.event_count(),
+                ?pace,
+                "ingesting from a replay file, not live Universalis"
+            );
+            Ok(Arc::new(replay.with_pace(pace)))
+        }
+        None => {
+            let mut live = live.clone();
+            if let Some(directory) = std::env::var("ULTROS_CAPTURE_DIR")
+                .ok()
+                .filter(|s| !s.is_empty())
+            {
+                let mut config = JournalConfig::new(&directory);
+                config.max_files = std::env::var("ULTROS_CAPTURE_MAX_FILES")
+                    .ok()
+                    .and_then(|n| n.parse().ok());
+                info!(directory, "capturing the Universalis websocket feed");
+                live = live.with_journal(config);
+            }
+            Ok(Arc::new(live))
+        }
+    }
+}
+
 async fn run_socket_listener(
     db: UltrosDb,
+    source: Arc<dyn MarketDataSource>,
     listings_tx: EventProducer<ListingEventData>,
     sales_tx: EventProducer<SaleEventData>,
     token: CancellationToken,
 ) {
-    let mut socket = WebsocketClient::connect(UNIVERSALIS_USER_AGENT).await;
-    socket
-        .update_subscription(SubscribeMode::Subscribe, EventChannel::ListingsAdd, None)
+    let mut events = source
+        .subscribe(&[
+            EventChannel::ListingsAdd,
+            EventChannel::ListingsRemove,
+            EventChannel::SalesAdd,
+        ])
         .await;
-    socket
-        .update_subscription(SubscribeMode::Subscribe, EventChannel::ListingsRemove, None)
-        .await;
-    socket
-        .update_subscription(SubscribeMode::Subscribe, EventChannel::SalesAdd, None)
-        .await;
-    let receiver = socket.get_receiver();
     loop {
         tokio::select! {
             _ = token.cancelled() => {
                 info!("socket listener cancelled");
                 break;
             }
-            msg = receiver.recv() => {
+            msg = events.next() => {
                 if let Some(msg) = msg {
                     // create a new task for each message
                     let db = db.clone();
@@ -179,6 +234,11 @@ async fn run_socket_listener(
                     }
                 }
             });
+                } else {
+                    // The live socket reconnects forever, so only a finite
+                    // source (a replay) ever gets here.
+                    info!("market event source exhausted");
+                    break;
                 }
             }
         }
@@ -380,6 +440,8 @@ async fn main() -> Result<()> {
     let db = UltrosDb::connect().await?;
     info!("Fetching datacenters/worlds from universalis");
     let universalis_client = UniversalisClient::new(UNIVERSALIS_USER_AGENT);
+    let market_source = market_data_source(&universalis_client)?;
+    let socket_source = market_source.clone();
     let startup_client = universalis_client.clone();
     let init = db.clone();
     let (senders, receivers) = create_event_busses();
@@ -398,7 +460,14 @@ async fn main() -> Result<()> {
             .await
             .expect("Unable to populate worlds datacenters- is universalis down?");
         info!("starting websocket");
-        run_socket_listener(init, listings_sender, history_sender, socket_token).await;
+        run_socket_listener(
+            init,
+            socket_source,
+            listings_sender,
+            history_sender,
+            socket_token,
+        )
+        .await;
     });
     // on first run, the world cache may be empty
     let world_cache = Arc::new(WorldCache::new(&db).await);
@@ -441,7 +510,7 @@ async fn main() -> Result<()> {
     let update_service = Arc::new(UpdateService {
         db: db.clone(),
         world_cache: world_cache.clone(),
-        universalis: universalis_client.clone(),
+        source: market_source,
         listings: senders.listings.clone(),
         sales: senders.history.clone(),
         full_sweep_cooldowns: Default::default(),
@@ -452,6 +521,7 @@ async fn main() -> Result<()> {
     // failure looks like a healthy process serving frozen numbers, so this gauge
     // is the only thing that makes one visible from outside.
     ingest_health::spawn_staleness_gauge(db.clone(), world_cache.clone(), token.clone());
+    retainer_ledger::spawn_retainer_ledger(db.clone(), receivers.clone(), token.clone());
     // begin listening to universalis events
     // load configuration from environment
     let config = envy::from_env::<Config>()?;
@@ -0,0 +1,218 @@
+//! Gathers the market around a user's own listings for the relist
+//! suggestions in [`ultros_api_types::repricing`]: the competing listings
+//! from Postgres, recent sell-through from the analyzer's sale buffer and the
+//! cleaned 30-day percentile band from ClickHouse.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::Result;
+use tracing::{instrument, warn};
+use ultros_api_types::{
+    ActiveListing, Retainer,
+    repricing::{
+        DEFAULT_URGENCY, ListingReprice, PriceBand, RepriceInputs, RepriceSuggestion,
+        RetainerReprice, SellThrough,
+    },
+    trends::ConfidenceBand,
+};
+use ultros_clickhouse::{ClickHouseClient, queries::deep_scan_batch};
+use ultros_db::{UltrosDb, entity::active_listing};
+
+use crate::analyzer_service::{AnalyzerService, ItemKey};
+
+/// Band window, matching the percentile chips elsewhere on the site.
+const BAND_WINDOW_DAYS: u16 = 30;
+/// Deep scans per ClickHouse query, the batch size the analyzer uses.
+const BAND_BATCH: usize = 50;
+
+#[derive(Clone)]
+pub(crate) struct Repricer {
+    db: UltrosDb,
+    analyzer: AnalyzerService,
+    ch: ClickHouseClient,
+}
+
+impl Repricer {
+    pub(crate) fn new(db: UltrosDb, analyzer: AnalyzerService, ch: ClickHouseClient) -> Self {
+        Self { db, analyzer, ch }
+    }
+
+    /// Every listing on the user's retainers with the market around it.
+    #[instrument(skip(self))]
+    pub(crate) async fn for_user(&self, discord_user_id: u64) -> Result<Vec<RetainerReprice>> {
+        let retainers = self
+            .db
+            .get_retainer_listings_for_discord_user(discord_user_id)
+            .await?;
+        let own_retainers: HashSet<i32> = retainers.iter().map(|(_, r, _)| r.id).collect();
+        let listings: Vec<&active_listing::Model> = retainers
+            .iter()
+            .flat_map(|(_, _, listings)| listings.iter())
+            .collect();
+        let mut inputs = self
+            .inputs_for(&listings, &own_retainers)
+            .await?
+            .into_iter();
+        Ok(retainers
+            .into_iter()
+            .map(|(_, retainer, listings)| RetainerReprice {
+                retainer: Retainer::from(retainer),
+                listings: listings
+                    .into_iter()
+                    .zip(inputs.by_ref())
+                    .map(|(listing, inputs)| ListingReprice {
+                        listing: ActiveListing::from(listing),
+                        inputs,
+                    })
+                    .collect(),
+            })
+            .collect())
+    }
+
+    /// The suggested relist price at the default urgency for the user's
+    /// `item_id` listings on `world_id`, for undercut alerts.
+    #[instrument(skip(self))]
+    pub(crate) async fn suggest_relist(
+        &self,
+        discord_user_id: u64,
+        world_id: i32,
+        item_id: i32,
+        hq: bool,
+    ) -> Result<Option<RepriceSuggestion>> {
+        let retainers = self
+            .db
+            .get_retainer_listings_for_discord_user(discord_user_id)
+            .await?;
+        let own_retainers: HashSet<i32> = retainers.iter().map(|(_, r, _)| r.id).collect();
+        let Some(listing) = retainers
+            .iter()
+            .flat_map(|(_, _, listings)| listings.iter())
+            .find(|l| l.world_id == world_id && l.item_id == item_id && l.hq == hq)
+        else {
+            return Ok(None);
+        };
+        Ok(self
+            .inputs_for(&[listing], &own_retainers)
+            .await?
+            .first()
+            .and_then(|inputs| inputs.suggest(DEFAULT_URGENCY)))
+    }
+
+    /// Suggestion inputs for each of `listings`, in order. Listings on
+    /// `own_retainers` never count as competition.
+    async fn inputs_for(
+        &self,
+        listings: &[&active_listing::Model],
+        own_retainers: &HashSet<i32>,
+    ) -> Result<Vec<RepriceInputs>> {
+        let mut worlds: Vec<i32> = listings.iter().map(|l| l.world_id).collect();
+        worlds.sort_unstable();
+        worlds.dedup();
+        let mut items: Vec<i32> = listings.iter().map(|l| l.item_id).collect();
+        items.sort_unstable();
+        items.dedup();
+
+        let mut market: HashMap<(i32, i32), Vec<(bool, i32)>> = HashMap::new();
+        for listing in self
+            .db
+            .get_listings_for_items_in_worlds(&worlds, &items)
+            .await?
+        {
+            if !own_retainers.contains(&listing.retainer_id) {
+                market
+                    .entry((listing.world_id, listing.item_id))
+                    .or_default()
+                    .push((listing.hq, listing.price_per_unit));
+            }
+        }
+
+        let sell_through = self.sell_through(listings).await;
+        let bands = self.price_bands(listings).await;
+
+        Ok(listings
+            .iter()
+            .map(|listing| {
+                let mut competitors: Vec<i32> = market
+                    .get(&(listing.world_id, listing.item_id))
+                    .into_iter()
+                    .flatten()
+                    .filter(|(hq, _)| !listing.hq || *hq)
+                    .map(|(_, price)| *price)
+                    .collect();
+                competitors.sort_unstable();
+                let key = ItemKey {
+                    item_id: listing.item_id,
+                    hq: listing.hq,
+                };
+                RepriceInputs {
+                    competitors,
+                    sell_through: sell_through.get(&(listing.world_id, key)).copied(),
+                    band: bands
+                        .get(&(listing.item_id, listing.hq as u8, listing.world_id))
+                        .copied(),
+                }
+            })
+            .collect())
+    }
+
+    /// Buffer stats per (world, item). Worlds the analyzer can't answer for
+    /// yet just go without.
+    async fn sell_through(
+        &self,
+        listings: &[&active_listing::Model],
+    ) -> HashMap<(i32, ItemKey), SellThrough> {
+        let mut by_world: BTreeMap<i32, Vec<ItemKey>> = BTreeMap::new();
+        for listing in listings {
+            by_world.entry(listing.world_id).or_default().push(ItemKey {
+                item_id: listing.item_id,
+                hq: listing.hq,
+            });
+        }
+        let mut recent = HashMap::new();
+        for (world_id, keys) in by_world {
+            match self.analyzer.get_sell_through(world_id, keys).await {
+                Ok(stats) => recent.extend(
+                    stats
+                        .into_iter()
+                        .map(|(key, stats)| ((world_id, key), stats)),
+                ),
+                Err(e) => warn!(world_id, "no sell-through for repricing: {e}"),
+            }
+        }
+        recent
+    }
+
+    /// 30-day bands keyed like [`deep_scan_batch`]'s requests. Bands built on
+    /// nothing, or too noisy to trust, are dropped so the suggestion falls
+    /// back to recent sales.
+    async fn price_bands(
+        &self,
+        listings: &[&active_listing::Model],
+    ) -> HashMap<(i32, u8, i32), PriceBand> {
+        let mut requests: Vec<(i32, u8, i32)> = listings
+            .iter()
+            .map(|l| (l.item_id, l.hq as u8, l.world_id))
+            .collect();
+        requests.sort_unstable();
+        requests.dedup();
+        let mut bands = HashMap::new();
+        for chunk in requests.chunks(BAND_BATCH) {
+            match deep_scan_batch(&self.ch, BAND_WINDOW_DAYS, chunk).await {
+                Ok(scans) => bands.extend(
+                    scans
+                        .into_iter()
+                        .filter(|scan| {
+                            scan.cleaned_sample_size > 0
+                                && scan.confidence_band() != ConfidenceBand::Unusable
+                        })
+                        .map(|scan| ((scan.item_id, scan.hq, scan.world_id), scan.price_band())),
+                ),
+                Err(e) => {
+                    warn!(error = ?e, "price bands unavailable for repricing");
+                    break;
+                }
+            }
+        }
+        bands
+    }
+}
@@ -0,0 +1,455 @@
+//! Credits marketboard sales to the retainers that made them.
+//!
+//! Universalis never says who sold something: a sale event carries the buyer,
+//! the item and the price, and the seller's listing just disappears. When a
+//! tracked retainer's listing is removed and a sale of the same item, quality,
+//! quantity and unit price lands on the same world around the same time, that
+//! sale was almost certainly theirs. The two events arrive on separate buses in
+//! either order, so [`LedgerMatcher`] holds each side for a while waiting for
+//! its partner. Only sales of something a tracked retainer has listed on that
+//! world are held; every other sale could never match.
+//!
+//! Listings that vanish without a matching sale (pulled, repriced, expired)
+//! simply age out. An identical listing on another retainer selling at the same
+//! moment could be credited to the wrong one; that's rare enough to accept.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::{Duration, Instant},
+};
+
+use chrono::NaiveDateTime;
+use sea_orm::ActiveValue;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, warn};
+use ultros_api_types::{ActiveListing, SaleHistory, user::market_tax};
+use ultros_db::{UltrosDb, entity::retainer_sale};
+
+use crate::event::{BusRecv, EventReceivers, EventType, handle_bus_recv};
+
+/// How long either half of a pair waits for the other. Both come out of the
+/// same Universalis upload, so in practice they're seconds apart; the margin
+/// covers a backed-up bus.
+const MATCH_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// How far a sale may appear to precede the listing's last review. The two
+/// timestamps come from different uploaders' clocks.
+const CLOCK_SLACK: chrono::Duration = chrono::Duration::seconds(60);
+
+/// How often the tracked retainer set is reloaded and stale entries dropped.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Everything that has to agree for a removal and a sale to pair up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct SaleKey {
+    world_id: i32,
+    item_id: i32,
+    hq: bool,
+    quantity: i32,
+    price_per_unit: i32,
+}
+
+impl From<&ActiveListing> for SaleKey {
+    fn from(listing: &ActiveListing) -> Self {
+        SaleKey {
+            world_id: listing.world_id,
+            item_id: listing.item_id,
+            hq: listing.hq,
+            quantity: listing.quantity,
+            price_per_unit: listing.price_per_unit,
+        }
+    }
+}
+
+impl From<&SaleHistory> for SaleKey {
+    fn from(sale: &SaleHistory) -> Self {
+        SaleKey {
+            world_id: sale.world_id,
+            item_id: sale.sold_item_id,
+            hq: sale.hq,
+            quantity: sale.quantity,
+            price_per_unit: sale.price_per_item,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PendingListing {
+    retainer_id: i32,
+    listed_at: NaiveDateTime,
+    seen: Instant,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PendingSale {
+    sale_history_id: i32,
+    sold_at: NaiveDateTime,
+    seen: Instant,
+}
+
+/// A sale credited to a retainer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LedgerMatch {
+    pub(crate) retainer_id: i32,
+    pub(crate) sale_history_id: i32,
+    pub(crate) world_id: i32,
+    pub(crate) item_id: i32,
+    pub(crate) hq: bool,
+    pub(crate) quantity: i32,
+    pub(crate) price_per_unit: i32,
+    pub(crate) listed_at: NaiveDateTime,
+    pub(crate) sold_at: NaiveDateTime,
+}
+
+impl LedgerMatch {
+    fn new(key: SaleKey, listing: PendingListing, sale: PendingSale) -> Self {
+        LedgerMatch {
+            retainer_id: listing.retainer_id,
+            sale_history_id: sale.sale_history_id,
+            world_id: key.world_id,
+            item_id: key.item_id,
+            hq: key.hq,
+            quantity: key.quantity,
+            price_per_unit: key.price_per_unit,
+            listed_at: listing.listed_at,
+            sold_at: sale.sold_at,
+        }
+    }
+
+    fn into_active_model(self) -> retainer_sale::ActiveModel {
+        let gross = self.quantity as i64 * self.price_per_unit as i64;
+        retainer_sale::ActiveModel {
+            id: ActiveValue::NotSet,
+            retainer_id: ActiveValue::Set(self.retainer_id),
+            sale_history_id: ActiveValue::Set(self.sale_history_id),
+            world_id: ActiveValue::Set(self.world_id),
+            item_id: ActiveValue::Set(self.item_id),
+            hq: ActiveValue::Set(self.hq),
+            quantity: ActiveValue::Set(self.quantity),
+            price_per_unit: ActiveValue::Set(self.price_per_unit),
+            gross: ActiveValue::Set(gross),
+            tax: ActiveValue::Set(market_tax(gross)),
+            listed_at: ActiveValue::Set(self.listed_at),
+            sold_at: ActiveValue::Set(self.sold_at),
+        }
+    }
+}
+
+fn plausible(listing: &PendingListing, sale: &PendingSale) -> bool {
+    sale.sold_at >= listing.listed_at - CLOCK_SLACK
+}
+
+/// Pairs removed retainer listings with sales. A sale is held when it may
+/// belong to a listing whose removal hasn't arrived yet, which is only when a
+/// tracked retainer lists its item on its world; only removals of tracked
+/// retainers' listings are ever passed in.
+#[derive(Debug, Default)]
+pub(crate) struct LedgerMatcher {
+    listings: HashMap<SaleKey, VecDeque<PendingListing>>,
+    sales: HashMap<SaleKey, VecDeque<PendingSale>>,
+    /// `(world_id, item_id)` of tracked retainers' listings.
+    watched: HashSet<(i32, i32)>,
+}
+
+impl LedgerMatcher {
+    /// Replace the watched listings with a fresh load from the database.
+    pub(crate) fn set_watched(&mut self, watched: HashSet<(i32, i32)>) {
+        self.watched = watched;
+    }
+
+    /// A tracked retainer put something up since the last load.
+    pub(crate) fn listing_added(&mut self, listing: &ActiveListing) {
+        self.watched.insert((listing.world_id, listing.item_id));
+    }
+
+    /// A tracked retainer's listing disappeared.
+    pub(crate) fn listing_removed(
+        &mut self,
+        listing: &ActiveListing,
+        now: Instant,
+    ) -> Option<LedgerMatch> {
+        let key = SaleKey::from(listing);
+        let pending = PendingListing {
+            retainer_id: listing.retainer_id,
+            listed_at: listing.timestamp,
+            seen: now,
+        };
+        if let Some(sales) = self.sales.get_mut(&key)
+            && let Some(index) = sales.iter().position(|sale| plausible(&pending, sale))
+        {
+            let sale = sales.remove(index).expect("index is in bounds");
+            if sales.is_empty() {
+                self.sales.remove(&key);
+            }
+            return Some(LedgerMatch::new(key, pending, sale));
+        }
+        self.listings.entry(key).or_default().push_back(pending);
+        None
+    }
+
+    /// A sale was recorded, by anyone.
+    pub(crate) fn sale_added(&mut self, sale: &SaleHistory, now: Instant) -> Option<LedgerMatch> {
+        let key = SaleKey::from(sale);
+        let pending = PendingSale {
+            sale_history_id: sale.id,
+            sold_at: sale.sold_date,
+            seen: now,
+        };
+        if let Some(listings) = self.listings.get_mut(&key)
+            && let Some(index) = listings
+                .iter()
+                .position(|listing| plausible(listing, &pending))
+        {
+            let listing = listings.remove(index).expect("index is in bounds");
+            if listings.is_empty() {
+                self.listings.remove(&key);
+            }
+            return Some(LedgerMatch::new(key, listing, pending));
+        }
+        if self.watched.contains(&(key.world_id, key.item_id)) {
+            self.sales.entry(key).or_default().push_back(pending);
+        }
+        None
+    }
+
+    /// Forget anything that has waited longer than [`MATCH_WINDOW`].
+    pub(crate) fn expire(&mut self, now: Instant) {
+        let fresh = |seen: Instant| now.saturating_duration_since(seen) < MATCH_WINDOW;
+        self.listings.retain(|_, pending| {
+            pending.retain(|listing| fresh(listing.seen));
+            !pending.is_empty()
+        });
+        self.sales.retain(|_, pending| {
+            pending.retain(|sale| fresh(sale.seen));
+            !pending.is_empty()
+        });
+    }
+
+    fn pending(&self) -> (usize, usize) {
+        (
+            self.listings.values().map(VecDeque::len).sum(),
+            self.sales.values().map(VecDeque::len).sum(),
+        )
+    }
+}
+
+/// Watches the listing and sale buses and records matched sales until
+/// `token` is cancelled.
+pub(crate) fn spawn_retainer_ledger(
+    db: UltrosDb,
+    mut receivers: EventReceivers,
+    token: CancellationToken,
+) {
+    tokio::spawn(async move {
+        let mut matcher = LedgerMatcher::default();
+        // Nothing publishes on the retainers bus, so claims and releases are
+        // picked up by polling.
+        let mut tracked: HashSet<i32> = HashSet::new();
+        let mut refresh = tokio::time::interval(REFRESH_INTERVAL);
+        refresh.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut refresh_failed = false;
+        loop {
+            let matches = tokio::select! {
+                _ = token.cancelled() => break,
+                _ = refresh.tick() => {
+                    match tokio::try_join!(db.get_tracked_retainer_ids(), db.get_tracked_listing_keys()) {
+                        Ok((ids, keys)) => {
+                            tracked = ids;
+                            matcher.set_watched(keys);
+                            refresh_failed = false;
+                        }
+                        Err(e) => {
+                            if !refresh_failed {
+                                error!(error = ?e, "unable to load tracked retainers");
+                            }
+                            refresh_failed = true;
+                        }
+                    }
+                    matcher.expire(Instant::now());
+                    let (listings, sales) = matcher.pending();
+                    metrics::gauge!("ultros_retainer_ledger_pending", "side" => "listings")
+                        .set(listings as f64);
+                    metrics::gauge!("ultros_retainer_ledger_pending", "side" => "sales")
+                        .set(sales as f64);
+                    continue;
+                }
+                listings = receivers.listings.recv() => {
+                    match handle_bus_recv("retainer_ledger_listings", listings) {
+                        BusRecv::Msg(EventType::Add(added)) => {
+                            for (listing, _) in &added.listings {
+                                if tracked.contains(&listing.retainer_id) {
+                                    matcher.listing_added(listing);
+                                }
+                            }
+                            continue;
+                        }
+                        BusRecv::Msg(EventType::Remove(removed)) => {
+                            let now = Instant::now();
+                            removed
+                                .listings
+                                .iter()
+                                .filter(|(listing, _)| tracked.contains(&listing.retainer_id))
+                                .filter_map(|(listing, _)| matcher.listing_removed(listing, now))
+                                .collect::<Vec<_>>()
+                        }
+                        BusRecv::Msg(_) | BusRecv::Lagged => continue,
+                        BusRecv::Closed => break,
+                    }
+                }
+                history = receivers.history.recv() => {
+                    match handle_bus_recv("retainer_ledger_history", history) {
+                        BusRecv::Msg(EventType::Add(added)) => {
+                            let now = Instant::now();
+                            added
+                                .sales
+                                .iter()
+                                .filter_map(|(sale, _)| matcher.sale_added(sale, now))
+                                .collect::<Vec<_>>()
+                        }
+                        BusRecv::Msg(_) | BusRecv::Lagged => continue,
+                        BusRecv::Closed => break,
+                    }
+                }
+            };
+            if matches.is_empty() {
+                continue;
+            }
+            let count = matches.len();
+            match db
+                .record_retainer_sales(
+                    matches
+                        .into_iter()
+                        .map(LedgerMatch::into_active_model)
+                        .collect(),
+                )
+                .await
+            {
+                Ok(inserted) => {
+                    metrics::counter!("ultros_retainer_ledger_sales_total").increment(inserted)
+                }
+                Err(e) => warn!(error = ?e, count, "unable to record retainer sales"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minute: u32) -> NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(2026, 10, 1)
+            .unwrap()
+            .and_hms_opt(12, minute, 0)
+            .unwrap()
+    }
+
+    fn listing(retainer_id: i32, price_per_unit: i32, listed_at: NaiveDateTime) -> ActiveListing {
+        ActiveListing {
+            id: 1,
+            world_id: 34,
+            item_id: 5057,
+            retainer_id,
+            price_per_unit,
+            quantity: 3,
+            hq: false,
+            timestamp: listed_at,
+            materia: vec![],
+        }
+    }
+
+    fn sale(id: i32, price_per_item: i32, sold_date: NaiveDateTime) -> SaleHistory {
+        SaleHistory {
+            id,
+            quantity: 3,
+            price_per_item,
+            buying_character_id: 9,
+            hq: false,
+            sold_item_id: 5057,
+            sold_date,
+            world_id: 34,
+            buyer_name: None,
+        }
+    }
+
+    /// A matcher watching the item the helpers list and sell.
+    fn matcher() -> LedgerMatcher {
+        let mut matcher = LedgerMatcher::default();
+        matcher.set_watched(HashSet::from([(34, 5057)]));
+        matcher
+    }
+
+    #[test]
+    fn pairs_in_either_order() {
+        let now = Instant::now();
+        let mut matcher = matcher();
+        assert_eq!(matcher.listing_removed(&listing(7, 100, at(0)), now), None);
+        let matched = matcher.sale_added(&sale(1, 100, at(5)), now).unwrap();
+        assert_eq!(matched.retainer_id, 7);
+        assert_eq!(matched.sale_history_id, 1);
+        assert_eq!((matched.listed_at, matched.sold_at), (at(0), at(5)));
+
+        assert_eq!(matcher.sale_added(&sale(2, 100, at(6)), now), None);
+        let matched = matcher
+            .listing_removed(&listing(8, 100, at(1)), now)
+            .unwrap();
+        assert_eq!((matched.retainer_id, matched.sale_history_id), (8, 2));
+        assert_eq!(matcher.pending(), (0, 0));
+    }
+
+    #[test]
+    fn ignores_different_prices_and_sales_before_the_listing() {
+        let now = Instant::now();
+        let mut matcher = matcher();
+        matcher.listing_removed(&listing(7, 100, at(10)), now);
+        assert_eq!(matcher.sale_added(&sale(1, 99, at(11)), now), None);
+        // Sold well before the listing was last reviewed: someone else's.
+        assert_eq!(matcher.sale_added(&sale(2, 100, at(5)), now), None);
+        assert!(matcher.sale_added(&sale(3, 100, at(11)), now).is_some());
+    }
+
+    #[test]
+    fn only_sales_a_tracked_retainer_could_have_made_are_held() {
+        let now = Instant::now();
+        let mut matcher = LedgerMatcher::default();
+        assert_eq!(matcher.sale_added(&sale(1, 100, at(5)), now), None);
+        assert_eq!(matcher.pending(), (0, 0));
+
+        matcher.listing_added(&listing(7, 100, at(0)));
+        assert_eq!(matcher.sale_added(&sale(2, 100, at(5)), now), None);
+        assert_eq!(matcher.pending(), (0, 1));
+        let matched = matcher
+            .listing_removed(&listing(7, 100, at(0)), now)
+            .unwrap();
+        assert_eq!(matched.sale_history_id, 2);
+    }
+
+    #[test]
+    fn stale_entries_expire() {
+        let start = Instant::now();
+        let mut matcher = matcher();
+        matcher.listing_removed(&listing(7, 100, at(0)), start);
+        matcher.sale_added(&sale(1, 50, at(0)), start);
+        matcher.expire(start + MATCH_WINDOW / 2);
+        assert_eq!(matcher.pending(), (1, 1));
+        matcher.expire(start + MATCH_WINDOW);
+        assert_eq!(matcher.pending(), (0, 0));
+        assert_eq!(
+            matcher.sale_added(&sale(2, 100, at(1)), start + MATCH_WINDOW),
+            None
+        );
+    }
+
+    #[test]
+    fn tax_is_taken_from_the_whole_sale() {
+        let now = Instant::now();
+        let mut matcher = matcher();
+        matcher.listing_removed(&listing(7, 1_000, at(0)), now);
+        let model = matcher
+            .sale_added(&sale(1, 1_000, at(1)), now)
+            .unwrap()
+            .into_active_model();
+        assert_eq!(model.gross, ActiveValue::Set(3_000));
+        assert_eq!(model.tax, ActiveValue::Set(150));
+    }
+}
@@ -0,0 +1,346 @@
+//! Budgeted multi-stop buying plan behind `/api/v1/route_planner`.
+//!
+//! Kept free of the analyzer and the database so the allocation rules are
+//! unit-testable: the handler gathers candidates and listing depth, this
+//! module decides what to buy.
+//!
+//! Allocation is greedy by profit per gil spent. Within one item the stacks
+//! are walked cheapest first, so each further stack of that item is worth
+//! no more than the one before — which is what makes the greedy pick across
+//! items sound. A stack that is unaffordable or unprofitable now stays that
+//! way (the budget only shrinks, the sell-through cap only fills), so it is
+//! skipped for good rather than retried.
+
+use std::collections::BTreeMap;
+
+use ultros_api_types::route_planner::{RoutePlan, RoutePurchase, RouteStop};
+
+/// One listing the planner may buy.
+#[derive(Debug, Clone)]
+pub(crate) struct PlannerListing {
+    pub(crate) listing_id: i32,
+    pub(crate) world_id: i32,
+    pub(crate) price_per_unit: i32,
+    pub(crate) quantity: i32,
+    pub(crate) retainer_name: Option<String>,
+}
+
+/// An item worth reselling on the home world, with every listing of it in
+/// the data center.
+#[derive(Debug, Clone)]
+pub(crate) struct PlannerItem {
+    pub(crate) item_id: i32,
+    pub(crate) hq: bool,
+    /// Post-tax gil per unit on the home world.
+    pub(crate) net_sale_price: i32,
+    /// Units the home world can absorb within the horizon; see [`sellable_units`].
+    pub(crate) sellable_units: i32,
+    pub(crate) listings: Vec<PlannerListing>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PlanRequest {
+    pub(crate) home_world_id: i32,
+    pub(crate) datacenter_id: i32,
+    pub(crate) budget: i64,
+    pub(crate) horizon_days: f32,
+}
+
+/// How many units of an item the home world should absorb in `horizon_days`.
+///
+/// The recent-sales buffer doesn't carry quantities, so each sale counts as
+/// one unit. That undercounts stackable materials, which is the safe
+/// direction for a plan that spends real gil. Never below one: an item only
+/// becomes a candidate by having sold at all.
+pub(crate) fn sellable_units(velocity_per_day: Option<f32>, horizon_days: f32) -> i32 {
+    let units = velocity_per_day.unwrap_or(0.0) * horizon_days.max(0.0);
+    (units.floor() as i32).max(1)
+}
+
+struct Cursor {
+    item: PlannerItem,
+    next: usize,
+    planned_units: i32,
+}
+
+impl Cursor {
+    fn sellable(&self, listing: &PlannerListing) -> i32 {
+        listing
+            .quantity
+            .min(self.item.sellable_units - self.planned_units)
+            .max(0)
+    }
+
+    fn profit(&self, listing: &PlannerListing) -> i64 {
+        self.item.net_sale_price as i64 * self.sellable(listing) as i64 - stack_cost(listing)
+    }
+
+    /// Skips stacks that can no longer pay off, leaving `next` on the first
+    /// one that can (or past the end).
+    fn advance(&mut self, remaining_budget: i64) {
+        while let Some(listing) = self.item.listings.get(self.next) {
+            if stack_cost(listing) <= remaining_budget && self.profit(listing) > 0 {
+                break;
+            }
+            self.next += 1;
+        }
+    }
+}
+
+fn stack_cost(listing: &PlannerListing) -> i64 {
+    listing.price_per_unit as i64 * listing.quantity as i64
+}
+
+pub(crate) fn plan_route(request: PlanRequest, items: Vec<PlannerItem>) -> RoutePlan {
+    let mut cursors: Vec<Cursor> = items
+        .into_iter()
+        .map(|mut item| {
+            item.listings
+                .retain(|l| l.price_per_unit > 0 && l.quantity > 0);
+            item.listings
+                .sort_by_key(|l| (l.price_per_unit, l.quantity, l.listing_id));
+            Cursor {
+                item,
+                next: 0,
+                planned_units: 0,
+            }
+        })
+        .collect();
+
+    let mut remaining = request.budget;
+    let mut purchases = vec![];
+    loop {
+        let mut best: Option<(usize, f64)> = None;
+        for (index, cursor) in cursors.iter_mut().enumerate() {
+            cursor.advance(remaining);
+            let Some(listing) = cursor.item.listings.get(cursor.next) else {
+                continue;
+            };
+            let ratio = cursor.profit(listing) as f64 / stack_cost(listing) as f64;
+            if best.is_none_or(|(_, best_ratio)| ratio > best_ratio) {
+                best = Some((index, ratio));
+            }
+        }
+        let Some((index, _)) = best else {
+            break;
+        };
+        let cursor = &mut cursors[index];
+        let listing = &cursor.item.listings[cursor.next];
+        let sellable_quantity = cursor.sellable(listing);
+        let expected_profit = cursor.profit(listing);
+        remaining -= stack_cost(listing);
+        purchases.push((
+            listing.world_id,
+            RoutePurchase {
+                listing_id: listing.listing_id,
+                item_id: cursor.item.item_id,
+                hq: cursor.item.hq,
+                quantity: listing.quantity,
+                price_per_unit: listing.price_per_unit,
+                retainer_name: listing.retainer_name.clone(),
+                sellable_quantity,
+                net_sale_price: cursor.item.net_sale_price,
+                expected_profit,
+            },
+        ));
+        cursor.planned_units += listing.quantity;
+        cursor.next += 1;
+    }
+
+    let mut by_world: BTreeMap<i32, Vec<RoutePurchase>> = BTreeMap::new();
+    for (world_id, purchase) in purchases {
+        by_world.entry(world_id).or_default().push(purchase);
+    }
+    let mut stops: Vec<RouteStop> = by_world
+        .into_iter()
+        .map(|(world_id, mut purchases)| {
+            purchases.sort_by_key(|p| (p.item_id, p.hq, p.price_per_unit));
+            RouteStop {
+                world_id,
+                cost: purchases
+                    .iter()
+                    .map(|p| p.price_per_unit as i64 * p.quantity as i64)
+                    .sum(),
+                expected_profit: purchases.iter().map(|p| p.expected_profit).sum(),
+                purchases,
+            }
+        })
+        .collect();
+    stops.sort_by_key(|s| {
+        (
+            s.world_id != request.home_world_id,
+            std::cmp::Reverse(s.expected_profit),
+            s.world_id,
+        )
+    });
+
+    RoutePlan {
+        home_world_id: request.home_world_id,
+        datacenter_id: request.datacenter_id,
+        budget: request.budget,
+        horizon_days: request.horizon_days,
+        total_cost: request.budget - remaining,
+        expected_profit: stops.iter().map(|s| s.expected_profit).sum(),
+        stops,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOME: i32 = 1;
+
+    fn request(budget: i64) -> PlanRequest {
+        PlanRequest {
+            home_world_id: HOME,
+            datacenter_id: 100,
+            budget,
+            horizon_days: 7.0,
+        }
+    }
+
+    fn listing(
+        listing_id: i32,
+        world_id: i32,
+        price_per_unit: i32,
+        quantity: i32,
+    ) -> PlannerListing {
+        PlannerListing {
+            listing_id,
+            world_id,
+            price_per_unit,
+            quantity,
+            retainer_name: None,
+        }
+    }
+
+    fn item(
+        item_id: i32,
+        net_sale_price: i32,
+        sellable_units: i32,
+        listings: Vec<PlannerListing>,
+    ) -> PlannerItem {
+        PlannerItem {
+            item_id,
+            hq: false,
+            net_sale_price,
+            sellable_units,
+            listings,
+        }
+    }
+
+    fn bought(plan: &RoutePlan) -> Vec<i32> {
+        let mut ids: Vec<_> = plan
+            .stops
+            .iter()
+            .flat_map(|s| s.purchases.iter().map(|p| p.listing_id))
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn walks_listing_depth_until_stacks_stop_paying() {
+        let plan = plan_route(
+            request(1_000_000),
+            vec![item(
+                1,
+                300,
+                10,
+                vec![
+                    listing(3, 2, 400, 5),
+                    listing(1, 2, 100, 2),
+                    listing(2, 2, 150, 3),
+                ],
+            )],
+        );
+        assert_eq!(bought(&plan), [1, 2]);
+        assert_eq!(plan.total_cost, 2 * 100 + 3 * 150);
+        assert_eq!(plan.expected_profit, 2 * 200 + 3 * 150);
+    }
+
+    #[test]
+    fn surplus_beyond_the_sell_through_cap_is_worth_nothing() {
+        // Ten units at 100 against a cap of two nets 600 for 1000 spent;
+        // the single pricier unit is the only stack that pays.
+        let plan = plan_route(
+            request(1_000_000),
+            vec![item(
+                1,
+                300,
+                2,
+                vec![listing(1, 2, 100, 10), listing(2, 2, 150, 1)],
+            )],
+        );
+        assert_eq!(bought(&plan), [2]);
+        let purchase = &plan.stops[0].purchases[0];
+        assert_eq!(purchase.sellable_quantity, 1);
+        assert_eq!(purchase.expected_profit, 150);
+    }
+
+    #[test]
+    fn partially_sellable_stack_counts_only_the_sellable_units() {
+        let plan = plan_route(
+            request(1_000_000),
+            vec![item(1, 1_000, 3, vec![listing(1, 2, 100, 5)])],
+        );
+        let purchase = &plan.stops[0].purchases[0];
+        assert_eq!(purchase.sellable_quantity, 3);
+        assert_eq!(purchase.expected_profit, 3 * 1_000 - 5 * 100);
+    }
+
+    #[test]
+    fn a_tight_budget_goes_to_the_best_return_per_gil() {
+        // Item 1 returns 100% on 1000 gil, item 2 returns 50% on 1000 gil.
+        let plan = plan_route(
+            request(1_000),
+            vec![
+                item(1, 2_000, 5, vec![listing(1, 2, 1_000, 1)]),
+                item(2, 1_500, 5, vec![listing(2, 3, 1_000, 1)]),
+            ],
+        );
+        assert_eq!(bought(&plan), [1]);
+        assert_eq!(plan.total_cost, 1_000);
+    }
+
+    #[test]
+    fn unaffordable_stacks_are_skipped_not_fatal() {
+        let plan = plan_route(
+            request(500),
+            vec![item(
+                1,
+                300,
+                10,
+                vec![listing(1, 2, 100, 9), listing(2, 2, 120, 4)],
+            )],
+        );
+        assert_eq!(bought(&plan), [2]);
+        assert_eq!(plan.total_cost, 480);
+    }
+
+    #[test]
+    fn home_world_comes_first_then_stops_by_profit() {
+        let plan = plan_route(
+            request(1_000_000),
+            vec![
+                item(1, 200, 10, vec![listing(1, HOME, 100, 1)]),
+                item(2, 2_000, 10, vec![listing(2, 2, 100, 1)]),
+                item(3, 5_000, 10, vec![listing(3, 3, 100, 1)]),
+            ],
+        );
+        let worlds: Vec<_> = plan.stops.iter().map(|s| s.world_id).collect();
+        assert_eq!(worlds, [HOME, 3, 2]);
+        assert_eq!(
+            plan.expected_profit,
+            plan.stops.iter().map(|s| s.expected_profit).sum::<i64>()
+        );
+    }
+
+    #[test]
+    fn sellable_units_floor_at_one() {
+        assert_eq!(sellable_units(Some(0.01), 7.0), 1);
+        assert_eq!(sellable_units(None, 7.0), 1);
+        assert_eq!(sellable_units(Some(2.5), 7.0), 17);
+    }
+}
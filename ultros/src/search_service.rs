@@ -1,165 +1,238 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Bound;
 use std::sync::Arc;
-use tantivy::collector::TopDocs;
-use tantivy::query::{BooleanQuery, Query, QueryParser};
-use tantivy::schema::{STORED, Schema, TextOptions, Value};
-use tantivy::{Index, IndexReader, ReloadPolicy, doc};
+use tantivy::collector::{Count, FacetCollector, FacetCounts, TopDocs};
+use tantivy::query::{
+    BooleanQuery, Occur, Query, QueryParser, RangeQuery, TermQuery, TermSetQuery,
+};
+use tantivy::schema::{
+    FAST, Facet, FacetOptions, Field, INDEXED, IndexRecordOption, STORED, STRING, Schema,
+    TantivyDocument, TextFieldIndexing, TextOptions, Value,
+};
+use tantivy::tokenizer::{
+    AsciiFoldingFilter, Language as StemLanguage, LowerCaser, NgramTokenizer, RemoveLongFilter,
+    SimpleTokenizer, Stemmer, TextAnalyzer,
+};
+use tantivy::{Index, IndexReader, ReloadPolicy, Term};
 use tracing::{error, info, warn};
-use ultros_api_types::search::SearchResult;
-use xiv_gen::{ItemId, ItemSearchCategoryId, ItemUiCategoryId};
+use ultros_api_types::search::{
+    NumberFilter, SearchFacet, SearchFacets, SearchFilters, SearchPage, SearchResult,
+    parse_search_query,
+};
+use xiv_gen::{ClassJobCategoryId, ItemId, ItemSearchCategoryId, ItemUiCategoryId, Language};
 
+/// Results returned by the unpaginated [`SearchService::search`].
+const QUICK_SEARCH_LIMIT: usize = 10;
+/// Facet values returned per facet, most common first.
+const MAX_FACET_VALUES: usize = 20;
+
+/// One in-memory index per client language, so a search typed on a Japanese
+/// or German client matches the names that client actually shows.
 #[derive(Clone)]
 pub struct SearchService {
-    index: Arc<Index>,
-    reader: IndexReader,
-    title_field: tantivy::schema::Field,
-    type_field: tantivy::schema::Field,
-    url_field: tantivy::schema::Field,
-    icon_id_field: tantivy::schema::Field,
-    category_field: tantivy::schema::Field,
+    indexes: Arc<HashMap<Language, LanguageIndex>>,
 }
 
-impl SearchService {
-    pub fn new() -> anyhow::Result<Self> {
-        let mut schema_builder = Schema::builder();
+struct LanguageIndex {
+    index: Index,
+    reader: IndexReader,
+    fields: SearchFields,
+    /// Fuzzy matching only helps space-separated scripts. Over 1–2 character
+    /// CJK grams an edit distance of 2 matches nearly every term.
+    fuzzy: bool,
+}
 
-        // Use a tokenizer that handles apostrophes better if possible, or just standard English
-        // For now, we'll stick to standard but rely on fuzzy search to help with "Samurai's" vs "Samurai"
-        let title_options = TextOptions::default()
-            .set_indexing_options(
-                tantivy::schema::TextFieldIndexing::default()
-                    .set_tokenizer("en_stem")
-                    .set_index_option(tantivy::schema::IndexRecordOption::WithFreqsAndPositions),
-            )
-            .set_stored();
+#[derive(Clone, Copy)]
+struct SearchFields {
+    title: Field,
+    result_type: Field,
+    url: Field,
+    icon_id: Field,
+    category: Field,
+    item_level: Field,
+    equip_level: Field,
+    hq: Field,
+    craftable: Field,
+    marketable: Field,
+    /// Canonical acronyms of every job that can use the item.
+    jobs: Field,
+    /// The item's UI category as its id and its lower-cased localized and
+    /// English names, so `cat:` matches whichever one was typed.
+    ui_category: Field,
+    /// `/category/<ui category>` and `/type/<result type>`.
+    facets: Field,
+}
 
-        let title_field = schema_builder.add_text_field("title", title_options.clone());
-        let type_field = schema_builder.add_text_field("type", STORED);
-        let url_field = schema_builder.add_text_field("url", STORED);
-        let icon_id_field = schema_builder.add_i64_field("icon_id", STORED);
-        // Category field uses same options as title for searchability
-        let category_field = schema_builder.add_text_field("category", title_options);
+/// A single entry to index, already localized.
+struct SearchDocument {
+    title: String,
+    result_type: &'static str,
+    url: String,
+    icon_id: i64,
+    category: String,
+    /// Filterable attributes; only items have them.
+    item: Option<ItemAttributes>,
+}
 
-        let schema = schema_builder.build();
+struct ItemAttributes {
+    item_level: u64,
+    equip_level: u64,
+    hq: bool,
+    craftable: bool,
+    marketable: bool,
+    jobs: Vec<String>,
+    ui_category_keys: Vec<String>,
+    /// Localized UI category name, as shown in facets.
+    ui_category: String,
+}
 
-        let index = Index::create_in_ram(schema.clone());
-        let mut index_writer = index.writer(50_000_000)?;
+/// Facts decided once from the English pack and shared by every language.
+struct SharedFacts {
+    /// Which items count as currencies is decided by English names.
+    currency_ids: HashSet<ItemId>,
+    /// Items some recipe produces.
+    craftable: HashSet<ItemId>,
+    /// Job acronyms each `ClassJobCategory` allows.
+    jobs_by_category: HashMap<ClassJobCategoryId, Vec<String>>,
+}
 
-        let data = xiv_gen_db::data();
-
-        // Index Items
-        for (id, item) in &data.items {
-            if item.item_search_category > 0 {
-                let category_name = data
-                    .item_search_categorys
-                    .get(&ItemSearchCategoryId(item.item_search_category))
-                    .map(|c| c.name.as_str())
-                    .unwrap_or("");
-
-                index_writer.add_document(doc!(
-                    title_field => item.name.as_str(),
-                    type_field => "item",
-                    url_field => format!("/item/{}", id.0),
-                    icon_id_field => id.0 as i64, // Use Item ID for image lookup
-                    category_field => category_name,
-                ))?;
-            }
+impl SharedFacts {
+    fn new(en: &xiv_gen::Data) -> Self {
+        let craftable = en
+            .recipes
+            .values()
+            .map(|recipe| ItemId(recipe.item_result))
+            .collect();
+        // English abbreviations are the canonical acronyms the category
+        // columns are named after; BST has no column and drops out here.
+        let acronyms: HashSet<String> = en
+            .class_jobs
+            .values()
+            .map(|job| job.abbreviation.to_uppercase())
+            .filter(|acronym| !acronym.is_empty())
+            .collect();
+        let jobs_by_category = en
+            .class_job_categorys
+            .iter()
+            .map(|(id, category)| {
+                let mut jobs: Vec<String> = acronyms
+                    .iter()
+                    .filter(|acronym| category.includes_job(acronym) == Some(true))
+                    .cloned()
+                    .collect();
+                jobs.sort();
+                (*id, jobs)
+            })
+            .collect();
+        Self {
+            currency_ids: currency_item_ids(en),
+            craftable,
+            jobs_by_category,
         }
+    }
+}
 
-        // Index Categories
-        for cat in data.item_search_categorys.values() {
-            index_writer.add_document(doc!(
-                title_field => cat.name.as_str(),
-                type_field => "category",
-                // Keyed by id: this index is built from English game data, so
-                // a name-keyed URL would send every non-English visitor to a
-                // category their client cannot resolve. See
-                // `resolve_category_param` in `item_explorer.rs`.
-                url_field => format!("/items/category/{}", cat.key_id.0),
-                // Categories don't have a direct icon, maybe use a default or 0
-                icon_id_field => 0i64,
-                category_field => "",
-            ))?;
-        }
+impl SearchService {
+    pub fn new() -> anyhow::Result<Self> {
+        let en = xiv_gen_db::data();
+        let shared = SharedFacts::new(en);
 
-        // Index Jobs
-        for job in data.class_jobs.values() {
-            if job.job_index > 0 || job.doh_dol_job_index >= 0 {
-                let name = if job.abbreviation.is_empty() {
-                    job.name.to_string()
-                } else {
-                    format!("{} ({})", job.name, job.abbreviation)
-                };
-                index_writer.add_document(doc!(
-                    title_field => name,
-                    type_field => "job equipment", // Renamed from "job"
-                    url_field => format!("/items/jobset/{}", job.name),
-                    icon_id_field => 0i64, // Jobs don't have a simple icon ID in this context easily accessible or needed?
-                    category_field => "",
-                ))?;
-            }
+        let mut indexes = HashMap::new();
+        for lang in Language::ALL {
+            // Decoded one pack at a time and dropped once indexed, rather than
+            // through `data_for`, so startup doesn't pin every language's game
+            // data in memory for the life of the process.
+            let decoded;
+            let data = if lang == Language::En {
+                en
+            } else {
+                decoded = xiv_gen_db::decompress_data(xiv_gen_db::embedded_bytes(lang))?;
+                &decoded
+            };
+            let documents = search_documents(data, en, &shared);
+            indexes.insert(lang, LanguageIndex::build(lang, documents)?);
         }
+        info!("SearchService: Indexing complete.");
 
-        // Index Currencies
-        // Logic adapted from CurrencySelection to find items used as currency for marketable items
-        let ui_categories = &data.item_ui_categorys;
-        let allowed_item_ui_categories = ["Currency", "Miscellany", "Other"]
-            .into_iter()
-            .filter_map(|category| {
-                ui_categories
-                    .iter()
-                    .find(|f| f.1.name == category)
-                    .map(|(id, _)| *id)
-            })
-            .collect::<Vec<_>>();
-
-        let mut currency_ids = std::collections::HashSet::new();
-
-        // Extract currency items from special shops
-        // The SpecialShop struct now has a flat `item: Vec<u16>` containing all item IDs.
-        // We check if any items in the shop are marketable, and if so, collect all non-zero
-        // item IDs as potential currencies (filtered later by UI category).
-        for shop in data.special_shops.values() {
-            let has_marketable_item = shop.item.iter().any(|&item_id| {
-                item_id != 0
-                    && data
-                        .items
-                        .get(&ItemId(item_id as i32))
-                        .is_some_and(|item| item.item_search_category > 0)
-            });
+        Ok(Self {
+            indexes: Arc::new(indexes),
+        })
+    }
 
-            if has_marketable_item {
-                for &item_id in &shop.item {
-                    if item_id != 0 {
-                        currency_ids.insert(ItemId(item_id as i32));
-                    }
-                }
-            }
-        }
+    /// The best few matches for `query_str`, filters included.
+    pub fn search(&self, query_str: &str, lang: Language) -> Vec<SearchResult> {
+        self.search_page(query_str, lang, 0, QUICK_SEARCH_LIMIT)
+            .results
+    }
 
-        for item in data.items.values() {
-            if item.name == "Gil" || item.name == "MGP" {
-                currency_ids.insert(item.key_id);
-            }
+    /// Search the names shown on a `lang` client, honouring any filters in
+    /// `query_str` (see [`parse_search_query`]). Falls back to English when
+    /// nothing matches, so a name copied from an English guide still works.
+    pub fn search_page(
+        &self,
+        query_str: &str,
+        lang: Language,
+        offset: usize,
+        limit: usize,
+    ) -> SearchPage {
+        let query = parse_search_query(query_str);
+        let search_in = |lang| {
+            self.indexes
+                .get(&lang)
+                .map(|index: &LanguageIndex| {
+                    index.search(&query.text, &query.filters, offset, limit)
+                })
+                .unwrap_or_default()
+        };
+        let page = search_in(lang);
+        if page.total == 0 && lang != Language::En {
+            return search_in(Language::En);
         }
+        page
+    }
+}
 
-        for id in currency_ids {
-            if let Some(item) = data.items.get(&id)
-                && (allowed_item_ui_categories.contains(&ItemUiCategoryId(item.item_ui_category))
-                    || item.name == "Gil"
-                    || item.name == "MGP")
-            {
-                index_writer.add_document(doc!(
-                    title_field => item.name.as_str(),
-                    type_field => "currency",
-                    url_field => format!("/currency-exchange/{}", id.0),
-                    icon_id_field => id.0 as i64, // Use Item ID for image lookup
-                    category_field => "",
-                ))?;
-            }
-        }
+impl LanguageIndex {
+    fn build(
+        lang: Language,
+        documents: impl IntoIterator<Item = SearchDocument>,
+    ) -> anyhow::Result<Self> {
+        let mut schema_builder = Schema::builder();
+
+        // Use a tokenizer that handles apostrophes better if possible, or just standard English
+        // For now, we'll stick to standard but rely on fuzzy search to help with "Samurai's" vs "Samurai"
+        let title_options = TextOptions::default()
+            .set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer(tokenizer_name(lang))
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            )
+            .set_stored();
+
+        let fields = SearchFields {
+            title: schema_builder.add_text_field("title", title_options.clone()),
+            result_type: schema_builder.add_text_field("type", STRING | STORED),
+            url: schema_builder.add_text_field("url", STORED),
+            icon_id: schema_builder.add_i64_field("icon_id", STORED),
+            // Category field uses same options as title for searchability
+            category: schema_builder.add_text_field("category", title_options),
+            item_level: schema_builder.add_u64_field("item_level", INDEXED | FAST),
+            equip_level: schema_builder.add_u64_field("equip_level", INDEXED | FAST),
+            hq: schema_builder.add_u64_field("hq", INDEXED | FAST),
+            craftable: schema_builder.add_u64_field("craftable", INDEXED | FAST),
+            marketable: schema_builder.add_u64_field("marketable", INDEXED | FAST),
+            jobs: schema_builder.add_text_field("jobs", STRING),
+            ui_category: schema_builder.add_text_field("ui_category", STRING),
+            facets: schema_builder.add_facet_field("facets", FacetOptions::default()),
+        };
 
+        let index = Index::create_in_ram(schema_builder.build());
+        register_tokenizers(&index)?;
+        let mut index_writer = index.writer(50_000_000)?;
+        for document in documents {
+            index_writer.add_document(fields.document(document))?;
+        }
         index_writer.commit()?;
-        info!("SearchService: Indexing complete.");
 
         let reader = index
             .reader_builder()
@@ -167,94 +240,597 @@ impl SearchService {
             .try_into()?;
 
         Ok(Self {
-            index: Arc::new(index),
+            index,
             reader,
-            title_field,
-            type_field,
-            url_field,
-            icon_id_field,
-            category_field,
+            fields,
+            fuzzy: !is_cjk(lang),
         })
     }
 
-    pub fn search(&self, query_str: &str) -> Vec<SearchResult> {
-        let searcher = self.reader.searcher();
-        // Exact match parser (High boost)
-        let mut exact_parser =
-            QueryParser::for_index(&self.index, vec![self.title_field, self.category_field]);
-        exact_parser.set_field_boost(self.title_field, 5.0);
-        exact_parser.set_field_boost(self.category_field, 1.0);
-
-        // Fuzzy match parser (Low boost)
-        let mut fuzzy_parser =
-            QueryParser::for_index(&self.index, vec![self.title_field, self.category_field]);
-        fuzzy_parser.set_field_boost(self.title_field, 0.5);
-        fuzzy_parser.set_field_boost(self.category_field, 0.1);
-        fuzzy_parser.set_field_fuzzy(self.title_field, false, 2, true);
-        fuzzy_parser.set_field_fuzzy(self.category_field, false, 1, true);
-
-        let exact_query = exact_parser.parse_query(query_str);
-        let fuzzy_query = fuzzy_parser.parse_query(query_str);
-
-        let query = match (exact_query, fuzzy_query) {
-            (Ok(eq), Ok(fq)) => Box::new(BooleanQuery::union(vec![eq, fq])) as Box<dyn Query>,
-            (Ok(eq), Err(_)) => eq,
-            (Err(_), Ok(fq)) => fq,
-            (Err(e), Err(_)) => {
-                warn!("SearchService: Invalid query '{}': {}", query_str, e);
-                return vec![];
-            }
+    fn search(
+        &self,
+        text: &str,
+        filters: &SearchFilters,
+        offset: usize,
+        limit: usize,
+    ) -> SearchPage {
+        let Some(query) = self.query(text, filters) else {
+            return SearchPage::default();
         };
 
+        let searcher = self.reader.searcher();
+        let mut facet_collector = FacetCollector::for_field("facets");
+        facet_collector.add_facet("/category");
+        facet_collector.add_facet("/type");
         // tantivy 0.26: `TopDocs` itself no longer implements `Collector`; chain
         // `.order_by_score()` to get a score-ordered collector (the previous default).
-        let collector = TopDocs::with_limit(10).order_by_score();
-        let top_docs = match searcher.search(&query, &collector) {
-            Ok(docs) => docs,
+        let collector = (
+            TopDocs::with_limit(limit.max(1))
+                .and_offset(offset)
+                .order_by_score(),
+            Count,
+            facet_collector,
+        );
+        let (top_docs, total, facet_counts) = match searcher.search(&query, &collector) {
+            Ok(found) => found,
             Err(e) => {
                 error!("SearchService: Search execution failed: {}", e);
-                return vec![];
+                return SearchPage::default();
             }
         };
 
-        top_docs
+        let SearchFields {
+            title,
+            result_type,
+            url,
+            icon_id,
+            category,
+            ..
+        } = self.fields;
+        let results = top_docs
             .into_iter()
             .map(|(score, doc_address)| {
-                let retrieved_doc: tantivy::schema::TantivyDocument =
-                    searcher.doc(doc_address).unwrap();
-                let title = retrieved_doc
-                    .get_first(self.title_field)
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string();
-                let result_type = retrieved_doc
-                    .get_first(self.type_field)
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string();
-                let url = retrieved_doc
-                    .get_first(self.url_field)
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string();
+                let retrieved_doc: TantivyDocument = searcher.doc(doc_address).unwrap();
+                let text = |field| {
+                    retrieved_doc
+                        .get_first(field)
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string())
+                };
                 let icon_id = retrieved_doc
-                    .get_first(self.icon_id_field)
+                    .get_first(icon_id)
                     .and_then(|v| v.as_i64())
                     .map(|v| v as i32);
-                let category = retrieved_doc
-                    .get_first(self.category_field)
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string());
 
                 SearchResult {
                     score,
-                    title,
-                    result_type,
-                    url,
+                    title: text(title).unwrap_or_default(),
+                    result_type: text(result_type).unwrap_or_default(),
+                    url: text(url).unwrap_or_default(),
                     icon_id,
-                    category,
+                    category: text(category),
                 }
             })
-            .collect()
+            .collect();
+
+        SearchPage {
+            results,
+            total,
+            offset,
+            facets: SearchFacets {
+                categories: facet_values(&facet_counts, "/category"),
+                types: facet_values(&facet_counts, "/type"),
+            },
+        }
+    }
+
+    /// The free text combined with every filter. `None` when there's nothing
+    /// to search for or the text can't be parsed.
+    fn query(&self, text: &str, filters: &SearchFilters) -> Option<Box<dyn Query>> {
+        if text.trim().is_empty() && *filters == SearchFilters::default() {
+            return None;
+        }
+        let fields = self.fields;
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        if !text.trim().is_empty() {
+            clauses.push((Occur::Must, self.text_query(text)?));
+        }
+
+        let number = |field, filter: NumberFilter| -> Box<dyn Query> {
+            let bound = |value: Option<u32>| match value {
+                Some(value) => Bound::Included(Term::from_field_u64(field, value.into())),
+                None => Bound::Unbounded,
+            };
+            Box::new(RangeQuery::new(bound(filter.min), bound(filter.max)))
+        };
+        let flag = |field, value: bool| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_u64(field, value.into()),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let any_of = |field, values: &[String]| -> Box<dyn Query> {
+            Box::new(TermSetQuery::new(
+                values
+                    .iter()
+                    .map(|value| Term::from_field_text(field, value)),
+            ))
+        };
+
+        if let Some(filter) = filters.item_level {
+            clauses.push((Occur::Must, number(fields.item_level, filter)));
+        }
+        if let Some(filter) = filters.equip_level {
+            clauses.push((Occur::Must, number(fields.equip_level, filter)));
+        }
+        if let Some(hq) = filters.hq {
+            clauses.push((Occur::Must, flag(fields.hq, hq)));
+        }
+        if let Some(craftable) = filters.craftable {
+            clauses.push((Occur::Must, flag(fields.craftable, craftable)));
+        }
+        // Unfiltered searches have only ever shown marketable items; anything
+        // else is opt-in.
+        clauses.push((
+            Occur::Must,
+            flag(fields.marketable, filters.marketable.unwrap_or(true)),
+        ));
+        if !filters.jobs.is_empty() {
+            clauses.push((Occur::Must, any_of(fields.jobs, &filters.jobs)));
+        }
+        if !filters.categories.is_empty() {
+            clauses.push((Occur::Must, any_of(fields.ui_category, &filters.categories)));
+        }
+        if !filters.types.is_empty() {
+            clauses.push((Occur::Must, any_of(fields.result_type, &filters.types)));
+        }
+
+        Some(Box::new(BooleanQuery::new(clauses)))
+    }
+
+    fn text_query(&self, query_str: &str) -> Option<Box<dyn Query>> {
+        let SearchFields {
+            title, category, ..
+        } = self.fields;
+        // Exact match parser (High boost)
+        let mut exact_parser = QueryParser::for_index(&self.index, vec![title, category]);
+        exact_parser.set_field_boost(title, 5.0);
+        exact_parser.set_field_boost(category, 1.0);
+        if !self.fuzzy {
+            // Space-separated CJK words are each broken into grams; require
+            // every word, or one shared character would be enough to match.
+            exact_parser.set_conjunction_by_default();
+        }
+        let exact_query = exact_parser.parse_query(query_str);
+
+        let query = if self.fuzzy {
+            // Fuzzy match parser (Low boost)
+            let mut fuzzy_parser = QueryParser::for_index(&self.index, vec![title, category]);
+            fuzzy_parser.set_field_boost(title, 0.5);
+            fuzzy_parser.set_field_boost(category, 0.1);
+            fuzzy_parser.set_field_fuzzy(title, false, 2, true);
+            fuzzy_parser.set_field_fuzzy(category, false, 1, true);
+            let fuzzy_query = fuzzy_parser.parse_query(query_str);
+
+            match (exact_query, fuzzy_query) {
+                (Ok(eq), Ok(fq)) => Box::new(BooleanQuery::union(vec![eq, fq])) as Box<dyn Query>,
+                (Ok(eq), Err(_)) => eq,
+                (Err(_), Ok(fq)) => fq,
+                (Err(e), Err(_)) => {
+                    warn!("SearchService: Invalid query '{}': {}", query_str, e);
+                    return None;
+                }
+            }
+        } else {
+            match exact_query {
+                Ok(eq) => eq,
+                Err(e) => {
+                    warn!("SearchService: Invalid query '{}': {}", query_str, e);
+                    return None;
+                }
+            }
+        };
+        Some(query)
+    }
+}
+
+impl SearchFields {
+    fn document(&self, document: SearchDocument) -> TantivyDocument {
+        let mut doc = TantivyDocument::default();
+        doc.add_text(self.title, &document.title);
+        doc.add_text(self.result_type, document.result_type);
+        doc.add_text(self.url, &document.url);
+        doc.add_i64(self.icon_id, document.icon_id);
+        doc.add_text(self.category, &document.category);
+        doc.add_facet(
+            self.facets,
+            Facet::from_path(["type", document.result_type]),
+        );
+        match document.item {
+            Some(item) => {
+                doc.add_u64(self.item_level, item.item_level);
+                doc.add_u64(self.equip_level, item.equip_level);
+                doc.add_u64(self.hq, item.hq.into());
+                doc.add_u64(self.craftable, item.craftable.into());
+                doc.add_u64(self.marketable, item.marketable.into());
+                for job in &item.jobs {
+                    doc.add_text(self.jobs, job);
+                }
+                for key in &item.ui_category_keys {
+                    doc.add_text(self.ui_category, key);
+                }
+                if !item.ui_category.is_empty() {
+                    doc.add_facet(
+                        self.facets,
+                        Facet::from_path(["category", item.ui_category.as_str()]),
+                    );
+                }
+            }
+            // Categories, jobs and currencies all lead to market board pages,
+            // so they show up in unfiltered searches.
+            None => doc.add_u64(self.marketable, 1),
+        }
+        doc
+    }
+}
+
+/// Children of `parent`, most common first.
+fn facet_values(counts: &FacetCounts, parent: &str) -> Vec<SearchFacet> {
+    let mut values: Vec<SearchFacet> = counts
+        .get(parent)
+        .filter_map(|(facet, count)| {
+            facet.to_path().last().map(|value| SearchFacet {
+                value: value.to_string(),
+                count,
+            })
+        })
+        .collect();
+    values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    values.truncate(MAX_FACET_VALUES);
+    values
+}
+
+fn is_cjk(lang: Language) -> bool {
+    matches!(
+        lang,
+        Language::Ja | Language::Cn | Language::Ko | Language::Tc
+    )
+}
+
+/// Tokenizer used for `lang`'s text fields. English uses tantivy's built-in
+/// `en_stem`; the others are added by [`register_tokenizers`].
+fn tokenizer_name(lang: Language) -> &'static str {
+    match lang {
+        Language::En => "en_stem",
+        Language::De => "de_stem",
+        Language::Fr => "fr_stem",
+        Language::Ja | Language::Cn | Language::Ko | Language::Tc => "cjk_ngram",
+    }
+}
+
+fn register_tokenizers(index: &Index) -> tantivy::Result<()> {
+    let stemmed = |language| {
+        TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(RemoveLongFilter::limit(40))
+            .filter(LowerCaser)
+            .filter(Stemmer::new(language))
+            // Lets "epee" find "Épée" once the stemmer has seen the accents.
+            .filter(AsciiFoldingFilter)
+            .build()
+    };
+    let tokenizers = index.tokenizers();
+    tokenizers.register("de_stem", stemmed(StemLanguage::German));
+    tokenizers.register("fr_stem", stemmed(StemLanguage::French));
+    // Japanese and Chinese names have no spaces to split on, and Korean item
+    // names are mostly unspaced compounds, so index overlapping 1–2 character
+    // grams instead of words.
+    tokenizers.register(
+        "cjk_ngram",
+        TextAnalyzer::builder(NgramTokenizer::all_ngrams(1, 2)?)
+            .filter(LowerCaser)
+            .build(),
+    );
+    Ok(())
+}
+
+/// Items offered as currency by shops that sell marketable items, chosen by
+/// their English names and UI categories.
+fn currency_item_ids(data: &xiv_gen::Data) -> HashSet<ItemId> {
+    // Logic adapted from CurrencySelection to find items used as currency for marketable items
+    let ui_categories = &data.item_ui_categorys;
+    let allowed_item_ui_categories = ["Currency", "Miscellany", "Other"]
+        .into_iter()
+        .filter_map(|category| {
+            ui_categories
+                .iter()
+                .find(|f| f.1.name == category)
+                .map(|(id, _)| *id)
+        })
+        .collect::<Vec<_>>();
+
+    let mut currency_ids = HashSet::new();
+
+    // Extract currency items from special shops
+    // The SpecialShop struct now has a flat `item: Vec<u16>` containing all item IDs.
+    // We check if any items in the shop are marketable, and if so, collect all non-zero
+    // item IDs as potential currencies (filtered later by UI category).
+    for shop in data.special_shops.values() {
+        let has_marketable_item = shop.item.iter().any(|&item_id| {
+            item_id != 0
+                && data
+                    .items
+                    .get(&ItemId(item_id as i32))
+                    .is_some_and(|item| item.item_search_category > 0)
+        });
+
+        if has_marketable_item {
+            for &item_id in &shop.item {
+                if item_id != 0 {
+                    currency_ids.insert(ItemId(item_id as i32));
+                }
+            }
+        }
+    }
+
+    for item in data.items.values() {
+        if item.name == "Gil" || item.name == "MGP" {
+            currency_ids.insert(item.key_id);
+        }
+    }
+
+    currency_ids.retain(|id| {
+        data.items.get(id).is_some_and(|item| {
+            allowed_item_ui_categories.contains(&ItemUiCategoryId(item.item_ui_category))
+                || item.name == "Gil"
+                || item.name == "MGP"
+        })
+    });
+    currency_ids
+}
+
+/// Everything searchable in `data`, titled in its language. URLs are built
+/// from ids or English names (`en`) so every locale links to the same pages.
+/// Entries the pack leaves unnamed — content not yet released in that region —
+/// are skipped.
+fn search_documents(
+    data: &xiv_gen::Data,
+    en: &xiv_gen::Data,
+    shared: &SharedFacts,
+) -> Vec<SearchDocument> {
+    let mut documents = Vec::new();
+
+    // Index Items. Unmarketable ones are kept for `marketable:false`.
+    for (id, item) in &data.items {
+        if item.name.is_empty() {
+            continue;
+        }
+        let ui_category_id = ItemUiCategoryId(item.item_ui_category);
+        let ui_category = data
+            .item_ui_categorys
+            .get(&ui_category_id)
+            .map(|c| c.name.as_str())
+            .unwrap_or("");
+        let category_name = data
+            .item_search_categorys
+            .get(&ItemSearchCategoryId(item.item_search_category))
+            .map(|c| c.name.as_str())
+            .filter(|name| !name.is_empty())
+            .unwrap_or(ui_category);
+
+        let mut ui_category_keys = vec![item.item_ui_category.to_string()];
+        let en_ui_category = en
+            .item_ui_categorys
+            .get(&ui_category_id)
+            .map(|c| c.name.as_str());
+        for name in [Some(ui_category), en_ui_category].into_iter().flatten() {
+            let key = name.to_lowercase();
+            if !key.is_empty() && !ui_category_keys.contains(&key) {
+                ui_category_keys.push(key);
+            }
+        }
+
+        documents.push(SearchDocument {
+            title: item.name.to_string(),
+            result_type: "item",
+            url: format!("/item/{}", id.0),
+            icon_id: id.0 as i64, // Use Item ID for image lookup
+            category: category_name.to_string(),
+            item: Some(ItemAttributes {
+                item_level: item.level_item.max(0) as u64,
+                equip_level: item.level_equip.max(0) as u64,
+                hq: item.can_be_hq,
+                craftable: shared.craftable.contains(id),
+                marketable: item.item_search_category > 0,
+                jobs: shared
+                    .jobs_by_category
+                    .get(&ClassJobCategoryId(item.class_job_category))
+                    .cloned()
+                    .unwrap_or_default(),
+                ui_category_keys,
+                ui_category: ui_category.to_string(),
+            }),
+        });
+    }
+
+    // Index Categories
+    for cat in data.item_search_categorys.values() {
+        if cat.name.is_empty() {
+            continue;
+        }
+        documents.push(SearchDocument {
+            title: cat.name.to_string(),
+            result_type: "category",
+            // Keyed by id: a name-keyed URL would send visitors to a category
+            // their client cannot resolve. See `resolve_category_param` in
+            // `item_explorer.rs`.
+            url: format!("/items/category/{}", cat.key_id.0),
+            // Categories don't have a direct icon, maybe use a default or 0
+            icon_id: 0,
+            category: String::new(),
+            item: None,
+        });
+    }
+
+    // Index Jobs
+    for (id, job) in &data.class_jobs {
+        if (job.job_index > 0 || job.doh_dol_job_index >= 0) && !job.name.is_empty() {
+            let title = if job.abbreviation.is_empty() {
+                job.name.to_string()
+            } else {
+                format!("{} ({})", job.name, job.abbreviation)
+            };
+            // The jobset page is keyed by the English job name.
+            let url_name = en
+                .class_jobs
+                .get(id)
+                .map(|job| job.name.as_str())
+                .unwrap_or(job.name.as_str());
+            documents.push(SearchDocument {
+                title,
+                result_type: "job equipment", // Renamed from "job"
+                url: format!("/items/jobset/{url_name}"),
+                icon_id: 0, // Jobs don't have a simple icon ID in this context easily accessible or needed?
+                category: String::new(),
+                item: None,
+            });
+        }
+    }
+
+    // Index Currencies
+    for id in &shared.currency_ids {
+        if let Some(item) = data.items.get(id)
+            && !item.name.is_empty()
+        {
+            documents.push(SearchDocument {
+                title: item.name.to_string(),
+                result_type: "currency",
+                url: format!("/currency-exchange/{}", id.0),
+                icon_id: id.0 as i64, // Use Item ID for image lookup
+                category: String::new(),
+                item: None,
+            });
+        }
+    }
+
+    documents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(title: &str, id: i64) -> SearchDocument {
+        SearchDocument {
+            title: title.to_string(),
+            result_type: "item",
+            url: format!("/item/{id}"),
+            icon_id: id,
+            category: String::new(),
+            item: Some(ItemAttributes {
+                item_level: 1,
+                equip_level: 1,
+                hq: false,
+                craftable: false,
+                marketable: true,
+                jobs: Vec::new(),
+                ui_category_keys: Vec::new(),
+                ui_category: String::new(),
+            }),
+        }
+    }
+
+    fn gear(title: &str, item_level: u64, jobs: &[&str], ui_category: &str) -> SearchDocument {
+        let mut document = item(title, item_level as i64);
+        let attributes = document.item.as_mut().unwrap();
+        attributes.item_level = item_level;
+        attributes.jobs = jobs.iter().map(|job| job.to_string()).collect();
+        attributes.ui_category_keys = vec![ui_category.to_lowercase()];
+        attributes.ui_category = ui_category.to_string();
+        attributes.hq = true;
+        document
+    }
+
+    fn titles(index: &LanguageIndex, query: &str) -> Vec<String> {
+        let query = parse_search_query(query);
+        let mut titles: Vec<String> = index
+            .search(&query.text, &query.filters, 0, 10)
+            .results
+            .into_iter()
+            .map(|r| r.title)
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    #[test]
+    fn japanese_names_match_without_spaces() {
+        let index = LanguageIndex::build(
+            Language::Ja,
+            [item("ハイポーション", 1), item("エーテル", 2)],
+        )
+        .unwrap();
+        assert_eq!(titles(&index, "ポーション"), ["ハイポーション"]);
+        // Sharing a single character ("ー") with another name isn't a match.
+        assert_eq!(titles(&index, "エーテル"), ["エーテル"]);
+    }
+
+    #[test]
+    fn german_names_are_stemmed_and_folded() {
+        let index = LanguageIndex::build(Language::De, [item("Hochwertiger Trank", 1)]).unwrap();
+        assert_eq!(titles(&index, "hochwertige tränke"), ["Hochwertiger Trank"]);
+    }
+
+    #[test]
+    fn french_accents_are_optional() {
+        let index = LanguageIndex::build(Language::Fr, [item("Épée de bronze", 1)]).unwrap();
+        assert_eq!(titles(&index, "epee"), ["Épée de bronze"]);
+    }
+
+    #[test]
+    fn filters_narrow_without_text() {
+        let mut unmarketable = gear("Retired Robe", 710, &["WHM"], "Body");
+        unmarketable.item.as_mut().unwrap().marketable = false;
+        let index = LanguageIndex::build(
+            Language::En,
+            [
+                gear("Healer's Robe", 690, &["WHM", "SCH"], "Body"),
+                gear("Healer's Hat", 700, &["WHM", "SCH"], "Head"),
+                gear("Striker's Robe", 700, &["MNK"], "Body"),
+                gear("Old Robe", 500, &["WHM"], "Body"),
+                unmarketable,
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            titles(&index, r#"ilvl:>=690 job:WHM cat:"Body""#),
+            ["Healer's Robe"]
+        );
+        assert_eq!(
+            titles(&index, "robe ilvl:690..700"),
+            ["Healer's Robe", "Striker's Robe"]
+        );
+        assert_eq!(titles(&index, "marketable:false"), ["Retired Robe"]);
+        assert!(titles(&index, "hq:false").is_empty());
+    }
+
+    #[test]
+    fn pages_count_every_match_and_facet() {
+        let index = LanguageIndex::build(
+            Language::En,
+            (0..25).map(|i| gear(&format!("Robe {i}"), 600, &["WHM"], "Body")),
+        )
+        .unwrap();
+        let page = index.search("robe", &SearchFilters::default(), 20, 10);
+        assert_eq!(page.total, 25);
+        assert_eq!(page.offset, 20);
+        assert_eq!(page.results.len(), 5);
+        assert_eq!(
+            page.facets.categories,
+            [SearchFacet {
+                value: "Body".to_string(),
+                count: 25
+            }]
+        );
+        assert_eq!(page.facets.types[0].value, "item");
     }
 }
@@ -5,7 +5,9 @@ pub(crate) mod error;
 pub(crate) mod item_card;
 pub(crate) mod list_permission;
 pub(crate) mod oauth;
+pub(crate) mod openapi;
 pub(crate) mod price_series_cache;
+pub(crate) mod rate_limit;
 pub(crate) mod sitemap;
 pub(crate) mod state;
 pub(crate) mod static_files;
@@ -44,13 +46,16 @@ use ultros_api_types::list::{
 use ultros_api_types::price_series::{
     HqFilter, PriceBucket, PriceSeries, PriceSeriesEntry, SeriesGroup,
 };
+use ultros_api_types::repricing::RetainerReprice;
+use ultros_api_types::result::JsonErrorWrapper;
 use ultros_api_types::retainer::RetainerListings;
 use ultros_api_types::user::group::{
     CreateGroup, CreateGroupFromGuild, CreateGroupInvite, DiscordManageableGuild, GroupInvite,
     UserGroup, UserGroupMember,
 };
 use ultros_api_types::user::{
-    AssignRetainerCharacter, OwnedRetainer, UserData, UserRetainerListings, UserRetainers,
+    ApiToken, AssignRetainerCharacter, CreateApiToken, CreatedApiToken, OwnedRetainer,
+    RetainerLedger, UserData, UserRetainerListings, UserRetainers, UserSession,
 };
 use ultros_api_types::websocket::{ListEventData, ListingEventData};
 use ultros_api_types::world::WorldData;
@@ -68,15 +73,20 @@ use ultros_db::ActiveValue;
 use ultros_db::world_data::world_cache::{AnyResult, AnySelector};
 use ultros_db::{UltrosDb, world_data::world_cache::WorldCache};
 use universalis::{ItemId, ListingView, UniversalisClient, WorldId};
+use utoipa::OpenApi;
+use utoipa_scalar::{Scalar, Servable};
 
 use crate::character_claim::CharacterClaimService;
 
 use self::country_code_decoder::Region;
 use self::error::{ApiError, WebError};
 use self::oauth::{AuthDiscordUser, AuthUserCache};
+use self::rate_limit::RateLimiter;
 use crate::alerts::price_alert_tracker::resolve_item_name;
+use crate::analyzer_service::AnalyzerService;
 use crate::event::{EventSenders, EventType};
 use crate::leptos::create_leptos_app;
+use crate::repricing::Repricer;
 use crate::search_service::SearchService;
 use crate::web::api::alerts::{
     create_alert, delete_alert, list_alert_events, list_alerts, resend_alert_event, update_alert,
@@ -87,8 +97,9 @@ use crate::web::api::endpoints::{
 };
 use crate::web::api::real_time_data::real_time_data;
 use crate::web::api::{
-    cheapest_per_world, get_best_deals, get_item_stats, get_market_heat, get_market_pulse,
-    get_movers, get_trends, post_resale_quality, post_sparklines, recent_sales,
+    cheapest_per_world, export_sales, get_best_deals, get_fill_cost, get_item_stats,
+    get_market_heat, get_market_pulse, get_movers, get_patch_diff, get_route_plan, get_trends,
+    post_craft_plan, post_fill_cost, post_resale_quality, post_sparklines, recent_sales,
 };
 use crate::web::sitemap::{generic_pages_sitemap, item_sitemap, sitemap_index};
 use crate::web::{
@@ -233,6 +244,17 @@ async fn remove_owned_retainer(
     Ok(Redirect::to("/retainers/edit"))
 }
 
+/// Current listings and recent sales for an item.
+#[utoipa::path(
+    get,
+    path = "/api/v1/listings/{world}/{itemid}",
+    tag = "market",
+    params(
+        ("world" = String, Path, description = "World, datacenter or region name"),
+        ("itemid" = i32, Path, description = "Item id"),
+    ),
+    responses((status = 200, body = CurrentlyShownItem)),
+)]
 #[tracing::instrument(skip(db, world_cache))]
 async fn world_item_listings(
     State(db): State<UltrosDb>,
@@ -1207,6 +1229,13 @@ pub(crate) async fn invite() -> Redirect {
     ))
 }
 
+/// Every region, datacenter and world, with the ids other routes use.
+#[utoipa::path(
+    get,
+    path = "/api/v1/world_data",
+    tag = "market",
+    responses((status = 200, body = WorldData)),
+)]
 pub(crate) async fn world_data(State(world_cache): State<Arc<WorldCache>>) -> impl IntoResponse {
     static ONCE: OnceLock<WorldData> = OnceLock::new();
     let world_data = ONCE.get_or_init(move || WorldData::from(world_cache.as_ref()));
@@ -1217,6 +1246,17 @@ pub(crate) async fn world_data(State(world_cache): State<Arc<WorldCache>>) -> im
     response
 }
 
+/// The user the request is authenticated as.
+#[utoipa::path(
+    get,
+    path = "/api/v1/current_user",
+    tag = "user",
+    security(("api_token" = [])),
+    responses(
+        (status = 200, body = UserData),
+        (status = 401, body = JsonErrorWrapper),
+    ),
+)]
 pub(crate) async fn current_user(user: AuthDiscordUser) -> Json<UserData> {
     Json(UserData {
         id: user.id,
@@ -1225,6 +1265,117 @@ pub(crate) async fn current_user(user: AuthDiscordUser) -> Json<UserData> {
     })
 }
 
+/// The signed-in user's devices.
+async fn user_sessions(
+    user: AuthDiscordUser,
+    State(db): State<UltrosDb>,
+) -> Result<Json<Vec<UserSession>>, ApiError> {
+    let sessions = db
+        .list_user_sessions(user.id)
+        .await?
+        .into_iter()
+        .map(|session| UserSession {
+            current: Some(session.id) == user.session_id(),
+            id: session.id,
+            created_at: session.created_at,
+            last_seen_at: session.last_seen_at,
+            expires_at: session.expires_at,
+            user_agent: session.user_agent,
+        })
+        .collect();
+    Ok(Json(sessions))
+}
+
+async fn revoke_user_session(
+    user: AuthDiscordUser,
+    State(db): State<UltrosDb>,
+    State(cache): State<AuthUserCache>,
+    Path(session_id): Path<i64>,
+) -> Result<Json<bool>, ApiError> {
+    let revoked = db.revoke_user_session(user.id, session_id).await?;
+    cache.remove_session(session_id).await;
+    Ok(Json(revoked))
+}
+
+/// Sign out every device but the one making the request.
+async fn revoke_other_user_sessions(
+    user: AuthDiscordUser,
+    State(db): State<UltrosDb>,
+    State(cache): State<AuthUserCache>,
+) -> Result<Json<u64>, ApiError> {
+    let session_id = user
+        .session_id()
+        .ok_or(ApiError::Forbidden("Only a signed-in browser can do this"))?;
+    let revoked = db.revoke_other_user_sessions(user.id, session_id).await?;
+    cache
+        .remove_user_sessions_except(user.id, Some(session_id))
+        .await;
+    Ok(Json(revoked))
+}
+
+/// The user's personal API tokens. Secrets are only shown at creation.
+async fn api_tokens(
+    user: AuthDiscordUser,
+    State(db): State<UltrosDb>,
+) -> Result<Json<Vec<ApiToken>>, ApiError> {
+    let tokens = db.list_api_tokens(user.id).await?;
+    Ok(Json(tokens.into_iter().map(ApiToken::from).collect()))
+}
+
+async fn create_api_token(
+    user: AuthDiscordUser,
+    State(db): State<UltrosDb>,
+    Json(request): Json<CreateApiToken>,
+) -> Result<Json<CreatedApiToken>, ApiError> {
+    let name = request.name.trim();
+    if name.is_empty() || name.chars().count() > 64 {
+        return Err(anyhow::anyhow!("Token name must be 1 to 64 characters").into());
+    }
+    let mut scopes = request.scopes;
+    scopes.sort();
+    scopes.dedup();
+    if scopes.is_empty() {
+        return Err(anyhow::anyhow!("A token needs at least one scope").into());
+    }
+    let expires_in = match request.expires_in_days {
+        Some(days @ 1..=3650) => Some(chrono::TimeDelta::days(days.into())),
+        Some(_) => return Err(anyhow::anyhow!("Expiry must be 1 to 3650 days").into()),
+        None => None,
+    };
+    let (secret, token) = db
+        .create_api_token(
+            user.id,
+            name.to_string(),
+            &scopes,
+            user.avatar_url,
+            expires_in,
+        )
+        .await?;
+    Ok(Json(CreatedApiToken {
+        token: token.into(),
+        secret,
+    }))
+}
+
+async fn revoke_api_token(
+    user: AuthDiscordUser,
+    State(db): State<UltrosDb>,
+    State(cache): State<AuthUserCache>,
+    Path(token_id): Path<i64>,
+) -> Result<Json<bool>, ApiError> {
+    let revoked = db.revoke_api_token(user.id, token_id).await?;
+    cache.remove_api_token(token_id).await;
+    Ok(Json(revoked))
+}
+
+/// A retainer and its current listings.
+#[utoipa::path(
+    get,
+    path = "/api/v1/retainer/listings/{id}",
+    tag = "market",
+    params(("id" = i32, Path, description = "Retainer id")),
+    responses((status = 200, body = RetainerListings)),
+)]
 pub(crate) async fn retainer_listings(
     State(db): State<UltrosDb>,
     Path(id): Path<i32>,
@@ -1237,6 +1388,17 @@ pub(crate) async fn retainer_listings(
     Ok(Json(listings))
 }
 
+/// The user's retainers, grouped by character.
+#[utoipa::path(
+    get,
+    path = "/api/v1/user/retainer",
+    tag = "user",
+    security(("api_token" = ["read:retainers"])),
+    responses(
+        (status = 200, body = UserRetainers),
+        (status = 401, body = JsonErrorWrapper),
+    ),
+)]
 pub(crate) async fn user_retainers(
     State(db): State<UltrosDb>,
     user: AuthDiscordUser,
@@ -1263,6 +1425,17 @@ pub(crate) async fn user_retainers(
     Ok(Json(retainers))
 }
 
+/// The user's retainers with their current listings.
+#[utoipa::path(
+    get,
+    path = "/api/v1/user/retainer/listings",
+    tag = "user",
+    security(("api_token" = ["read:retainers"])),
+    responses(
+        (status = 200, body = UserRetainerListings),
+        (status = 401, body = JsonErrorWrapper),
+    ),
+)]
 pub(crate) async fn user_retainer_listings(
     State(db): State<UltrosDb>,
     user: AuthDiscordUser,
@@ -1297,6 +1470,65 @@ pub(crate) async fn user_retainer_listings(
     Ok(Json(retainers))
 }
 
+/// Default and longest look-back for the retainer ledger, in days.
+const DEFAULT_LEDGER_DAYS: i64 = 90;
+const MAX_LEDGER_DAYS: i64 = 365;
+
+#[derive(Deserialize)]
+pub(crate) struct RetainerLedgerQuery {
+    days: Option<i64>,
+}
+
+/// Sales credited to the user's retainers: revenue, market tax, units sold
+/// and time to sell, per retainer, per item and per week.
+#[utoipa::path(
+    get,
+    path = "/api/v1/user/retainer/ledger",
+    tag = "user",
+    security(("api_token" = ["read:retainers"])),
+    params(
+        ("days" = Option<i64>, Query, description = "How many days back to look, 1 to 365. Defaults to 90"),
+    ),
+    responses(
+        (status = 200, body = RetainerLedger),
+        (status = 401, body = JsonErrorWrapper),
+    ),
+)]
+pub(crate) async fn user_retainer_ledger(
+    State(db): State<UltrosDb>,
+    user: AuthDiscordUser,
+    Query(query): Query<RetainerLedgerQuery>,
+) -> Result<Json<RetainerLedger>, ApiError> {
+    let days = query
+        .days
+        .unwrap_or(DEFAULT_LEDGER_DAYS)
+        .clamp(1, MAX_LEDGER_DAYS);
+    let since = chrono::Utc::now().naive_utc() - chrono::Duration::days(days);
+    Ok(Json(db.get_retainer_ledger(user.id, since).await?))
+}
+
+/// Every listing on the user's retainers with the competing listings, recent
+/// sales and 30-day price band a relist suggestion is computed from.
+#[utoipa::path(
+    get,
+    path = "/api/v1/user/retainer/reprice",
+    tag = "user",
+    security(("api_token" = ["read:retainers"])),
+    responses(
+        (status = 200, body = Vec<RetainerReprice>),
+        (status = 401, body = JsonErrorWrapper),
+    ),
+)]
+pub(crate) async fn user_retainer_reprice(
+    State(db): State<UltrosDb>,
+    State(analyzer): State<AnalyzerService>,
+    State(ch): State<ClickHouseClient>,
+    user: AuthDiscordUser,
+) -> Result<Json<Vec<RetainerReprice>>, ApiError> {
+    let repricer = Repricer::new(db, analyzer, ch);
+    Ok(Json(repricer.for_user(user.id).await?))
+}
+
 pub(crate) async fn retainer_search(
     State(db): State<UltrosDb>,
     Path(retainer_name): Path<String>,
@@ -1323,6 +1555,17 @@ pub(crate) async fn unclaim_retainer(
     Ok(())
 }
 
+/// Lists the user owns or that are shared with them.
+#[utoipa::path(
+    get,
+    path = "/api/v1/list",
+    tag = "user",
+    security(("api_token" = ["read:lists"])),
+    responses(
+        (status = 200, body = Vec<ListWithPermission>),
+        (status = 401, body = JsonErrorWrapper),
+    ),
+)]
 pub(crate) async fn get_lists(
     State(db): State<UltrosDb>,
     user: AuthDiscordUser,
@@ -1855,13 +2098,78 @@ async fn claim_character(
 #[derive(Deserialize)]
 struct SearchQuery {
     q: String,
-}
-
+    /// Client language to match names in (`en`, `ja`, `de`, `fr`, `cn`, `ko`,
+    /// `tc`). Missing or unknown values search English.
+    #[serde(default)]
+    lang: Option<String>,
+}
+
+/// The ten best matches for a query. `q` accepts `key:value` filters such as
+/// `ilvl:>=690`, `job:WHM` or `cat:Body`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/search",
+    tag = "search",
+    params(
+        ("q" = String, Query, description = "Search text and filters"),
+        ("lang" = Option<String>, Query, description = "en, ja, de, fr, cn, ko or tc"),
+    ),
+    responses((status = 200, body = Vec<ultros_api_types::search::SearchResult>)),
+)]
 async fn search(
     State(service): State<SearchService>,
     Query(query): Query<SearchQuery>,
 ) -> Json<Vec<ultros_api_types::search::SearchResult>> {
-    Json(service.search(&query.q))
+    Json(service.search(&query.q, search_language(query.lang.as_deref())))
+}
+
+fn search_language(lang: Option<&str>) -> xiv_gen::Language {
+    lang.and_then(xiv_gen::Language::from_path_part)
+        .unwrap_or(xiv_gen::Language::En)
+}
+
+/// Largest page `/api/v1/search/page` returns.
+const MAX_SEARCH_PAGE: usize = 100;
+/// Deepest result `/api/v1/search/page` pages to; the collector keeps every
+/// result up to `offset + limit` in memory.
+const MAX_SEARCH_OFFSET: usize = 10_000;
+
+#[derive(Deserialize)]
+struct SearchPageQuery {
+    q: String,
+    #[serde(default)]
+    lang: Option<String>,
+    #[serde(default)]
+    offset: usize,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// Paginated search with facet counts. `q` takes the same `key:value`
+/// filters as `/api/v1/search`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/search/page",
+    tag = "search",
+    params(
+        ("q" = String, Query, description = "Search text and filters"),
+        ("lang" = Option<String>, Query, description = "en, ja, de, fr, cn, ko or tc"),
+        ("offset" = Option<usize>, Query, description = "Results to skip, up to 10000"),
+        ("limit" = Option<usize>, Query, description = "Page size, 1 to 100, default 50"),
+    ),
+    responses((status = 200, body = ultros_api_types::search::SearchPage)),
+)]
+async fn search_page(
+    State(service): State<SearchService>,
+    Query(query): Query<SearchPageQuery>,
+) -> Json<ultros_api_types::search::SearchPage> {
+    let limit = query.limit.unwrap_or(50).clamp(1, MAX_SEARCH_PAGE);
+    Json(service.search_page(
+        &query.q,
+        search_language(query.lang.as_deref()),
+        query.offset.min(MAX_SEARCH_OFFSET),
+        limit,
+    ))
 }
 
 // #[debug_handler(state = WebState)]
@@ -2304,10 +2612,10 @@ async fn delete_user(
         .ok_or(anyhow::anyhow!("Failed to get icon"))?
         .value()
         .to_owned();
+    // deleting the user cascades to their sessions, evict them from the cache too
     cache.remove_token(&token).await;
+    cache.remove_user_sessions_except(id, None).await;
     let cookie_jar = cookie_jar.remove(Cookie::from("discord_auth"));
-    // remove the token from the cache
-    // remove the auth cookie from the cache
     Ok((cookie_jar, Redirect::to("/")))
 }
 
@@ -2364,19 +2672,26 @@ pub(crate) async fn start_web(
     // build our application with a route
     let worlds = state.world_helper.clone();
     let token = state.token.clone();
+    let rate_limiter = RateLimiter::new(state.db.clone(), state.user_cache.clone());
     let app = Router::new()
         .route("/alerts/websocket", get(connect_websocket))
         .route("/api/v1/search", get(search))
+        .route("/api/v1/search/page", get(search_page))
         .route("/api/v1/realtime/events", get(real_time_data))
         .route("/api/v1/cheapest/{world}", get(cheapest_per_world))
+        .route("/api/v1/fill_cost/{world}", post(post_fill_cost))
+        .route("/api/v1/fill_cost/{world}/{itemid}", get(get_fill_cost))
+        .route("/api/v1/craft_plan/{world}", post(post_craft_plan))
         .route("/api/v1/trends/{world}", get(get_trends))
         .route("/api/v1/best_deals/{world}", get(get_best_deals))
+        .route("/api/v1/route_planner/{world}", get(get_route_plan))
         .route("/api/v1/market_pulse/{world}", get(get_market_pulse))
         .route("/api/v1/item_stats/{world}/{itemid}", get(get_item_stats))
         .route("/api/v1/movers/{world}", get(get_movers))
         .route("/api/v1/sparklines/{world}", post(post_sparklines))
         .route("/api/v1/resale_quality/{world}", post(post_resale_quality))
         .route("/api/v1/market_heat/{world}", get(get_market_heat))
+        .route("/api/v1/patch_diff", get(get_patch_diff))
         .route("/api/v1/recentSales/{world}", get(recent_sales))
         .route("/api/v1/alerts/events", get(list_alert_events))
         .route(
@@ -2418,6 +2733,7 @@ pub(crate) async fn start_web(
             get(extended_sale_history),
         )
         .route("/api/v1/price_series/{world}/{itemid}", get(price_series))
+        .route("/api/v1/export/sales", get(export_sales))
         .route("/api/v1/price_density/{world}/{itemid}", get(price_density))
         .route("/api/v1/game-history", get(game_history))
         .route(
@@ -2483,6 +2799,11 @@ pub(crate) async fn start_web(
         .route("/api/v1/invite/{id}/use", post(use_invite))
         .route("/api/v1/invite/{id}", delete(delete_invite))
         .route("/api/v1/world_data", get(world_data))
+        .route("/api/openapi.json", get(self::openapi::openapi_json))
+        .merge(Scalar::with_url(
+            "/api/docs",
+            self::openapi::ApiDoc::openapi(),
+        ))
         .route("/api/v1/current_user", get(current_user))
         .route("/api/v1/user/retainer", get(user_retainers))
         .route("/api/v1/retainer/reorder", post(reorder_retainer))
@@ -2494,6 +2815,8 @@ pub(crate) async fn start_web(
             "/api/v1/user/retainer/listings",
             get(user_retainer_listings),
         )
+        .route("/api/v1/user/retainer/ledger", get(user_retainer_ledger))
+        .route("/api/v1/user/retainer/reprice", get(user_retainer_reprice))
         .route("/api/v1/retainer/search/{query}", get(retainer_search))
         .route("/api/v1/retainer/claim/{id}", get(claim_retainer))
         .route("/api/v1/retainer/unclaim/{id}", get(unclaim_retainer))
@@ -2517,6 +2840,14 @@ pub(crate) async fn start_web(
         .route("/login", get(begin_login))
         .route("/logout", get(logout))
         .route("/api/v1/current_user", delete(delete_user))
+        .route("/api/v1/sessions", get(user_sessions))
+        .route("/api/v1/sessions/{id}", delete(revoke_user_session))
+        .route(
+            "/api/v1/sessions/revoke_others",
+            post(revoke_other_user_sessions),
+        )
+        .route("/api/v1/tokens", get(api_tokens).post(create_api_token))
+        .route("/api/v1/tokens/{id}", delete(revoke_api_token))
         .route("/invitebot", get(invite))
         .route("/favicon.ico", get(favicon))
         .route("/robots.txt", get(robots))
@@ -2540,6 +2871,10 @@ pub(crate) async fn start_web(
             |options| shell(options, String::new()),
         ))
         .with_state(state)
+        .route_layer(middleware::from_fn_with_state(
+            rate_limiter,
+            self::rate_limit::rate_limit,
+        ))
         .route_layer(middleware::from_fn(track_metrics))
         .layer(middleware::from_fn(redirect_legacy_book_host))
         // tower-http's default `on_failure` logs every 5xx via `tracing::error!`,
@@ -2608,12 +2943,15 @@ pub(crate) async fn start_web(
             let addr = SocketAddr::from(([0, 0, 0, 0], port));
             tracing::info!("listening on {}", addr);
             let listener = TcpListener::bind(addr).await.unwrap();
-            axum::serve(listener, app)
-                .with_graceful_shutdown(async move {
-                    token.cancelled().await;
-                })
-                .await
-                .unwrap();
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(async move {
+                token.cancelled().await;
+            })
+            .await
+            .unwrap();
         },
         start_metrics_server(prometheus_handle),
     )
@@ -133,6 +133,7 @@ async fn handle_upgrade(
                             Some(Undercut {
                                 item_id,
                                 undercut_retainers,
+                                ..
                             }) => {
                                 let item_name = utils::get_item_name(item_id).to_string();
                                 Action::Tx(AlertsTx::RetainerUndercut {
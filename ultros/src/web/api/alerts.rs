@@ -6,7 +6,9 @@ use ultros_api_types::alert::{
     Alert, AlertDelivery, AlertEvent as ApiAlertEvent, AlertTrigger, CreateAlertRequest,
     ResendResult, UpdateAlertRequest,
 };
+use ultros_api_types::alert_rule::{AlertRule, MarketMoveRule};
 use ultros_api_types::list::ListPermission;
+use ultros_api_types::world_helper::AnySelector;
 use ultros_db::UltrosDb;
 
 use crate::event::{EventSenders, EventType};
@@ -52,6 +54,59 @@ pub(crate) fn validate_margin_percent(margin_percent: i32) -> Result<(), ApiErro
     }
 }
 
+/// Validate a user-authored expression rule before it is stored. The tracker
+/// evaluates every rule against every matching listing, so malformed or
+/// oversized rules are rejected here rather than skipped at fire time.
+#[allow(clippy::result_large_err)]
+pub(crate) fn validate_alert_rule(rule: &AlertRule) -> Result<(), ApiError> {
+    rule.validate()
+        .map_err(|e| ApiError::from(anyhow::anyhow!("invalid rule: {e}")))
+}
+
+/// Reject item ids the game data doesn't know, so a typo can't store a rule
+/// that never fires.
+#[allow(clippy::result_large_err)]
+pub(crate) fn validate_item_id(item_id: i32) -> Result<(), ApiError> {
+    if xiv_gen_db::data()
+        .items
+        .contains_key(&xiv_gen::ItemId(item_id))
+    {
+        Ok(())
+    } else {
+        Err(ApiError::from(anyhow::anyhow!("unknown item_id {item_id}")))
+    }
+}
+
+#[allow(clippy::result_large_err)]
+pub(crate) fn validate_market_move_rule(rule: &MarketMoveRule) -> Result<(), ApiError> {
+    rule.validate()
+        .map_err(|e| ApiError::from(anyhow::anyhow!("invalid market move rule: {e}")))
+}
+
+/// The legacy `delivery` shape for an alert, read from the first endpoint it
+/// is bound to. Older clients only understand DM and webhook, so every other
+/// endpoint kind reads as a DM.
+async fn delivery_for_alert(db: &UltrosDb, alert_id: i32) -> Result<AlertDelivery, ApiError> {
+    Ok(
+        match db
+            .get_first_endpoint_for_alert(alert_id)
+            .await
+            .map_err(ApiError::from)?
+        {
+            Some(e) if e.method == "Webhook" => {
+                let url = e
+                    .config
+                    .get("url")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string();
+                AlertDelivery::Webhook { url }
+            }
+            _ => AlertDelivery::DiscordDm,
+        },
+    )
+}
+
 pub(crate) async fn create_alert(
     State(db): State<UltrosDb>,
     State(senders): State<EventSenders>,
@@ -89,6 +144,41 @@ pub(crate) async fn create_alert(
             return create_list_update_alert_handler(&db, &senders, owner, list_id, cooldown, &req)
                 .await;
         }
+        AlertTrigger::Expression {
+            item_id,
+            world_selector,
+            ref rule,
+        } => {
+            return create_rule_alert_handler(
+                &db,
+                &senders,
+                owner,
+                item_id,
+                world_selector,
+                RuleAlert::Expression(rule.clone()),
+                cooldown,
+                &req,
+            )
+            .await;
+        }
+        AlertTrigger::MarketMove {
+            item_id,
+            world_selector,
+            hq_only,
+            rule,
+        } => {
+            return create_rule_alert_handler(
+                &db,
+                &senders,
+                owner,
+                item_id,
+                world_selector,
+                RuleAlert::MarketMove { hq_only, rule },
+                cooldown,
+                &req,
+            )
+            .await;
+        }
     };
 
     validate_price_threshold(price_threshold)?;
@@ -133,8 +223,8 @@ pub(crate) async fn create_alert(
                 price_threshold,
                 hq_only,
             },
-            // deprecated fallback; real delivery is described by endpoint_ids
-            delivery: AlertDelivery::DiscordDm,
+            // deprecated; real delivery is described by endpoint_ids
+            delivery: delivery_for_alert(&db, alert.id).await?,
             endpoint_ids: req.endpoint_ids,
             enabled: alert.enabled,
             cooldown_seconds: alert.cooldown_seconds,
@@ -251,9 +341,9 @@ async fn create_list_threshold_alert_handler(
     Ok(Json(Alert {
         id: alert.id,
         trigger: AlertTrigger::ListItemThreshold { list_id },
-        // Deprecated fallback. Real delivery is endpoint_ids; this field only
-        // exists for the older clients that pre-date the endpoints framework.
-        delivery: AlertDelivery::DiscordDm,
+        // Deprecated. Real delivery is endpoint_ids; this field only exists
+        // for the older clients that pre-date the endpoints framework.
+        delivery: delivery_for_alert(db, alert.id).await?,
         endpoint_ids: req.endpoint_ids.clone(),
         enabled: alert.enabled,
         cooldown_seconds: alert.cooldown_seconds,
@@ -286,7 +376,7 @@ async fn create_retainer_undercut_alert_handler(
     Ok(Json(Alert {
         id: alert.id,
         trigger: AlertTrigger::RetainerUndercut { margin_percent },
-        delivery: AlertDelivery::DiscordDm,
+        delivery: delivery_for_alert(db, alert.id).await?,
         endpoint_ids: req.endpoint_ids.clone(),
         enabled: alert.enabled,
         cooldown_seconds: alert.cooldown_seconds,
@@ -327,7 +417,120 @@ async fn create_list_update_alert_handler(
     Ok(Json(Alert {
         id: alert.id,
         trigger: AlertTrigger::ListUpdate { list_id },
-        delivery: AlertDelivery::DiscordDm,
+        delivery: delivery_for_alert(db, alert.id).await?,
+        endpoint_ids: req.endpoint_ids.clone(),
+        enabled: alert.enabled,
+        cooldown_seconds: alert.cooldown_seconds,
+        last_fired_at: alert.last_fired_at.map(|t| t.with_timezone(&chrono::Utc)),
+    }))
+}
+
+/// The rule half of an expression or market-move alert. Both are evaluated
+/// by the same tracker against rollup statistics, so they share one create
+/// flow and differ only in how the rule is checked and stored.
+enum RuleAlert {
+    Expression(AlertRule),
+    MarketMove { hq_only: bool, rule: MarketMoveRule },
+}
+
+impl RuleAlert {
+    fn kind(&self) -> &'static str {
+        match self {
+            RuleAlert::Expression(_) => "expression",
+            RuleAlert::MarketMove { .. } => "market move",
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn validate(&self) -> Result<(), ApiError> {
+        match self {
+            RuleAlert::Expression(rule) => validate_alert_rule(rule),
+            RuleAlert::MarketMove { rule, .. } => validate_market_move_rule(rule),
+        }
+    }
+
+    fn rule_json(&self) -> serde_json::Result<serde_json::Value> {
+        match self {
+            RuleAlert::Expression(rule) => serde_json::to_value(rule),
+            RuleAlert::MarketMove { rule, .. } => serde_json::to_value(rule),
+        }
+    }
+
+    fn into_trigger(self, item_id: i32, world_selector: AnySelector) -> AlertTrigger {
+        match self {
+            RuleAlert::Expression(rule) => AlertTrigger::Expression {
+                item_id,
+                world_selector,
+                rule,
+            },
+            RuleAlert::MarketMove { hq_only, rule } => AlertTrigger::MarketMove {
+                item_id,
+                world_selector,
+                hq_only,
+                rule,
+            },
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn create_rule_alert_handler(
+    db: &UltrosDb,
+    senders: &EventSenders,
+    owner: i64,
+    item_id: i32,
+    world_selector: AnySelector,
+    rule: RuleAlert,
+    cooldown: i32,
+    req: &CreateAlertRequest,
+) -> Result<Json<Alert>, ApiError> {
+    validate_item_id(item_id)?;
+    rule.validate()?;
+    if req.endpoint_ids.is_empty() {
+        return Err(ApiError::from(anyhow::anyhow!(
+            "{} alerts require endpoint_ids",
+            rule.kind()
+        )));
+    }
+
+    let world_selector_json = serde_json::to_value(world_selector)
+        .map_err(|e| ApiError::from(anyhow::anyhow!("invalid world_selector: {}", e)))?;
+    let rule_json = rule
+        .rule_json()
+        .map_err(|e| ApiError::from(anyhow::anyhow!("invalid rule: {}", e)))?;
+
+    let alert = match &rule {
+        RuleAlert::Expression(_) => {
+            db.create_expression_alert(
+                owner,
+                item_id,
+                world_selector_json,
+                rule_json,
+                cooldown,
+                &req.endpoint_ids,
+            )
+            .await
+        }
+        RuleAlert::MarketMove { hq_only, .. } => {
+            db.create_market_move_alert(
+                owner,
+                item_id,
+                world_selector_json,
+                *hq_only,
+                rule_json,
+                cooldown,
+                &req.endpoint_ids,
+            )
+            .await
+        }
+    }
+    .map_err(ApiError::from)?;
+    let _ = senders.alerts.send(EventType::added(alert.clone()));
+
+    Ok(Json(Alert {
+        id: alert.id,
+        trigger: rule.into_trigger(item_id, world_selector),
+        delivery: delivery_for_alert(db, alert.id).await?,
         endpoint_ids: req.endpoint_ids.clone(),
         enabled: alert.enabled,
         cooldown_seconds: alert.cooldown_seconds,
@@ -351,22 +554,7 @@ pub(crate) async fn list_alerts(
             .list_endpoint_ids_for_alert(a.id)
             .await
             .map_err(ApiError::from)?;
-        let delivery = match db
-            .get_first_endpoint_for_alert(a.id)
-            .await
-            .map_err(ApiError::from)?
-        {
-            Some(e) if e.method == "Webhook" => {
-                let url = e
-                    .config
-                    .get("url")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string();
-                AlertDelivery::Webhook { url }
-            }
-            _ => AlertDelivery::DiscordDm,
-        };
+        let delivery = delivery_for_alert(&db, a.id).await?;
         out.push(Alert {
             id: a.id,
             trigger: AlertTrigger::BelowThreshold {
@@ -398,7 +586,7 @@ pub(crate) async fn list_alerts(
             id: a.id,
             trigger: AlertTrigger::ListItemThreshold { list_id: t.list_id },
             // Deprecated; new clients use endpoint_ids.
-            delivery: AlertDelivery::DiscordDm,
+            delivery: delivery_for_alert(&db, a.id).await?,
             endpoint_ids,
             enabled: a.enabled,
             cooldown_seconds: a.cooldown_seconds,
@@ -420,7 +608,7 @@ pub(crate) async fn list_alerts(
             trigger: AlertTrigger::RetainerUndercut {
                 margin_percent: t.margin_percent,
             },
-            delivery: AlertDelivery::DiscordDm,
+            delivery: delivery_for_alert(&db, a.id).await?,
             endpoint_ids,
             enabled: a.enabled,
             cooldown_seconds: a.cooldown_seconds,
@@ -440,7 +628,64 @@ pub(crate) async fn list_alerts(
         out.push(Alert {
             id: a.id,
             trigger: AlertTrigger::ListUpdate { list_id: t.list_id },
-            delivery: AlertDelivery::DiscordDm,
+            delivery: delivery_for_alert(&db, a.id).await?,
+            endpoint_ids,
+            enabled: a.enabled,
+            cooldown_seconds: a.cooldown_seconds,
+            last_fired_at: a.last_fired_at.map(|t| t.with_timezone(&chrono::Utc)),
+        });
+    }
+
+    let expression_rows = db
+        .get_user_expression_alerts(user.id as i64)
+        .await
+        .map_err(ApiError::from)?;
+    for (a, t) in expression_rows {
+        let world_selector = serde_json::from_value(t.world_selector.clone())
+            .map_err(|e| ApiError::from(anyhow::anyhow!("bad world_selector in db: {}", e)))?;
+        let rule = serde_json::from_value(t.rule.clone())
+            .map_err(|e| ApiError::from(anyhow::anyhow!("bad rule in db: {}", e)))?;
+        let endpoint_ids = db
+            .list_endpoint_ids_for_alert(a.id)
+            .await
+            .map_err(ApiError::from)?;
+        out.push(Alert {
+            id: a.id,
+            trigger: AlertTrigger::Expression {
+                item_id: t.item_id,
+                world_selector,
+                rule,
+            },
+            delivery: delivery_for_alert(&db, a.id).await?,
+            endpoint_ids,
+            enabled: a.enabled,
+            cooldown_seconds: a.cooldown_seconds,
+            last_fired_at: a.last_fired_at.map(|t| t.with_timezone(&chrono::Utc)),
+        });
+    }
+
+    let market_move_rows = db
+        .get_user_market_move_alerts(user.id as i64)
+        .await
+        .map_err(ApiError::from)?;
+    for (a, t) in market_move_rows {
+        let world_selector = serde_json::from_value(t.world_selector.clone())
+            .map_err(|e| ApiError::from(anyhow::anyhow!("bad world_selector in db: {}", e)))?;
+        let rule = serde_json::from_value(t.rule.clone())
+            .map_err(|e| ApiError::from(anyhow::anyhow!("bad rule in db: {}", e)))?;
+        let endpoint_ids = db
+            .list_endpoint_ids_for_alert(a.id)
+            .await
+            .map_err(ApiError::from)?;
+        out.push(Alert {
+            id: a.id,
+            trigger: AlertTrigger::MarketMove {
+                item_id: t.item_id,
+                world_selector,
+                hq_only: t.hq_only,
+                rule,
+            },
+            delivery: delivery_for_alert(&db, a.id).await?,
             endpoint_ids,
             enabled: a.enabled,
             cooldown_seconds: a.cooldown_seconds,
@@ -572,6 +817,16 @@ pub(crate) async fn resend_alert_event(
         "Resending alert for item {} (matched price: {:?})",
         event.item_id, event.matched_price
     );
+    let fields = crate::alerts::delivery::AlertFields {
+        alert_id: Some(event.alert_id),
+        item_id: Some(event.item_id),
+        item_name: Some(crate::alerts::price_alert_tracker::resolve_item_name(
+            event.item_id,
+        )),
+        price: event.matched_price,
+        listing_id: event.matched_listing_id,
+        ..Default::default()
+    };
     let mut last_err: Option<String> = None;
     let mut any_ok = false;
     let owner = user.id as i64;
@@ -592,7 +847,7 @@ pub(crate) async fn resend_alert_event(
             match serenity_ctx.as_ref() {
                 Some(ctx) => {
                     crate::alerts::delivery::deliver_to_endpoint(
-                        &endpoint, title, &body, "/alerts", &db, ctx,
+                        &endpoint, title, &body, "/alerts", &fields, &db, ctx,
                     )
                     .await
                 }
@@ -600,7 +855,7 @@ pub(crate) async fn resend_alert_event(
             }
         } else {
             crate::alerts::delivery::deliver_non_discord_endpoint(
-                &endpoint, title, &body, "/alerts", &db,
+                &endpoint, title, &body, "/alerts", &fields, &db,
             )
             .await
         };
@@ -664,4 +919,43 @@ mod tests {
         assert!(validate_price_threshold(-1).is_err());
         assert!(validate_price_threshold(i32::MIN).is_err());
     }
+
+    // ---------- validate_alert_rule ----------
+
+    #[test]
+    fn alert_rule_accepts_statistical_comparison() {
+        use ultros_api_types::alert_rule::{MarketStat, RuleComparison, RuleTerm};
+        let rule = AlertRule::Compare {
+            left: RuleTerm::ListingPrice,
+            cmp: RuleComparison::Lt,
+            right: RuleTerm::Scaled {
+                percent: 60,
+                of: Box::new(RuleTerm::Stat {
+                    stat: MarketStat::Median,
+                    window_days: 7,
+                }),
+            },
+        };
+        assert!(validate_alert_rule(&rule).is_ok());
+    }
+
+    #[test]
+    fn alert_rule_rejects_empty_group() {
+        assert!(validate_alert_rule(&AlertRule::Any { rules: vec![] }).is_err());
+    }
+
+    #[test]
+    fn market_move_rule_rejects_full_drop() {
+        use ultros_api_types::alert_rule::{MoveDirection, MoveReference, MoveSource};
+        let mut rule = MarketMoveRule {
+            source: MoveSource::CheapestListing,
+            direction: MoveDirection::Drop,
+            reference: MoveReference::Median,
+            percent: 30,
+            window_days: 7,
+        };
+        assert!(validate_market_move_rule(&rule).is_ok());
+        rule.percent = 100;
+        assert!(validate_market_move_rule(&rule).is_err());
+    }
 }
@@ -24,6 +24,15 @@ pub(crate) struct CheapestPerWorld {
     cheapest_listings: Vec<CheapestListingData>,
 }
 
+/// The cheapest listing of every item, NQ and HQ separately, within a world,
+/// datacenter or region.
+#[utoipa::path(
+    get,
+    path = "/api/v1/cheapest/{world}",
+    tag = "market",
+    params(("world" = String, Path, description = "World, datacenter or region name")),
+    responses((status = 200, body = ultros_api_types::cheapest_listings::CheapestListings)),
+)]
 pub(crate) async fn cheapest_per_world(
     State(analyzer): State<AnalyzerService>,
     State(world_cache): State<Arc<WorldCache>>,
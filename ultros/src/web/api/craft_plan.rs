@@ -0,0 +1,85 @@
+//! `/api/v1/craft_plan/{world}` — cheapest way to end up holding N of an item.
+//!
+//! The recipe tree comes from the game data and the order book of every item
+//! in it is read from Postgres in one query; [`crate::craft_plan::plan_craft`]
+//! decides what to buy and what to craft.
+
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use ultros_api_types::craft_plan::{CraftPlan, CraftPlanRequest};
+use ultros_db::{UltrosDb, world_data::world_cache::WorldCache};
+
+use crate::{
+    craft_plan::{Catalog, OrderBook, PlanTooLarge, plan_craft},
+    web::error::WebError,
+};
+
+/// Most units one plan may be for. Ingredient quantities multiply down the
+/// tree, and each one is a fill solve over its order book.
+pub(crate) const MAX_PLAN_QUANTITY: i32 = 999;
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/craft_plan/{world}",
+    tag = "market",
+    params(("world" = String, Path, description = "World, datacenter or region name to shop in")),
+    request_body = CraftPlanRequest,
+    responses(
+        (status = 200, body = CraftPlan),
+        (status = 400, description = "Quantity outside 1 to 999, or a recipe tree too large to plan at that quantity"),
+    ),
+)]
+pub(crate) async fn post_craft_plan(
+    State(db): State<UltrosDb>,
+    State(world_cache): State<Arc<WorldCache>>,
+    Path(world): Path<String>,
+    Json(request): Json<CraftPlanRequest>,
+) -> Result<Json<CraftPlan>, WebError> {
+    if !(1..=MAX_PLAN_QUANTITY).contains(&request.quantity) {
+        return Err(WebError::BadRequest);
+    }
+    let selector = world_cache.lookup_value_by_name(&world)?;
+    let worlds = world_cache
+        .get_all_worlds_in(&selector)
+        .ok_or(WebError::NotFound)?;
+    let plan = craft_plan(&db, &worlds, &request).await.map_err(|e| {
+        match e.downcast::<PlanTooLarge>() {
+            Ok(_) => WebError::BadRequest,
+            Err(e) => e.into(),
+        }
+    })?;
+    Ok(Json(plan))
+}
+
+/// Plans `request` shopping in `worlds`. Shared with the Discord command.
+pub(crate) async fn craft_plan(
+    db: &UltrosDb,
+    worlds: &[i32],
+    request: &CraftPlanRequest,
+) -> anyhow::Result<CraftPlan> {
+    let catalog = Catalog::from_game_data(xiv_gen_db::data(), request.item_id, request.depth());
+    let item_ids: Vec<i32> = catalog.items.iter().copied().collect();
+    let books: HashMap<i32, OrderBook> = db
+        .get_listings_for_items(worlds, &item_ids)
+        .await?
+        .into_iter()
+        .map(|(item_id, listings)| {
+            let book = listings
+                .iter()
+                .map(|(listing, retainer)| {
+                    (listing.clone().into(), retainer.clone().map(Into::into))
+                })
+                .collect();
+            (item_id, book)
+        })
+        .collect();
+    let request = request.clone();
+    // A deep tree is thousands of fill solves; keep them off the runtime.
+    let plan =
+        tokio::task::spawn_blocking(move || plan_craft(&catalog, &request, &books)).await??;
+    Ok(plan)
+}
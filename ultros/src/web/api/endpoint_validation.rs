@@ -1,3 +1,7 @@
+use std::net::{IpAddr, Ipv4Addr};
+
+use ultros_api_types::alert::WebhookHeader;
+
 use crate::web::error::ApiError;
 
 #[allow(clippy::result_large_err)]
@@ -29,6 +33,115 @@ pub(crate) fn validate_discord_webhook_url(url: &str) -> Result<(), ApiError> {
     Ok(())
 }
 
+/// Check the destination of a generic webhook. Any public https host is fine;
+/// loopback, private and link-local addresses are refused so an endpoint can't
+/// be pointed back at the server's own network. This only inspects the URL as
+/// written; names are checked again when delivery resolves them, so a public
+/// name that points at a private address is refused there.
+#[allow(clippy::result_large_err)]
+pub(crate) fn validate_generic_webhook_url(url: &str) -> Result<(), ApiError> {
+    let parsed = url::Url::parse(url)
+        .map_err(|e| ApiError::from(anyhow::anyhow!("invalid webhook URL: {e}")))?;
+    if parsed.scheme() != "https" {
+        return Err(ApiError::from(anyhow::anyhow!(
+            "webhook URL must use https"
+        )));
+    }
+    if !parsed.username().is_empty() || parsed.password().is_some() {
+        return Err(ApiError::from(anyhow::anyhow!(
+            "webhook URL must not embed credentials; use a header instead"
+        )));
+    }
+    let internal = match parsed.host() {
+        None => true,
+        Some(url::Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            domain == "localhost"
+                || [".localhost", ".local", ".internal"]
+                    .iter()
+                    .any(|suffix| domain.ends_with(suffix))
+        }
+        Some(url::Host::Ipv4(ip)) => is_internal_ip(ip.into()),
+        Some(url::Host::Ipv6(ip)) => is_internal_ip(ip.into()),
+    };
+    if internal {
+        return Err(ApiError::from(anyhow::anyhow!(
+            "webhook URL must point at a public host"
+        )));
+    }
+    Ok(())
+}
+
+/// Whether `ip` belongs to a loopback, private, link-local or otherwise
+/// non-public range that webhooks may not reach.
+pub(crate) fn is_internal_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_internal_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_internal_ipv4(v4),
+            None => {
+                let first = ip.segments()[0];
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || first & 0xfe00 == 0xfc00 // unique local, fc00::/7
+                    || first & 0xffc0 == 0xfe80 // link-local, fe80::/10
+            }
+        },
+    }
+}
+
+fn is_internal_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        // Carrier-grade NAT, 100.64.0.0/10.
+        || (a == 100 && (64..128).contains(&b))
+}
+
+/// Most custom headers one webhook may send.
+pub(crate) const MAX_WEBHOOK_HEADERS: usize = 16;
+
+/// Headers the server sets itself; letting a user override them would break
+/// the request or forge its signature.
+const RESERVED_WEBHOOK_HEADERS: &[&str] = &[
+    "host",
+    "content-length",
+    "content-type",
+    "transfer-encoding",
+    "connection",
+];
+
+#[allow(clippy::result_large_err)]
+pub(crate) fn validate_webhook_headers(headers: &[WebhookHeader]) -> Result<(), ApiError> {
+    if headers.len() > MAX_WEBHOOK_HEADERS {
+        return Err(ApiError::from(anyhow::anyhow!(
+            "at most {MAX_WEBHOOK_HEADERS} custom headers are allowed"
+        )));
+    }
+    for header in headers {
+        let name = axum::http::HeaderName::from_bytes(header.name.as_bytes()).map_err(|_| {
+            ApiError::from(anyhow::anyhow!("invalid header name {:?}", header.name))
+        })?;
+        if RESERVED_WEBHOOK_HEADERS.contains(&name.as_str())
+            || name.as_str().starts_with("x-ultros-")
+        {
+            return Err(ApiError::from(anyhow::anyhow!(
+                "header {} is set by Ultros and can't be overridden",
+                header.name
+            )));
+        }
+        axum::http::HeaderValue::from_str(&header.value).map_err(|_| {
+            ApiError::from(anyhow::anyhow!("invalid value for header {}", header.name))
+        })?;
+    }
+    Ok(())
+}
+
 #[allow(clippy::result_large_err)]
 pub(crate) fn validate_discord_channel_id(channel_id: i64) -> Result<(), ApiError> {
     if channel_id <= 0 {
@@ -95,4 +208,71 @@ mod tests {
         assert!(validate_discord_webhook_url("not a url").is_err());
         assert!(validate_discord_webhook_url("").is_err());
     }
+
+    // ---------- validate_generic_webhook_url ----------
+
+    #[test]
+    fn generic_webhook_accepts_public_https_hosts() {
+        assert!(validate_generic_webhook_url("https://hooks.slack.com/services/T/B/x").is_ok());
+        assert!(validate_generic_webhook_url("https://203.0.113.7:8443/hook").is_ok());
+    }
+
+    #[test]
+    fn generic_webhook_rejects_plain_http_and_credentials() {
+        assert!(validate_generic_webhook_url("http://example.com/hook").is_err());
+        assert!(validate_generic_webhook_url("https://user:pw@example.com/hook").is_err());
+    }
+
+    #[test]
+    fn generic_webhook_rejects_internal_destinations() {
+        for url in [
+            "https://localhost/hook",
+            "https://api.localhost/hook",
+            "https://printer.local/hook",
+            "https://127.0.0.1/hook",
+            "https://10.0.0.4/hook",
+            "https://192.168.1.1/hook",
+            "https://169.254.169.254/latest/meta-data",
+            "https://100.64.0.1/hook",
+            "https://[::1]/hook",
+            "https://[fd00::1]/hook",
+            "https://[fe80::1]/hook",
+            "https://[::ffff:127.0.0.1]/hook",
+        ] {
+            assert!(
+                validate_generic_webhook_url(url).is_err(),
+                "expected err for {url}"
+            );
+        }
+    }
+
+    // ---------- validate_webhook_headers ----------
+
+    fn header(name: &str, value: &str) -> WebhookHeader {
+        WebhookHeader {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn webhook_headers_accept_custom_auth() {
+        assert!(validate_webhook_headers(&[header("Authorization", "Bearer abc")]).is_ok());
+    }
+
+    #[test]
+    fn webhook_headers_reject_reserved_and_malformed_names() {
+        assert!(validate_webhook_headers(&[header("Content-Type", "text/plain")]).is_err());
+        assert!(validate_webhook_headers(&[header("X-Ultros-Signature", "forged")]).is_err());
+        assert!(validate_webhook_headers(&[header("bad name", "x")]).is_err());
+        assert!(validate_webhook_headers(&[header("X-Ok", "line\nbreak")]).is_err());
+    }
+
+    #[test]
+    fn webhook_headers_are_capped() {
+        let headers: Vec<_> = (0..=MAX_WEBHOOK_HEADERS)
+            .map(|i| header(&format!("X-H{i}"), "v"))
+            .collect();
+        assert!(validate_webhook_headers(&headers).is_err());
+    }
 }
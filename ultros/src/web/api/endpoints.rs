@@ -4,13 +4,14 @@ use axum::{
 };
 use serde_json::Value as JsonValue;
 use ultros_api_types::alert::{
-    CreateEndpointRequest, DeleteEndpointResponse, DiscordWritableGuild, Endpoint, EndpointMethod,
-    ResendResult, UpdateEndpointRequest,
+    CreateEndpointRequest, DeleteEndpointResponse, DeliverySchedule, DiscordWritableGuild,
+    Endpoint, EndpointMethod, ResendResult, UpdateEndpointRequest,
 };
 use ultros_db::UltrosDb;
 
 use crate::web::api::endpoint_validation::{
-    validate_discord_channel_id, validate_discord_webhook_url,
+    validate_discord_channel_id, validate_discord_webhook_url, validate_generic_webhook_url,
+    validate_webhook_headers,
 };
 use crate::web::error::ApiError;
 use crate::web::oauth::AuthDiscordUser;
@@ -46,6 +47,27 @@ pub(crate) fn method_to_db(m: &EndpointMethod) -> (&'static str, JsonValue) {
             "WebPush",
             serde_json::json!({ "subscription_id": subscription_id }),
         ),
+        EndpointMethod::GenericWebhook {
+            url,
+            template,
+            headers,
+            secret,
+            signed: _,
+        } => {
+            let mut obj = serde_json::Map::new();
+            obj.insert("url".into(), serde_json::json!(url));
+            if let Some(template) = template {
+                obj.insert("template".into(), serde_json::json!(template));
+            }
+            if !headers.is_empty() {
+                obj.insert("headers".into(), serde_json::json!(headers));
+            }
+            // An empty secret means "remove it" on update; don't store it.
+            if let Some(secret) = secret.as_ref().filter(|s| !s.is_empty()) {
+                obj.insert("secret".into(), serde_json::json!(secret));
+            }
+            ("GenericWebhook", serde_json::Value::Object(obj))
+        }
     }
 }
 
@@ -86,10 +108,33 @@ pub(crate) fn db_to_method(method: &str, config: &JsonValue) -> anyhow::Result<E
                 .and_then(|v| i32::try_from(v).ok())
                 .ok_or_else(|| anyhow::anyhow!("WebPush missing subscription_id"))?,
         }),
+        // The signing secret stays server-side; callers only learn whether
+        // one is set.
+        "GenericWebhook" => Ok(EndpointMethod::GenericWebhook {
+            url: config
+                .get("url")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow::anyhow!("GenericWebhook missing url"))?
+                .to_string(),
+            template: config
+                .get("template")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            headers: config
+                .get("headers")
+                .map(|v| serde_json::from_value(v.clone()))
+                .transpose()?
+                .unwrap_or_default(),
+            secret: None,
+            signed: config.get("secret").is_some_and(|v| v.is_string()),
+        }),
         other => Err(anyhow::anyhow!("unknown method {other}")),
     }
 }
 
+/// Longest generic webhook signing secret accepted, in bytes.
+const MAX_WEBHOOK_SECRET_BYTES: usize = 256;
+
 #[allow(clippy::result_large_err)]
 pub(crate) fn validate_endpoint_method(m: &EndpointMethod) -> Result<(), ApiError> {
     match m {
@@ -98,6 +143,29 @@ pub(crate) fn validate_endpoint_method(m: &EndpointMethod) -> Result<(), ApiErro
             validate_discord_channel_id(*channel_id)
         }
         EndpointMethod::DiscordDm { .. } => Ok(()),
+        EndpointMethod::GenericWebhook {
+            url,
+            template,
+            headers,
+            secret,
+            ..
+        } => {
+            validate_generic_webhook_url(url)?;
+            if let Some(template) = template {
+                crate::alerts::webhook::validate_template(template)
+                    .map_err(|e| ApiError::from(anyhow::anyhow!("invalid template: {e}")))?;
+            }
+            validate_webhook_headers(headers)?;
+            if secret
+                .as_ref()
+                .is_some_and(|s| s.len() > MAX_WEBHOOK_SECRET_BYTES)
+            {
+                return Err(ApiError::from(anyhow::anyhow!(
+                    "signing secret must be at most {MAX_WEBHOOK_SECRET_BYTES} bytes"
+                )));
+            }
+            Ok(())
+        }
         EndpointMethod::WebPush { subscription_id } => {
             // WebPush endpoints are created via POST /api/v1/push/subscribe, never
             // through the generic CRUD — the row is meaningless without a real
@@ -116,6 +184,18 @@ pub(crate) fn validate_endpoint_method(m: &EndpointMethod) -> Result<(), ApiErro
     }
 }
 
+#[allow(clippy::result_large_err)]
+pub(crate) fn validate_delivery_schedule(schedule: &DeliverySchedule) -> Result<(), ApiError> {
+    schedule
+        .validate()
+        .map_err(|e| ApiError::from(anyhow::anyhow!("invalid schedule: {e}")))
+}
+
+/// Stored form of a schedule; immediate delivery is the NULL default.
+fn schedule_to_db(schedule: &DeliverySchedule) -> Option<JsonValue> {
+    (!schedule.is_immediate()).then(|| serde_json::json!(schedule))
+}
+
 pub(crate) async fn list_endpoints(
     State(db): State<UltrosDb>,
     user: AuthDiscordUser,
@@ -130,12 +210,14 @@ pub(crate) async fn list_endpoints(
         // Only surface the reason while the endpoint is actually disabled.
         // Recovery clears both columns together, so this is belt-and-braces: a
         // stray `last_error` can never make a working endpoint look broken.
+        let schedule = crate::alerts::delivery::endpoint_schedule(&r);
         let disabled_reason = r.disabled_at.and(r.last_error);
         out.push(Endpoint {
             id: r.id,
             name: r.name,
             method,
             disabled_reason,
+            schedule,
         });
     }
     Ok(Json(out))
@@ -167,6 +249,7 @@ pub(crate) async fn create_endpoint(
         other => other,
     };
     validate_endpoint_method(&method)?;
+    validate_delivery_schedule(&req.schedule)?;
 
     // The display name we will store. Defaults to whatever the client sent; for a
     // freshly resolved DiscordChannel we replace it with the real channel name so
@@ -206,15 +289,23 @@ pub(crate) async fn create_endpoint(
     }
 
     let (method_str, config) = method_to_db(&method);
+    // Respond with the stored form, which never includes a webhook secret.
+    let method = db_to_method(method_str, &config).map_err(ApiError::from)?;
     let id = db
         .create_endpoint(user.id as i64, &name, method_str, config)
         .await
         .map_err(ApiError::from)?;
+    if !req.schedule.is_immediate() {
+        db.set_endpoint_schedule(user.id as i64, id, schedule_to_db(&req.schedule))
+            .await
+            .map_err(ApiError::from)?;
+    }
     Ok(Json(Endpoint {
         id,
         name,
         method,
         disabled_reason: None,
+        schedule: req.schedule,
     }))
 }
 
@@ -227,14 +318,35 @@ pub(crate) async fn update_endpoint(
     let method_and_config = match &req.method {
         Some(m) => {
             validate_endpoint_method(m)?;
-            let (method, config) = method_to_db(m);
+            let (method, mut config) = method_to_db(m);
+            if let EndpointMethod::GenericWebhook { secret: None, .. } = m {
+                // Secrets are never sent back to the client, so an edit that
+                // doesn't mention one keeps whatever is stored.
+                let existing = db
+                    .get_endpoint_owned_by(user.id as i64, id)
+                    .await
+                    .map_err(ApiError::from)?;
+                if existing.method == method
+                    && let Some(secret) = existing.config.get("secret")
+                {
+                    config["secret"] = secret.clone();
+                }
+            }
             Some((method.to_string(), config))
         }
         None => None,
     };
+    if let Some(schedule) = &req.schedule {
+        validate_delivery_schedule(schedule)?;
+    }
     db.update_endpoint(user.id as i64, id, req.name, method_and_config)
         .await
         .map_err(ApiError::from)?;
+    if let Some(schedule) = &req.schedule {
+        db.set_endpoint_schedule(user.id as i64, id, schedule_to_db(schedule))
+            .await
+            .map_err(ApiError::from)?;
+    }
     Ok(Json(()))
 }
 
@@ -320,12 +432,16 @@ pub(crate) async fn test_endpoint(
         crate::alerts::delivery::get_serenity_ctx()
     };
 
+    // A test isn't about any item, so template variables for listing details
+    // render as null.
+    let fields = crate::alerts::delivery::AlertFields::default();
     let result = if let Some(ctx) = serenity_ctx.as_ref() {
         crate::alerts::delivery::deliver_to_endpoint(
             &endpoint,
             "Ultros test notification",
             "If you can read this, your endpoint is wired up correctly.",
             "/alerts",
+            &fields,
             &db,
             ctx,
         )
@@ -337,6 +453,7 @@ pub(crate) async fn test_endpoint(
             "Ultros test notification",
             "If you can read this, your endpoint is wired up correctly.",
             "/alerts",
+            &fields,
             &db,
         )
         .await
@@ -487,6 +604,62 @@ mod tests {
         assert_eq!(db_to_method(method, &config).unwrap(), m);
     }
 
+    #[test]
+    fn generic_webhook_secret_is_stored_but_never_read_back() {
+        let m = EndpointMethod::GenericWebhook {
+            url: "https://example.com/hook".into(),
+            template: Some(r#"{"text": "{{title}}"}"#.into()),
+            headers: vec![],
+            secret: Some("hunter2".into()),
+            signed: false,
+        };
+        let (method, config) = method_to_db(&m);
+        assert_eq!(method, "GenericWebhook");
+        assert_eq!(
+            config,
+            json!({
+                "url": "https://example.com/hook",
+                "template": r#"{"text": "{{title}}"}"#,
+                "secret": "hunter2",
+            })
+        );
+        assert_eq!(
+            db_to_method(method, &config).unwrap(),
+            EndpointMethod::GenericWebhook {
+                url: "https://example.com/hook".into(),
+                template: Some(r#"{"text": "{{title}}"}"#.into()),
+                headers: vec![],
+                secret: None,
+                signed: true,
+            }
+        );
+    }
+
+    #[test]
+    fn empty_generic_webhook_secret_is_not_stored() {
+        let m = EndpointMethod::GenericWebhook {
+            url: "https://example.com/hook".into(),
+            template: None,
+            headers: vec![],
+            secret: Some(String::new()),
+            signed: false,
+        };
+        let (_, config) = method_to_db(&m);
+        assert_eq!(config, json!({ "url": "https://example.com/hook" }));
+    }
+
+    #[test]
+    fn validate_method_rejects_generic_webhook_with_unknown_variable() {
+        let m = EndpointMethod::GenericWebhook {
+            url: "https://example.com/hook".into(),
+            template: Some(r#"{"text": "{{gil}}"}"#.into()),
+            headers: vec![],
+            secret: None,
+            signed: false,
+        };
+        assert!(validate_endpoint_method(&m).is_err());
+    }
+
     #[test]
     fn validate_method_rejects_bad_webhook_url() {
         let m = EndpointMethod::Webhook {
@@ -495,6 +668,18 @@ mod tests {
         assert!(validate_endpoint_method(&m).is_err());
     }
 
+    #[test]
+    fn immediate_schedule_is_stored_as_null() {
+        assert_eq!(schedule_to_db(&DeliverySchedule::Immediate), None);
+        let hourly = DeliverySchedule::Digest {
+            interval_minutes: 60,
+        };
+        assert_eq!(
+            schedule_to_db(&hourly),
+            Some(json!({"mode": "digest", "interval_minutes": 60}))
+        );
+    }
+
     #[test]
     fn validate_method_rejects_zero_channel_id() {
         let m = EndpointMethod::DiscordChannel {
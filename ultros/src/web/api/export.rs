@@ -0,0 +1,201 @@
+//! `/api/v1/export/sales` — bulk sale history for offline analysis, streamed
+//! straight out of ClickHouse as CSV, NDJSON or Parquet.
+//!
+//! The chart endpoints cap and bucket what they return for drawing; this one
+//! returns every sale (or every day, with `aggregate=daily`) in the window so
+//! notebooks don't have to scrape `price_series`. The same export is
+//! available offline through the `clickhouse_export` binary.
+//!
+//! Exports are the heaviest reads the site serves, so they need an API token
+//! with the `export:sales` scope, and only a few run at once server-wide.
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::header,
+    response::Response,
+};
+use futures::{StreamExt, TryStreamExt, stream};
+use tokio::sync::Semaphore;
+use ultros_api_types::{price_series::HqFilter, world_helper::WorldHelper};
+use ultros_clickhouse::{
+    ClickHouseClient,
+    export::ExportFormat,
+    queries::{ExportGranularity, SalesExport},
+};
+use xiv_gen::ItemId;
+
+use crate::web::{
+    error::{ClickHouseQueryError, WebError},
+    oauth::{AuthDiscordUser, Credential},
+};
+
+/// Most items one export may name.
+const MAX_EXPORT_ITEMS: usize = 100;
+
+/// Longest window for a per-sale export.
+const MAX_SALES_EXPORT_DAYS: i64 = 366;
+
+/// Longest window for a daily-aggregate export. Rows are small, but the scan
+/// behind them still reads every sale in the window.
+const MAX_DAILY_EXPORT_DAYS: i64 = 3 * 366;
+
+/// Exports streaming at once across every client. Each holds a ClickHouse
+/// query open for as long as the download runs.
+static EXPORT_SLOTS: Semaphore = Semaphore::const_new(4);
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct ExportQuery {
+    /// Comma separated item ids.
+    items: String,
+    /// World, datacenter or region name.
+    world: String,
+    from: Option<i64>,
+    to: Option<i64>,
+    format: Option<String>,
+    aggregate: Option<String>,
+    hq: Option<String>,
+}
+
+/// Stream sale history for a set of items as a file download.
+#[utoipa::path(
+    get,
+    path = "/api/v1/export/sales",
+    tag = "market",
+    params(
+        ("items" = String, Query, description = "Comma separated item ids, at most 100"),
+        ("world" = String, Query, description = "World, datacenter or region name"),
+        ("from" = Option<i64>, Query, description = "Unix seconds, inclusive. Defaults to 30 days before `to`"),
+        ("to" = Option<i64>, Query, description = "Unix seconds, exclusive. Defaults to now"),
+        ("format" = Option<String>, Query, description = "csv (default), ndjson or parquet"),
+        ("aggregate" = Option<String>, Query, description = "daily for one row per item, world, quality and UTC day with OHLC, VWAP and volume, over at most 1098 days. Without it each sale is a row and the window is limited to 366 days"),
+        ("hq" = Option<String>, Query, description = "hq, nq or any (default)"),
+    ),
+    responses(
+        (status = 200, description = "The export file", content_type = ["text/csv", "application/x-ndjson", "application/vnd.apache.parquet"]),
+        (status = 400, description = "Unknown option, bad item id or window too long"),
+        (status = 401, description = "No API token, or not an API token"),
+        (status = 403, description = "The token lacks the export:sales scope"),
+        (status = 429, description = "Too many exports are running; retry shortly"),
+    ),
+    security(("api_token" = ["export:sales"])),
+)]
+pub(crate) async fn export_sales(
+    State(ch): State<ClickHouseClient>,
+    State(world_helper): State<Arc<WorldHelper>>,
+    user: AuthDiscordUser,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, WebError> {
+    // The extractor has already checked the token's scope; browser sessions
+    // would pass it, so they're turned away here.
+    if !matches!(user.credential, Credential::ApiToken { .. }) {
+        return Err(WebError::NotAuthenticated);
+    }
+    let format = match query.format.as_deref() {
+        None => ExportFormat::Csv,
+        Some(format) => ExportFormat::parse(format).ok_or(WebError::BadRequest)?,
+    };
+    let granularity = match query.aggregate.as_deref() {
+        None | Some("none") => ExportGranularity::Sales,
+        Some("daily") => ExportGranularity::Daily,
+        Some(_) => return Err(WebError::BadRequest),
+    };
+    let hq = match query.hq.as_deref() {
+        None | Some("any") => HqFilter::Any,
+        Some("hq") => HqFilter::Hq,
+        Some("nq") => HqFilter::Nq,
+        Some(_) => return Err(WebError::BadRequest),
+    };
+
+    let mut item_ids = query
+        .items
+        .split(',')
+        .map(|id| id.trim().parse::<i32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| WebError::BadRequest)?;
+    item_ids.sort_unstable();
+    item_ids.dedup();
+    if item_ids.is_empty() || item_ids.len() > MAX_EXPORT_ITEMS {
+        return Err(WebError::BadRequest);
+    }
+    let items = &xiv_gen_db::data().items;
+    if let Some(unknown) = item_ids
+        .iter()
+        .find(|id| !items.contains_key(&ItemId(**id)))
+    {
+        return Err(WebError::InvalidItemId(*unknown));
+    }
+
+    let scope = world_helper
+        .lookup_world_by_name(&query.world)
+        .ok_or_else(|| WebError::WorldNotFound(query.world.clone()))?;
+    let world_ids: Vec<i32> = scope.all_worlds().map(|w| w.id).collect();
+
+    let to = query
+        .to
+        .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+        .unwrap_or_else(chrono::Utc::now);
+    let from = query
+        .from
+        .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+        .unwrap_or_else(|| to - chrono::Duration::days(30));
+    let max_days = match granularity {
+        ExportGranularity::Sales => MAX_SALES_EXPORT_DAYS,
+        ExportGranularity::Daily => MAX_DAILY_EXPORT_DAYS,
+    };
+    if from >= to || to - from > chrono::Duration::days(max_days) {
+        return Err(WebError::BadRequest);
+    }
+    // Held until the body finishes streaming or the client goes away.
+    let permit = EXPORT_SLOTS
+        .try_acquire()
+        .map_err(|_| WebError::TooManyRequests)?;
+
+    let export = SalesExport {
+        item_ids,
+        world_ids,
+        hq,
+        from,
+        to,
+        granularity,
+    };
+    let query_error = |e: ultros_clickhouse::ClickHouseError| {
+        tracing::warn!(error = ?e, "export_sales CH query failed");
+        ClickHouseQueryError::new("export_sales", e)
+    };
+    let mut chunks =
+        ultros_clickhouse::queries::export_sales(&ch, &export, format).map_err(query_error)?;
+    // Wait for the first chunk before committing to a 200, so a query that
+    // fails outright still gets a proper error response instead of an empty
+    // download. Anything that fails later can only cut the body short.
+    let first = chunks.try_next().await.map_err(query_error)?;
+    let body = stream::iter(first.map(Ok))
+        .chain(chunks.map_err(|e| {
+            tracing::warn!(error = ?e, "export_sales stream failed");
+            e
+        }))
+        .map(move |chunk| {
+            let _permit = &permit;
+            chunk
+        });
+
+    let filename = format!(
+        "ultros-sales-{}-{}-{}.{}",
+        export.from.format("%Y%m%d"),
+        export.to.format("%Y%m%d"),
+        match granularity {
+            ExportGranularity::Sales => "sales",
+            ExportGranularity::Daily => "daily",
+        },
+        format.extension()
+    );
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, format.content_type())
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}\""),
+        )
+        .body(Body::from_stream(body))?)
+}
@@ -0,0 +1,157 @@
+//! `/api/v1/fill_cost/{world}` — what buying N units actually costs.
+//!
+//! `{world}` is any world, data center or region name; the order book is
+//! every active listing in it. The solver itself is shared with the frontend
+//! and lives in [`ultros_api_types::fill_cost`].
+
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use itertools::Itertools;
+use serde::Deserialize;
+use ultros_api_types::fill_cost::{FillCost, FillCostQuery, MAX_FILL_QUANTITY};
+use ultros_db::{UltrosDb, world_data::world_cache::WorldCache};
+
+use crate::web::error::WebError;
+
+/// Items one batch request may price; each is a separate solve.
+const MAX_BATCH_ITEMS: usize = 20;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct FillCostParams {
+    pub(crate) quantity: i32,
+    #[serde(default, deserialize_with = "super::query::optional_flag")]
+    pub(crate) hq: Option<bool>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/fill_cost/{world}/{itemid}",
+    tag = "market",
+    params(
+        ("world" = String, Path, description = "World, datacenter or region name to shop in"),
+        ("itemid" = i32, Path, description = "Item id"),
+        ("quantity" = i32, Query, description = "Units to buy, 1 to 2000"),
+        ("hq" = Option<bool>, Query, description = "Only HQ (true) or only NQ (false) listings; either when omitted"),
+    ),
+    responses(
+        (status = 200, body = FillCost),
+        (status = 400, description = "Quantity outside 1 to 2000"),
+    ),
+)]
+pub(crate) async fn get_fill_cost(
+    State(db): State<UltrosDb>,
+    State(world_cache): State<Arc<WorldCache>>,
+    Path((world, item_id)): Path<(String, i32)>,
+    Query(params): Query<FillCostParams>,
+) -> Result<Json<FillCost>, WebError> {
+    let query = FillCostQuery {
+        item_id,
+        hq: params.hq,
+        quantity: params.quantity,
+    };
+    let mut costs = fill_costs(&db, &world_cache, &world, vec![query]).await?;
+    Ok(Json(costs.remove(0)))
+}
+
+/// Batch form for list views: one order-book read for every item.
+#[utoipa::path(
+    post,
+    path = "/api/v1/fill_cost/{world}",
+    tag = "market",
+    params(("world" = String, Path, description = "World, datacenter or region name to shop in")),
+    request_body = Vec<FillCostQuery>,
+    responses(
+        (status = 200, body = Vec<FillCost>),
+        (status = 400, description = "More than 20 items, or a quantity outside 1 to 2000"),
+    ),
+)]
+pub(crate) async fn post_fill_cost(
+    State(db): State<UltrosDb>,
+    State(world_cache): State<Arc<WorldCache>>,
+    Path(world): Path<String>,
+    Json(queries): Json<Vec<FillCostQuery>>,
+) -> Result<Json<Vec<FillCost>>, WebError> {
+    if queries.len() > MAX_BATCH_ITEMS {
+        return Err(WebError::BadRequest);
+    }
+    Ok(Json(fill_costs(&db, &world_cache, &world, queries).await?))
+}
+
+async fn fill_costs(
+    db: &UltrosDb,
+    world_cache: &WorldCache,
+    world: &str,
+    queries: Vec<FillCostQuery>,
+) -> Result<Vec<FillCost>, WebError> {
+    if queries
+        .iter()
+        .any(|q| q.quantity <= 0 || q.quantity > MAX_FILL_QUANTITY)
+    {
+        return Err(WebError::BadRequest);
+    }
+    let selector = world_cache.lookup_value_by_name(world)?;
+    let worlds = world_cache
+        .get_all_worlds_in(&selector)
+        .ok_or(WebError::NotFound)?;
+    let item_ids: Vec<i32> = queries.iter().map(|q| q.item_id).unique().collect();
+    let listings = db.get_listings_for_items(&worlds, &item_ids).await?;
+    let books: Vec<_> = queries
+        .into_iter()
+        .map(|query| {
+            let book: Vec<_> = listings
+                .get(&query.item_id)
+                .map(|l| {
+                    l.iter()
+                        .map(|(listing, retainer)| {
+                            (listing.clone().into(), retainer.clone().map(Into::into))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            (query, book)
+        })
+        .collect();
+    // A full batch is up to 20 solves of up to 2k units each; keep them off
+    // the async workers.
+    let costs = tokio::task::spawn_blocking(move || {
+        books
+            .into_iter()
+            .map(|(query, book)| FillCost::solve(query, book))
+            .collect()
+    })
+    .await
+    .map_err(anyhow::Error::from)?;
+    Ok(costs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FillCostParams;
+    use axum::extract::Query;
+    use axum::http::Uri;
+
+    fn extract(query: &str) -> Result<FillCostParams, String> {
+        let uri: Uri = format!("http://ultros.app/api/v1/fill_cost/Aether/5?{query}")
+            .parse()
+            .expect("test URI should parse");
+        Query::<FillCostParams>::try_from_uri(&uri)
+            .map(|Query(q)| q)
+            .map_err(|rejection| rejection.body_text())
+    }
+
+    #[test]
+    fn quantity_is_required() {
+        assert!(extract("hq=1").is_err());
+    }
+
+    #[test]
+    fn hq_accepts_numeric_and_literal_flags() {
+        assert_eq!(extract("quantity=99&hq=1").unwrap().hq, Some(true));
+        assert_eq!(extract("quantity=99&hq=false").unwrap().hq, Some(false));
+        assert_eq!(extract("quantity=99").unwrap().hq, None);
+    }
+}
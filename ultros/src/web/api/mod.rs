@@ -1,26 +1,36 @@
 pub(crate) mod alerts;
 mod best_deals;
-mod cheapest_per_world;
+pub(crate) mod cheapest_per_world;
+pub(crate) mod craft_plan;
 pub(crate) mod discord_lookup;
 pub(crate) mod endpoint_validation;
 pub(crate) mod endpoints;
+pub(crate) mod export;
+pub(crate) mod fill_cost;
 mod item_stats;
 mod market_heat;
 mod market_pulse;
 mod movers;
+mod patch_diff;
 pub(crate) mod push;
 mod query;
 pub(crate) mod real_time_data;
-mod recent_sales;
+pub(crate) mod recent_sales;
 mod resale_quality;
+mod route_planner;
 mod trends;
 
 pub(crate) use best_deals::get_best_deals;
 pub(crate) use cheapest_per_world::cheapest_per_world;
+pub(crate) use craft_plan::post_craft_plan;
+pub(crate) use export::export_sales;
+pub(crate) use fill_cost::{get_fill_cost, post_fill_cost};
 pub(crate) use item_stats::get_item_stats;
 pub(crate) use market_heat::get_market_heat;
 pub(crate) use market_pulse::get_market_pulse;
 pub(crate) use movers::{get_movers, post_sparklines};
+pub(crate) use patch_diff::get_patch_diff;
 pub(crate) use recent_sales::recent_sales;
 pub(crate) use resale_quality::post_resale_quality;
+pub(crate) use route_planner::get_route_plan;
 pub(crate) use trends::get_trends;
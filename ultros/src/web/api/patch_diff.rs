@@ -0,0 +1,54 @@
+//! `/api/v1/patch_diff` — what the current game data added over the last.
+//!
+//! `game-data-pack` writes the report next to the packs it builds, so it is
+//! embedded the same way the packs are and only changes with a deploy.
+
+use std::{sync::OnceLock, time::Duration};
+
+use axum::{
+    Json,
+    response::{IntoResponse, Response},
+};
+use axum_extra::headers::{CacheControl, HeaderMapExt};
+use ultros_api_types::patch_diff::PatchDiff;
+
+use crate::web::error::WebError;
+
+const REPORT: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../data/patch_diff.json"
+));
+
+/// The embedded report, parsed once. A report that doesn't parse fails every
+/// request rather than the whole server.
+fn report() -> Result<&'static PatchDiff, WebError> {
+    static PARSED: OnceLock<Result<PatchDiff, String>> = OnceLock::new();
+    PARSED
+        .get_or_init(|| serde_json::from_str(REPORT).map_err(|e| e.to_string()))
+        .as_ref()
+        .map_err(|e| anyhow::anyhow!("data/patch_diff.json doesn't parse: {e}").into())
+}
+
+/// New, removed and changed items, recipes and shop entries in the latest
+/// game data update.
+#[utoipa::path(
+    get,
+    path = "/api/v1/patch_diff",
+    tag = "market",
+    responses((status = 200, body = PatchDiff)),
+)]
+pub(crate) async fn get_patch_diff() -> Result<Response, WebError> {
+    let mut response = Json(report()?).into_response();
+    response
+        .headers_mut()
+        .typed_insert(CacheControl::new().with_max_age(Duration::from_secs(60 * 60)));
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn committed_report_parses() {
+        assert!(super::report().is_ok());
+    }
+}
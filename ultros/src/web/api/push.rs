@@ -15,7 +15,7 @@
 use axum::{Json, extract::State};
 use hyper::StatusCode;
 use ultros_api_types::alert::{
-    CreatePushSubscriptionRequest, Endpoint, EndpointMethod, VapidPublicKey,
+    CreatePushSubscriptionRequest, DeliverySchedule, Endpoint, EndpointMethod, VapidPublicKey,
 };
 use ultros_db::UltrosDb;
 
@@ -84,6 +84,7 @@ pub(crate) async fn create_push_subscription(
         name,
         method: EndpointMethod::WebPush { subscription_id },
         disabled_reason: None,
+        schedule: DeliverySchedule::Immediate,
     }))
 }
 
@@ -11,6 +11,14 @@ use ultros_db::world_data::world_cache::{AnySelector, WorldCache};
 
 use crate::{analyzer_service::AnalyzerService, web::error::WebError};
 
+/// Recent sale prices per item within a world, datacenter or region.
+#[utoipa::path(
+    get,
+    path = "/api/v1/recentSales/{world}",
+    tag = "market",
+    params(("world" = String, Path, description = "World, datacenter or region name")),
+    responses((status = 200, body = RecentSales)),
+)]
 pub(crate) async fn recent_sales(
     State(analyzer): State<AnalyzerService>,
     State(world_cache): State<Arc<WorldCache>>,
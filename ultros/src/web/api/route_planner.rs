@@ -0,0 +1,171 @@
+//! `/api/v1/route_planner/{world}` — budgeted multi-world shopping route.
+//!
+//! The analyzer only keeps the cheapest listing per item per world, which is
+//! enough to pick candidates but not to spend a budget: the second and third
+//! stacks matter as soon as the first one is bought. So the candidates are
+//! ranked from the analyzer's floor prices, and their full listing depth is
+//! then read from Postgres for [`crate::route_planner::plan_route`].
+
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use serde::Deserialize;
+use ultros_api_types::route_planner::RoutePlan;
+use ultros_db::{
+    UltrosDb,
+    world_data::world_cache::{AnyResult, WorldCache},
+};
+
+use crate::{
+    analyzer_service::AnalyzerService,
+    resale_eligibility::EligibilityPolicy,
+    route_planner::{PlanRequest, PlannerItem, PlannerListing, plan_route},
+    web::error::WebError,
+};
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RoutePlannerQuery {
+    /// Gil to spend. Default 1,000,000.
+    pub(crate) budget: Option<i64>,
+    /// Data center to shop in. Defaults to the home world's own; must be in
+    /// the home world's region, since travel stops at the region boundary.
+    pub(crate) datacenter: Option<String>,
+    /// Days the purchases should sell through in. Default 7, clamped to [1, 30].
+    pub(crate) horizon_days: Option<f32>,
+    /// Items whose listing depth is considered. Default 50, clamped to [1, 200].
+    pub(crate) max_items: Option<u32>,
+    /// Same eligibility gates as `/api/v1/best_deals`.
+    pub(crate) min_velocity: Option<f32>,
+    pub(crate) min_buffer_sales: Option<u8>,
+    pub(crate) max_roi: Option<f32>,
+}
+
+pub(crate) async fn get_route_plan(
+    State(analyzer): State<AnalyzerService>,
+    State(world_cache): State<Arc<WorldCache>>,
+    State(db): State<UltrosDb>,
+    Path(world_name): Path<String>,
+    Query(query): Query<RoutePlannerQuery>,
+) -> Result<Json<RoutePlan>, WebError> {
+    let world = world_cache.lookup_value_by_name(&world_name)?;
+    let home_world_id = world.as_world()?.id;
+    let datacenter = match query.datacenter.as_deref() {
+        Some(name) => {
+            let datacenter = world_cache.lookup_value_by_name(name)?;
+            let same_region = world_cache.get_region(&datacenter).map(|r| r.id)
+                == world_cache.get_region(&world).map(|r| r.id);
+            if !matches!(datacenter, AnyResult::Datacenter(_)) || !same_region {
+                return Err(WebError::BadRequest);
+            }
+            datacenter
+        }
+        None => world_cache
+            .get_datacenters(&world)
+            .and_then(|dcs| dcs.first().copied())
+            .map(AnyResult::Datacenter)
+            .ok_or(WebError::NotFound)?,
+    };
+    let AnyResult::Datacenter(datacenter_model) = &datacenter else {
+        return Err(WebError::BadRequest);
+    };
+    let worlds = world_cache
+        .get_all_worlds_in(&datacenter)
+        .ok_or(WebError::NotFound)?;
+
+    let budget = query.budget.unwrap_or(1_000_000);
+    if budget <= 0 {
+        return Err(WebError::BadRequest);
+    }
+    let horizon_days = query.horizon_days.unwrap_or(7.0).clamp(1.0, 30.0);
+    let max_items = query.max_items.unwrap_or(50).clamp(1, 200) as usize;
+    let policy = EligibilityPolicy {
+        min_velocity_per_day: query.min_velocity,
+        min_buffer_sales: query.min_buffer_sales,
+        max_roi: query.max_roi,
+    };
+
+    let candidates = analyzer
+        .get_route_candidates(
+            home_world_id,
+            datacenter_model.id,
+            horizon_days,
+            policy,
+            max_items,
+        )
+        .await?;
+    let item_ids: Vec<i32> = candidates.iter().map(|c| c.item_id).collect();
+    let listings = db.get_listings_for_items(&worlds, &item_ids).await?;
+    let items = candidates
+        .into_iter()
+        .map(|candidate| PlannerItem {
+            item_id: candidate.item_id,
+            hq: candidate.hq,
+            net_sale_price: candidate.net_sale_price,
+            sellable_units: candidate.sellable_units,
+            // NQ and HQ of one item are separate candidates sharing a
+            // listing set, so this filters rather than moves.
+            listings: listings
+                .get(&candidate.item_id)
+                .map(|l| {
+                    l.iter()
+                        .filter(|(listing, _)| listing.hq == candidate.hq)
+                        .map(|(listing, retainer)| PlannerListing {
+                            listing_id: listing.id,
+                            world_id: listing.world_id,
+                            price_per_unit: listing.price_per_unit,
+                            quantity: listing.quantity,
+                            retainer_name: retainer.as_ref().map(|r| r.name.clone()),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    Ok(Json(plan_route(
+        PlanRequest {
+            home_world_id,
+            datacenter_id: datacenter_model.id,
+            budget,
+            horizon_days,
+        },
+        items,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RoutePlannerQuery;
+    use axum::extract::Query;
+    use axum::http::Uri;
+
+    fn extract(query: &str) -> Result<RoutePlannerQuery, String> {
+        let uri: Uri = format!("http://ultros.app/api/v1/route_planner/Sargatanas?{query}")
+            .parse()
+            .expect("test URI should parse");
+        Query::<RoutePlannerQuery>::try_from_uri(&uri)
+            .map(|Query(q)| q)
+            .map_err(|rejection| rejection.body_text())
+    }
+
+    #[test]
+    fn all_params_are_optional() {
+        let q = extract("").expect("empty query must extract");
+        assert_eq!(q.budget, None);
+        assert_eq!(q.datacenter, None);
+        assert_eq!(q.horizon_days, None);
+    }
+
+    #[test]
+    fn params_extract_together() {
+        let q = extract("budget=5000000&datacenter=Aether&horizon_days=3.5&max_items=20")
+            .expect("params must extract");
+        assert_eq!(q.budget, Some(5_000_000));
+        assert_eq!(q.datacenter.as_deref(), Some("Aether"));
+        assert_eq!(q.horizon_days, Some(3.5));
+        assert_eq!(q.max_items, Some(20));
+    }
+}
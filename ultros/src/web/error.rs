@@ -293,6 +293,8 @@ define_error_enum!(WebError {
     NotFound,
     #[error("Bad request")]
     BadRequest,
+    #[error("Too many requests, try again shortly")]
+    TooManyRequests,
 });
 
 /// The title error reporting groups this error under.
@@ -321,6 +323,7 @@ impl WebError {
             WebError::NotAuthenticated => StatusCode::UNAUTHORIZED,
             WebError::NotFound => StatusCode::NOT_FOUND,
             WebError::BadRequest => StatusCode::BAD_REQUEST,
+            WebError::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
             WebError::InvalidItemId(_) | WebError::WorldNotFound(_) => StatusCode::BAD_REQUEST,
             // Analyzer warm-up isn't a server bug — it's a transient state at
             // startup. 503 lets clients retry instead of treating it as fatal.
@@ -1,6 +1,10 @@
 use axum::{
     extract::{FromRef, FromRequestParts, Query, State},
-    http::request::Parts,
+    http::{
+        HeaderMap, Method,
+        header::{AUTHORIZATION, USER_AGENT},
+        request::Parts,
+    },
     response::Redirect,
 };
 use axum_extra::extract::{
@@ -20,8 +24,10 @@ use std::{
     collections::{HashMap, HashSet},
     fmt::{Display, Formatter},
     sync::Arc,
+    time::Instant,
 };
 use tokio::sync::RwLock;
+use ultros_api_types::user::{ApiTokenScope, parse_scopes};
 use ultros_db::UltrosDb;
 
 use super::error::{ApiError, WebError};
@@ -221,6 +227,8 @@ pub struct RedirectParameters {
 pub async fn redirect(
     mut cookies: PrivateCookieJar,
     State(config): State<DiscordAuthConfig>,
+    State(db): State<UltrosDb>,
+    headers: HeaderMap,
     Query(RedirectParameters { code, state }): Query<RedirectParameters>,
 ) -> Result<(PrivateCookieJar, Redirect), WebError> {
     let code = AuthorizationCode::new(code);
@@ -250,12 +258,29 @@ pub async fn redirect(
     };
     let mut request = config.inner.client.exchange_code(code);
     request = request.set_pkce_verifier(PkceCodeVerifier::new(pkce_verifier));
-    let token = request
+    let access_token = request
         .request_async(&config.inner.http_client)
         .await?
         .access_token()
-        .secret()
         .clone();
+    // The Discord token is only needed to learn who logged in; the cookie
+    // carries our own session token from here on.
+    let user = Http::new(&format!("Bearer {}", access_token.secret()))
+        .get_current_user()
+        .await
+        .map_err(anyhow::Error::from)?;
+    let avatar_url = user
+        .static_avatar_url()
+        .unwrap_or_else(|| user.default_avatar_url());
+    db.get_or_create_discord_user(user.id.get(), user.name.clone())
+        .await?;
+    let (token, _) = db
+        .create_user_session(user.id.get(), avatar_url, user_agent(&headers))
+        .await?;
+    config.revoke(access_token).await;
+    if let Err(e) = db.delete_expired_user_sessions().await {
+        tracing::warn!("Failed to delete expired sessions: {e:?}");
+    }
     // store the token into a cookie
     let mut cookie = Cookie::new("discord_auth", token);
     cookie.set_secure(true);
@@ -267,10 +292,17 @@ pub async fn redirect(
     Ok((cookies, Redirect::to(&redirect_to)))
 }
 
-#[allow(clippy::collapsible_if)]
+fn user_agent(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(USER_AGENT)
+        .and_then(|agent| agent.to_str().ok())
+        .map(|agent| agent.chars().take(512).collect())
+}
+
 pub async fn logout(
     cookie_jar: PrivateCookieJar,
     State(config): State<DiscordAuthConfig>,
+    State(db): State<UltrosDb>,
     State(cache): State<AuthUserCache>,
 ) -> Result<(PrivateCookieJar, Redirect), WebError> {
     let cookie = cookie_jar
@@ -278,53 +310,126 @@ pub async fn logout(
         .ok_or(WebError::NotAuthenticated)?;
 
     let token_value = cookie.value().to_string();
+    db.revoke_user_session_token(&token_value).await?;
     cache.remove_token(&token_value).await;
-
-    let token = AccessToken::new(token_value);
-    // now try to revoke it async style
-    if let Ok(revocable_token) = config
-        .inner
-        .client
-        .revoke_token(StandardRevocableToken::AccessToken(token))
-    {
-        if let Err(e) = revocable_token
-            .request_async(&config.inner.http_client)
-            .await
-        {
-            tracing::warn!("Failed to revoke discord token on logout: {}", e);
-        }
-    }
+    // cookies from before sessions were stored hold a discord token, try to revoke it too
+    config.revoke(AccessToken::new(token_value)).await;
 
     let cookie_jar = cookie_jar.remove(cookie);
     Ok((cookie_jar, Redirect::to("/")))
 }
 
+/// How long a session is trusted without going back to the database, which
+/// bounds how late a revocation from another server takes effect.
+const SESSION_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+struct CachedSession {
+    user: AuthDiscordUser,
+    loaded_at: Instant,
+}
+
+/// Short-lived read-through cache in front of the `user_session` and
+/// `api_token` tables, keyed by the cookie or bearer token.
 #[derive(Debug, Clone)]
 pub struct AuthUserCache {
-    users: Arc<RwLock<HashMap<String, AuthDiscordUser>>>,
+    sessions: Arc<RwLock<HashMap<String, CachedSession>>>,
 }
 
 impl AuthUserCache {
     pub fn new() -> Self {
         Self {
-            users: Arc::default(),
+            sessions: Arc::default(),
         }
     }
 
     async fn store_user(&self, token: &str, user: AuthDiscordUser) {
-        let mut users = self.users.write().await;
-        users.insert(token.to_string(), user);
+        let mut sessions = self.sessions.write().await;
+        sessions.retain(|_, session| session.loaded_at.elapsed() < SESSION_CACHE_TTL);
+        sessions.insert(
+            token.to_string(),
+            CachedSession {
+                user,
+                loaded_at: Instant::now(),
+            },
+        );
     }
 
     async fn get_user(&self, token: &str) -> Option<AuthDiscordUser> {
-        let users = self.users.read().await;
-        users.get(token).cloned()
+        let sessions = self.sessions.read().await;
+        sessions
+            .get(token)
+            .filter(|session| session.loaded_at.elapsed() < SESSION_CACHE_TTL)
+            .map(|session| session.user.clone())
+    }
+
+    /// The user behind an API token secret, from cache or the `api_token`
+    /// table. Browser sessions share this cache under their cookie value, so a
+    /// cached session is never returned here: its secret is not a bearer token.
+    pub(crate) async fn api_token_user(
+        &self,
+        db: &UltrosDb,
+        secret: &str,
+    ) -> Result<Option<AuthDiscordUser>, ApiError> {
+        if let Some(user) = self.get_user(secret).await {
+            return Ok(matches!(user.credential, Credential::ApiToken { .. }).then_some(user));
+        }
+        let Some((token, discord_user)) = db.get_api_token(secret).await? else {
+            return Ok(None);
+        };
+        db.touch_api_token(token.id).await?;
+        let user = AuthDiscordUser {
+            id: discord_user.id as u64,
+            name: discord_user.username,
+            avatar_url: token.avatar_url,
+            credential: Credential::ApiToken {
+                id: token.id,
+                scopes: parse_scopes(&token.scopes),
+            },
+        };
+        self.store_user(secret, user.clone()).await;
+        Ok(Some(user))
     }
 
     pub(crate) async fn remove_token(&self, token: &str) {
-        let mut users = self.users.write().await;
-        users.remove(token);
+        let mut sessions = self.sessions.write().await;
+        sessions.remove(token);
+    }
+
+    pub(crate) async fn remove_session(&self, session_id: i64) {
+        let mut sessions = self.sessions.write().await;
+        sessions.retain(|_, session| session.user.session_id() != Some(session_id));
+    }
+
+    pub(crate) async fn remove_api_token(&self, token_id: i64) {
+        let mut sessions = self.sessions.write().await;
+        sessions.retain(|_, session| {
+            !matches!(session.user.credential, Credential::ApiToken { id, .. } if id == token_id)
+        });
     }
+
+    /// Evict everything cached for `user_id` but `keep_session_id`. API tokens
+    /// go too; they're reloaded on next use if they're still valid.
+    pub(crate) async fn remove_user_sessions_except(
+        &self,
+        user_id: u64,
+        keep_session_id: Option<i64>,
+    ) {
+        let mut sessions = self.sessions.write().await;
+        sessions.retain(|_, session| {
+            session.user.id != user_id
+                || (keep_session_id.is_some() && session.user.session_id() == keep_session_id)
+        });
+    }
+}
+
+/// How a request proved who it is.
+#[derive(Debug, Clone)]
+pub(crate) enum Credential {
+    /// A browser session, which can use every route.
+    Session(i64),
+    /// A personal API token, limited to the routes its scopes cover.
+    ApiToken { id: i64, scopes: Vec<ApiTokenScope> },
 }
 
 #[derive(Debug, Clone)]
@@ -332,6 +437,46 @@ pub struct AuthDiscordUser {
     pub(crate) id: u64,
     pub(crate) name: String,
     pub(crate) avatar_url: String,
+    pub(crate) credential: Credential,
+}
+
+impl AuthDiscordUser {
+    /// The `user_session` row this request authenticated with, if it came
+    /// from a browser.
+    pub(crate) fn session_id(&self) -> Option<i64> {
+        match self.credential {
+            Credential::Session(id) => Some(id),
+            Credential::ApiToken { .. } => None,
+        }
+    }
+}
+
+/// Whether an API token with `scopes` may make this request. Routes not
+/// listed here are browser-only.
+fn token_may_access(scopes: &[ApiTokenScope], method: &Method, path: &str) -> bool {
+    let under = |prefix: &str| {
+        path.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    };
+    let read = method == Method::GET || method == Method::HEAD;
+    let needed = if path == "/api/v1/current_user" && read {
+        return true;
+    } else if under("/api/v1/list") {
+        if read {
+            ApiTokenScope::ReadLists
+        } else {
+            ApiTokenScope::WriteLists
+        }
+    } else if under("/api/v1/alerts") || under("/api/v1/endpoints") {
+        ApiTokenScope::ManageAlerts
+    } else if under("/api/v1/user/retainer") && read {
+        ApiTokenScope::ReadRetainers
+    } else if under("/api/v1/export") && read {
+        ApiTokenScope::ExportSales
+    } else {
+        return false;
+    };
+    scopes.contains(&needed)
 }
 
 impl<S> FromRequestParts<S> for AuthDiscordUser
@@ -343,40 +488,83 @@ where
 {
     type Rejection = ApiError;
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let State(ultros): State<UltrosDb> = State::from_request_parts(parts, state).await.unwrap();
+        let State(user_cache): State<AuthUserCache> =
+            State::from_request_parts(parts, state).await.unwrap();
+
+        let bearer = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        if let Some(secret) = bearer {
+            let user = user_cache
+                .api_token_user(&ultros, secret.trim())
+                .await?
+                .ok_or(ApiError::NoAuthCookie)?;
+            // A bearer is only ever an API token, so every request made with
+            // one is held to the token's scopes.
+            let Credential::ApiToken { scopes, .. } = &user.credential else {
+                return Err(ApiError::NoAuthCookie);
+            };
+            if !token_may_access(scopes, &parts.method, parts.uri.path()) {
+                return Err(ApiError::Forbidden(
+                    "This API token's scopes don't cover this route",
+                ));
+            }
+            return Ok(user);
+        }
+
         let cookie_jar: PrivateCookieJar<Key> = PrivateCookieJar::from_request_parts(parts, state)
             .await
             .unwrap();
         let discord_auth = cookie_jar
             .get("discord_auth")
             .ok_or(ApiError::NoAuthCookie)?;
-        // get the discord user
-        let State(ultros): State<UltrosDb> = State::from_request_parts(parts, state).await.unwrap();
-        let State(user_cache): State<AuthUserCache> =
-            State::from_request_parts(parts, state).await.unwrap();
 
-        if let Some(user) = user_cache.get_user(discord_auth.value()).await {
+        let token = discord_auth.value();
+        if let Some(user) = user_cache.get_user(token).await {
             return Ok(user);
         }
 
-        let http = Http::new(&format!("Bearer {}", discord_auth.value()));
-        let user = http
-            .get_current_user()
-            .await
-            .map_err(|_| ApiError::DiscordTokenInvalid(cookie_jar))?;
-        let avatar_url = user
-            .static_avatar_url()
-            .unwrap_or_else(|| user.default_avatar_url());
-        let user = AuthDiscordUser {
-            id: user.id.get(),
-            name: user.name.clone(),
-            avatar_url,
+        let user = if let Some((session, discord_user)) = ultros.get_user_session(token).await? {
+            ultros.touch_user_session(session.id).await?;
+            AuthDiscordUser {
+                id: discord_user.id as u64,
+                name: discord_user.username,
+                avatar_url: session.avatar_url,
+                credential: Credential::Session(session.id),
+            }
+        } else {
+            // Cookies issued before sessions were stored hold the discord
+            // access token. Check it with discord once and keep it as a session.
+            let http = Http::new(&format!("Bearer {token}"));
+            let user = http
+                .get_current_user()
+                .await
+                .map_err(|_| ApiError::DiscordTokenInvalid(cookie_jar))?;
+            let avatar_url = user
+                .static_avatar_url()
+                .unwrap_or_else(|| user.default_avatar_url());
+            ultros
+                .get_or_create_discord_user(user.id.get(), user.name.clone())
+                .await?;
+            let session = ultros
+                .adopt_user_session(
+                    token,
+                    user.id.get(),
+                    avatar_url.clone(),
+                    user_agent(&parts.headers),
+                )
+                .await?;
+            AuthDiscordUser {
+                id: user.id.get(),
+                name: user.name.clone(),
+                avatar_url,
+                credential: Credential::Session(session.id),
+            }
         };
-        ultros
-            .get_or_create_discord_user(user.id, user.name.clone())
-            .await?;
-        user_cache
-            .store_user(discord_auth.value(), user.clone())
-            .await;
+        user_cache.store_user(token, user.clone()).await;
         Ok(user)
     }
 }
@@ -412,6 +600,20 @@ struct DiscordAuthConfigImpl {
 }
 
 impl DiscordAuthConfig {
+    /// Best-effort revocation of a Discord access token.
+    #[allow(clippy::collapsible_if)]
+    async fn revoke(&self, token: AccessToken) {
+        if let Ok(revocable_token) = self
+            .inner
+            .client
+            .revoke_token(StandardRevocableToken::AccessToken(token))
+        {
+            if let Err(e) = revocable_token.request_async(&self.inner.http_client).await {
+                tracing::warn!("Failed to revoke discord token: {}", e);
+            }
+        }
+    }
+
     pub fn new(
         client_id: String,
         client_secret: String,
@@ -453,7 +655,7 @@ impl DiscordAuthConfig {
 
 #[cfg(test)]
 mod tests {
-    use super::safe_login_next;
+    use super::{ApiTokenScope, Method, safe_login_next, token_may_access};
 
     #[test]
     fn safe_login_next_accepts_same_origin_relative_paths() {
@@ -481,6 +683,75 @@ mod tests {
         assert_eq!(safe_login_next(Some("/\t/evil.example")), None);
         assert_eq!(safe_login_next(Some("/ /evil.example")), None);
     }
+
+    #[test]
+    fn token_scopes_gate_routes() {
+        let read_lists = [ApiTokenScope::ReadLists];
+        assert!(token_may_access(
+            &read_lists,
+            &Method::GET,
+            "/api/v1/list/4"
+        ));
+        assert!(!token_may_access(
+            &read_lists,
+            &Method::POST,
+            "/api/v1/list/4/add/item"
+        ));
+        assert!(!token_may_access(
+            &read_lists,
+            &Method::GET,
+            "/api/v1/listings/1/2"
+        ));
+        assert!(token_may_access(
+            &read_lists,
+            &Method::GET,
+            "/api/v1/current_user"
+        ));
+        assert!(!token_may_access(
+            &read_lists,
+            &Method::DELETE,
+            "/api/v1/current_user"
+        ));
+
+        let alerts = [ApiTokenScope::ManageAlerts];
+        assert!(token_may_access(
+            &alerts,
+            &Method::DELETE,
+            "/api/v1/alerts/3"
+        ));
+        assert!(token_may_access(
+            &alerts,
+            &Method::POST,
+            "/api/v1/endpoints"
+        ));
+        assert!(!token_may_access(
+            &alerts,
+            &Method::GET,
+            "/api/v1/user/retainer"
+        ));
+
+        let retainers = [ApiTokenScope::ReadRetainers];
+        assert!(token_may_access(
+            &retainers,
+            &Method::GET,
+            "/api/v1/user/retainer/listings"
+        ));
+        let export = [ApiTokenScope::ExportSales];
+        assert!(token_may_access(
+            &export,
+            &Method::GET,
+            "/api/v1/export/sales"
+        ));
+        assert!(!token_may_access(
+            &retainers,
+            &Method::GET,
+            "/api/v1/export/sales"
+        ));
+        // browser-only: sessions and tokens can't be managed with a token
+        let all = ApiTokenScope::ALL;
+        assert!(!token_may_access(&all, &Method::GET, "/api/v1/sessions"));
+        assert!(!token_may_access(&all, &Method::POST, "/api/v1/tokens"));
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -492,13 +763,13 @@ mod tests {
 //
 // Flow: caller hits `GET /test/login?user_id=...&username=...`, we
 //   1. upsert the `discord_user` row,
-//   2. seed an `AuthDiscordUser` into the in-memory `AuthUserCache` keyed by
-//      a sentinel token like `test-token-<user_id>`,
+//   2. store a `user_session` for a sentinel token like `test-token-<user_id>`
+//      and seed it into the `AuthUserCache`,
 //   3. set the `discord_auth` cookie to that token,
-// so subsequent requests resolve via the cache and never touch Discord.
+// so subsequent requests resolve via the session and never touch Discord.
 #[cfg(feature = "test-auth")]
 pub mod test_auth {
-    use super::{AuthDiscordUser, AuthUserCache};
+    use super::{AuthDiscordUser, AuthUserCache, Credential};
     use axum::{
         extract::{Query, State},
         response::Redirect,
@@ -539,13 +810,18 @@ pub mod test_auth {
             .await?;
 
         let token = format!("test-token-{}", params.user_id);
+        let avatar_url = format!(
+            "https://cdn.discordapp.com/embed/avatars/{}.png",
+            params.user_id % 5
+        );
+        let session = db
+            .adopt_user_session(&token, params.user_id, avatar_url.clone(), None)
+            .await?;
         let user = AuthDiscordUser {
             id: params.user_id,
             name: params.username,
-            avatar_url: format!(
-                "https://cdn.discordapp.com/embed/avatars/{}.png",
-                params.user_id % 5
-            ),
+            avatar_url,
+            credential: Credential::Session(session.id),
         };
         cache.store_user(&token, user).await;
 
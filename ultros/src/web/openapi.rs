@@ -0,0 +1,367 @@
+//! The documented, third-party-facing slice of `/api/v1`.
+//!
+//! [`PUBLIC_ROUTES`] is the list of routes tools may build on, with how
+//! stable each one is and how hard it may be called. The OpenAPI document is
+//! generated from the `#[utoipa::path]` annotations on those handlers and the
+//! `ultros-api-types` schemas, then stamped with the stability and limit from
+//! the table. Routes missing from the table are first-party only and may
+//! change without notice.
+
+use std::{sync::OnceLock, time::Duration};
+
+use axum::{
+    Json,
+    http::Method,
+    response::{IntoResponse, Response},
+};
+use axum_extra::headers::{CacheControl, HeaderMapExt};
+use utoipa::{
+    Modify, OpenApi,
+    openapi::{
+        self, PathItem,
+        extensions::ExtensionsBuilder,
+        path::Operation,
+        security::{Http, HttpAuthScheme, SecurityScheme},
+    },
+};
+
+/// How much a documented route may change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Stability {
+    /// Only changed additively within `/api/v1`.
+    Stable,
+    /// Works today, but its shape may still change.
+    Beta,
+}
+
+impl Stability {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Stability::Stable => "stable",
+            Stability::Beta => "beta",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RateLimit {
+    pub(crate) requests: u32,
+    pub(crate) per: Duration,
+}
+
+impl RateLimit {
+    const fn per_minute(requests: u32) -> Self {
+        Self {
+            requests,
+            per: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct PublicRoute {
+    pub(crate) method: &'static str,
+    /// The path as registered with the router, e.g. `/api/v1/list/{id}`.
+    pub(crate) path: &'static str,
+    pub(crate) stability: Stability,
+    pub(crate) rate_limit: RateLimit,
+}
+
+impl PublicRoute {
+    const fn new(
+        method: &'static str,
+        path: &'static str,
+        stability: Stability,
+        rate_limit: RateLimit,
+    ) -> Self {
+        Self {
+            method,
+            path,
+            stability,
+            rate_limit,
+        }
+    }
+
+    /// The documented route a request matched, with its index in
+    /// [`PUBLIC_ROUTES`].
+    pub(crate) fn find(method: &Method, matched_path: &str) -> Option<(usize, &'static Self)> {
+        PUBLIC_ROUTES
+            .iter()
+            .enumerate()
+            .find(|(_, route)| route.path == matched_path && route.method == method.as_str())
+    }
+}
+
+pub(crate) const PUBLIC_ROUTES: &[PublicRoute] = &[
+    PublicRoute::new(
+        "GET",
+        "/api/v1/world_data",
+        Stability::Stable,
+        RateLimit::per_minute(30),
+    ),
+    PublicRoute::new(
+        "GET",
+        "/api/v1/listings/{world}/{itemid}",
+        Stability::Stable,
+        RateLimit::per_minute(120),
+    ),
+    PublicRoute::new(
+        "GET",
+        "/api/v1/cheapest/{world}",
+        Stability::Stable,
+        RateLimit::per_minute(30),
+    ),
+    PublicRoute::new(
+        "GET",
+        "/api/v1/recentSales/{world}",
+        Stability::Stable,
+        RateLimit::per_minute(30),
+    ),
+    // Each call can stream a year of sales for a hundred items out of
+    // ClickHouse, so this is rationed far tighter than the JSON routes.
+    PublicRoute::new(
+        "GET",
+        "/api/v1/export/sales",
+        Stability::Beta,
+        RateLimit::per_minute(6),
+    ),
+    PublicRoute::new(
+        "GET",
+        "/api/v1/fill_cost/{world}/{itemid}",
+        Stability::Beta,
+        RateLimit::per_minute(60),
+    ),
+    // Every item in a batch is a separate solve over its whole order book.
+    PublicRoute::new(
+        "POST",
+        "/api/v1/fill_cost/{world}",
+        Stability::Beta,
+        RateLimit::per_minute(20),
+    ),
+    // Walks a whole recipe tree and solves a fill per ingredient.
+    PublicRoute::new(
+        "POST",
+        "/api/v1/craft_plan/{world}",
+        Stability::Beta,
+        RateLimit::per_minute(20),
+    ),
+    PublicRoute::new(
+        "GET",
+        "/api/v1/patch_diff",
+        Stability::Beta,
+        RateLimit::per_minute(30),
+    ),
+    PublicRoute::new(
+        "GET",
+        "/api/v1/search",
+        Stability::Stable,
+        RateLimit::per_minute(120),
+    ),
+    PublicRoute::new(
+        "GET",
+        "/api/v1/search/page",
+        Stability::Beta,
+        RateLimit::per_minute(60),
+    ),
+    PublicRoute::new(
+        "GET",
+        "/api/v1/current_user",
+        Stability::Stable,
+        RateLimit::per_minute(60),
+    ),
+    PublicRoute::new(
+        "GET",
+        "/api/v1/list",
+        Stability::Beta,
+        RateLimit::per_minute(60),
+    ),
+    PublicRoute::new(
+        "GET",
+        "/api/v1/user/retainer",
+        Stability::Beta,
+        RateLimit::per_minute(30),
+    ),
+    PublicRoute::new(
+        "GET",
+        "/api/v1/user/retainer/listings",
+        Stability::Beta,
+        RateLimit::per_minute(30),
+    ),
+    PublicRoute::new(
+        "GET",
+        "/api/v1/user/retainer/ledger",
+        Stability::Beta,
+        RateLimit::per_minute(30),
+    ),
+    PublicRoute::new(
+        "GET",
+        "/api/v1/user/retainer/reprice",
+        Stability::Beta,
+        RateLimit::per_minute(30),
+    ),
+    PublicRoute::new(
+        "GET",
+        "/api/v1/retainer/listings/{id}",
+        Stability::Beta,
+        RateLimit::per_minute(60),
+    ),
+];
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Ultros API",
+        description = "Market board data for Final Fantasy XIV. Each operation carries \
+            `x-stability` and `x-rate-limit`; only the operations in this document are \
+            supported for third-party use. Requests over the limit get `429` with \
+            `Retry-After`. Routes that act for a user take a personal API token from the \
+            profile page as `Authorization: Bearer`."
+    ),
+    paths(
+        super::world_data,
+        super::world_item_listings,
+        super::api::cheapest_per_world::cheapest_per_world,
+        super::api::recent_sales::recent_sales,
+        super::api::export::export_sales,
+        super::api::fill_cost::get_fill_cost,
+        super::api::fill_cost::post_fill_cost,
+        super::api::craft_plan::post_craft_plan,
+        super::api::patch_diff::get_patch_diff,
+        super::search,
+        super::search_page,
+        super::current_user,
+        super::get_lists,
+        super::user_retainers,
+        super::user_retainer_listings,
+        super::user_retainer_ledger,
+        super::user_retainer_reprice,
+        super::retainer_listings,
+    ),
+    tags(
+        (name = "market", description = "Listings and sales"),
+        (name = "search", description = "Item and page search"),
+        (name = "user", description = "The signed-in user's data"),
+    ),
+    modifiers(&PublicRouteMarkers),
+)]
+pub(crate) struct ApiDoc;
+
+/// Name of the bearer security scheme used in `#[utoipa::path(security(..))]`.
+pub(crate) const API_TOKEN_SCHEME: &str = "api_token";
+
+struct PublicRouteMarkers;
+
+impl Modify for PublicRouteMarkers {
+    fn modify(&self, openapi: &mut openapi::OpenApi) {
+        openapi
+            .components
+            .get_or_insert_with(Default::default)
+            .add_security_scheme(
+                API_TOKEN_SCHEME,
+                SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+            );
+        for route in PUBLIC_ROUTES {
+            let Some(operation) = openapi
+                .paths
+                .paths
+                .get_mut(route.path)
+                .and_then(|item| operation_mut(item, route.method))
+            else {
+                continue;
+            };
+            let limit = route.rate_limit;
+            let extensions = ExtensionsBuilder::new()
+                .add("x-stability", route.stability.as_str())
+                .add(
+                    "x-rate-limit",
+                    serde_json::json!({
+                        "requests": limit.requests,
+                        "per_seconds": limit.per.as_secs(),
+                    }),
+                )
+                .build();
+            match &mut operation.extensions {
+                Some(existing) => existing.merge(extensions),
+                None => operation.extensions = Some(extensions),
+            }
+            let note = format!(
+                "Stability: {}. Limit: {} requests per {} seconds.",
+                route.stability.as_str(),
+                limit.requests,
+                limit.per.as_secs()
+            );
+            operation.description = Some(match operation.description.take() {
+                Some(description) => format!("{description}\n\n{note}"),
+                None => note,
+            });
+        }
+    }
+}
+
+fn operation_mut<'a>(item: &'a mut PathItem, method: &str) -> Option<&'a mut Operation> {
+    match method {
+        "GET" => item.get.as_mut(),
+        "POST" => item.post.as_mut(),
+        "PUT" => item.put.as_mut(),
+        "PATCH" => item.patch.as_mut(),
+        "DELETE" => item.delete.as_mut(),
+        _ => None,
+    }
+}
+
+/// `/api/openapi.json`
+pub(crate) async fn openapi_json() -> Response {
+    static DOCUMENT: OnceLock<openapi::OpenApi> = OnceLock::new();
+    let mut response = Json(DOCUMENT.get_or_init(ApiDoc::openapi)).into_response();
+    response
+        .headers_mut()
+        .typed_insert(CacheControl::new().with_max_age(Duration::from_secs(60 * 60)));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_public_route_is_documented_and_marked() {
+        let document = ApiDoc::openapi();
+        for route in PUBLIC_ROUTES {
+            let mut item = document
+                .paths
+                .paths
+                .get(route.path)
+                .unwrap_or_else(|| panic!("{} isn't in the document", route.path))
+                .clone();
+            let operation = operation_mut(&mut item, route.method)
+                .unwrap_or_else(|| panic!("{} {} isn't documented", route.method, route.path));
+            let extensions = operation.extensions.as_ref().unwrap();
+            assert_eq!(
+                extensions.get("x-stability"),
+                Some(&serde_json::json!(route.stability.as_str()))
+            );
+        }
+    }
+
+    #[test]
+    fn every_documented_operation_has_a_policy() {
+        let document = ApiDoc::openapi();
+        for (path, item) in &document.paths.paths {
+            for (method, operation) in [
+                ("GET", &item.get),
+                ("POST", &item.post),
+                ("PUT", &item.put),
+                ("PATCH", &item.patch),
+                ("DELETE", &item.delete),
+            ] {
+                if operation.is_some() {
+                    let method = Method::from_bytes(method.as_bytes()).unwrap();
+                    assert!(
+                        PublicRoute::find(&method, path).is_some(),
+                        "{method} {path} is documented but has no stability or rate limit"
+                    );
+                }
+            }
+        }
+    }
+}
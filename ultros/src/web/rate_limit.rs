@@ -0,0 +1,401 @@
+//! Per-client request limits for the documented `/api/v1` routes.
+//!
+//! Limits come from [`PUBLIC_ROUTES`]; routes that aren't listed there are
+//! first-party only and aren't limited here. Clients are told apart by their
+//! API token when they send a valid one, otherwise by their address. A bearer
+//! that doesn't resolve to a token counts against the address, so inventing
+//! tokens buys no headroom, and is remembered so inventing them doesn't cost
+//! a database read each.
+//!
+//! The address is the one Fly's proxy reports in `Fly-Client-IP`, which it
+//! sets itself, falling back to the connecting peer. `CF-Connecting-IP` is
+//! only believed when that address is one of Cloudflare's: anyone else could
+//! put whatever they like in it.
+
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use ipnet::IpNet;
+use ultros_db::UltrosDb;
+
+use super::{
+    oauth::{AuthUserCache, Credential},
+    openapi::{PUBLIC_ROUTES, PublicRoute, RateLimit},
+};
+
+static CF_CONNECTING_IP: HeaderName = HeaderName::from_static("cf-connecting-ip");
+static FLY_CLIENT_IP: HeaderName = HeaderName::from_static("fly-client-ip");
+static RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+static RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+
+/// Windows are swept once the map holds this many entries.
+const SWEEP_THRESHOLD: usize = 50_000;
+
+/// How long a bearer that matched no token is remembered as such. Secrets
+/// are random, so one that was unknown can only become valid by being
+/// revoked and reissued, which never happens; this only bounds the memory.
+const REJECTED_TOKEN_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Cloudflare's published edge ranges (<https://www.cloudflare.com/ips/>).
+static CLOUDFLARE_RANGES: LazyLock<Vec<IpNet>> = LazyLock::new(|| {
+    [
+        "173.245.48.0/20",
+        "103.21.244.0/22",
+        "103.22.200.0/22",
+        "103.31.4.0/22",
+        "141.101.64.0/18",
+        "108.162.192.0/18",
+        "190.93.240.0/20",
+        "188.114.96.0/20",
+        "197.234.240.0/22",
+        "198.41.128.0/17",
+        "162.158.0.0/15",
+        "104.16.0.0/13",
+        "104.24.0.0/14",
+        "172.64.0.0/13",
+        "131.0.72.0/22",
+        "2400:cb00::/32",
+        "2606:4700::/32",
+        "2803:f800::/32",
+        "2405:b500::/32",
+        "2405:8100::/32",
+        "2a06:98c0::/29",
+        "2c0f:f248::/32",
+    ]
+    .iter()
+    .map(|range| range.parse().expect("Cloudflare range should parse"))
+    .collect()
+});
+
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    started: Instant,
+    count: u32,
+}
+
+/// Fixed-window counters keyed by client and route.
+#[derive(Debug, Clone, Default)]
+struct Counters {
+    windows: Arc<Mutex<HashMap<(u64, usize), Window>>>,
+}
+
+/// Bearers that matched no token, by hash, with when they were last seen.
+#[derive(Debug, Clone, Default)]
+struct RejectedTokens {
+    seen: Arc<Mutex<HashMap<u64, Instant>>>,
+}
+
+impl RejectedTokens {
+    fn key(secret: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        secret.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn contains(&self, secret: &str, now: Instant) -> bool {
+        self.seen
+            .lock()
+            .unwrap()
+            .get(&Self::key(secret))
+            .is_some_and(|seen| now.duration_since(*seen) < REJECTED_TOKEN_TTL)
+    }
+
+    fn insert(&self, secret: &str, now: Instant) {
+        let mut seen = self.seen.lock().unwrap();
+        if seen.len() >= SWEEP_THRESHOLD {
+            seen.retain(|_, seen| now.duration_since(*seen) < REJECTED_TOKEN_TTL);
+        }
+        // Still full of live entries: start over rather than grow without
+        // bound. The worst case is one more lookup per forgotten bearer.
+        if seen.len() >= SWEEP_THRESHOLD {
+            seen.clear();
+        }
+        seen.insert(Self::key(secret), now);
+    }
+}
+
+/// State for [`rate_limit`]: the counters, plus what it takes to check that a
+/// bearer token is real before giving it a bucket of its own.
+#[derive(Clone)]
+pub(crate) struct RateLimiter {
+    counters: Counters,
+    rejected: RejectedTokens,
+    db: UltrosDb,
+    user_cache: AuthUserCache,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(db: UltrosDb, user_cache: AuthUserCache) -> Self {
+        Self {
+            counters: Counters::default(),
+            rejected: RejectedTokens::default(),
+            db,
+            user_cache,
+        }
+    }
+
+    /// The id of the API token `request` carries, if it carries a valid one.
+    async fn token_id(&self, headers: &HeaderMap) -> Option<i64> {
+        let secret = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))?
+            .trim();
+        let now = Instant::now();
+        if self.rejected.contains(secret, now) {
+            return None;
+        }
+        // Database errors aren't remembered: the token may well be real.
+        let user = match self
+            .user_cache
+            .api_token_user(&self.db, secret)
+            .await
+            .ok()?
+        {
+            Some(user) => user,
+            None => {
+                self.rejected.insert(secret, now);
+                return None;
+            }
+        };
+        match user.credential {
+            Credential::ApiToken { id, .. } => Some(id),
+            Credential::Session(_) => None,
+        }
+    }
+}
+
+/// What [`RateLimiter::check`] decided.
+#[derive(Debug, PartialEq, Eq)]
+enum Decision {
+    Allow { remaining: u32 },
+    Deny { retry_after_secs: u64 },
+}
+
+impl Counters {
+    fn check(&self, client: u64, route: usize, limit: RateLimit, now: Instant) -> Decision {
+        let mut windows = self.windows.lock().unwrap();
+        if windows.len() >= SWEEP_THRESHOLD {
+            windows.retain(|(_, route), window| {
+                now.duration_since(window.started) < PUBLIC_ROUTES[*route].rate_limit.per
+            });
+        }
+        let window = windows.entry((client, route)).or_insert(Window {
+            started: now,
+            count: 0,
+        });
+        if now.duration_since(window.started) >= limit.per {
+            *window = Window {
+                started: now,
+                count: 0,
+            };
+        }
+        if window.count >= limit.requests {
+            let retry_after = limit.per - now.duration_since(window.started);
+            return Decision::Deny {
+                retry_after_secs: retry_after.as_secs().max(1),
+            };
+        }
+        window.count += 1;
+        Decision::Allow {
+            remaining: limit.requests - window.count,
+        }
+    }
+}
+
+fn header_ip(headers: &HeaderMap, name: &HeaderName) -> Option<IpAddr> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// The client's address: what Fly's proxy saw, else the connecting peer, and
+/// then what Cloudflare saw if the former is a Cloudflare edge.
+fn client_address(headers: &HeaderMap, peer: Option<SocketAddr>) -> Option<IpAddr> {
+    let seen = header_ip(headers, &FLY_CLIENT_IP).or(peer.map(|peer| peer.ip()))?;
+    if CLOUDFLARE_RANGES.iter().any(|range| range.contains(&seen))
+        && let Some(ip) = header_ip(headers, &CF_CONNECTING_IP)
+    {
+        return Some(ip);
+    }
+    Some(seen)
+}
+
+/// Who to count a request against: its API token when it has a valid one,
+/// else its address. `None` only when the address isn't known either (a
+/// request that never came over a socket).
+fn client_key(token_id: Option<i64>, headers: &HeaderMap, peer: Option<SocketAddr>) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    match token_id {
+        Some(token_id) => ("token", token_id).hash(&mut hasher),
+        None => ("ip", client_address(headers, peer)?).hash(&mut hasher),
+    }
+    Some(hasher.finish())
+}
+
+pub(crate) async fn rate_limit(
+    State(limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|path| PublicRoute::find(request.method(), path.as_str()));
+    let Some((index, route)) = route else {
+        return next.run(request).await;
+    };
+    let token_id = limiter.token_id(request.headers()).await;
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| *addr);
+    let Some(client) = client_key(token_id, request.headers(), peer) else {
+        return next.run(request).await;
+    };
+    let limit = route.rate_limit;
+    match limiter.counters.check(client, index, limit, Instant::now()) {
+        Decision::Allow { remaining } => {
+            let mut response = next.run(request).await;
+            let headers = response.headers_mut();
+            headers.insert(RATE_LIMIT_LIMIT.clone(), limit.requests.into());
+            headers.insert(RATE_LIMIT_REMAINING.clone(), remaining.into());
+            response
+        }
+        Decision::Deny { retry_after_secs } => (
+            StatusCode::TOO_MANY_REQUESTS,
+            [
+                (header::RETRY_AFTER, HeaderValue::from(retry_after_secs)),
+                (RATE_LIMIT_LIMIT.clone(), limit.requests.into()),
+                (RATE_LIMIT_REMAINING.clone(), HeaderValue::from_static("0")),
+            ],
+            "Rate limit exceeded",
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_fill_then_reset() {
+        let limiter = Counters::default();
+        let limit = RateLimit {
+            requests: 2,
+            per: Duration::from_secs(60),
+        };
+        let start = Instant::now();
+        assert_eq!(
+            limiter.check(1, 0, limit, start),
+            Decision::Allow { remaining: 1 }
+        );
+        assert_eq!(
+            limiter.check(1, 0, limit, start),
+            Decision::Allow { remaining: 0 }
+        );
+        assert_eq!(
+            limiter.check(1, 0, limit, start + Duration::from_secs(15)),
+            Decision::Deny {
+                retry_after_secs: 45
+            }
+        );
+        // other clients and routes have their own windows
+        assert_eq!(
+            limiter.check(2, 0, limit, start),
+            Decision::Allow { remaining: 1 }
+        );
+        assert_eq!(
+            limiter.check(1, 1, limit, start),
+            Decision::Allow { remaining: 1 }
+        );
+        assert_eq!(
+            limiter.check(1, 0, limit, start + Duration::from_secs(60)),
+            Decision::Allow { remaining: 1 }
+        );
+    }
+
+    #[test]
+    fn tokens_take_precedence_over_addresses() {
+        let mut headers = HeaderMap::new();
+        headers.insert(&FLY_CLIENT_IP, "203.0.113.7".parse().unwrap());
+        let mut other_address = headers.clone();
+        other_address.insert(&FLY_CLIENT_IP, "203.0.113.8".parse().unwrap());
+        assert_eq!(
+            client_key(Some(4), &headers, None),
+            client_key(Some(4), &other_address, None)
+        );
+
+        assert!(client_key(None, &headers, None).is_some());
+        assert_ne!(
+            client_key(None, &headers, None),
+            client_key(Some(4), &headers, None)
+        );
+        assert_ne!(
+            client_key(None, &headers, None),
+            client_key(None, &other_address, None)
+        );
+    }
+
+    #[test]
+    fn addresses_fall_back_to_the_peer() {
+        let peer: SocketAddr = "198.51.100.2:50000".parse().unwrap();
+        let same_host: SocketAddr = "198.51.100.2:50001".parse().unwrap();
+        let empty = HeaderMap::new();
+        assert!(client_key(None, &empty, Some(peer)).is_some());
+        assert_eq!(
+            client_key(None, &empty, Some(peer)),
+            client_key(None, &empty, Some(same_host))
+        );
+        let mut forwarded = HeaderMap::new();
+        forwarded.insert(&FLY_CLIENT_IP, "203.0.113.7".parse().unwrap());
+        assert_ne!(
+            client_key(None, &forwarded, Some(peer)),
+            client_key(None, &empty, Some(peer))
+        );
+        assert_eq!(client_key(None, &empty, None), None);
+    }
+
+    #[test]
+    fn cloudflare_header_is_only_believed_from_cloudflare() {
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+        let mut via_cloudflare = HeaderMap::new();
+        via_cloudflare.insert(&FLY_CLIENT_IP, "172.70.1.1".parse().unwrap());
+        via_cloudflare.insert(&CF_CONNECTING_IP, "203.0.113.7".parse().unwrap());
+        assert_eq!(client_address(&via_cloudflare, None), Some(client));
+
+        let mut spoofed = via_cloudflare.clone();
+        spoofed.insert(&FLY_CLIENT_IP, "198.51.100.9".parse().unwrap());
+        assert_eq!(
+            client_address(&spoofed, None),
+            Some("198.51.100.9".parse().unwrap())
+        );
+
+        // Without Fly in front, the peer decides.
+        let mut direct = HeaderMap::new();
+        direct.insert(&CF_CONNECTING_IP, "203.0.113.7".parse().unwrap());
+        let peer: SocketAddr = "198.51.100.2:50000".parse().unwrap();
+        assert_eq!(client_address(&direct, Some(peer)), Some(peer.ip()));
+    }
+
+    #[test]
+    fn rejected_tokens_are_remembered_for_a_while() {
+        let rejected = RejectedTokens::default();
+        let start = Instant::now();
+        assert!(!rejected.contains("made-up", start));
+        rejected.insert("made-up", start);
+        assert!(rejected.contains("made-up", start + Duration::from_secs(60)));
+        assert!(!rejected.contains("other", start));
+        assert!(!rejected.contains("made-up", start + REJECTED_TOKEN_TTL));
+    }
+}
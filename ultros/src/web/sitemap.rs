@@ -77,11 +77,21 @@ pub(crate) async fn generic_pages_sitemap() -> Result<Xml, WebError> {
             0.8,
             ChangeFrequency::Daily,
         ),
+        (
+            "https://ultros.app/route-planner",
+            0.7,
+            ChangeFrequency::Daily,
+        ),
         (
             "https://ultros.app/recipe-analyzer",
             0.8,
             ChangeFrequency::Daily,
         ),
+        (
+            "https://ultros.app/new-this-patch",
+            0.7,
+            ChangeFrequency::Weekly,
+        ),
         (
             "https://ultros.app/leve-analyzer",
             0.7,
@@ -138,7 +148,9 @@ pub(crate) async fn generic_pages_sitemap() -> Result<Xml, WebError> {
         "getting-started",
         "flip-finder",
         "vendor-resale",
+        "route-planner",
         "recipe-analyzer",
+        "new-this-patch",
         "leve-analyzer",
         "fc-crafting",
         "scrip-sources",
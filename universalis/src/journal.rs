@@ -0,0 +1,363 @@
+//! Append-only capture of the decoded websocket feed.
+//!
+//! Every message the [`WebsocketClient`](crate::WebsocketClient) decodes is
+//! written as a [`ReplayRecord::Event`] stamped with its receive time, so a
+//! journal directory is a valid input to
+//! [`ReplaySource::open_journal`](crate::replay::ReplaySource::open_journal).
+//!
+//! Files are gzip-compressed NDJSON named `ws-<UTC start time>.ndjson.gz`, so
+//! lexicographic order is chronological order. A file is rotated once it has
+//! taken [`JournalConfig::max_file_bytes`] of uncompressed records, and the
+//! oldest files are deleted past [`JournalConfig::max_files`].
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, SyncSender, TrySendError, sync_channel};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use flate2::Compression;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use tracing::{error, info, warn};
+
+use crate::Error;
+use crate::replay::ReplayRecord;
+use crate::websocket::event_types::WSMessage;
+
+const FILE_PREFIX: &str = "ws-";
+const FILE_SUFFIX: &str = ".ndjson.gz";
+
+/// Entries buffered between the socket worker and the writer thread. The
+/// writer never blocks the socket: once this is full, entries are dropped and
+/// counted in `ultros_websocket_journal_dropped_total`.
+const QUEUE_DEPTH: usize = 4096;
+
+/// With no traffic for this long, the writer flushes so a crash loses at most
+/// the entries received since the last quiet spell.
+const IDLE_FLUSH: Duration = Duration::from_secs(5);
+
+/// Entries written between forced flushes during sustained traffic.
+const FLUSH_EVERY: usize = 512;
+
+#[derive(Debug, Clone)]
+pub struct JournalConfig {
+    pub directory: PathBuf,
+    /// Uncompressed bytes written to one file before rotating to the next.
+    pub max_file_bytes: u64,
+    /// Oldest files beyond this count are deleted on rotation. `None` keeps everything.
+    pub max_files: Option<usize>,
+}
+
+impl JournalConfig {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            max_file_bytes: 256 * 1024 * 1024,
+            max_files: None,
+        }
+    }
+}
+
+/// Cheap, cloneable handle the socket worker records through.
+#[derive(Clone)]
+pub struct JournalSink {
+    sender: SyncSender<ReplayRecord>,
+}
+
+impl JournalSink {
+    /// Starts the writer thread. Fails if the journal directory can't be created.
+    pub fn spawn(config: JournalConfig) -> Result<Self, Error> {
+        std::fs::create_dir_all(&config.directory)?;
+        let (sender, receiver) = sync_channel(QUEUE_DEPTH);
+        std::thread::Builder::new()
+            .name("ws-journal".into())
+            .spawn(move || JournalWriter::new(config).run(receiver))?;
+        Ok(Self { sender })
+    }
+
+    pub fn record(&self, received_at: DateTime<Utc>, message: &WSMessage) {
+        let record = ReplayRecord::Event {
+            received_at: Some(received_at),
+            message: message.clone(),
+        };
+        match self.sender.try_send(record) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                metrics::counter!("ultros_websocket_journal_dropped_total").increment(1);
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                warn!("websocket journal writer has stopped, event not captured");
+                metrics::counter!("ultros_websocket_journal_dropped_total").increment(1);
+            }
+        }
+    }
+}
+
+struct OpenFile {
+    encoder: GzEncoder<File>,
+    written: u64,
+    unflushed: usize,
+}
+
+struct JournalWriter {
+    config: JournalConfig,
+    current: Option<OpenFile>,
+}
+
+impl JournalWriter {
+    fn new(config: JournalConfig) -> Self {
+        Self {
+            config,
+            current: None,
+        }
+    }
+
+    fn run(mut self, receiver: Receiver<ReplayRecord>) {
+        loop {
+            match receiver.recv_timeout(IDLE_FLUSH) {
+                Ok(record) => {
+                    if let Err(e) = self.write(&record) {
+                        error!(error = ?e, "failed to write websocket journal entry");
+                        // Start a fresh file next time rather than appending to
+                        // one that may now be corrupt.
+                        self.current = None;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => self.flush(),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        self.close();
+    }
+
+    fn write(&mut self, record: &ReplayRecord) -> Result<(), Error> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|f| f.written + line.len() as u64 > self.config.max_file_bytes)
+        {
+            self.close();
+        }
+        if self.current.is_none() {
+            self.current = Some(self.open()?);
+        }
+        let file = self.current.as_mut().expect("journal file was just opened");
+        file.encoder.write_all(&line)?;
+        file.written += line.len() as u64;
+        file.unflushed += 1;
+        if file.unflushed >= FLUSH_EVERY {
+            file.encoder.flush()?;
+            file.unflushed = 0;
+        }
+        Ok(())
+    }
+
+    fn open(&self) -> Result<OpenFile, Error> {
+        let path = self.config.directory.join(format!(
+            "{FILE_PREFIX}{}{FILE_SUFFIX}",
+            Utc::now().format("%Y%m%dT%H%M%S%.3fZ")
+        ));
+        info!(path = %path.display(), "opening websocket journal file");
+        let file = File::options().create_new(true).write(true).open(&path)?;
+        self.prune();
+        Ok(OpenFile {
+            encoder: GzEncoder::new(file, Compression::default()),
+            written: 0,
+            unflushed: 0,
+        })
+    }
+
+    fn flush(&mut self) {
+        if let Some(file) = &mut self.current
+            && file.unflushed > 0
+        {
+            if let Err(e) = file.encoder.flush() {
+                error!(error = ?e, "failed to flush websocket journal");
+            }
+            file.unflushed = 0;
+        }
+    }
+
+    fn close(&mut self) {
+        if let Some(file) = self.current.take()
+            && let Err(e) = file.encoder.finish()
+        {
+            error!(error = ?e, "failed to finish websocket journal file");
+        }
+    }
+
+    /// Deletes the oldest files beyond `max_files`, counting the one just opened.
+    fn prune(&self) {
+        let Some(max_files) = self.config.max_files else {
+            return;
+        };
+        let files = match journal_files(&self.config.directory) {
+            Ok(files) => files,
+            Err(e) => {
+                warn!(error = ?e, "unable to list websocket journal files for pruning");
+                return;
+            }
+        };
+        for path in files.iter().take(files.len().saturating_sub(max_files)) {
+            if let Err(e) = std::fs::remove_file(path) {
+                warn!(error = ?e, path = %path.display(), "unable to prune websocket journal file");
+            }
+        }
+    }
+}
+
+/// Journal files in `directory`, oldest first.
+pub fn journal_files(directory: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut files = vec![];
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        let is_journal = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with(FILE_PREFIX) && n.ends_with(FILE_SUFFIX));
+        if is_journal {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Reads every record from one journal file.
+///
+/// A file whose writer died mid-stream ends in a truncated gzip member; that is
+/// treated as the end of the file, since everything before the last flush is
+/// still intact.
+pub fn read_journal_file(path: &Path) -> Result<Vec<ReplayRecord>, Error> {
+    let reader = BufReader::new(MultiGzDecoder::new(File::open(path)?));
+    let mut records = vec![];
+    for (index, line) in reader.lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                warn!(path = %path.display(), "websocket journal file is truncated, reading up to the break");
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if line.is_empty() {
+            continue;
+        }
+        records.push(
+            serde_json::from_str(&line).map_err(|source| Error::ReplayRecord {
+                line: index + 1,
+                source,
+            })?,
+        );
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ItemId, WorldId};
+
+    fn message(item: i32) -> WSMessage {
+        WSMessage::SalesAdd {
+            item: ItemId(item),
+            world: WorldId(34),
+            sales: vec![],
+        }
+    }
+
+    fn item_ids(records: &[ReplayRecord]) -> Vec<i32> {
+        records
+            .iter()
+            .map(|r| match r {
+                ReplayRecord::Event { message, .. } => ItemId::from(message).0,
+                other => panic!("unexpected record {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn writer_rotates_and_reader_reassembles_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = JournalConfig::new(dir.path());
+        // Small enough that every record lands in its own file.
+        config.max_file_bytes = 1;
+        let mut writer = JournalWriter::new(config);
+        for item in 1..=3 {
+            writer
+                .write(&ReplayRecord::Event {
+                    received_at: Some(Utc::now()),
+                    message: message(item),
+                })
+                .unwrap();
+            // File names have millisecond resolution.
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        writer.close();
+
+        let files = journal_files(dir.path()).unwrap();
+        assert_eq!(files.len(), 3);
+        let records: Vec<_> = files
+            .iter()
+            .flat_map(|f| read_journal_file(f).unwrap())
+            .collect();
+        assert_eq!(item_ids(&records), [1, 2, 3]);
+    }
+
+    #[test]
+    fn pruning_keeps_only_the_newest_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = JournalConfig::new(dir.path());
+        config.max_file_bytes = 1;
+        config.max_files = Some(2);
+        let mut writer = JournalWriter::new(config);
+        for item in 1..=4 {
+            writer
+                .write(&ReplayRecord::Event {
+                    received_at: None,
+                    message: message(item),
+                })
+                .unwrap();
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        writer.close();
+
+        let files = journal_files(dir.path()).unwrap();
+        let records: Vec<_> = files
+            .iter()
+            .flat_map(|f| read_journal_file(f).unwrap())
+            .collect();
+        assert_eq!(item_ids(&records), [3, 4]);
+    }
+
+    #[test]
+    fn a_flushed_but_unfinished_file_is_still_readable() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = JournalWriter::new(JournalConfig::new(dir.path()));
+        for item in 1..=2 {
+            writer
+                .write(&ReplayRecord::Event {
+                    received_at: None,
+                    message: message(item),
+                })
+                .unwrap();
+        }
+        writer.flush();
+        // Simulate a crash: the gzip trailer is never written.
+        std::mem::forget(writer.current.take());
+
+        let files = journal_files(dir.path()).unwrap();
+        assert_eq!(item_ids(&read_journal_file(&files[0]).unwrap()), [1, 2]);
+    }
+
+    #[test]
+    fn unrelated_files_are_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "hi").unwrap();
+        assert!(journal_files(dir.path()).unwrap().is_empty());
+    }
+}
@@ -2,6 +2,14 @@
 pub mod websocket;
 #[cfg(feature = "websocket")]
 pub use websocket::WebsocketClient;
+#[cfg(feature = "websocket")]
+pub mod journal;
+#[cfg(feature = "websocket")]
+pub mod replay;
+#[cfg(feature = "websocket")]
+pub mod source;
+#[cfg(feature = "websocket")]
+pub use source::MarketDataSource;
 extern crate core;
 
 use crate::MarketView::{MultiView, SingleView};
@@ -33,6 +41,14 @@ pub enum Error {
     BadId(u32),
     #[error("No items were suggested")]
     NoItems,
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    /// A replay file line that is not a valid [`replay::ReplayRecord`].
+    #[error("invalid replay record on line {line}: {source}")]
+    ReplayRecord {
+        line: usize,
+        source: serde_json::Error,
+    },
     /// Universalis answered with a non-success status. See [`check_status`] for why
     /// this exists rather than letting the body fall through to the deserializer.
     #[error("universalis returned HTTP {status} for {url}: {body}")]
@@ -165,7 +181,7 @@ pub struct SaleView {
     pub total: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum MarketView {
     SingleView(CurrentlyShownSingleView),
@@ -208,7 +224,7 @@ impl MarketView {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CurrentlyShownSingleView {
     #[serde(rename = "itemID")]
@@ -217,7 +233,7 @@ pub struct CurrentlyShownSingleView {
     pub recent_history: Vec<SaleView>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CurrentlyShownMultiView {
     #[serde(rename = "itemIDs")]
@@ -230,6 +246,12 @@ pub struct CurrentlyShownMultiView {
 #[derive(Clone)]
 pub struct UniversalisClient {
     client: Client,
+    /// Kept so [`MarketDataSource::subscribe`] can open the websocket with the
+    /// same identity the REST requests use.
+    user_agent: String,
+    /// Where [`MarketDataSource::subscribe`] captures the feed, if anywhere.
+    #[cfg(feature = "websocket")]
+    journal: Option<journal::JournalConfig>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
@@ -318,13 +340,13 @@ pub struct HistorySingleView {
     pub hq_sale_velocity: f64,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct MostRecentlyUpdatedItemsView {
     pub items: Vec<WorldItemRecencyView>,
 }
 
 #[serde_as]
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct WorldItemRecencyView {
     // The item ID.
@@ -395,12 +417,26 @@ impl UniversalisClient {
     const UNIVERSALIS_BASE_URL: &'static str = "https://universalis.app/api/v2";
 
     pub fn new(user_agent: impl ToString) -> Self {
+        let user_agent = user_agent.to_string();
         let client = Client::builder()
-            .user_agent(user_agent.to_string())
+            .user_agent(user_agent.clone())
             .build()
             .unwrap();
 
-        UniversalisClient { client }
+        UniversalisClient {
+            client,
+            user_agent,
+            #[cfg(feature = "websocket")]
+            journal: None,
+        }
+    }
+
+    /// Capture every websocket message received through
+    /// [`MarketDataSource::subscribe`] into a [`journal`].
+    #[cfg(feature = "websocket")]
+    pub fn with_journal(mut self, config: journal::JournalConfig) -> Self {
+        self.journal = Some(config);
+        self
     }
 
     pub async fn get_data_centers(&self) -> Result<DataCentersView, Error> {
@@ -0,0 +1,507 @@
+//! Offline [`MarketDataSource`] that serves previously captured Universalis
+//! payloads.
+//!
+//! A replay file is newline-delimited JSON, one [`ReplayRecord`] per line.
+//! Websocket events are replayed in file order; REST snapshots answer
+//! [`MarketDataSource::marketboard_current_data`] with the last snapshot seen
+//! for each world + item, the same way the live API reports the current board.
+//!
+//! A capture directory written by [`crate::journal`] loads through
+//! [`ReplaySource::open_journal`], and its receive timestamps let
+//! [`ReplayPace::Scaled`] reproduce the original timing.
+
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::Path;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::journal::{journal_files, read_journal_file};
+use crate::source::{MarketDataSource, MarketEventStream};
+use crate::websocket::SocketRx;
+use crate::websocket::event_types::{EventChannel, WSMessage};
+use crate::{
+    CurrentlyShownMultiView, CurrentlyShownSingleView, Error, ListingMultiViewData, ListingView,
+    MarketView, MostRecentlyUpdatedItemsView, SaleView, WorldOrDatacenter,
+};
+
+/// One line of a replay file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReplayRecord {
+    /// A decoded websocket message, with when it arrived if it was captured live.
+    Event {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        received_at: Option<DateTime<Utc>>,
+        message: WSMessage,
+    },
+    /// A `marketboard_current_data` response for `world_or_datacenter`.
+    MarketData {
+        world_or_datacenter: String,
+        view: MarketView,
+    },
+    /// A `recently_updated_items` response for `world_or_datacenter`.
+    RecentlyUpdated {
+        world_or_datacenter: String,
+        view: MostRecentlyUpdatedItemsView,
+    },
+}
+
+/// How fast [`ReplaySource`] delivers events.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ReplayPace {
+    /// Back to back, as fast as the consumer reads them.
+    #[default]
+    Unpaced,
+    /// Keep the recorded gaps between events, divided by this factor: `1.0` is
+    /// real time, `10.0` ten times faster. Events without a receive time are
+    /// delivered immediately.
+    Scaled(f64),
+}
+
+impl ReplayPace {
+    /// Wait before delivering an event received at `at`, when the previous one
+    /// was received at `previous`.
+    fn delay(self, previous: Option<DateTime<Utc>>, at: Option<DateTime<Utc>>) -> Duration {
+        match (self, previous, at) {
+            (ReplayPace::Scaled(speed), Some(previous), Some(at)) if speed > 0.0 => (at - previous)
+                .to_std()
+                .map(|gap| gap.div_f64(speed))
+                .unwrap_or_default(),
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// Replays a recorded Universalis session. Cheap to query; every
+/// [`MarketDataSource::subscribe`] call starts the event sequence from the top.
+#[derive(Debug, Default)]
+pub struct ReplaySource {
+    events: Vec<(Option<DateTime<Utc>>, WSMessage)>,
+    pace: ReplayPace,
+    /// Keyed by lowercased world/datacenter name, then item id.
+    market_data: HashMap<String, HashMap<u32, (Vec<ListingView>, Vec<SaleView>)>>,
+    recently_updated: HashMap<String, MostRecentlyUpdatedItemsView>,
+}
+
+impl ReplaySource {
+    /// Loads a replay file. Fails on the first line that is not a valid
+    /// [`ReplayRecord`]; blank lines are skipped.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        Self::from_reader(std::io::BufReader::new(file))
+    }
+
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, Error> {
+        let mut records = vec![];
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(&line).map_err(|source| Error::ReplayRecord {
+                line: index + 1,
+                source,
+            })?;
+            records.push(record);
+        }
+        Ok(Self::from_records(records))
+    }
+
+    /// Loads every file of a [`crate::journal`] capture directory. Events are
+    /// ordered by receive time, since decoding happens off the socket's read loop
+    /// and can finish slightly out of order.
+    pub fn open_journal(directory: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut records = vec![];
+        for file in journal_files(directory.as_ref())? {
+            records.extend(read_journal_file(&file)?);
+        }
+        let mut source = Self::from_records(records);
+        source.events.sort_by_key(|(received_at, _)| *received_at);
+        Ok(source)
+    }
+
+    pub fn with_pace(mut self, pace: ReplayPace) -> Self {
+        self.pace = pace;
+        self
+    }
+
+    pub fn from_records(records: impl IntoIterator<Item = ReplayRecord>) -> Self {
+        let mut source = Self::default();
+        for record in records {
+            match record {
+                ReplayRecord::Event {
+                    received_at,
+                    message,
+                } => source.events.push((received_at, message)),
+                ReplayRecord::MarketData {
+                    world_or_datacenter,
+                    view,
+                } => {
+                    let items = source
+                        .market_data
+                        .entry(world_or_datacenter.to_lowercase())
+                        .or_default();
+                    for (item_id, listings, sales) in view.items() {
+                        items.insert(item_id.0 as u32, (listings, sales));
+                    }
+                }
+                ReplayRecord::RecentlyUpdated {
+                    world_or_datacenter,
+                    view,
+                } => {
+                    source
+                        .recently_updated
+                        .insert(world_or_datacenter.to_lowercase(), view);
+                }
+            }
+        }
+        source
+    }
+
+    /// Number of websocket events a subscription to every channel would yield.
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    fn current(
+        &self,
+        world_or_datacenter: &str,
+        item_id: u32,
+    ) -> (Vec<ListingView>, Vec<SaleView>) {
+        self.market_data
+            .get(&world_or_datacenter.to_lowercase())
+            .and_then(|items| items.get(&item_id))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// A multi-item entry carrying only what ingest reads. The aggregate fields are
+/// derived by Universalis and nothing downstream of `MarketView::items` uses them.
+fn multi_view_entry(
+    item_id: u32,
+    listings: Vec<ListingView>,
+    recent_history: Vec<SaleView>,
+) -> ListingMultiViewData {
+    ListingMultiViewData {
+        item_id,
+        last_upload_time: 0,
+        listings,
+        recent_history,
+        current_average_price: 0.0,
+        current_average_price_nq: 0.0,
+        current_average_price_hq: 0.0,
+        regular_sale_velocity: 0.0,
+        nq_sale_velocity: 0.0,
+        hq_sale_velocity: 0.0,
+        average_price: 0.0,
+        average_price_nq: 0.0,
+        average_price_hq: 0.0,
+        min_price: 0.0,
+        min_price_nq: 0.0,
+        min_price_hq: 0.0,
+        max_price: 0.0,
+        max_price_nq: 0.0,
+        max_price_hq: 0.0,
+        stack_size_histogram: HashMap::new(),
+        stack_size_histogram_nq: HashMap::new(),
+        stack_size_histogram_hq: HashMap::new(),
+        world_upload_times: None,
+    }
+}
+
+#[async_trait]
+impl MarketDataSource for ReplaySource {
+    async fn marketboard_current_data(
+        &self,
+        world_or_datacenter: &str,
+        item_ids: &[i32],
+    ) -> Result<MarketView, Error> {
+        match item_ids {
+            [] => Err(Error::NoItems),
+            [item_id] => {
+                let item_id = *item_id as u32;
+                let (listings, recent_history) = self.current(world_or_datacenter, item_id);
+                Ok(MarketView::SingleView(CurrentlyShownSingleView {
+                    item_id,
+                    listings,
+                    recent_history,
+                }))
+            }
+            item_ids => {
+                let items = item_ids
+                    .iter()
+                    .map(|id| {
+                        let id = *id as u32;
+                        let (listings, sales) = self.current(world_or_datacenter, id);
+                        (id, multi_view_entry(id, listings, sales))
+                    })
+                    .collect();
+                Ok(MarketView::MultiView(CurrentlyShownMultiView {
+                    item_ids: item_ids.iter().map(|id| *id as u32).collect(),
+                    items,
+                    unresolved_items: vec![],
+                    dc_name: None,
+                }))
+            }
+        }
+    }
+
+    async fn recently_updated_items(
+        &self,
+        filter: WorldOrDatacenter<'_>,
+        entries: u8,
+    ) -> Result<MostRecentlyUpdatedItemsView, Error> {
+        let key = match filter {
+            WorldOrDatacenter::World(name) | WorldOrDatacenter::Datacenter(name) => {
+                name.to_lowercase()
+            }
+        };
+        let mut view = self
+            .recently_updated
+            .get(&key)
+            .cloned()
+            .unwrap_or(MostRecentlyUpdatedItemsView { items: vec![] });
+        view.items.truncate(usize::from(entries));
+        Ok(view)
+    }
+
+    async fn subscribe(&self, channels: &[EventChannel]) -> MarketEventStream {
+        let events: Vec<_> = self
+            .events
+            .iter()
+            .filter(|(_, message)| channels.contains(&EventChannel::from(message)))
+            .cloned()
+            .collect();
+        let pace = self.pace;
+        let mut previous = None;
+        futures::stream::iter(events)
+            .then(move |(received_at, message)| {
+                let delay = pace.delay(previous, received_at);
+                previous = received_at.or(previous);
+                async move {
+                    if !delay.is_zero() {
+                        tokio::time::sleep(delay).await;
+                    }
+                    SocketRx::Event(Ok(message))
+                }
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ItemId, WorldId};
+    use chrono::{DateTime, Local};
+
+    fn listing(price: u32) -> ListingView {
+        ListingView {
+            last_review_time: DateTime::<Local>::from(
+                DateTime::parse_from_rfc3339("2024-01-01T00:00:00+00:00").unwrap(),
+            ),
+            price_per_unit: Some(price),
+            quantity: Some(1),
+            stain_id: None,
+            world_name: None,
+            world_id: None,
+            creator_name: None,
+            creator_id: None,
+            hq: false,
+            is_crafted: false,
+            listing_id: Some("1".into()),
+            materia: vec![],
+            on_mannequin: false,
+            retainer_city: 1,
+            retainer_id: None,
+            retainer_name: "Bob".into(),
+            seller_id: None,
+            total: price,
+            tax: 0,
+        }
+    }
+
+    fn single(item_id: u32, price: u32) -> MarketView {
+        MarketView::SingleView(CurrentlyShownSingleView {
+            item_id,
+            listings: vec![listing(price)],
+            recent_history: vec![],
+        })
+    }
+
+    fn listings_add(item: i32) -> ReplayRecord {
+        ReplayRecord::Event {
+            received_at: None,
+            message: WSMessage::ListingsAdd {
+                item: ItemId(item),
+                world: WorldId(34),
+                listings: vec![listing(100)],
+            },
+        }
+    }
+
+    #[test]
+    fn records_round_trip_through_a_replay_file() {
+        let records = [
+            listings_add(5),
+            ReplayRecord::MarketData {
+                world_or_datacenter: "Gilgamesh".into(),
+                view: single(5, 250),
+            },
+            ReplayRecord::RecentlyUpdated {
+                world_or_datacenter: "Gilgamesh".into(),
+                view: MostRecentlyUpdatedItemsView { items: vec![] },
+            },
+        ];
+        let file = records
+            .iter()
+            .map(|r| serde_json::to_string(r).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let source = ReplaySource::from_reader(file.as_bytes()).unwrap();
+        assert_eq!(source.event_count(), 1);
+        assert!(source.recently_updated.contains_key("gilgamesh"));
+    }
+
+    #[test]
+    fn a_bad_line_reports_its_line_number() {
+        let file = format!(
+            "{}\nnot json",
+            serde_json::to_string(&listings_add(1)).unwrap()
+        );
+        let err = ReplaySource::from_reader(file.as_bytes()).unwrap_err();
+        assert!(matches!(err, Error::ReplayRecord { line: 2, .. }), "{err}");
+    }
+
+    #[tokio::test]
+    async fn current_data_serves_the_latest_snapshot_case_insensitively() {
+        let source = ReplaySource::from_records([
+            ReplayRecord::MarketData {
+                world_or_datacenter: "Gilgamesh".into(),
+                view: single(5, 250),
+            },
+            ReplayRecord::MarketData {
+                world_or_datacenter: "Gilgamesh".into(),
+                view: single(5, 200),
+            },
+        ]);
+        let view = source
+            .marketboard_current_data("gilgamesh", &[5])
+            .await
+            .unwrap();
+        let listings = view.get_listings_for_item_id(5).unwrap();
+        assert_eq!(listings[0].price_per_unit, Some(200));
+    }
+
+    #[tokio::test]
+    async fn multi_item_requests_return_every_requested_id() {
+        let source = ReplaySource::from_records([ReplayRecord::MarketData {
+            world_or_datacenter: "Gilgamesh".into(),
+            view: single(5, 250),
+        }]);
+        let view = source
+            .marketboard_current_data("Gilgamesh", &[5, 6])
+            .await
+            .unwrap();
+        let mut items: Vec<_> = view
+            .items()
+            .map(|(id, listings, _)| (id.0, listings.len()))
+            .collect();
+        items.sort();
+        assert_eq!(items, [(5, 1), (6, 0)]);
+        assert!(matches!(
+            source.marketboard_current_data("Gilgamesh", &[]).await,
+            Err(Error::NoItems)
+        ));
+    }
+
+    #[tokio::test]
+    async fn subscribe_replays_only_the_requested_channels_in_order() {
+        let source = ReplaySource::from_records([
+            listings_add(1),
+            ReplayRecord::Event {
+                received_at: None,
+                message: WSMessage::SalesAdd {
+                    item: ItemId(2),
+                    world: WorldId(34),
+                    sales: vec![],
+                },
+            },
+            listings_add(3),
+        ]);
+        let items: Vec<i32> = source
+            .subscribe(&[EventChannel::ListingsAdd])
+            .await
+            .map(|SocketRx::Event(e)| ItemId::from(&e.unwrap()).0)
+            .collect()
+            .await;
+        assert_eq!(items, [1, 3]);
+    }
+
+    #[test]
+    fn scaled_pace_divides_the_recorded_gap() {
+        let start = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let later = start + chrono::Duration::seconds(10);
+        assert_eq!(
+            ReplayPace::Scaled(1.0).delay(Some(start), Some(later)),
+            Duration::from_secs(10)
+        );
+        assert_eq!(
+            ReplayPace::Scaled(10.0).delay(Some(start), Some(later)),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            ReplayPace::Unpaced.delay(Some(start), Some(later)),
+            Duration::ZERO
+        );
+        // Out-of-order or untimed events never stall the replay.
+        assert_eq!(
+            ReplayPace::Scaled(1.0).delay(Some(later), Some(start)),
+            Duration::ZERO
+        );
+        assert_eq!(
+            ReplayPace::Scaled(1.0).delay(None, Some(start)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn journal_directories_load_in_receive_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let start = Utc::now();
+        let file = dir.path().join("ws-20240101T000000.000Z.ndjson.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&file).unwrap(),
+            flate2::Compression::default(),
+        );
+        for (offset, item) in [(2, 3), (0, 1), (1, 2)] {
+            let record = ReplayRecord::Event {
+                received_at: Some(start + chrono::Duration::milliseconds(offset)),
+                message: WSMessage::SalesAdd {
+                    item: ItemId(item),
+                    world: WorldId(34),
+                    sales: vec![],
+                },
+            };
+            use std::io::Write;
+            writeln!(encoder, "{}", serde_json::to_string(&record).unwrap()).unwrap();
+        }
+        encoder.finish().unwrap();
+
+        let source = ReplaySource::open_journal(dir.path()).unwrap();
+        let items: Vec<i32> = source
+            .events
+            .iter()
+            .map(|(_, message)| ItemId::from(message).0)
+            .collect();
+        assert_eq!(items, [1, 2, 3]);
+    }
+}
@@ -0,0 +1,100 @@
+//! Where ingest gets its market data from.
+//!
+//! Ultros consumes Universalis in two shapes: REST snapshots (the catch-up
+//! sweep in `item_update_service.rs`) and the realtime websocket feed. Both go
+//! through [`MarketDataSource`] so the whole ingest pipeline can run against
+//! something other than the live API — see [`crate::replay::ReplaySource`].
+
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use futures::Stream;
+use tracing::error;
+
+use crate::journal::JournalSink;
+use crate::websocket::SocketRx;
+use crate::websocket::event_types::{EventChannel, SubscribeMode};
+use crate::{
+    Error, MarketView, MostRecentlyUpdatedItemsView, UniversalisClient, WebsocketClient,
+    WorldOrDatacenter,
+};
+
+/// Realtime events, in the order the source delivered them.
+pub type MarketEventStream = Pin<Box<dyn Stream<Item = SocketRx> + Send>>;
+
+/// A provider of Universalis-shaped market data.
+///
+/// Object safe on purpose: the services that hold one (`UpdateService`, the
+/// socket listener) are shared behind an `Arc` and pick their source at
+/// startup, not at compile time.
+#[async_trait]
+pub trait MarketDataSource: Send + Sync {
+    /// Current listings and recent sales. Mirrors
+    /// [`UniversalisClient::marketboard_current_data`], including returning a
+    /// [`MarketView::SingleView`] for exactly one id.
+    async fn marketboard_current_data(
+        &self,
+        world_or_datacenter: &str,
+        item_ids: &[i32],
+    ) -> Result<MarketView, Error>;
+
+    /// Mirrors [`UniversalisClient::recently_updated_items`].
+    async fn recently_updated_items(
+        &self,
+        filter: WorldOrDatacenter<'_>,
+        entries: u8,
+    ) -> Result<MostRecentlyUpdatedItemsView, Error>;
+
+    /// Starts delivering realtime events for `channels` across every world.
+    ///
+    /// The stream owns whatever keeps it alive; dropping it ends the
+    /// subscription.
+    async fn subscribe(&self, channels: &[EventChannel]) -> MarketEventStream;
+}
+
+#[async_trait]
+impl MarketDataSource for UniversalisClient {
+    async fn marketboard_current_data(
+        &self,
+        world_or_datacenter: &str,
+        item_ids: &[i32],
+    ) -> Result<MarketView, Error> {
+        UniversalisClient::marketboard_current_data(self, world_or_datacenter, item_ids).await
+    }
+
+    async fn recently_updated_items(
+        &self,
+        filter: WorldOrDatacenter<'_>,
+        entries: u8,
+    ) -> Result<MostRecentlyUpdatedItemsView, Error> {
+        UniversalisClient::recently_updated_items(self, filter, entries).await
+    }
+
+    async fn subscribe(&self, channels: &[EventChannel]) -> MarketEventStream {
+        let journal = self
+            .journal
+            .clone()
+            .and_then(|config| match JournalSink::spawn(config) {
+                Ok(sink) => Some(sink),
+                Err(e) => {
+                    error!(error = ?e, "unable to start the websocket journal, continuing without capture");
+                    None
+                }
+            });
+        let socket = match journal {
+            Some(journal) => {
+                WebsocketClient::connect_with_journal(self.user_agent.clone(), journal).await
+            }
+            None => WebsocketClient::connect(self.user_agent.clone()).await,
+        };
+        for channel in channels {
+            socket
+                .update_subscription(SubscribeMode::Subscribe, *channel, None)
+                .await;
+        }
+        // `WebsocketClient` is itself the stream. Handing over the client rather
+        // than just its receiver keeps the worker's command channel open for as
+        // long as the caller holds on to the stream.
+        Box::pin(socket)
+    }
+}
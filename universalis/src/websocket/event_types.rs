@@ -9,7 +9,7 @@ pub enum SubscribeMode {
     Unsubscribe,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "event")]
 pub enum WSMessage {
     #[serde(rename = "listings/add")]
@@ -1,6 +1,7 @@
 pub mod event_types;
 
 use crate::WorldId;
+use crate::journal::JournalSink;
 use crate::websocket::event_types::{
     Channel, EventChannel, SubscribeMode, WSMessage, WebSocketSubscriptionUpdate, WorldFilter,
 };
@@ -11,6 +12,7 @@ use async_tungstenite::tungstenite::http::HeaderValue;
 use async_tungstenite::tungstenite::http::header::USER_AGENT;
 
 use bson::Document;
+use chrono::Utc;
 use futures::future::Either;
 
 use futures::{Stream, StreamExt};
@@ -159,7 +161,16 @@ impl WebsocketClient {
     }
 
     pub async fn connect(user_agent: impl Into<String>) -> Self {
-        let user_agent: String = user_agent.into();
+        Self::connect_inner(user_agent.into(), None).await
+    }
+
+    /// Like [`WebsocketClient::connect`], but also writes every decoded message
+    /// to `journal`. See [`crate::journal`].
+    pub async fn connect_with_journal(user_agent: impl Into<String>, journal: JournalSink) -> Self {
+        Self::connect_inner(user_agent.into(), Some(journal)).await
+    }
+
+    async fn connect_inner(user_agent: String, journal: Option<JournalSink>) -> Self {
         let mut websocket: Option<WebSocketStream<ConnectStream>> =
             Self::start_websocket(&user_agent)
                 .await
@@ -304,7 +315,11 @@ impl WebsocketClient {
                                 );
                             }
                             Message::Binary(b) => {
+                                // Stamped before handing off, so the journal
+                                // records arrival order rather than decode order.
+                                let received_at = Utc::now();
                                 let sender = listing_sender.clone();
+                                let journal = journal.clone();
                                 tokio::spawn(async move {
                                     let b = bson::deserialize_from_slice::<WSMessage>(b.as_ref()).map_err(|e| {
                                     if let Ok(document) = bson::deserialize_from_slice::<Document>(b.as_ref()) {
@@ -312,6 +327,9 @@ impl WebsocketClient {
                                     }
                                     e.into()
                                 });
+                                    if let (Some(journal), Ok(message)) = (&journal, &b) {
+                                        journal.record(received_at, message);
+                                    }
                                     if let Err(e) = sender.send(SocketRx::Event(b)).await {
                                         error!("Error sending websocket data {e:?}");
                                     }
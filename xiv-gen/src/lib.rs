@@ -38,6 +38,24 @@ pub enum Language {
 }
 
 impl Language {
+    /// Every client language a data pack is built for.
+    pub const ALL: [Language; 7] = [
+        Language::En,
+        Language::Ja,
+        Language::De,
+        Language::Fr,
+        Language::Cn,
+        Language::Ko,
+        Language::Tc,
+    ];
+
+    /// Inverse of [`Language::to_path_part`].
+    pub fn from_path_part(part: &str) -> Option<Language> {
+        Self::ALL
+            .into_iter()
+            .find(|lang| lang.to_path_part() == part)
+    }
+
     pub fn to_path_part(&self) -> &'static str {
         match self {
             Language::En => "en",
@@ -342,6 +360,110 @@ pub struct ClassJobCategory {
     pub pct: bool,
 }
 
+impl ClassJobCategory {
+    /// Whether the job with this canonical English acronym ("WHM", any case)
+    /// is in the category. `None` for an acronym the sheet has no column for.
+    pub fn includes_job(&self, job_acronym: &str) -> Option<bool> {
+        let lower_case = job_acronym.to_lowercase();
+        // this is kind of dumb, but the exhaustive destructure gives a compile
+        // time error whenever a job is added or removed.
+        let ClassJobCategory {
+            key_id: _,
+            name: _,
+            adv,
+            gla,
+            pgl,
+            mrd,
+            lnc,
+            arc,
+            cnj,
+            thm,
+            crp,
+            bsm,
+            arm,
+            gsm,
+            ltw,
+            wvr,
+            alc,
+            cul,
+            min,
+            btn,
+            fsh,
+            pld,
+            mnk,
+            war,
+            drg,
+            brd,
+            whm,
+            blm,
+            acn,
+            smn,
+            sch,
+            rog,
+            nin,
+            mch,
+            drk,
+            ast,
+            sam,
+            rdm,
+            blu,
+            gnb,
+            dnc,
+            rpr,
+            sge,
+            vpr,
+            pct,
+        } = self;
+        let included = match lower_case.as_str() {
+            "adv" => *adv,
+            "gla" => *gla,
+            "pgl" => *pgl,
+            "mrd" => *mrd,
+            "lnc" => *lnc,
+            "arc" => *arc,
+            "cnj" => *cnj,
+            "thm" => *thm,
+            "crp" => *crp,
+            "bsm" => *bsm,
+            "arm" => *arm,
+            "gsm" => *gsm,
+            "ltw" => *ltw,
+            "wvr" => *wvr,
+            "alc" => *alc,
+            "cul" => *cul,
+            "min" => *min,
+            "btn" => *btn,
+            "fsh" => *fsh,
+            "pld" => *pld,
+            "mnk" => *mnk,
+            "war" => *war,
+            "drg" => *drg,
+            "brd" => *brd,
+            "whm" => *whm,
+            "blm" => *blm,
+            "acn" => *acn,
+            "smn" => *smn,
+            "sch" => *sch,
+            "rog" => *rog,
+            "nin" => *nin,
+            "mch" => *mch,
+            "drk" => *drk,
+            "ast" => *ast,
+            "sam" => *sam,
+            "rdm" => *rdm,
+            "blu" => *blu,
+            "gnb" => *gnb,
+            "dnc" => *dnc,
+            "rpr" => *rpr,
+            "sge" => *sge,
+            "vpr" => *vpr,
+            "pct" => *pct,
+            _ => return None,
+        };
+        Some(included)
+    }
+}
+
 #[derive(
     Debug,
     Clone,